@@ -115,7 +115,7 @@ pub use resilience::{
     // Circuit Breaker
     CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError, CircuitState,
     // Retry
-    retry, retry_with_context, ConstantBackoff, ExponentialBackoff, JitterStrategy,
+    retry, retry_if, retry_with_context, ConstantBackoff, ExponentialBackoff, JitterStrategy,
     LinearBackoff, RetryConfig, RetryError, RetryPolicy,
     // Timeout
     timeout_after, with_timeout, TimeoutConfig, TimeoutError, TimeoutLayer,
@@ -54,8 +54,8 @@ pub use circuit_breaker::{
     CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError, CircuitState,
 };
 pub use retry::{
-    retry, retry_with_context, ConstantBackoff, ExponentialBackoff, JitterStrategy, LinearBackoff,
-    RetryConfig, RetryError, RetryPolicy,
+    retry, retry_if, retry_with_context, ConstantBackoff, ExponentialBackoff, JitterStrategy,
+    LinearBackoff, RetryConfig, RetryError, RetryPolicy,
 };
 pub use shutdown::{
     ConnectionGuard, GracefulShutdown, ShutdownCoordinator, ShutdownError, ShutdownSignal,
@@ -1,3 +1,4 @@
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -25,6 +26,33 @@ pub enum CoreError {
 
     #[error("Serialization error: {0}")]
     Serialization(String),
+
+    /// A service refused the request because too many were sent; it may say
+    /// how long to wait before trying again
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+}
+
+impl CoreError {
+    /// Whether the operation that failed with this error may succeed if
+    /// tried again. Errors in the request itself, such as invalid input or
+    /// missing permissions, fail the same way every time.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            CoreError::Internal(_) | CoreError::Database(_) | CoreError::RateLimited { .. } => {
+                true
+            }
+            CoreError::Validation(_)
+            | CoreError::NotFound(_)
+            | CoreError::AlreadyExists(_)
+            | CoreError::InvalidState(_)
+            | CoreError::Unauthorized(_)
+            | CoreError::Serialization(_) => false,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, CoreError>;
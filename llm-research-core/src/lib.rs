@@ -1,5 +1,6 @@
 pub mod domain;
 pub mod error;
+pub mod retry;
 pub mod traits;
 
 pub use domain::*;
@@ -0,0 +1,265 @@
+use llm_research_core::retry::*;
+use llm_research_core::CoreError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_retry_succeeds_immediately() {
+    let policy = ExponentialBackoff::new(RetryConfig::default());
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter_clone = counter.clone();
+
+    let result = retry(policy, || {
+        let c = counter_clone.clone();
+        async move {
+            c.fetch_add(1, Ordering::Relaxed);
+            Ok::<_, ()>(42)
+        }
+    })
+    .await;
+
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(counter.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn test_retry_succeeds_after_failures() {
+    let policy = ExponentialBackoff::new(RetryConfig {
+        max_attempts: 5,
+        initial_delay: Duration::from_millis(10),
+        ..Default::default()
+    });
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter_clone = counter.clone();
+
+    let result = retry(policy, || {
+        let c = counter_clone.clone();
+        async move {
+            let count = c.fetch_add(1, Ordering::Relaxed);
+            if count < 3 {
+                Err("temporary error")
+            } else {
+                Ok(42)
+            }
+        }
+    })
+    .await;
+
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(counter.load(Ordering::Relaxed), 4);
+}
+
+#[tokio::test]
+async fn test_retry_fails_after_max_attempts() {
+    let policy = ExponentialBackoff::new(RetryConfig {
+        max_attempts: 3,
+        initial_delay: Duration::from_millis(10),
+        ..Default::default()
+    });
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter_clone = counter.clone();
+
+    let result = retry(policy, || {
+        let c = counter_clone.clone();
+        async move {
+            c.fetch_add(1, Ordering::Relaxed);
+            Err::<(), _>("permanent error")
+        }
+    })
+    .await;
+
+    assert!(result.is_err());
+    let err = result.unwrap_err();
+    // With max_attempts=3, we make attempts 0, 1, 2, 3 = 4 total attempts
+    // (attempt 3 fails and next_delay(3) returns None since 3 >= max_attempts)
+    assert_eq!(err.attempts, 4);
+    assert_eq!(counter.load(Ordering::Relaxed), 4);
+}
+
+#[tokio::test]
+async fn test_exponential_backoff_delays() {
+    let config = RetryConfig {
+        max_attempts: 5,
+        initial_delay: Duration::from_millis(100),
+        max_delay: Duration::from_secs(10),
+        multiplier: 2.0,
+        jitter: false,
+    };
+
+    let policy = ExponentialBackoff::new(config);
+
+    assert_eq!(policy.next_delay(0), Some(Duration::from_millis(100)));
+    assert_eq!(policy.next_delay(1), Some(Duration::from_millis(200)));
+    assert_eq!(policy.next_delay(2), Some(Duration::from_millis(400)));
+    assert_eq!(policy.next_delay(5), None);
+}
+
+#[tokio::test]
+async fn test_exponential_backoff_max_delay() {
+    let config = RetryConfig {
+        max_attempts: 10,
+        initial_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(5),
+        multiplier: 2.0,
+        jitter: false,
+    };
+
+    let policy = ExponentialBackoff::new(config);
+
+    // Should cap at max_delay
+    let delay = policy.next_delay(5).unwrap();
+    assert_eq!(delay, Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_constant_backoff() {
+    let policy = ConstantBackoff::new(3, Duration::from_millis(100));
+
+    assert_eq!(policy.next_delay(0), Some(Duration::from_millis(100)));
+    assert_eq!(policy.next_delay(1), Some(Duration::from_millis(100)));
+    assert_eq!(policy.next_delay(2), Some(Duration::from_millis(100)));
+    assert_eq!(policy.next_delay(3), None);
+}
+
+#[tokio::test]
+async fn test_linear_backoff() {
+    let policy = LinearBackoff::new(
+        5,
+        Duration::from_millis(100),
+        Duration::from_millis(50),
+        Duration::from_secs(1),
+    );
+
+    assert_eq!(policy.next_delay(0), Some(Duration::from_millis(100)));
+    assert_eq!(policy.next_delay(1), Some(Duration::from_millis(150)));
+    assert_eq!(policy.next_delay(2), Some(Duration::from_millis(200)));
+    assert_eq!(policy.next_delay(5), None);
+}
+
+#[tokio::test]
+async fn test_linear_backoff_max_delay() {
+    let policy = LinearBackoff::new(
+        10,
+        Duration::from_millis(100),
+        Duration::from_millis(200),
+        Duration::from_millis(500),
+    );
+
+    // Should cap at max_delay
+    let delay = policy.next_delay(5).unwrap();
+    assert_eq!(delay, Duration::from_millis(500));
+}
+
+#[tokio::test]
+async fn test_jitter_adds_randomness() {
+    let config = RetryConfig {
+        max_attempts: 5,
+        initial_delay: Duration::from_millis(100),
+        max_delay: Duration::from_secs(10),
+        multiplier: 2.0,
+        jitter: true,
+    };
+
+    let policy = ExponentialBackoff::new(config);
+
+    // Get multiple delays for the same attempt
+    let delays: Vec<_> = (0..10)
+        .map(|_| policy.next_delay(1).unwrap())
+        .collect();
+
+    // Check that we have different values (with high probability)
+    let all_same = delays.windows(2).all(|w| w[0] == w[1]);
+    assert!(!all_same, "Jitter should produce different delays");
+
+    // All delays should be <= base delay without jitter
+    let base_delay = Duration::from_millis(200);
+    for delay in delays {
+        assert!(delay <= base_delay);
+    }
+}
+
+#[tokio::test]
+async fn test_retry_with_context() {
+    let policy = ExponentialBackoff::new(RetryConfig {
+        max_attempts: 2,
+        initial_delay: Duration::from_millis(10),
+        ..Default::default()
+    });
+
+    let result = retry_with_context(
+        policy,
+        || async { Err::<(), _>("error") },
+        "test operation",
+    )
+    .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_retry_error_display() {
+    let error = RetryError {
+        error: "test error",
+        attempts: 3,
+    };
+
+    let display = format!("{}", error);
+    assert!(display.contains("3 attempts"));
+    assert!(display.contains("test error"));
+}
+
+#[tokio::test]
+async fn test_retry_if_gives_up_on_permanent_errors() {
+    let policy = ConstantBackoff::new(5, Duration::from_millis(10));
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter_clone = counter.clone();
+
+    let result = retry_if(
+        &policy,
+        || {
+            let c = counter_clone.clone();
+            async move {
+                if c.fetch_add(1, Ordering::Relaxed) == 0 {
+                    Err::<(), _>(CoreError::Internal("connection reset".to_string()))
+                } else {
+                    Err(CoreError::Validation("unknown model".to_string()))
+                }
+            }
+        },
+        CoreError::is_retryable,
+    )
+    .await;
+
+    let err = result.unwrap_err();
+    assert_eq!(err.attempts, 2);
+    assert!(matches!(err.error, CoreError::Validation(_)));
+    assert_eq!(counter.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn test_core_error_retryable_classification() {
+    assert!(CoreError::Internal("timeout".to_string()).is_retryable());
+    assert!(CoreError::Database("connection lost".to_string()).is_retryable());
+    assert!(CoreError::RateLimited {
+        message: "slow down".to_string(),
+        retry_after: Some(std::time::Duration::from_secs(2)),
+    }
+    .is_retryable());
+    assert!(!CoreError::Validation("bad config".to_string()).is_retryable());
+    assert!(!CoreError::NotFound("model".to_string()).is_retryable());
+    assert!(!CoreError::Unauthorized("api key".to_string()).is_retryable());
+}
+
+#[test]
+fn test_jitter_strategy_serde() {
+    assert_eq!(JitterStrategy::default(), JitterStrategy::Full);
+    assert_eq!(
+        serde_json::to_value(JitterStrategy::Decorrelated).unwrap(),
+        "decorrelated"
+    );
+    let parsed: JitterStrategy = serde_json::from_str("\"equal\"").unwrap();
+    assert_eq!(parsed, JitterStrategy::Equal);
+}
@@ -0,0 +1,385 @@
+use llm_research_core::{CoreError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// Ratings laid out as annotator × item, with `None` for items an annotator
+/// did not label. Categorical labels are stored as numeric codes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnnotationMatrix {
+    annotators: Vec<String>,
+    ratings: Vec<Vec<Option<f64>>>,
+    categories: Option<Vec<String>>,
+}
+
+impl AnnotationMatrix {
+    pub fn new(ratings: Vec<Vec<Option<f64>>>) -> Result<Self> {
+        let num_items = ratings.first().map(|row| row.len()).unwrap_or(0);
+        if ratings.iter().any(|row| row.len() != num_items) {
+            return Err(CoreError::Validation(
+                "Every annotator row must cover the same number of items".to_string(),
+            ));
+        }
+        if ratings.iter().flatten().flatten().any(|v| !v.is_finite()) {
+            return Err(CoreError::Validation(
+                "Ratings must be finite numbers".to_string(),
+            ));
+        }
+
+        let annotators = (0..ratings.len()).map(|i| format!("annotator_{}", i)).collect();
+        Ok(Self {
+            annotators,
+            ratings,
+            categories: None,
+        })
+    }
+
+    /// Build a matrix from categorical labels, coding categories in sorted order
+    pub fn from_labels(labels: &[Vec<Option<&str>>]) -> Result<Self> {
+        let categories: BTreeSet<&str> = labels.iter().flatten().flatten().copied().collect();
+        let categories: Vec<&str> = categories.into_iter().collect();
+        Self::from_ordered_labels(labels, &categories)
+    }
+
+    /// Build a matrix from categorical labels whose codes follow `order`,
+    /// which is what ordinal agreement measures rely on
+    pub fn from_ordered_labels(labels: &[Vec<Option<&str>>], order: &[&str]) -> Result<Self> {
+        let codes: HashMap<&str, f64> = order
+            .iter()
+            .enumerate()
+            .map(|(i, label)| (*label, i as f64))
+            .collect();
+
+        let ratings = labels
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|label| match label {
+                        Some(label) => codes.get(label).copied().map(Some).ok_or_else(|| {
+                            CoreError::Validation(format!("Unknown category '{}'", label))
+                        }),
+                        None => Ok(None),
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut matrix = Self::new(ratings)?;
+        matrix.categories = Some(order.iter().map(|c| c.to_string()).collect());
+        Ok(matrix)
+    }
+
+    pub fn with_annotators(mut self, annotators: Vec<String>) -> Result<Self> {
+        if annotators.len() != self.ratings.len() {
+            return Err(CoreError::Validation(format!(
+                "Expected {} annotator names, got {}",
+                self.ratings.len(),
+                annotators.len()
+            )));
+        }
+        self.annotators = annotators;
+        Ok(self)
+    }
+
+    pub fn annotators(&self) -> &[String] {
+        &self.annotators
+    }
+
+    pub fn categories(&self) -> Option<&[String]> {
+        self.categories.as_deref()
+    }
+
+    pub fn num_annotators(&self) -> usize {
+        self.ratings.len()
+    }
+
+    pub fn num_items(&self) -> usize {
+        self.ratings.first().map(|row| row.len()).unwrap_or(0)
+    }
+
+    /// Ratings given to one item, skipping missing values
+    fn item_ratings(&self, item: usize) -> Vec<f64> {
+        self.ratings.iter().filter_map(|row| row[item]).collect()
+    }
+
+    /// Items that received at least two ratings, the only ones that carry
+    /// information about agreement
+    fn pairable_items(&self) -> Vec<Vec<f64>> {
+        (0..self.num_items())
+            .map(|item| self.item_ratings(item))
+            .filter(|ratings| ratings.len() >= 2)
+            .collect()
+    }
+
+    /// Distinct rating values in ascending order
+    fn values(&self) -> Vec<f64> {
+        let mut values: Vec<f64> = self.ratings.iter().flatten().flatten().copied().collect();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        values.dedup();
+        values
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MeasurementLevel {
+    Nominal,
+    Ordinal,
+    Interval,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PairwiseKappa {
+    pub annotator_a: String,
+    pub annotator_b: String,
+    pub kappa: Option<f64>,
+    pub items_compared: usize,
+}
+
+/// Reliability summary for a set of annotators. Coefficients that are
+/// undefined for the data (e.g. no variation in labels) are `None`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgreementReport {
+    pub annotators: usize,
+    pub items: usize,
+    pub pairable_items: usize,
+    pub level: MeasurementLevel,
+    pub percent_agreement: Option<f64>,
+    pub fleiss_kappa: Option<f64>,
+    pub krippendorff_alpha: Option<f64>,
+    pub pairwise_cohens_kappa: Vec<PairwiseKappa>,
+}
+
+pub struct AgreementAnalyzer;
+
+impl AgreementAnalyzer {
+    /// Mean share of agreeing rater pairs per item, over items with two or more ratings
+    pub fn percent_agreement(matrix: &AnnotationMatrix) -> Result<f64> {
+        let items = matrix.pairable_items();
+        if items.is_empty() {
+            return Err(Self::no_pairable_items());
+        }
+
+        let total: f64 = items
+            .iter()
+            .map(|ratings| {
+                let m = ratings.len();
+                let mut agreeing = 0usize;
+                for i in 0..m {
+                    for j in (i + 1)..m {
+                        if ratings[i] == ratings[j] {
+                            agreeing += 1;
+                        }
+                    }
+                }
+                agreeing as f64 / (m * (m - 1) / 2) as f64
+            })
+            .sum();
+
+        Ok(total / items.len() as f64)
+    }
+
+    /// Cohen's kappa between two annotators over the items both of them rated
+    pub fn cohens_kappa(matrix: &AnnotationMatrix, a: usize, b: usize) -> Result<f64> {
+        let (kappa, _) = Self::cohens_kappa_with_count(matrix, a, b)?;
+        Ok(kappa)
+    }
+
+    /// Fleiss' kappa, generalized to items rated by varying numbers of annotators
+    pub fn fleiss_kappa(matrix: &AnnotationMatrix) -> Result<f64> {
+        let items = matrix.pairable_items();
+        if items.is_empty() {
+            return Err(Self::no_pairable_items());
+        }
+        let values = matrix.values();
+
+        let mut category_totals = vec![0.0; values.len()];
+        let mut total_ratings = 0.0;
+        let mut observed = 0.0;
+
+        for ratings in &items {
+            let m = ratings.len() as f64;
+            let mut counts = vec![0.0; values.len()];
+            for rating in ratings {
+                counts[Self::index_of(&values, *rating)] += 1.0;
+            }
+            observed += counts.iter().map(|c| c * (c - 1.0)).sum::<f64>() / (m * (m - 1.0));
+            for (total, count) in category_totals.iter_mut().zip(&counts) {
+                *total += count;
+            }
+            total_ratings += m;
+        }
+
+        let p_observed = observed / items.len() as f64;
+        let p_expected: f64 = category_totals
+            .iter()
+            .map(|total| (total / total_ratings).powi(2))
+            .sum();
+
+        Self::chance_corrected(p_observed, p_expected)
+    }
+
+    /// Krippendorff's alpha at the given level of measurement
+    pub fn krippendorff_alpha(matrix: &AnnotationMatrix, level: MeasurementLevel) -> Result<f64> {
+        let items = matrix.pairable_items();
+        if items.is_empty() {
+            return Err(Self::no_pairable_items());
+        }
+        let values = matrix.values();
+        let k = values.len();
+
+        // Coincidence matrix: each ordered pair of ratings within a unit
+        // contributes 1 / (m_u - 1)
+        let mut coincidences = vec![vec![0.0; k]; k];
+        for ratings in &items {
+            let weight = 1.0 / (ratings.len() - 1) as f64;
+            for (i, a) in ratings.iter().enumerate() {
+                for (j, b) in ratings.iter().enumerate() {
+                    if i != j {
+                        coincidences[Self::index_of(&values, *a)][Self::index_of(&values, *b)] +=
+                            weight;
+                    }
+                }
+            }
+        }
+
+        let marginals: Vec<f64> = coincidences.iter().map(|row| row.iter().sum()).collect();
+        let n: f64 = marginals.iter().sum();
+
+        let distance = |c: usize, k: usize| -> f64 {
+            match level {
+                MeasurementLevel::Nominal => {
+                    if c == k {
+                        0.0
+                    } else {
+                        1.0
+                    }
+                }
+                MeasurementLevel::Interval => (values[c] - values[k]).powi(2),
+                MeasurementLevel::Ordinal => {
+                    let (lo, hi) = if c <= k { (c, k) } else { (k, c) };
+                    let between: f64 = marginals[lo..=hi].iter().sum();
+                    (between - (marginals[c] + marginals[k]) / 2.0).powi(2)
+                }
+            }
+        };
+
+        let mut disagreement_observed = 0.0;
+        let mut disagreement_expected = 0.0;
+        for c in 0..k {
+            for j in 0..k {
+                let d = distance(c, j);
+                disagreement_observed += coincidences[c][j] * d;
+                disagreement_expected += marginals[c] * marginals[j] * d;
+            }
+        }
+        disagreement_expected /= n - 1.0;
+
+        if disagreement_expected == 0.0 {
+            return Err(CoreError::Validation(
+                "Krippendorff's alpha is undefined when all ratings are identical".to_string(),
+            ));
+        }
+
+        Ok(1.0 - disagreement_observed / disagreement_expected)
+    }
+
+    /// Compute every agreement coefficient for the matrix
+    pub fn report(matrix: &AnnotationMatrix, level: MeasurementLevel) -> AgreementReport {
+        let mut pairwise_cohens_kappa = Vec::new();
+        for a in 0..matrix.num_annotators() {
+            for b in (a + 1)..matrix.num_annotators() {
+                let (kappa, items_compared) = match Self::cohens_kappa_with_count(matrix, a, b) {
+                    Ok((kappa, count)) => (Some(kappa), count),
+                    Err(_) => (None, Self::shared_items(matrix, a, b).len()),
+                };
+                pairwise_cohens_kappa.push(PairwiseKappa {
+                    annotator_a: matrix.annotators[a].clone(),
+                    annotator_b: matrix.annotators[b].clone(),
+                    kappa,
+                    items_compared,
+                });
+            }
+        }
+
+        AgreementReport {
+            annotators: matrix.num_annotators(),
+            items: matrix.num_items(),
+            pairable_items: matrix.pairable_items().len(),
+            level,
+            percent_agreement: Self::percent_agreement(matrix).ok(),
+            fleiss_kappa: Self::fleiss_kappa(matrix).ok(),
+            krippendorff_alpha: Self::krippendorff_alpha(matrix, level).ok(),
+            pairwise_cohens_kappa,
+        }
+    }
+
+    fn cohens_kappa_with_count(
+        matrix: &AnnotationMatrix,
+        a: usize,
+        b: usize,
+    ) -> Result<(f64, usize)> {
+        if a >= matrix.num_annotators() || b >= matrix.num_annotators() || a == b {
+            return Err(CoreError::Validation(format!(
+                "Invalid annotator pair ({}, {})",
+                a, b
+            )));
+        }
+
+        let shared = Self::shared_items(matrix, a, b);
+        if shared.is_empty() {
+            return Err(CoreError::Validation(format!(
+                "Annotators {} and {} have no items in common",
+                matrix.annotators[a], matrix.annotators[b]
+            )));
+        }
+
+        let values = matrix.values();
+        let n = shared.len() as f64;
+        let mut marginals_a = vec![0.0; values.len()];
+        let mut marginals_b = vec![0.0; values.len()];
+        let mut agreeing = 0.0;
+
+        for (x, y) in &shared {
+            marginals_a[Self::index_of(&values, *x)] += 1.0;
+            marginals_b[Self::index_of(&values, *y)] += 1.0;
+            if x == y {
+                agreeing += 1.0;
+            }
+        }
+
+        let p_expected: f64 = marginals_a
+            .iter()
+            .zip(&marginals_b)
+            .map(|(pa, pb)| (pa / n) * (pb / n))
+            .sum();
+
+        Ok((Self::chance_corrected(agreeing / n, p_expected)?, shared.len()))
+    }
+
+    fn shared_items(matrix: &AnnotationMatrix, a: usize, b: usize) -> Vec<(f64, f64)> {
+        matrix.ratings[a]
+            .iter()
+            .zip(&matrix.ratings[b])
+            .filter_map(|(x, y)| Some(((*x)?, (*y)?)))
+            .collect()
+    }
+
+    fn chance_corrected(p_observed: f64, p_expected: f64) -> Result<f64> {
+        if (1.0 - p_expected).abs() < f64::EPSILON {
+            return Err(CoreError::Validation(
+                "Kappa is undefined when chance agreement is 1".to_string(),
+            ));
+        }
+        Ok((p_observed - p_expected) / (1.0 - p_expected))
+    }
+
+    fn index_of(values: &[f64], value: f64) -> usize {
+        values
+            .binary_search_by(|v| v.partial_cmp(&value).unwrap())
+            .unwrap()
+    }
+
+    fn no_pairable_items() -> CoreError {
+        CoreError::Validation("No item was rated by at least two annotators".to_string())
+    }
+}
@@ -0,0 +1,379 @@
+use llm_research_core::{CoreError, Result};
+use rand::distributions::Distribution;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{Beta, ContinuousCDF, StudentsT};
+use statrs::function::beta::ln_beta;
+use statrs::statistics::Statistics;
+
+use crate::statistical::quantile;
+
+/// Largest posterior alpha for which P(B > A) is computed with the closed-form
+/// Beta sum instead of Monte Carlo.
+const MAX_ANALYTIC_ALPHA: f64 = 100_000.0;
+
+/// Beta prior over a success rate
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct BetaPrior {
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl BetaPrior {
+    pub fn new(alpha: f64, beta: f64) -> Self {
+        Self { alpha, beta }
+    }
+
+    /// Uniform prior, Beta(1, 1)
+    pub fn uniform() -> Self {
+        Self::new(1.0, 1.0)
+    }
+
+    /// Jeffreys prior, Beta(0.5, 0.5)
+    pub fn jeffreys() -> Self {
+        Self::new(0.5, 0.5)
+    }
+}
+
+impl Default for BetaPrior {
+    fn default() -> Self {
+        Self::uniform()
+    }
+}
+
+/// Observed successes out of a number of trials for one variant
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BinaryOutcome {
+    pub successes: u64,
+    pub trials: u64,
+}
+
+impl BinaryOutcome {
+    pub fn new(successes: u64, trials: u64) -> Self {
+        Self { successes, trials }
+    }
+
+    pub fn from_outcomes(outcomes: &[bool]) -> Self {
+        Self {
+            successes: outcomes.iter().filter(|&&o| o).count() as u64,
+            trials: outcomes.len() as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BayesianConfig {
+    /// Mass of the central credible intervals (e.g. 0.95)
+    pub credible_level: f64,
+    /// Region of practical equivalence on the difference B - A
+    pub rope: Option<(f64, f64)>,
+    /// Number of posterior draws used for Monte Carlo estimates
+    pub monte_carlo_samples: usize,
+    /// Seed for the Monte Carlo sampler, so results are reproducible
+    pub seed: u64,
+}
+
+impl Default for BayesianConfig {
+    fn default() -> Self {
+        Self {
+            credible_level: 0.95,
+            rope: None,
+            monte_carlo_samples: 100_000,
+            seed: 42,
+        }
+    }
+}
+
+impl BayesianConfig {
+    pub fn with_credible_level(mut self, credible_level: f64) -> Self {
+        self.credible_level = credible_level;
+        self
+    }
+
+    pub fn with_rope(mut self, lower: f64, upper: f64) -> Self {
+        self.rope = Some((lower, upper));
+        self
+    }
+
+    pub fn with_samples(mut self, monte_carlo_samples: usize) -> Self {
+        self.monte_carlo_samples = monte_carlo_samples;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(self.credible_level > 0.0 && self.credible_level < 1.0) {
+            return Err(CoreError::Validation(format!(
+                "credible_level must be in (0, 1), got {}",
+                self.credible_level
+            )));
+        }
+        if self.monte_carlo_samples == 0 {
+            return Err(CoreError::Validation(
+                "monte_carlo_samples must be greater than zero".to_string(),
+            ));
+        }
+        if let Some((lower, upper)) = self.rope {
+            if lower > upper {
+                return Err(CoreError::Validation(format!(
+                    "ROPE lower bound {} exceeds upper bound {}",
+                    lower, upper
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Summary of a one-dimensional posterior distribution
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PosteriorSummary {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub credible_interval: (f64, f64),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RopeDecision {
+    /// The credible interval of B - A lies entirely below the ROPE
+    PreferA,
+    /// The credible interval of B - A lies entirely above the ROPE
+    PreferB,
+    /// The credible interval of B - A lies entirely inside the ROPE
+    Equivalent,
+    /// The credible interval overlaps the ROPE boundary
+    Undecided,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RopeResult {
+    pub lower: f64,
+    pub upper: f64,
+    pub probability_in_rope: f64,
+    pub decision: RopeDecision,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PosteriorMethod {
+    Analytic,
+    MonteCarlo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BayesianComparison {
+    pub posterior_a: PosteriorSummary,
+    pub posterior_b: PosteriorSummary,
+    /// Posterior of the difference B - A
+    pub difference: PosteriorSummary,
+    pub prob_b_better: f64,
+    /// Expected shortfall incurred by choosing A if B is actually better
+    pub expected_loss_a: f64,
+    /// Expected shortfall incurred by choosing B if A is actually better
+    pub expected_loss_b: f64,
+    pub rope: Option<RopeResult>,
+    /// How `prob_b_better` was computed
+    pub method: PosteriorMethod,
+}
+
+pub struct BayesianAnalyzer;
+
+impl BayesianAnalyzer {
+    /// Compare two success rates with conjugate Beta-Binomial posteriors
+    pub fn compare_binary(
+        a: BinaryOutcome,
+        b: BinaryOutcome,
+        prior: BetaPrior,
+        config: &BayesianConfig,
+    ) -> Result<BayesianComparison> {
+        config.validate()?;
+        if !(prior.alpha > 0.0 && prior.beta > 0.0) {
+            return Err(CoreError::Validation(format!(
+                "Beta prior parameters must be positive, got ({}, {})",
+                prior.alpha, prior.beta
+            )));
+        }
+        for (label, outcome) in [("A", &a), ("B", &b)] {
+            if outcome.successes > outcome.trials {
+                return Err(CoreError::Validation(format!(
+                    "Variant {} has {} successes out of {} trials",
+                    label, outcome.successes, outcome.trials
+                )));
+            }
+        }
+
+        let (alpha_a, beta_a) = Self::beta_posterior(a, prior);
+        let (alpha_b, beta_b) = Self::beta_posterior(b, prior);
+        let dist_a = Self::beta(alpha_a, beta_a)?;
+        let dist_b = Self::beta(alpha_b, beta_b)?;
+
+        let posterior_a = Self::beta_summary(&dist_a, alpha_a, beta_a, config.credible_level);
+        let posterior_b = Self::beta_summary(&dist_b, alpha_b, beta_b, config.credible_level);
+
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let draws: Vec<(f64, f64)> = (0..config.monte_carlo_samples)
+            .map(|_| (dist_a.sample(&mut rng), dist_b.sample(&mut rng)))
+            .collect();
+
+        let mut comparison = Self::summarize_draws(&draws, &posterior_a, &posterior_b, config);
+
+        if alpha_b.fract() == 0.0 && alpha_b <= MAX_ANALYTIC_ALPHA {
+            comparison.prob_b_better = Self::beta_prob_b_better(alpha_a, beta_a, alpha_b, beta_b);
+            comparison.method = PosteriorMethod::Analytic;
+        }
+
+        Ok(comparison)
+    }
+
+    /// Compare two sets of continuous scores.
+    ///
+    /// Each mean gets the marginal Student-t posterior that results from a
+    /// Normal likelihood with unknown variance and a non-informative prior.
+    pub fn compare_continuous(
+        sample_a: &[f64],
+        sample_b: &[f64],
+        config: &BayesianConfig,
+    ) -> Result<BayesianComparison> {
+        config.validate()?;
+        if sample_a.len() < 2 || sample_b.len() < 2 {
+            return Err(CoreError::Validation(
+                "Each sample needs at least two observations".to_string(),
+            ));
+        }
+
+        let dist_a = Self::mean_posterior(sample_a)?;
+        let dist_b = Self::mean_posterior(sample_b)?;
+
+        let posterior_a = Self::t_summary(&dist_a, config.credible_level);
+        let posterior_b = Self::t_summary(&dist_b, config.credible_level);
+
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let draws: Vec<(f64, f64)> = (0..config.monte_carlo_samples)
+            .map(|_| (dist_a.sample(&mut rng), dist_b.sample(&mut rng)))
+            .collect();
+
+        Ok(Self::summarize_draws(&draws, &posterior_a, &posterior_b, config))
+    }
+
+    fn beta_posterior(outcome: BinaryOutcome, prior: BetaPrior) -> (f64, f64) {
+        (
+            prior.alpha + outcome.successes as f64,
+            prior.beta + (outcome.trials - outcome.successes) as f64,
+        )
+    }
+
+    fn beta(alpha: f64, beta: f64) -> Result<Beta> {
+        Beta::new(alpha, beta)
+            .map_err(|e| CoreError::Validation(format!("Invalid Beta posterior: {}", e)))
+    }
+
+    fn beta_summary(dist: &Beta, alpha: f64, beta: f64, level: f64) -> PosteriorSummary {
+        let total = alpha + beta;
+        let tail = (1.0 - level) / 2.0;
+        PosteriorSummary {
+            mean: alpha / total,
+            std_dev: (alpha * beta / (total * total * (total + 1.0))).sqrt(),
+            credible_interval: (dist.inverse_cdf(tail), dist.inverse_cdf(1.0 - tail)),
+        }
+    }
+
+    /// Closed-form P(p_B > p_A) for Beta posteriors, valid when `alpha_b` is an integer
+    fn beta_prob_b_better(alpha_a: f64, beta_a: f64, alpha_b: f64, beta_b: f64) -> f64 {
+        let base = ln_beta(alpha_a, beta_a);
+        let total: f64 = (0..alpha_b as u64)
+            .map(|i| {
+                let i = i as f64;
+                (ln_beta(alpha_a + i, beta_a + beta_b)
+                    - (beta_b + i).ln()
+                    - ln_beta(1.0 + i, beta_b)
+                    - base)
+                    .exp()
+            })
+            .sum();
+        total.clamp(0.0, 1.0)
+    }
+
+    fn mean_posterior(sample: &[f64]) -> Result<StudentsT> {
+        let n = sample.len() as f64;
+        let scale = (sample.std_dev() / n.sqrt()).max(f64::EPSILON);
+        StudentsT::new(sample.mean(), scale, n - 1.0)
+            .map_err(|e| CoreError::Validation(format!("Invalid Student-t posterior: {}", e)))
+    }
+
+    fn t_summary(dist: &StudentsT, level: f64) -> PosteriorSummary {
+        let tail = (1.0 - level) / 2.0;
+        let df = dist.freedom();
+        // The Student-t variance is infinite for two or fewer degrees of freedom
+        let std_dev = if df > 2.0 {
+            dist.scale() * (df / (df - 2.0)).sqrt()
+        } else {
+            f64::INFINITY
+        };
+        PosteriorSummary {
+            mean: dist.location(),
+            std_dev,
+            credible_interval: (dist.inverse_cdf(tail), dist.inverse_cdf(1.0 - tail)),
+        }
+    }
+
+    fn summarize_draws(
+        draws: &[(f64, f64)],
+        posterior_a: &PosteriorSummary,
+        posterior_b: &PosteriorSummary,
+        config: &BayesianConfig,
+    ) -> BayesianComparison {
+        let n = draws.len() as f64;
+        let mut diffs: Vec<f64> = draws.iter().map(|(a, b)| b - a).collect();
+
+        let prob_b_better = diffs.iter().filter(|&&d| d > 0.0).count() as f64 / n;
+        let expected_loss_a = diffs.iter().map(|&d| d.max(0.0)).sum::<f64>() / n;
+        let expected_loss_b = diffs.iter().map(|&d| (-d).max(0.0)).sum::<f64>() / n;
+
+        let diff_mean = diffs.iter().sum::<f64>() / n;
+        let diff_std = (diffs.iter().map(|d| (d - diff_mean).powi(2)).sum::<f64>() / n).sqrt();
+
+        diffs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let tail = (1.0 - config.credible_level) / 2.0;
+        let interval = (quantile(&diffs, tail), quantile(&diffs, 1.0 - tail));
+
+        let rope = config.rope.map(|(lower, upper)| {
+            let inside = diffs.iter().filter(|&&d| d >= lower && d <= upper).count();
+            let decision = if interval.1 < lower {
+                RopeDecision::PreferA
+            } else if interval.0 > upper {
+                RopeDecision::PreferB
+            } else if interval.0 >= lower && interval.1 <= upper {
+                RopeDecision::Equivalent
+            } else {
+                RopeDecision::Undecided
+            };
+            RopeResult {
+                lower,
+                upper,
+                probability_in_rope: inside as f64 / n,
+                decision,
+            }
+        });
+
+        BayesianComparison {
+            posterior_a: posterior_a.clone(),
+            posterior_b: posterior_b.clone(),
+            difference: PosteriorSummary {
+                mean: posterior_b.mean - posterior_a.mean,
+                std_dev: diff_std,
+                credible_interval: interval,
+            },
+            prob_b_better,
+            expected_loss_a,
+            expected_loss_b,
+            rope,
+            method: PosteriorMethod::MonteCarlo,
+        }
+    }
+}
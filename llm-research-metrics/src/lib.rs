@@ -1,7 +1,17 @@
 pub mod calculators;
 pub mod aggregators;
 pub mod statistical;
+pub mod bayesian;
+pub mod sequential;
+pub mod agreement;
+pub mod ranking;
+pub mod streaming;
 
 pub use calculators::*;
 pub use aggregators::*;
 pub use statistical::*;
+pub use bayesian::*;
+pub use sequential::*;
+pub use agreement::*;
+pub use ranking::*;
+pub use streaming::*;
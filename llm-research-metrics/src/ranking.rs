@@ -0,0 +1,432 @@
+use llm_research_core::{CoreError, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::statistical::quantile;
+
+/// Floor on a Bradley-Terry strength, relative to the virtual opponent's 1
+const MIN_STRENGTH: f64 = 1e-12;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PairwiseOutcome {
+    WinA,
+    WinB,
+    Tie,
+}
+
+impl PairwiseOutcome {
+    /// Score of model A: 1 for a win, 0.5 for a tie, 0 for a loss
+    fn score_a(&self) -> f64 {
+        match self {
+            PairwiseOutcome::WinA => 1.0,
+            PairwiseOutcome::WinB => 0.0,
+            PairwiseOutcome::Tie => 0.5,
+        }
+    }
+}
+
+/// A single judgment between two models, from a human or an LLM judge
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PairwiseComparison {
+    pub model_a: String,
+    pub model_b: String,
+    pub outcome: PairwiseOutcome,
+}
+
+impl PairwiseComparison {
+    pub fn new(
+        model_a: impl Into<String>,
+        model_b: impl Into<String>,
+        outcome: PairwiseOutcome,
+    ) -> Self {
+        Self {
+            model_a: model_a.into(),
+            model_b: model_b.into(),
+            outcome,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankingConfig {
+    /// Rating every model starts from; also the anchor for Bradley-Terry scores
+    pub initial_rating: f64,
+    /// Rating difference corresponding to 10:1 odds
+    pub scale: f64,
+    pub elo_k_factor: f64,
+    /// Number of bootstrap resamples used for confidence intervals
+    pub bootstrap_rounds: usize,
+    pub confidence: f64,
+    pub seed: u64,
+    pub bt_max_iterations: usize,
+    pub bt_tolerance: f64,
+    /// Virtual games against an average opponent, split as a tie, that keep
+    /// Bradley-Terry scores finite for undefeated or winless models
+    pub bt_prior_games: f64,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            initial_rating: 1000.0,
+            scale: 400.0,
+            elo_k_factor: 4.0,
+            bootstrap_rounds: 1000,
+            confidence: 0.95,
+            seed: 42,
+            bt_max_iterations: 1000,
+            bt_tolerance: 1e-8,
+            bt_prior_games: 1.0,
+        }
+    }
+}
+
+impl RankingConfig {
+    pub fn with_bootstrap_rounds(mut self, bootstrap_rounds: usize) -> Self {
+        self.bootstrap_rounds = bootstrap_rounds;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_k_factor(mut self, elo_k_factor: f64) -> Self {
+        self.elo_k_factor = elo_k_factor;
+        self
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !(self.confidence > 0.0 && self.confidence < 1.0) {
+            return Err(CoreError::Validation(format!(
+                "confidence must be in (0, 1), got {}",
+                self.confidence
+            )));
+        }
+        for (name, value) in [("scale", self.scale), ("bt_tolerance", self.bt_tolerance)] {
+            if !(value > 0.0 && value.is_finite()) {
+                return Err(CoreError::Validation(format!(
+                    "{} must be positive, got {}",
+                    name, value
+                )));
+            }
+        }
+        if !(self.bt_prior_games >= 0.0 && self.bt_prior_games.is_finite()) {
+            return Err(CoreError::Validation(format!(
+                "bt_prior_games must be non-negative, got {}",
+                self.bt_prior_games
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RatingMethod {
+    Elo,
+    BradleyTerry,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub model: String,
+    pub rating: f64,
+    pub ci_lower: f64,
+    pub ci_upper: f64,
+    pub wins: u64,
+    pub losses: u64,
+    pub ties: u64,
+}
+
+impl LeaderboardEntry {
+    pub fn games(&self) -> u64 {
+        self.wins + self.losses + self.ties
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Leaderboard {
+    pub method: RatingMethod,
+    pub entries: Vec<LeaderboardEntry>,
+    pub total_comparisons: usize,
+    pub bootstrap_rounds: usize,
+    pub confidence: f64,
+}
+
+impl Leaderboard {
+    pub fn get(&self, model: &str) -> Option<&LeaderboardEntry> {
+        self.entries.iter().find(|e| e.model == model)
+    }
+}
+
+/// Comparisons with model names replaced by indices into a sorted model list
+struct IndexedComparisons {
+    models: Vec<String>,
+    games: Vec<(usize, usize, f64)>,
+}
+
+pub struct RankingAnalyzer;
+
+impl RankingAnalyzer {
+    /// Elo leaderboard. Because sequential Elo depends on the order in which
+    /// games are replayed, ratings are the median over bootstrap resamples,
+    /// each replayed in a random order.
+    pub fn elo(comparisons: &[PairwiseComparison], config: &RankingConfig) -> Result<Leaderboard> {
+        config.validate()?;
+        let indexed = Self::index(comparisons)?;
+        let point = Self::elo_ratings(&indexed.games, indexed.models.len(), config);
+
+        let (ratings, intervals) = if config.bootstrap_rounds == 0 {
+            let intervals = point.iter().map(|&r| (r, r)).collect();
+            (point, intervals)
+        } else {
+            let samples = Self::bootstrap(&indexed, config, |games| {
+                Self::elo_ratings(games, indexed.models.len(), config)
+            });
+            let medians = samples.iter().map(|s| quantile(s, 0.5)).collect();
+            (medians, Self::intervals(&samples, config.confidence))
+        };
+
+        Ok(Self::leaderboard(
+            RatingMethod::Elo,
+            comparisons,
+            &indexed,
+            ratings,
+            intervals,
+            config,
+        ))
+    }
+
+    /// Bradley-Terry leaderboard fitted by minorization-maximization, with
+    /// bootstrap confidence intervals. Scores are reported on the Elo scale.
+    pub fn bradley_terry(
+        comparisons: &[PairwiseComparison],
+        config: &RankingConfig,
+    ) -> Result<Leaderboard> {
+        config.validate()?;
+        let indexed = Self::index(comparisons)?;
+        let ratings = Self::bradley_terry_ratings(&indexed.games, indexed.models.len(), config);
+
+        let intervals = if config.bootstrap_rounds == 0 {
+            ratings.iter().map(|&r| (r, r)).collect()
+        } else {
+            let samples = Self::bootstrap(&indexed, config, |games| {
+                Self::bradley_terry_ratings(games, indexed.models.len(), config)
+            });
+            Self::intervals(&samples, config.confidence)
+        };
+
+        Ok(Self::leaderboard(
+            RatingMethod::BradleyTerry,
+            comparisons,
+            &indexed,
+            ratings,
+            intervals,
+            config,
+        ))
+    }
+
+    fn index(comparisons: &[PairwiseComparison]) -> Result<IndexedComparisons> {
+        if comparisons.is_empty() {
+            return Err(CoreError::Validation(
+                "At least one pairwise comparison is required".to_string(),
+            ));
+        }
+        if let Some(c) = comparisons.iter().find(|c| c.model_a == c.model_b) {
+            return Err(CoreError::Validation(format!(
+                "Model '{}' cannot be compared against itself",
+                c.model_a
+            )));
+        }
+
+        let models: Vec<String> = comparisons
+            .iter()
+            .flat_map(|c| [c.model_a.clone(), c.model_b.clone()])
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let position = |name: &str| models.binary_search_by(|m| m.as_str().cmp(name)).unwrap();
+
+        let games = comparisons
+            .iter()
+            .map(|c| {
+                (
+                    position(&c.model_a),
+                    position(&c.model_b),
+                    c.outcome.score_a(),
+                )
+            })
+            .collect();
+
+        Ok(IndexedComparisons { models, games })
+    }
+
+    fn elo_ratings(
+        games: &[(usize, usize, f64)],
+        num_models: usize,
+        config: &RankingConfig,
+    ) -> Vec<f64> {
+        let mut ratings = vec![config.initial_rating; num_models];
+        for &(a, b, score_a) in games {
+            let expected_a = 1.0 / (1.0 + 10f64.powf((ratings[b] - ratings[a]) / config.scale));
+            let delta = config.elo_k_factor * (score_a - expected_a);
+            ratings[a] += delta;
+            ratings[b] -= delta;
+        }
+        ratings
+    }
+
+    fn bradley_terry_ratings(
+        games: &[(usize, usize, f64)],
+        num_models: usize,
+        config: &RankingConfig,
+    ) -> Vec<f64> {
+        let prior = config.bt_prior_games;
+        let mut wins = vec![prior / 2.0; num_models];
+        let mut pair_games = vec![vec![0.0; num_models]; num_models];
+        for &(a, b, score_a) in games {
+            wins[a] += score_a;
+            wins[b] += 1.0 - score_a;
+            pair_games[a][b] += 1.0;
+            pair_games[b][a] += 1.0;
+        }
+
+        let mut strengths = vec![1.0; num_models];
+        for _ in 0..config.bt_max_iterations {
+            let mut updated: Vec<f64> = (0..num_models)
+                .map(|i| {
+                    // The virtual opponent has strength 1
+                    let mut denominator = prior / (strengths[i] + 1.0);
+                    for j in 0..num_models {
+                        if pair_games[i][j] > 0.0 {
+                            denominator += pair_games[i][j] / (strengths[i] + strengths[j]);
+                        }
+                    }
+                    let strength = if denominator > 0.0 {
+                        wins[i] / denominator
+                    } else {
+                        strengths[i]
+                    };
+                    // Without a prior a winless model's strength falls to
+                    // zero, whose log would turn every rating into NaN
+                    strength.max(MIN_STRENGTH)
+                })
+                .collect();
+
+            // Normalize to a geometric mean of one
+            let log_mean = updated.iter().map(|s| s.ln()).sum::<f64>() / num_models as f64;
+            let norm = log_mean.exp();
+            for s in updated.iter_mut() {
+                *s /= norm;
+            }
+
+            let change = updated
+                .iter()
+                .zip(&strengths)
+                .map(|(new, old)| (new.ln() - old.ln()).abs())
+                .fold(0.0, f64::max);
+            strengths = updated;
+            if change < config.bt_tolerance {
+                break;
+            }
+        }
+
+        strengths
+            .iter()
+            .map(|s| config.initial_rating + config.scale * s.log10())
+            .collect()
+    }
+
+    /// Resample comparisons with replacement and refit; returns per-model samples
+    fn bootstrap<F>(indexed: &IndexedComparisons, config: &RankingConfig, fit: F) -> Vec<Vec<f64>>
+    where
+        F: Fn(&[(usize, usize, f64)]) -> Vec<f64>,
+    {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let n = indexed.games.len();
+        let mut samples = vec![Vec::with_capacity(config.bootstrap_rounds); indexed.models.len()];
+
+        for _ in 0..config.bootstrap_rounds {
+            let resample: Vec<(usize, usize, f64)> =
+                (0..n).map(|_| indexed.games[rng.gen_range(0..n)]).collect();
+            for (model, rating) in fit(&resample).into_iter().enumerate() {
+                samples[model].push(rating);
+            }
+        }
+
+        for model_samples in samples.iter_mut() {
+            model_samples.sort_by(f64::total_cmp);
+        }
+        samples
+    }
+
+    fn intervals(samples: &[Vec<f64>], confidence: f64) -> Vec<(f64, f64)> {
+        let tail = (1.0 - confidence) / 2.0;
+        samples
+            .iter()
+            .map(|s| (quantile(s, tail), quantile(s, 1.0 - tail)))
+            .collect()
+    }
+
+    fn leaderboard(
+        method: RatingMethod,
+        comparisons: &[PairwiseComparison],
+        indexed: &IndexedComparisons,
+        ratings: Vec<f64>,
+        intervals: Vec<(f64, f64)>,
+        config: &RankingConfig,
+    ) -> Leaderboard {
+        let mut records = vec![(0u64, 0u64, 0u64); indexed.models.len()];
+        for &(a, b, score_a) in &indexed.games {
+            if score_a == 1.0 {
+                records[a].0 += 1;
+                records[b].1 += 1;
+            } else if score_a == 0.0 {
+                records[a].1 += 1;
+                records[b].0 += 1;
+            } else {
+                records[a].2 += 1;
+                records[b].2 += 1;
+            }
+        }
+
+        let mut entries: Vec<LeaderboardEntry> = indexed
+            .models
+            .iter()
+            .enumerate()
+            .map(|(i, model)| LeaderboardEntry {
+                rank: 0,
+                model: model.clone(),
+                rating: ratings[i],
+                ci_lower: intervals[i].0,
+                ci_upper: intervals[i].1,
+                wins: records[i].0,
+                losses: records[i].1,
+                ties: records[i].2,
+            })
+            .collect();
+
+        entries.sort_by(|a, b| {
+            b.rating
+                .total_cmp(&a.rating)
+                .then_with(|| a.model.cmp(&b.model))
+        });
+        for (i, entry) in entries.iter_mut().enumerate() {
+            entry.rank = i + 1;
+        }
+
+        Leaderboard {
+            method,
+            entries,
+            total_comparisons: comparisons.len(),
+            bootstrap_rounds: config.bootstrap_rounds,
+            confidence: config.confidence,
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use llm_research_core::{CoreError, Result};
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{ContinuousCDF, Normal};
+use statrs::statistics::Statistics;
+
+/// Alpha spending function for group-sequential designs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlphaSpending {
+    /// Lan-DeMets O'Brien-Fleming-type spending, conservative at early looks
+    OBrienFleming,
+    /// Lan-DeMets Pocock-type spending, roughly even across looks
+    Pocock,
+}
+
+impl AlphaSpending {
+    /// Cumulative alpha spent at information fraction `t` in [0, 1]
+    pub fn spent(&self, alpha: f64, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        if t == 0.0 {
+            return 0.0;
+        }
+        match self {
+            AlphaSpending::OBrienFleming => {
+                let z = standard_normal().inverse_cdf(1.0 - alpha / 2.0);
+                2.0 * (1.0 - standard_normal().cdf(z / t.sqrt()))
+            }
+            AlphaSpending::Pocock => alpha * (1.0 + (std::f64::consts::E - 1.0) * t).ln(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum SequentialMethod {
+    /// Mixture sequential probability ratio test with a N(0, mixing_variance)
+    /// prior on the effect. Valid under continuous monitoring.
+    Msprt { mixing_variance: f64 },
+    /// Group-sequential test with an alpha spending function. Information
+    /// fraction at each look is the number of observations over `max_samples`.
+    GroupSequential {
+        spending: AlphaSpending,
+        max_samples: usize,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SequentialConfig {
+    pub alpha: f64,
+    pub method: SequentialMethod,
+    /// Observations required per arm before any stopping decision is made
+    pub min_samples: usize,
+}
+
+impl Default for SequentialConfig {
+    fn default() -> Self {
+        Self {
+            alpha: 0.05,
+            method: SequentialMethod::Msprt {
+                mixing_variance: 0.01,
+            },
+            min_samples: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SequentialDecision {
+    /// Not enough evidence yet, keep collecting data
+    Continue,
+    /// The effect is significantly positive (B above A, or above the null mean)
+    Superior,
+    /// The effect is significantly negative
+    Inferior,
+    /// A group-sequential design used its whole budget without rejecting
+    MaxSamplesReached,
+}
+
+impl SequentialDecision {
+    pub fn should_stop(&self) -> bool {
+        !matches!(self, SequentialDecision::Continue)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SequentialResult {
+    /// 1-based index of this interim analysis
+    pub look: usize,
+    pub sample_size: usize,
+    /// Estimated effect: mean minus null mean, or mean(B) - mean(A)
+    pub estimate: f64,
+    /// Mixture likelihood ratio for mSPRT, z statistic for group-sequential
+    pub statistic: f64,
+    /// Rejection threshold the statistic was compared against
+    pub boundary: f64,
+    /// Always-valid p-value (mSPRT only)
+    pub p_value: Option<f64>,
+    /// Cumulative alpha spent so far (group-sequential only)
+    pub alpha_spent: Option<f64>,
+    pub decision: SequentialDecision,
+}
+
+/// Stateful sequential hypothesis test that can be consulted after every batch
+/// without inflating the false positive rate.
+#[derive(Debug, Clone)]
+pub struct SequentialTest {
+    config: SequentialConfig,
+    looks: usize,
+    p_value: f64,
+    alpha_spent: f64,
+    stopped: Option<SequentialDecision>,
+}
+
+impl SequentialTest {
+    pub fn new(config: SequentialConfig) -> Result<Self> {
+        if !(config.alpha > 0.0 && config.alpha < 1.0) {
+            return Err(CoreError::Validation(format!(
+                "alpha must be in (0, 1), got {}",
+                config.alpha
+            )));
+        }
+        match &config.method {
+            SequentialMethod::Msprt { mixing_variance } if *mixing_variance <= 0.0 => {
+                return Err(CoreError::Validation(
+                    "mSPRT mixing_variance must be positive".to_string(),
+                ));
+            }
+            SequentialMethod::GroupSequential { max_samples, .. } if *max_samples == 0 => {
+                return Err(CoreError::Validation(
+                    "Group-sequential max_samples must be positive".to_string(),
+                ));
+            }
+            _ => {}
+        }
+
+        Ok(Self {
+            config,
+            looks: 0,
+            p_value: 1.0,
+            alpha_spent: 0.0,
+            stopped: None,
+        })
+    }
+
+    pub fn config(&self) -> &SequentialConfig {
+        &self.config
+    }
+
+    pub fn looks(&self) -> usize {
+        self.looks
+    }
+
+    /// Test whether the mean of all observations so far differs from `null_mean`
+    pub fn update_one_sample(&mut self, values: &[f64], null_mean: f64) -> SequentialResult {
+        let n = values.len();
+        if n < 2 {
+            return self.record(n, 0.0, f64::INFINITY, 0);
+        }
+        let estimate = values.mean() - null_mean;
+        let variance = values.variance() / n as f64;
+        self.record(n, estimate, variance, n)
+    }
+
+    /// Test whether mean(B) differs from mean(A) over all observations so far
+    pub fn update_two_sample(&mut self, sample_a: &[f64], sample_b: &[f64]) -> SequentialResult {
+        let n = sample_a.len() + sample_b.len();
+        if sample_a.len() < 2 || sample_b.len() < 2 {
+            return self.record(n, 0.0, f64::INFINITY, 0);
+        }
+        let estimate = sample_b.mean() - sample_a.mean();
+        let variance = sample_a.variance() / sample_a.len() as f64
+            + sample_b.variance() / sample_b.len() as f64;
+        let per_arm = sample_a.len().min(sample_b.len());
+        self.record(n, estimate, variance, per_arm)
+    }
+
+    fn record(
+        &mut self,
+        sample_size: usize,
+        estimate: f64,
+        variance: f64,
+        per_arm: usize,
+    ) -> SequentialResult {
+        self.looks += 1;
+
+        // Once stopped, the decision is final; repeat it instead of re-testing
+        if let Some(decision) = self.stopped {
+            return SequentialResult {
+                look: self.looks,
+                sample_size,
+                estimate,
+                statistic: f64::NAN,
+                boundary: f64::NAN,
+                p_value: self.msprt_p_value(),
+                alpha_spent: self.group_alpha_spent(),
+                decision,
+            };
+        }
+
+        // Guard against zero-variance samples (e.g. all scores identical)
+        let variance = variance.max(f64::EPSILON);
+        let alpha = self.config.alpha;
+        let eligible = per_arm >= self.config.min_samples.max(2);
+
+        let (statistic, boundary, rejected, exhausted) = match self.config.method.clone() {
+            SequentialMethod::Msprt { mixing_variance } => {
+                let statistic = if variance.is_finite() {
+                    Self::mixture_likelihood_ratio(estimate, variance, mixing_variance)
+                } else {
+                    1.0
+                };
+                if eligible {
+                    self.p_value = self.p_value.min(1.0 / statistic);
+                }
+                (statistic, 1.0 / alpha, eligible && self.p_value <= alpha, false)
+            }
+            SequentialMethod::GroupSequential {
+                spending,
+                max_samples,
+            } => {
+                let fraction = sample_size as f64 / max_samples as f64;
+                let statistic = if variance.is_finite() {
+                    estimate / variance.sqrt()
+                } else {
+                    0.0
+                };
+                if !eligible {
+                    (statistic, f64::INFINITY, false, false)
+                } else {
+                    // Spend the increment of alpha at this look. Using the
+                    // per-look increment as a two-sided Bonferroni bound is
+                    // conservative relative to exact recursive boundaries.
+                    let cumulative = spending.spent(alpha, fraction);
+                    let increment = (cumulative - self.alpha_spent).max(0.0);
+                    self.alpha_spent = self.alpha_spent.max(cumulative);
+                    let boundary = if increment > 0.0 {
+                        standard_normal().inverse_cdf(1.0 - increment / 2.0)
+                    } else {
+                        f64::INFINITY
+                    };
+                    (
+                        statistic,
+                        boundary,
+                        statistic.abs() >= boundary,
+                        fraction >= 1.0,
+                    )
+                }
+            }
+        };
+
+        let decision = if rejected {
+            if estimate > 0.0 {
+                SequentialDecision::Superior
+            } else {
+                SequentialDecision::Inferior
+            }
+        } else if exhausted {
+            SequentialDecision::MaxSamplesReached
+        } else {
+            SequentialDecision::Continue
+        };
+
+        if decision.should_stop() {
+            self.stopped = Some(decision);
+        }
+
+        SequentialResult {
+            look: self.looks,
+            sample_size,
+            estimate,
+            statistic,
+            boundary,
+            p_value: self.msprt_p_value(),
+            alpha_spent: self.group_alpha_spent(),
+            decision,
+        }
+    }
+
+    /// Normal-mixture likelihood ratio of Johari et al. for an effect estimate
+    /// with sampling variance `variance` and mixing prior N(0, `tau_sq`)
+    fn mixture_likelihood_ratio(estimate: f64, variance: f64, tau_sq: f64) -> f64 {
+        let total = variance + tau_sq;
+        (variance / total).sqrt() * (tau_sq * estimate * estimate / (2.0 * variance * total)).exp()
+    }
+
+    fn msprt_p_value(&self) -> Option<f64> {
+        match self.config.method {
+            SequentialMethod::Msprt { .. } => Some(self.p_value),
+            SequentialMethod::GroupSequential { .. } => None,
+        }
+    }
+
+    fn group_alpha_spent(&self) -> Option<f64> {
+        match self.config.method {
+            SequentialMethod::Msprt { .. } => None,
+            SequentialMethod::GroupSequential { .. } => Some(self.alpha_spent),
+        }
+    }
+}
+
+fn standard_normal() -> Normal {
+    Normal::new(0.0, 1.0).unwrap()
+}
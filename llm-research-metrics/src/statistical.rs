@@ -229,3 +229,12 @@ impl StatisticalAnalyzer {
         sign * y
     }
 }
+
+/// Linearly interpolated quantile of already sorted values
+pub(crate) fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    let weight = position - lower as f64;
+    sorted[lower] * (1.0 - weight) + sorted[upper] * weight
+}
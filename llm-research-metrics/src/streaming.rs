@@ -0,0 +1,558 @@
+use chrono::Utc;
+use llm_research_core::{CoreError, DistributionMetric, Result};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::aggregators::{AggregatedMetrics, Histogram, HistogramBin};
+
+/// Count, mean and variance accumulated with Welford's algorithm. Partial
+/// results from different batches or workers combine exactly with `merge`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RunningStats {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for RunningStats {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl RunningStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Combine with another accumulator (Chan et al. parallel update)
+    pub fn merge(&mut self, other: &RunningStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        }
+
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * self.count as f64 * other.count as f64 / count as f64;
+        self.count = count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Population variance, matching `MetricAggregator::aggregate`
+    pub fn variance(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.m2 / self.count as f64
+        }
+    }
+
+    /// Unbiased sample variance
+    pub fn sample_variance(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.m2 / (self.count - 1) as f64
+        }
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Centroid {
+    pub mean: f64,
+    pub weight: f64,
+}
+
+/// Merging t-digest (Dunning) for quantile estimation in bounded memory.
+/// Accuracy is highest in the tails, which is where p95/p99 live.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    #[serde(default)]
+    buffer: Vec<f64>,
+    count: u64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(100.0)
+    }
+}
+
+impl TDigest {
+    /// `compression` bounds the number of centroids kept (roughly 2x)
+    pub fn new(compression: f64) -> Self {
+        Self {
+            compression: compression.max(10.0),
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn compression(&self) -> f64 {
+        self.compression
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn push(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        self.buffer.push(value);
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+
+        if self.buffer.len() >= self.buffer_capacity() {
+            self.compress();
+        }
+    }
+
+    pub fn merge(&mut self, other: &TDigest) {
+        if other.count == 0 {
+            return;
+        }
+        self.centroids.extend_from_slice(&other.centroids);
+        self.buffer.extend_from_slice(&other.buffer);
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.compress();
+    }
+
+    /// Fold buffered values into the centroid list
+    pub fn compress(&mut self) {
+        if self.buffer.is_empty() && self.centroids.len() <= self.compression as usize {
+            return;
+        }
+
+        let mut points: Vec<Centroid> = std::mem::take(&mut self.centroids);
+        points.extend(
+            self.buffer
+                .drain(..)
+                .map(|mean| Centroid { mean, weight: 1.0 }),
+        );
+        points.sort_by(|a, b| a.mean.partial_cmp(&b.mean).unwrap());
+
+        let total: f64 = points.iter().map(|c| c.weight).sum();
+        let mut merged = Vec::with_capacity(self.compression as usize * 2);
+        let mut points = points.into_iter();
+        let mut current = match points.next() {
+            Some(first) => first,
+            None => return,
+        };
+
+        let mut q_start = 0.0;
+        let mut q_limit = self.q_limit(q_start);
+        for point in points {
+            let q = q_start + (current.weight + point.weight) / total;
+            if q <= q_limit {
+                let weight = current.weight + point.weight;
+                current.mean += (point.mean - current.mean) * point.weight / weight;
+                current.weight = weight;
+            } else {
+                q_start += current.weight / total;
+                q_limit = self.q_limit(q_start);
+                merged.push(current);
+                current = point;
+            }
+        }
+        merged.push(current);
+
+        self.centroids = merged;
+    }
+
+    /// Estimate the `q`-quantile, q in [0, 1]
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        if !self.buffer.is_empty() {
+            let mut compressed = self.clone();
+            compressed.compress();
+            return compressed.quantile(q);
+        }
+
+        let q = q.clamp(0.0, 1.0);
+        if q == 0.0 {
+            return Some(self.min);
+        }
+        if q == 1.0 {
+            return Some(self.max);
+        }
+        if self.centroids.len() == 1 {
+            return Some(self.centroids[0].mean);
+        }
+
+        let total = self.count as f64;
+        let target = q * total;
+
+        // Each centroid's mass is centred on its mean; interpolate between
+        // neighbouring centres, and towards min/max at the ends
+        let first = self.centroids[0];
+        if target < first.weight / 2.0 {
+            return Some(self.min + (first.mean - self.min) * target / (first.weight / 2.0));
+        }
+
+        let mut cumulative = first.weight / 2.0;
+        for pair in self.centroids.windows(2) {
+            let step = (pair[0].weight + pair[1].weight) / 2.0;
+            if target < cumulative + step {
+                let fraction = (target - cumulative) / step;
+                return Some(pair[0].mean + (pair[1].mean - pair[0].mean) * fraction);
+            }
+            cumulative += step;
+        }
+
+        let last = self.centroids[self.centroids.len() - 1];
+        let remaining = (target - cumulative) / (last.weight / 2.0);
+        Some(last.mean + (self.max - last.mean) * remaining.min(1.0))
+    }
+
+    /// Number of centroids after compression
+    pub fn centroid_count(&self) -> usize {
+        let mut compressed = self.clone();
+        compressed.compress();
+        compressed.centroids.len()
+    }
+
+    fn buffer_capacity(&self) -> usize {
+        (self.compression as usize) * 5
+    }
+
+    /// Largest cumulative quantile a centroid starting at `q` may extend to,
+    /// from the arcsine scale function k(q) = d/(2pi) * asin(2q - 1)
+    fn q_limit(&self, q: f64) -> f64 {
+        let k = self.compression / (2.0 * std::f64::consts::PI) * (2.0 * q - 1.0).asin() + 1.0;
+        if k >= self.compression / 4.0 {
+            return 1.0;
+        }
+        ((2.0 * std::f64::consts::PI * k / self.compression).sin() + 1.0) / 2.0
+    }
+}
+
+/// Histogram with fixed bucket edges so that partial histograms can be added
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BucketHistogram {
+    edges: Vec<f64>,
+    counts: Vec<u64>,
+    underflow: u64,
+    overflow: u64,
+}
+
+impl BucketHistogram {
+    /// `edges` must be finite and strictly increasing; the last bucket
+    /// includes its upper edge
+    pub fn new(edges: Vec<f64>) -> Result<Self> {
+        if edges.len() < 2 {
+            return Err(CoreError::Validation(
+                "A histogram needs at least two bucket edges".to_string(),
+            ));
+        }
+        if edges.iter().any(|e| !e.is_finite()) || edges.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(CoreError::Validation(
+                "Histogram edges must be finite and strictly increasing".to_string(),
+            ));
+        }
+
+        let buckets = edges.len() - 1;
+        Ok(Self {
+            edges,
+            counts: vec![0; buckets],
+            underflow: 0,
+            overflow: 0,
+        })
+    }
+
+    /// `buckets` equal-width buckets spanning [min, max]
+    pub fn uniform(min: f64, max: f64, buckets: usize) -> Result<Self> {
+        if buckets == 0 {
+            return Err(CoreError::Validation(
+                "A histogram needs at least one bucket".to_string(),
+            ));
+        }
+        let width = (max - min) / buckets as f64;
+        let edges = (0..=buckets).map(|i| min + width * i as f64).collect();
+        Self::new(edges)
+    }
+
+    pub fn edges(&self) -> &[f64] {
+        &self.edges
+    }
+
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Values below the first edge
+    pub fn underflow(&self) -> u64 {
+        self.underflow
+    }
+
+    /// Values above the last edge
+    pub fn overflow(&self) -> u64 {
+        self.overflow
+    }
+
+    pub fn total_count(&self) -> u64 {
+        self.counts.iter().sum::<u64>() + self.underflow + self.overflow
+    }
+
+    pub fn push(&mut self, value: f64) {
+        let last = self.edges[self.edges.len() - 1];
+        if value < self.edges[0] {
+            self.underflow += 1;
+        } else if value > last {
+            self.overflow += 1;
+        } else if value == last {
+            *self.counts.last_mut().unwrap() += 1;
+        } else {
+            // Index of the first edge greater than value, minus one
+            let bucket = self.edges.partition_point(|&e| e <= value) - 1;
+            self.counts[bucket] += 1;
+        }
+    }
+
+    pub fn merge(&mut self, other: &BucketHistogram) -> Result<()> {
+        if self.edges != other.edges {
+            return Err(CoreError::Validation(
+                "Cannot merge histograms with different bucket edges".to_string(),
+            ));
+        }
+        for (count, other_count) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other_count;
+        }
+        self.underflow += other.underflow;
+        self.overflow += other.overflow;
+        Ok(())
+    }
+
+    /// Convert to the `Histogram` report type; frequencies are relative to
+    /// all values seen, including out-of-range ones
+    pub fn to_histogram(&self) -> Histogram {
+        let total = self.total_count();
+        let bins = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| HistogramBin {
+                lower_bound: self.edges[i],
+                upper_bound: self.edges[i + 1],
+                count: count as usize,
+                frequency: if total == 0 {
+                    0.0
+                } else {
+                    count as f64 / total as f64
+                },
+            })
+            .collect();
+
+        Histogram {
+            bins,
+            total_count: total as usize,
+        }
+    }
+}
+
+/// Mergeable, serializable counterpart of `MetricAggregator::aggregate`.
+/// Each batch or worker aggregates its own values; partial aggregators are
+/// merged without ever holding the full set of values in memory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StreamingAggregator {
+    stats: RunningStats,
+    digest: TDigest,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    histogram: Option<BucketHistogram>,
+}
+
+impl StreamingAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_compression(mut self, compression: f64) -> Self {
+        self.digest = TDigest::new(compression);
+        self
+    }
+
+    pub fn with_histogram(mut self, histogram: BucketHistogram) -> Self {
+        self.histogram = Some(histogram);
+        self
+    }
+
+    pub fn from_values(values: &[f64]) -> Self {
+        let mut aggregator = Self::new();
+        aggregator.extend(values);
+        aggregator
+    }
+
+    /// Non-finite values are ignored
+    pub fn push(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        self.stats.push(value);
+        self.digest.push(value);
+        if let Some(histogram) = self.histogram.as_mut() {
+            histogram.push(value);
+        }
+    }
+
+    pub fn extend(&mut self, values: &[f64]) {
+        for &value in values {
+            self.push(value);
+        }
+    }
+
+    pub fn merge(&mut self, other: &StreamingAggregator) -> Result<()> {
+        match (self.histogram.as_mut(), other.histogram.as_ref()) {
+            (Some(histogram), Some(other_histogram)) => histogram.merge(other_histogram)?,
+            (None, None) => {}
+            _ => {
+                return Err(CoreError::Validation(
+                    "Cannot merge aggregators with and without a histogram".to_string(),
+                ))
+            }
+        }
+        self.stats.merge(&other.stats);
+        self.digest.merge(&other.digest);
+        Ok(())
+    }
+
+    /// Merge any number of partial aggregators into one
+    pub fn merge_all<'a, I>(parts: I) -> Result<Option<StreamingAggregator>>
+    where
+        I: IntoIterator<Item = &'a StreamingAggregator>,
+    {
+        let mut parts = parts.into_iter();
+        let mut merged = match parts.next() {
+            Some(first) => first.clone(),
+            None => return Ok(None),
+        };
+        for part in parts {
+            merged.merge(part)?;
+        }
+        Ok(Some(merged))
+    }
+
+    pub fn count(&self) -> u64 {
+        self.stats.count()
+    }
+
+    pub fn stats(&self) -> &RunningStats {
+        &self.stats
+    }
+
+    pub fn digest(&self) -> &TDigest {
+        &self.digest
+    }
+
+    /// Estimated percentile, `percentile` in [0, 100]
+    pub fn percentile(&self, percentile: f64) -> Option<f64> {
+        self.digest.quantile(percentile / 100.0)
+    }
+
+    pub fn histogram(&self) -> Option<Histogram> {
+        self.histogram.as_ref().map(|h| h.to_histogram())
+    }
+
+    /// Summary in the same shape as `MetricAggregator::aggregate`
+    pub fn summary(&self) -> AggregatedMetrics {
+        let decimal = |value: f64| Decimal::try_from(value).unwrap_or_default();
+        let percentile = |p: f64| decimal(self.percentile(p).unwrap_or(0.0));
+
+        AggregatedMetrics {
+            mean: decimal(self.stats.mean()),
+            median: percentile(50.0),
+            std_dev: decimal(self.stats.std_dev()),
+            min: decimal(self.stats.min().unwrap_or(0.0)),
+            max: decimal(self.stats.max().unwrap_or(0.0)),
+            p50: percentile(50.0),
+            p90: percentile(90.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+            count: self.stats.count() as usize,
+            sum: decimal(self.stats.sum()),
+        }
+    }
+
+    pub fn to_distribution_metric(&self, name: impl Into<String>, step: u64) -> DistributionMetric {
+        let percentile = |p: f64| self.percentile(p).unwrap_or(0.0);
+
+        DistributionMetric {
+            name: name.into(),
+            step,
+            min: self.stats.min().unwrap_or(0.0),
+            max: self.stats.max().unwrap_or(0.0),
+            mean: self.stats.mean(),
+            median: percentile(50.0),
+            stddev: self.stats.std_dev(),
+            percentile_25: percentile(25.0),
+            percentile_75: percentile(75.0),
+            percentile_95: percentile(95.0),
+            percentile_99: percentile(99.0),
+            count: self.stats.count(),
+            timestamp: Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use llm_research_metrics::agreement::*;
+use approx::assert_relative_eq;
+
+/// Reliability data from Krippendorff (2011), "Computing Krippendorff's
+/// Alpha-Reliability": 4 coders × 12 units with missing values
+fn krippendorff_example() -> AnnotationMatrix {
+    let rows: [[Option<f64>; 12]; 4] = [
+        [Some(1.0), Some(2.0), Some(3.0), Some(3.0), Some(2.0), Some(1.0), Some(4.0), Some(1.0), Some(2.0), None, None, None],
+        [Some(1.0), Some(2.0), Some(3.0), Some(3.0), Some(2.0), Some(2.0), Some(4.0), Some(1.0), Some(2.0), Some(5.0), None, Some(3.0)],
+        [None, Some(3.0), Some(3.0), Some(3.0), Some(2.0), Some(3.0), Some(4.0), Some(2.0), Some(2.0), Some(5.0), Some(1.0), None],
+        [Some(1.0), Some(2.0), Some(3.0), Some(3.0), Some(2.0), Some(4.0), Some(4.0), Some(1.0), Some(2.0), Some(5.0), Some(1.0), None],
+    ];
+    AnnotationMatrix::new(rows.iter().map(|r| r.to_vec()).collect()).unwrap()
+}
+
+/// Fleiss (1971) example as reproduced on Wikipedia: 14 raters, 10 subjects,
+/// 5 categories, given as per-subject category counts
+fn fleiss_example() -> AnnotationMatrix {
+    let counts = [
+        [0, 0, 0, 0, 14],
+        [0, 2, 6, 4, 2],
+        [0, 0, 3, 5, 6],
+        [0, 3, 9, 2, 0],
+        [2, 2, 8, 1, 1],
+        [7, 7, 0, 0, 0],
+        [3, 2, 6, 3, 0],
+        [2, 5, 3, 2, 2],
+        [6, 5, 2, 1, 0],
+        [0, 2, 2, 3, 7],
+    ];
+    let mut ratings = vec![vec![None; counts.len()]; 14];
+    for (item, row) in counts.iter().enumerate() {
+        let mut rater = 0;
+        for (category, &count) in row.iter().enumerate() {
+            for _ in 0..count {
+                ratings[rater][item] = Some(category as f64);
+                rater += 1;
+            }
+        }
+    }
+    AnnotationMatrix::new(ratings).unwrap()
+}
+
+// ===== Matrix Construction Tests =====
+
+#[test]
+fn test_matrix_rejects_ragged_rows() {
+    let result = AnnotationMatrix::new(vec![vec![Some(1.0), Some(2.0)], vec![Some(1.0)]]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_matrix_from_labels() {
+    let matrix = AnnotationMatrix::from_labels(&[
+        vec![Some("yes"), Some("no"), None],
+        vec![Some("yes"), Some("yes"), Some("no")],
+    ])
+    .unwrap();
+
+    assert_eq!(matrix.num_annotators(), 2);
+    assert_eq!(matrix.num_items(), 3);
+    assert_eq!(matrix.categories().unwrap(), &["no".to_string(), "yes".to_string()]);
+}
+
+#[test]
+fn test_matrix_from_ordered_labels_rejects_unknown_category() {
+    let result = AnnotationMatrix::from_ordered_labels(
+        &[vec![Some("low"), Some("extreme")]],
+        &["low", "medium", "high"],
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_matrix_with_annotators() {
+    let matrix = AnnotationMatrix::new(vec![vec![Some(1.0)], vec![Some(1.0)]])
+        .unwrap()
+        .with_annotators(vec!["human".to_string(), "judge".to_string()])
+        .unwrap();
+    assert_eq!(matrix.annotators(), &["human".to_string(), "judge".to_string()]);
+
+    let wrong = AnnotationMatrix::new(vec![vec![Some(1.0)]])
+        .unwrap()
+        .with_annotators(vec![]);
+    assert!(wrong.is_err());
+}
+
+// ===== Percent Agreement Tests =====
+
+#[test]
+fn test_percent_agreement_perfect() {
+    let matrix = AnnotationMatrix::from_labels(&[
+        vec![Some("a"), Some("b"), Some("c")],
+        vec![Some("a"), Some("b"), Some("c")],
+        vec![Some("a"), None, Some("c")],
+    ])
+    .unwrap();
+
+    assert_relative_eq!(AgreementAnalyzer::percent_agreement(&matrix).unwrap(), 1.0);
+}
+
+#[test]
+fn test_percent_agreement_partial() {
+    let matrix = AnnotationMatrix::from_labels(&[
+        vec![Some("a"), Some("a")],
+        vec![Some("a"), Some("b")],
+    ])
+    .unwrap();
+
+    assert_relative_eq!(AgreementAnalyzer::percent_agreement(&matrix).unwrap(), 0.5);
+}
+
+#[test]
+fn test_percent_agreement_requires_pairable_items() {
+    let matrix = AnnotationMatrix::new(vec![vec![Some(1.0), None], vec![None, Some(1.0)]]).unwrap();
+    assert!(AgreementAnalyzer::percent_agreement(&matrix).is_err());
+}
+
+// ===== Cohen's Kappa Tests =====
+
+#[test]
+fn test_cohens_kappa_textbook_example() {
+    // 20 yes/yes, 5 yes/no, 10 no/yes, 15 no/no
+    let mut a = Vec::new();
+    let mut b = Vec::new();
+    for (x, y, n) in [("yes", "yes", 20), ("yes", "no", 5), ("no", "yes", 10), ("no", "no", 15)] {
+        for _ in 0..n {
+            a.push(Some(x));
+            b.push(Some(y));
+        }
+    }
+    let matrix = AnnotationMatrix::from_labels(&[a, b]).unwrap();
+
+    assert_relative_eq!(AgreementAnalyzer::cohens_kappa(&matrix, 0, 1).unwrap(), 0.4, epsilon = 1e-12);
+}
+
+#[test]
+fn test_cohens_kappa_ignores_missing() {
+    let matrix = AnnotationMatrix::from_labels(&[
+        vec![Some("x"), Some("y"), None, Some("x")],
+        vec![Some("x"), Some("y"), Some("x"), None],
+    ])
+    .unwrap();
+
+    assert_relative_eq!(AgreementAnalyzer::cohens_kappa(&matrix, 0, 1).unwrap(), 1.0);
+}
+
+#[test]
+fn test_cohens_kappa_invalid_pair() {
+    let matrix = krippendorff_example();
+    assert!(AgreementAnalyzer::cohens_kappa(&matrix, 0, 0).is_err());
+    assert!(AgreementAnalyzer::cohens_kappa(&matrix, 0, 9).is_err());
+}
+
+#[test]
+fn test_cohens_kappa_undefined_without_variation() {
+    let matrix = AnnotationMatrix::from_labels(&[
+        vec![Some("x"), Some("x")],
+        vec![Some("x"), Some("x")],
+    ])
+    .unwrap();
+    assert!(AgreementAnalyzer::cohens_kappa(&matrix, 0, 1).is_err());
+}
+
+// ===== Fleiss' Kappa Tests =====
+
+#[test]
+fn test_fleiss_kappa_reference_value() {
+    let kappa = AgreementAnalyzer::fleiss_kappa(&fleiss_example()).unwrap();
+    assert_relative_eq!(kappa, 0.210, epsilon = 1e-3);
+}
+
+#[test]
+fn test_fleiss_kappa_two_raters_matches_scott_pi_range() {
+    let matrix = AnnotationMatrix::from_labels(&[
+        vec![Some("a"), Some("b"), Some("a"), Some("b")],
+        vec![Some("a"), Some("b"), Some("b"), Some("b")],
+    ])
+    .unwrap();
+
+    let kappa = AgreementAnalyzer::fleiss_kappa(&matrix).unwrap();
+    assert!(kappa > 0.0 && kappa < 1.0);
+}
+
+// ===== Krippendorff's Alpha Tests =====
+
+#[test]
+fn test_krippendorff_alpha_nominal() {
+    let alpha =
+        AgreementAnalyzer::krippendorff_alpha(&krippendorff_example(), MeasurementLevel::Nominal)
+            .unwrap();
+    assert_relative_eq!(alpha, 0.743, epsilon = 1e-3);
+}
+
+#[test]
+fn test_krippendorff_alpha_ordinal() {
+    let alpha =
+        AgreementAnalyzer::krippendorff_alpha(&krippendorff_example(), MeasurementLevel::Ordinal)
+            .unwrap();
+    assert_relative_eq!(alpha, 0.815, epsilon = 1e-3);
+}
+
+#[test]
+fn test_krippendorff_alpha_interval() {
+    let alpha =
+        AgreementAnalyzer::krippendorff_alpha(&krippendorff_example(), MeasurementLevel::Interval)
+            .unwrap();
+    assert_relative_eq!(alpha, 0.849, epsilon = 1e-3);
+}
+
+#[test]
+fn test_krippendorff_alpha_undefined_for_constant_ratings() {
+    let matrix = AnnotationMatrix::new(vec![vec![Some(1.0); 3], vec![Some(1.0); 3]]).unwrap();
+    assert!(AgreementAnalyzer::krippendorff_alpha(&matrix, MeasurementLevel::Nominal).is_err());
+}
+
+// ===== Report Tests =====
+
+#[test]
+fn test_agreement_report() {
+    let matrix = krippendorff_example()
+        .with_annotators(vec![
+            "A".to_string(),
+            "B".to_string(),
+            "C".to_string(),
+            "judge".to_string(),
+        ])
+        .unwrap();
+
+    let report = AgreementAnalyzer::report(&matrix, MeasurementLevel::Interval);
+
+    assert_eq!(report.annotators, 4);
+    assert_eq!(report.items, 12);
+    assert_eq!(report.pairable_items, 11);
+    assert_eq!(report.pairwise_cohens_kappa.len(), 6);
+    assert_relative_eq!(report.krippendorff_alpha.unwrap(), 0.849, epsilon = 1e-3);
+    assert!(report.percent_agreement.is_some());
+    assert!(report.fleiss_kappa.is_some());
+
+    let a_judge = report
+        .pairwise_cohens_kappa
+        .iter()
+        .find(|p| p.annotator_a == "A" && p.annotator_b == "judge")
+        .unwrap();
+    assert_eq!(a_judge.items_compared, 9);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["level"], "interval");
+}
@@ -0,0 +1,273 @@
+use llm_research_metrics::bayesian::*;
+use approx::assert_relative_eq;
+
+// ===== Beta-Binomial Tests =====
+
+#[test]
+fn test_binary_posterior_mean_uniform_prior() {
+    let a = BinaryOutcome::new(30, 100);
+    let b = BinaryOutcome::new(45, 100);
+    let result =
+        BayesianAnalyzer::compare_binary(a, b, BetaPrior::uniform(), &BayesianConfig::default())
+            .unwrap();
+
+    assert_relative_eq!(result.posterior_a.mean, 31.0 / 102.0, epsilon = 1e-12);
+    assert_relative_eq!(result.posterior_b.mean, 46.0 / 102.0, epsilon = 1e-12);
+    assert!(result.posterior_a.credible_interval.0 < result.posterior_a.mean);
+    assert!(result.posterior_a.credible_interval.1 > result.posterior_a.mean);
+}
+
+#[test]
+fn test_binary_clear_winner() {
+    let a = BinaryOutcome::new(200, 1000);
+    let b = BinaryOutcome::new(300, 1000);
+    let result =
+        BayesianAnalyzer::compare_binary(a, b, BetaPrior::uniform(), &BayesianConfig::default())
+            .unwrap();
+
+    assert_eq!(result.method, PosteriorMethod::Analytic);
+    assert!(result.prob_b_better > 0.999);
+    assert!(result.expected_loss_b < result.expected_loss_a);
+    assert!(result.difference.credible_interval.0 > 0.0);
+}
+
+#[test]
+fn test_binary_identical_variants() {
+    let outcome = BinaryOutcome::new(50, 100);
+    let result = BayesianAnalyzer::compare_binary(
+        outcome,
+        outcome,
+        BetaPrior::uniform(),
+        &BayesianConfig::default(),
+    )
+    .unwrap();
+
+    assert_relative_eq!(result.prob_b_better, 0.5, epsilon = 0.01);
+    assert_relative_eq!(result.expected_loss_a, result.expected_loss_b, epsilon = 0.005);
+}
+
+#[test]
+fn test_binary_analytic_matches_monte_carlo() {
+    let a = BinaryOutcome::new(40, 120);
+    let b = BinaryOutcome::new(52, 130);
+    let config = BayesianConfig::default();
+
+    let analytic =
+        BayesianAnalyzer::compare_binary(a, b, BetaPrior::new(1.0, 1.0), &config).unwrap();
+    let monte_carlo =
+        BayesianAnalyzer::compare_binary(a, b, BetaPrior::new(1.0 + 1e-9, 1.0), &config).unwrap();
+
+    assert_eq!(analytic.method, PosteriorMethod::Analytic);
+    assert_eq!(monte_carlo.method, PosteriorMethod::MonteCarlo);
+    assert_relative_eq!(analytic.prob_b_better, monte_carlo.prob_b_better, epsilon = 0.01);
+}
+
+#[test]
+fn test_binary_seeded_results_are_reproducible() {
+    let a = BinaryOutcome::new(12, 40);
+    let b = BinaryOutcome::new(15, 40);
+    let config = BayesianConfig::default().with_seed(7).with_samples(10_000);
+
+    let first = BayesianAnalyzer::compare_binary(a, b, BetaPrior::jeffreys(), &config).unwrap();
+    let second = BayesianAnalyzer::compare_binary(a, b, BetaPrior::jeffreys(), &config).unwrap();
+
+    assert_eq!(first.prob_b_better, second.prob_b_better);
+    assert_eq!(first.difference, second.difference);
+    assert_eq!(first.expected_loss_a, second.expected_loss_a);
+}
+
+#[test]
+fn test_binary_from_outcomes() {
+    let outcome = BinaryOutcome::from_outcomes(&[true, false, true, true]);
+    assert_eq!(outcome.successes, 3);
+    assert_eq!(outcome.trials, 4);
+}
+
+#[test]
+fn test_binary_rejects_invalid_outcome() {
+    let result = BayesianAnalyzer::compare_binary(
+        BinaryOutcome::new(11, 10),
+        BinaryOutcome::new(5, 10),
+        BetaPrior::uniform(),
+        &BayesianConfig::default(),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_binary_rejects_invalid_prior() {
+    let result = BayesianAnalyzer::compare_binary(
+        BinaryOutcome::new(1, 10),
+        BinaryOutcome::new(5, 10),
+        BetaPrior::new(0.0, 1.0),
+        &BayesianConfig::default(),
+    );
+    assert!(result.is_err());
+}
+
+// ===== ROPE Tests =====
+
+#[test]
+fn test_rope_prefers_b() {
+    let config = BayesianConfig::default().with_rope(-0.01, 0.01);
+    let result = BayesianAnalyzer::compare_binary(
+        BinaryOutcome::new(200, 1000),
+        BinaryOutcome::new(300, 1000),
+        BetaPrior::uniform(),
+        &config,
+    )
+    .unwrap();
+
+    let rope = result.rope.unwrap();
+    assert_eq!(rope.decision, RopeDecision::PreferB);
+    assert!(rope.probability_in_rope < 0.01);
+}
+
+#[test]
+fn test_rope_prefers_a() {
+    let config = BayesianConfig::default().with_rope(-0.01, 0.01);
+    let result = BayesianAnalyzer::compare_binary(
+        BinaryOutcome::new(300, 1000),
+        BinaryOutcome::new(200, 1000),
+        BetaPrior::uniform(),
+        &config,
+    )
+    .unwrap();
+
+    assert_eq!(result.rope.unwrap().decision, RopeDecision::PreferA);
+}
+
+#[test]
+fn test_rope_equivalent() {
+    let config = BayesianConfig::default().with_rope(-0.05, 0.05);
+    let result = BayesianAnalyzer::compare_binary(
+        BinaryOutcome::new(5000, 10000),
+        BinaryOutcome::new(5010, 10000),
+        BetaPrior::uniform(),
+        &config,
+    )
+    .unwrap();
+
+    let rope = result.rope.unwrap();
+    assert_eq!(rope.decision, RopeDecision::Equivalent);
+    assert!(rope.probability_in_rope > 0.99);
+}
+
+#[test]
+fn test_rope_undecided_with_little_data() {
+    let config = BayesianConfig::default().with_rope(-0.01, 0.01);
+    let result = BayesianAnalyzer::compare_binary(
+        BinaryOutcome::new(5, 10),
+        BinaryOutcome::new(6, 10),
+        BetaPrior::uniform(),
+        &config,
+    )
+    .unwrap();
+
+    assert_eq!(result.rope.unwrap().decision, RopeDecision::Undecided);
+}
+
+#[test]
+fn test_no_rope_by_default() {
+    let result = BayesianAnalyzer::compare_binary(
+        BinaryOutcome::new(5, 10),
+        BinaryOutcome::new(6, 10),
+        BetaPrior::uniform(),
+        &BayesianConfig::default(),
+    )
+    .unwrap();
+
+    assert!(result.rope.is_none());
+}
+
+#[test]
+fn test_invalid_config() {
+    let outcome = BinaryOutcome::new(5, 10);
+
+    let bad_level = BayesianConfig::default().with_credible_level(1.5);
+    assert!(BayesianAnalyzer::compare_binary(outcome, outcome, BetaPrior::uniform(), &bad_level)
+        .is_err());
+
+    let bad_rope = BayesianConfig::default().with_rope(0.1, -0.1);
+    assert!(BayesianAnalyzer::compare_binary(outcome, outcome, BetaPrior::uniform(), &bad_rope)
+        .is_err());
+
+    let no_samples = BayesianConfig::default().with_samples(0);
+    assert!(
+        BayesianAnalyzer::compare_binary(outcome, outcome, BetaPrior::uniform(), &no_samples)
+            .is_err()
+    );
+}
+
+// ===== Continuous Model Tests =====
+
+#[test]
+fn test_continuous_clear_difference() {
+    let sample_a = vec![0.60, 0.62, 0.58, 0.61, 0.59, 0.63, 0.60, 0.57];
+    let sample_b = vec![0.80, 0.82, 0.79, 0.81, 0.83, 0.78, 0.80, 0.84];
+
+    let result =
+        BayesianAnalyzer::compare_continuous(&sample_a, &sample_b, &BayesianConfig::default())
+            .unwrap();
+
+    assert!(result.prob_b_better > 0.999);
+    assert!(result.difference.mean > 0.15);
+    assert!(result.difference.credible_interval.0 > 0.0);
+    assert_eq!(result.method, PosteriorMethod::MonteCarlo);
+}
+
+#[test]
+fn test_continuous_posterior_centered_on_sample_mean() {
+    let sample_a = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+    let sample_b = vec![2.0, 3.0, 4.0, 5.0, 6.0];
+
+    let result =
+        BayesianAnalyzer::compare_continuous(&sample_a, &sample_b, &BayesianConfig::default())
+            .unwrap();
+
+    assert_relative_eq!(result.posterior_a.mean, 3.0, epsilon = 1e-12);
+    assert_relative_eq!(result.posterior_b.mean, 4.0, epsilon = 1e-12);
+    assert!(result.posterior_a.credible_interval.0 < 3.0);
+    assert!(result.posterior_a.credible_interval.1 > 3.0);
+    assert!(result.posterior_a.std_dev.is_finite());
+}
+
+#[test]
+fn test_continuous_constant_samples() {
+    let sample_a = vec![1.0, 1.0, 1.0];
+    let sample_b = vec![0.5, 0.5, 0.5];
+
+    let result =
+        BayesianAnalyzer::compare_continuous(&sample_a, &sample_b, &BayesianConfig::default())
+            .unwrap();
+
+    assert!(result.prob_b_better < 0.001);
+}
+
+#[test]
+fn test_continuous_requires_two_observations() {
+    let result =
+        BayesianAnalyzer::compare_continuous(&[1.0], &[1.0, 2.0], &BayesianConfig::default());
+    assert!(result.is_err());
+}
+
+// ===== Serialization Tests =====
+
+#[test]
+fn test_comparison_serialization() {
+    let config = BayesianConfig::default().with_rope(-0.02, 0.02).with_samples(1_000);
+    let result = BayesianAnalyzer::compare_binary(
+        BinaryOutcome::new(20, 50),
+        BinaryOutcome::new(25, 50),
+        BetaPrior::uniform(),
+        &config,
+    )
+    .unwrap();
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["method"], "analytic");
+    assert!(json["rope"]["decision"].is_string());
+
+    let restored: BayesianComparison = serde_json::from_value(json).unwrap();
+    assert_eq!(restored.prob_b_better, result.prob_b_better);
+}
@@ -0,0 +1,284 @@
+use llm_research_metrics::ranking::*;
+
+fn games(
+    model_a: &str,
+    model_b: &str,
+    outcome: PairwiseOutcome,
+    count: usize,
+) -> Vec<PairwiseComparison> {
+    (0..count)
+        .map(|_| PairwiseComparison::new(model_a, model_b, outcome))
+        .collect()
+}
+
+/// A beats B and C most of the time, B beats C most of the time
+fn tournament() -> Vec<PairwiseComparison> {
+    let mut comparisons = Vec::new();
+    comparisons.extend(games("model-a", "model-b", PairwiseOutcome::WinA, 30));
+    comparisons.extend(games("model-a", "model-b", PairwiseOutcome::WinB, 10));
+    comparisons.extend(games("model-a", "model-c", PairwiseOutcome::WinA, 35));
+    comparisons.extend(games("model-a", "model-c", PairwiseOutcome::WinB, 5));
+    comparisons.extend(games("model-b", "model-c", PairwiseOutcome::WinA, 25));
+    comparisons.extend(games("model-b", "model-c", PairwiseOutcome::WinB, 10));
+    comparisons.extend(games("model-b", "model-c", PairwiseOutcome::Tie, 5));
+    comparisons
+}
+
+fn config() -> RankingConfig {
+    RankingConfig::default().with_bootstrap_rounds(200)
+}
+
+// ===== Elo Tests =====
+
+#[test]
+fn test_elo_orders_models_by_strength() {
+    let leaderboard = RankingAnalyzer::elo(&tournament(), &config()).unwrap();
+
+    let order: Vec<&str> = leaderboard
+        .entries
+        .iter()
+        .map(|e| e.model.as_str())
+        .collect();
+    assert_eq!(order, vec!["model-a", "model-b", "model-c"]);
+    assert_eq!(leaderboard.method, RatingMethod::Elo);
+    assert_eq!(leaderboard.entries[0].rank, 1);
+    assert_eq!(leaderboard.entries[2].rank, 3);
+}
+
+#[test]
+fn test_elo_is_order_independent() {
+    let comparisons = tournament();
+    let mut reversed = comparisons.clone();
+    reversed.reverse();
+
+    let forward = RankingAnalyzer::elo(&comparisons, &config()).unwrap();
+    let backward = RankingAnalyzer::elo(&reversed, &config()).unwrap();
+
+    for entry in &forward.entries {
+        let other = backward.get(&entry.model).unwrap();
+        assert!(
+            (entry.rating - other.rating).abs() < 15.0,
+            "{}: {} vs {}",
+            entry.model,
+            entry.rating,
+            other.rating
+        );
+    }
+}
+
+#[test]
+fn test_elo_is_reproducible_with_seed() {
+    let first = RankingAnalyzer::elo(&tournament(), &config()).unwrap();
+    let second = RankingAnalyzer::elo(&tournament(), &config()).unwrap();
+
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_elo_confidence_intervals_contain_rating() {
+    let leaderboard = RankingAnalyzer::elo(&tournament(), &config()).unwrap();
+
+    for entry in &leaderboard.entries {
+        assert!(entry.ci_lower <= entry.rating);
+        assert!(entry.rating <= entry.ci_upper);
+        assert!(entry.ci_upper > entry.ci_lower);
+    }
+}
+
+#[test]
+fn test_elo_without_bootstrap() {
+    let comparisons = games("model-a", "model-b", PairwiseOutcome::WinA, 1);
+    let config = RankingConfig::default()
+        .with_bootstrap_rounds(0)
+        .with_k_factor(32.0);
+
+    let leaderboard = RankingAnalyzer::elo(&comparisons, &config).unwrap();
+
+    let a = leaderboard.get("model-a").unwrap();
+    let b = leaderboard.get("model-b").unwrap();
+    assert!((a.rating - 1016.0).abs() < 1e-9);
+    assert!((b.rating - 984.0).abs() < 1e-9);
+    assert_eq!(a.ci_lower, a.ci_upper);
+}
+
+// ===== Bradley-Terry Tests =====
+
+#[test]
+fn test_bradley_terry_orders_models_by_strength() {
+    let leaderboard = RankingAnalyzer::bradley_terry(&tournament(), &config()).unwrap();
+
+    let order: Vec<&str> = leaderboard
+        .entries
+        .iter()
+        .map(|e| e.model.as_str())
+        .collect();
+    assert_eq!(order, vec!["model-a", "model-b", "model-c"]);
+    assert_eq!(leaderboard.method, RatingMethod::BradleyTerry);
+}
+
+#[test]
+fn test_bradley_terry_recovers_win_probability() {
+    // 75% win rate corresponds to 3:1 odds
+    let mut comparisons = games("model-a", "model-b", PairwiseOutcome::WinA, 300);
+    comparisons.extend(games("model-a", "model-b", PairwiseOutcome::WinB, 100));
+    let config = RankingConfig {
+        bt_prior_games: 0.0,
+        ..RankingConfig::default().with_bootstrap_rounds(0)
+    };
+
+    let leaderboard = RankingAnalyzer::bradley_terry(&comparisons, &config).unwrap();
+
+    let a = leaderboard.get("model-a").unwrap().rating;
+    let b = leaderboard.get("model-b").unwrap().rating;
+    let odds = 10f64.powf((a - b) / config.scale);
+    assert!((odds - 3.0).abs() < 1e-4, "odds: {}", odds);
+}
+
+#[test]
+fn test_bradley_terry_is_order_independent() {
+    let comparisons = tournament();
+    let mut reversed = comparisons.clone();
+    reversed.reverse();
+    let config = RankingConfig::default().with_bootstrap_rounds(0);
+
+    let forward = RankingAnalyzer::bradley_terry(&comparisons, &config).unwrap();
+    let backward = RankingAnalyzer::bradley_terry(&reversed, &config).unwrap();
+
+    for entry in &forward.entries {
+        let other = backward.get(&entry.model).unwrap();
+        assert!((entry.rating - other.rating).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_bradley_terry_undefeated_model_stays_finite() {
+    let comparisons = games("model-a", "model-b", PairwiseOutcome::WinA, 20);
+
+    let leaderboard = RankingAnalyzer::bradley_terry(&comparisons, &config()).unwrap();
+
+    for entry in &leaderboard.entries {
+        assert!(entry.rating.is_finite());
+        assert!(entry.ci_lower.is_finite());
+        assert!(entry.ci_upper.is_finite());
+    }
+    assert_eq!(leaderboard.entries[0].model, "model-a");
+}
+
+#[test]
+fn test_bradley_terry_confidence_intervals_shrink_with_data() {
+    let small: Vec<_> = tournament().into_iter().step_by(5).collect();
+    let large = tournament();
+
+    let narrow = RankingAnalyzer::bradley_terry(&large, &config()).unwrap();
+    let wide = RankingAnalyzer::bradley_terry(&small, &config()).unwrap();
+
+    let width = |board: &Leaderboard| {
+        let entry = board.get("model-b").unwrap();
+        entry.ci_upper - entry.ci_lower
+    };
+    assert!(width(&narrow) < width(&wide));
+}
+
+// ===== Leaderboard Tests =====
+
+#[test]
+fn test_leaderboard_records() {
+    let leaderboard = RankingAnalyzer::elo(&tournament(), &config()).unwrap();
+
+    let b = leaderboard.get("model-b").unwrap();
+    assert_eq!(b.wins, 10 + 25);
+    assert_eq!(b.losses, 30 + 10);
+    assert_eq!(b.ties, 5);
+    assert_eq!(b.games(), 80);
+    assert_eq!(leaderboard.total_comparisons, 120);
+    assert_eq!(leaderboard.bootstrap_rounds, 200);
+}
+
+#[test]
+fn test_leaderboard_serialization() {
+    let leaderboard = RankingAnalyzer::bradley_terry(&tournament(), &config()).unwrap();
+
+    let json = serde_json::to_value(&leaderboard).unwrap();
+    assert_eq!(json["method"], "bradley_terry");
+    assert_eq!(json["entries"][0]["model"], "model-a");
+    assert_eq!(json["entries"][0]["rank"], 1);
+
+    let restored: Leaderboard = serde_json::from_value(json).unwrap();
+    assert_eq!(restored, leaderboard);
+}
+
+#[test]
+fn test_comparison_deserialization() {
+    let json = serde_json::json!({
+        "model_a": "gpt-4",
+        "model_b": "claude-3",
+        "outcome": "tie"
+    });
+
+    let comparison: PairwiseComparison = serde_json::from_value(json).unwrap();
+    assert_eq!(comparison.outcome, PairwiseOutcome::Tie);
+}
+
+// ===== Validation Tests =====
+
+#[test]
+fn test_empty_comparisons() {
+    assert!(RankingAnalyzer::elo(&[], &config()).is_err());
+    assert!(RankingAnalyzer::bradley_terry(&[], &config()).is_err());
+}
+
+#[test]
+fn test_self_comparison() {
+    let comparisons = games("model-a", "model-a", PairwiseOutcome::Tie, 1);
+    assert!(RankingAnalyzer::elo(&comparisons, &config()).is_err());
+}
+
+#[test]
+fn test_invalid_confidence() {
+    let config = RankingConfig {
+        confidence: 1.0,
+        ..config()
+    };
+    assert!(RankingAnalyzer::elo(&tournament(), &config).is_err());
+}
+
+#[test]
+fn test_bradley_terry_winless_model_with_zero_prior() {
+    // model-c loses every game
+    let mut comparisons = games("model-a", "model-b", PairwiseOutcome::WinA, 10);
+    comparisons.extend(games("model-a", "model-b", PairwiseOutcome::WinB, 10));
+    comparisons.extend(games("model-b", "model-c", PairwiseOutcome::WinA, 20));
+    let config = RankingConfig {
+        bt_prior_games: 0.0,
+        ..config()
+    };
+
+    let leaderboard = RankingAnalyzer::bradley_terry(&comparisons, &config).unwrap();
+
+    for entry in &leaderboard.entries {
+        assert!(entry.rating.is_finite());
+        assert!(entry.ci_lower.is_finite());
+        assert!(entry.ci_upper.is_finite());
+    }
+    assert_eq!(leaderboard.entries[2].model, "model-c");
+}
+
+#[test]
+fn test_invalid_scale_tolerance_and_prior() {
+    for config in [
+        RankingConfig {
+            scale: 0.0,
+            ..config()
+        },
+        RankingConfig {
+            bt_tolerance: -1.0,
+            ..config()
+        },
+        RankingConfig {
+            bt_prior_games: -1.0,
+            ..config()
+        },
+    ] {
+        assert!(RankingAnalyzer::bradley_terry(&tournament(), &config).is_err());
+    }
+}
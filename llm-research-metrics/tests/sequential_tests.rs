@@ -0,0 +1,227 @@
+use llm_research_metrics::sequential::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn bernoulli(rng: &mut StdRng, p: f64, n: usize) -> Vec<f64> {
+    (0..n).map(|_| if rng.gen::<f64>() < p { 1.0 } else { 0.0 }).collect()
+}
+
+fn msprt(mixing_variance: f64, min_samples: usize) -> SequentialTest {
+    SequentialTest::new(SequentialConfig {
+        alpha: 0.05,
+        method: SequentialMethod::Msprt { mixing_variance },
+        min_samples,
+    })
+    .unwrap()
+}
+
+fn group_sequential(spending: AlphaSpending, max_samples: usize) -> SequentialTest {
+    SequentialTest::new(SequentialConfig {
+        alpha: 0.05,
+        method: SequentialMethod::GroupSequential {
+            spending,
+            max_samples,
+        },
+        min_samples: 10,
+    })
+    .unwrap()
+}
+
+// ===== mSPRT Tests =====
+
+#[test]
+fn test_msprt_detects_large_effect() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut test = msprt(0.01, 20);
+    let mut a = Vec::new();
+    let mut b = Vec::new();
+    let mut decision = SequentialDecision::Continue;
+
+    for _ in 0..50 {
+        a.extend(bernoulli(&mut rng, 0.5, 20));
+        b.extend(bernoulli(&mut rng, 0.7, 20));
+        let result = test.update_two_sample(&a, &b);
+        decision = result.decision;
+        if decision.should_stop() {
+            assert!(result.p_value.unwrap() <= 0.05);
+            break;
+        }
+    }
+
+    assert_eq!(decision, SequentialDecision::Superior);
+    assert!(a.len() < 1000);
+}
+
+#[test]
+fn test_msprt_detects_negative_effect() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let mut test = msprt(0.01, 20);
+    let mut values = Vec::new();
+    let mut decision = SequentialDecision::Continue;
+
+    for _ in 0..50 {
+        values.extend(bernoulli(&mut rng, 0.3, 20));
+        decision = test.update_one_sample(&values, 0.6).decision;
+        if decision.should_stop() {
+            break;
+        }
+    }
+
+    assert_eq!(decision, SequentialDecision::Inferior);
+}
+
+#[test]
+fn test_msprt_p_value_is_monotone() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut test = msprt(0.01, 10);
+    let mut values = Vec::new();
+    let mut previous = 1.0;
+
+    for _ in 0..30 {
+        values.extend(bernoulli(&mut rng, 0.5, 10));
+        let p_value = test.update_one_sample(&values, 0.5).p_value.unwrap();
+        assert!(p_value <= previous);
+        previous = p_value;
+    }
+}
+
+#[test]
+fn test_msprt_controls_false_positives_under_null() {
+    let mut false_positives = 0;
+
+    for seed in 0..200 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut test = msprt(0.01, 20);
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+
+        for _ in 0..25 {
+            a.extend(bernoulli(&mut rng, 0.5, 20));
+            b.extend(bernoulli(&mut rng, 0.5, 20));
+            if test.update_two_sample(&a, &b).decision.should_stop() {
+                false_positives += 1;
+                break;
+            }
+        }
+    }
+
+    // Peeking 25 times with a fixed-horizon test would far exceed alpha
+    assert!(false_positives <= 20, "false positives: {}", false_positives);
+}
+
+#[test]
+fn test_min_samples_prevents_early_decision() {
+    let mut test = msprt(0.01, 50);
+    let values = vec![1.0; 20];
+
+    let result = test.update_one_sample(&values, 0.0);
+    assert_eq!(result.decision, SequentialDecision::Continue);
+    assert_eq!(result.p_value, Some(1.0));
+}
+
+#[test]
+fn test_decision_is_final_once_stopped() {
+    let mut test = msprt(0.01, 10);
+    let strong = vec![1.0, 0.9, 1.0, 0.95, 1.0, 0.9, 1.0, 0.95, 1.0, 0.9, 1.0, 0.95];
+
+    let first = test.update_one_sample(&strong, 0.0);
+    assert_eq!(first.decision, SequentialDecision::Superior);
+
+    let second = test.update_one_sample(&[0.0, 0.0, 0.0], 0.0);
+    assert_eq!(second.decision, SequentialDecision::Superior);
+    assert_eq!(second.look, 2);
+    assert_eq!(test.looks(), 2);
+}
+
+// ===== Group-Sequential Tests =====
+
+#[test]
+fn test_obrien_fleming_spending_is_conservative_early() {
+    let early = AlphaSpending::OBrienFleming.spent(0.05, 0.25);
+    let pocock_early = AlphaSpending::Pocock.spent(0.05, 0.25);
+
+    assert!(early < pocock_early);
+    assert!((AlphaSpending::OBrienFleming.spent(0.05, 1.0) - 0.05).abs() < 1e-9);
+    assert!((AlphaSpending::Pocock.spent(0.05, 1.0) - 0.05).abs() < 1e-9);
+    assert_eq!(AlphaSpending::Pocock.spent(0.05, 0.0), 0.0);
+}
+
+#[test]
+fn test_group_sequential_detects_effect() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let mut test = group_sequential(AlphaSpending::OBrienFleming, 1000);
+    let mut values = Vec::new();
+    let mut decision = SequentialDecision::Continue;
+
+    for _ in 0..10 {
+        values.extend(bernoulli(&mut rng, 0.8, 100));
+        decision = test.update_one_sample(&values, 0.5).decision;
+        if decision.should_stop() {
+            break;
+        }
+    }
+
+    assert_eq!(decision, SequentialDecision::Superior);
+    assert!(values.len() < 1000);
+}
+
+#[test]
+fn test_group_sequential_exhausts_budget_under_null() {
+    let mut test = group_sequential(AlphaSpending::Pocock, 100);
+    let values: Vec<f64> = (0..100).map(|i| if i % 2 == 0 { 1.0 } else { 0.0 }).collect();
+
+    let mut last = None;
+    for look in 1..=4 {
+        last = Some(test.update_one_sample(&values[..look * 25], 0.5));
+    }
+
+    let last = last.unwrap();
+    assert_eq!(last.decision, SequentialDecision::MaxSamplesReached);
+    assert!((last.alpha_spent.unwrap() - 0.05).abs() < 1e-9);
+    assert!(last.p_value.is_none());
+}
+
+#[test]
+fn test_invalid_configuration() {
+    assert!(SequentialTest::new(SequentialConfig {
+        alpha: 0.0,
+        ..SequentialConfig::default()
+    })
+    .is_err());
+
+    assert!(SequentialTest::new(SequentialConfig {
+        method: SequentialMethod::Msprt {
+            mixing_variance: 0.0
+        },
+        ..SequentialConfig::default()
+    })
+    .is_err());
+
+    assert!(SequentialTest::new(SequentialConfig {
+        method: SequentialMethod::GroupSequential {
+            spending: AlphaSpending::Pocock,
+            max_samples: 0,
+        },
+        ..SequentialConfig::default()
+    })
+    .is_err());
+}
+
+#[test]
+fn test_config_serialization() {
+    let config = SequentialConfig {
+        alpha: 0.05,
+        method: SequentialMethod::GroupSequential {
+            spending: AlphaSpending::OBrienFleming,
+            max_samples: 500,
+        },
+        min_samples: 10,
+    };
+
+    let json = serde_json::to_value(&config).unwrap();
+    assert_eq!(json["method"]["method"], "group_sequential");
+    assert_eq!(json["method"]["spending"], "o_brien_fleming");
+
+    let restored: SequentialConfig = serde_json::from_value(json).unwrap();
+    assert_eq!(restored, config);
+}
@@ -0,0 +1,273 @@
+use approx::assert_relative_eq;
+use llm_research_metrics::aggregators::MetricAggregator;
+use llm_research_metrics::streaming::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn uniform_values(seed: u64, n: usize) -> Vec<f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| rng.gen::<f64>()).collect()
+}
+
+fn exact_percentile(values: &[f64], q: f64) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    sorted[((sorted.len() - 1) as f64 * q).round() as usize]
+}
+
+// ===== RunningStats Tests =====
+
+#[test]
+fn test_running_stats_matches_batch_aggregate() {
+    let values = vec![2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+    let mut stats = RunningStats::new();
+    for &value in &values {
+        stats.push(value);
+    }
+
+    assert_eq!(stats.count(), 8);
+    assert_relative_eq!(stats.mean(), 5.0, epsilon = 1e-12);
+    assert_relative_eq!(stats.std_dev(), 2.0, epsilon = 1e-12);
+    assert_relative_eq!(stats.sample_variance(), 32.0 / 7.0, epsilon = 1e-12);
+    assert_eq!(stats.min(), Some(2.0));
+    assert_eq!(stats.max(), Some(9.0));
+    assert_relative_eq!(stats.sum(), 40.0);
+}
+
+#[test]
+fn test_running_stats_merge_is_exact() {
+    let values = uniform_values(1, 1000);
+    let mut whole = RunningStats::new();
+    let mut left = RunningStats::new();
+    let mut right = RunningStats::new();
+    for (i, &value) in values.iter().enumerate() {
+        whole.push(value);
+        if i < 300 {
+            left.push(value);
+        } else {
+            right.push(value);
+        }
+    }
+
+    left.merge(&right);
+    assert_eq!(left.count(), whole.count());
+    assert_relative_eq!(left.mean(), whole.mean(), epsilon = 1e-12);
+    assert_relative_eq!(left.variance(), whole.variance(), epsilon = 1e-12);
+    assert_eq!(left.min(), whole.min());
+    assert_eq!(left.max(), whole.max());
+}
+
+#[test]
+fn test_running_stats_merge_with_empty() {
+    let mut stats = RunningStats::new();
+    stats.push(3.0);
+
+    let mut empty = RunningStats::new();
+    empty.merge(&stats);
+    stats.merge(&RunningStats::new());
+
+    assert_eq!(empty, stats);
+    assert_eq!(RunningStats::new().min(), None);
+}
+
+// ===== TDigest Tests =====
+
+#[test]
+fn test_tdigest_quantiles_are_accurate() {
+    let values = uniform_values(2, 50_000);
+    let mut digest = TDigest::default();
+    for &value in &values {
+        digest.push(value);
+    }
+
+    for q in [0.01, 0.25, 0.5, 0.75, 0.95, 0.99] {
+        let estimate = digest.quantile(q).unwrap();
+        let exact = exact_percentile(&values, q);
+        assert!(
+            (estimate - exact).abs() < 0.01,
+            "q={}: {} vs {}",
+            q,
+            estimate,
+            exact
+        );
+    }
+    assert!(digest.centroid_count() <= 200);
+}
+
+#[test]
+fn test_tdigest_extremes() {
+    let mut digest = TDigest::default();
+    for value in [5.0, 1.0, 9.0, 3.0] {
+        digest.push(value);
+    }
+
+    assert_eq!(digest.quantile(0.0), Some(1.0));
+    assert_eq!(digest.quantile(1.0), Some(9.0));
+    assert_eq!(TDigest::default().quantile(0.5), None);
+}
+
+#[test]
+fn test_tdigest_merge_matches_single_digest() {
+    let values = uniform_values(3, 20_000);
+    let mut parts: Vec<TDigest> = (0..8).map(|_| TDigest::default()).collect();
+    for (i, &value) in values.iter().enumerate() {
+        parts[i % 8].push(value);
+    }
+
+    let mut merged = TDigest::default();
+    for part in &parts {
+        merged.merge(part);
+    }
+
+    assert_eq!(merged.count(), 20_000);
+    for q in [0.5, 0.9, 0.99] {
+        let exact = exact_percentile(&values, q);
+        assert!((merged.quantile(q).unwrap() - exact).abs() < 0.01);
+    }
+}
+
+#[test]
+fn test_tdigest_ignores_non_finite() {
+    let mut digest = TDigest::default();
+    digest.push(f64::NAN);
+    digest.push(1.0);
+
+    assert_eq!(digest.count(), 1);
+    assert_eq!(digest.quantile(0.5), Some(1.0));
+}
+
+// ===== BucketHistogram Tests =====
+
+#[test]
+fn test_bucket_histogram_counts() {
+    let mut histogram = BucketHistogram::uniform(0.0, 1.0, 4).unwrap();
+    for value in [-0.5, 0.0, 0.1, 0.3, 0.5, 0.74, 1.0, 1.5] {
+        histogram.push(value);
+    }
+
+    assert_eq!(histogram.counts(), &[2, 1, 2, 1]);
+    assert_eq!(histogram.underflow(), 1);
+    assert_eq!(histogram.overflow(), 1);
+    assert_eq!(histogram.total_count(), 8);
+
+    let report = histogram.to_histogram();
+    assert_eq!(report.bins.len(), 4);
+    assert_relative_eq!(report.bins[0].frequency, 0.25);
+}
+
+#[test]
+fn test_bucket_histogram_merge() {
+    let mut a = BucketHistogram::new(vec![0.0, 10.0, 20.0]).unwrap();
+    let mut b = a.clone();
+    a.push(5.0);
+    b.push(15.0);
+    b.push(20.0);
+
+    a.merge(&b).unwrap();
+    assert_eq!(a.counts(), &[1, 2]);
+
+    let other = BucketHistogram::new(vec![0.0, 5.0, 20.0]).unwrap();
+    assert!(a.merge(&other).is_err());
+}
+
+#[test]
+fn test_bucket_histogram_invalid_edges() {
+    assert!(BucketHistogram::new(vec![1.0]).is_err());
+    assert!(BucketHistogram::new(vec![0.0, 0.0, 1.0]).is_err());
+    assert!(BucketHistogram::new(vec![0.0, f64::INFINITY]).is_err());
+    assert!(BucketHistogram::uniform(0.0, 1.0, 0).is_err());
+}
+
+// ===== StreamingAggregator Tests =====
+
+#[test]
+fn test_streaming_summary_matches_aggregate() {
+    let values = vec![10.0, 20.0, 30.0, 40.0, 50.0];
+    let exact = MetricAggregator::aggregate(&values);
+    let summary = StreamingAggregator::from_values(&values).summary();
+
+    assert_eq!(summary.count, exact.count);
+    assert_eq!(summary.mean, exact.mean);
+    assert_eq!(summary.min, exact.min);
+    assert_eq!(summary.max, exact.max);
+    assert_eq!(summary.sum, exact.sum);
+    assert_eq!(summary.median, exact.median);
+    let std_dev = f64::try_from(summary.std_dev).unwrap();
+    assert_relative_eq!(
+        std_dev,
+        f64::try_from(exact.std_dev).unwrap(),
+        epsilon = 1e-9
+    );
+}
+
+#[test]
+fn test_streaming_merge_of_worker_results() {
+    let values = uniform_values(4, 10_000);
+    let histogram = BucketHistogram::uniform(0.0, 1.0, 10).unwrap();
+    let workers: Vec<StreamingAggregator> = values
+        .chunks(1000)
+        .map(|chunk| {
+            let mut aggregator = StreamingAggregator::new().with_histogram(histogram.clone());
+            aggregator.extend(chunk);
+            aggregator
+        })
+        .collect();
+
+    let merged = StreamingAggregator::merge_all(&workers).unwrap().unwrap();
+    assert_eq!(merged.count(), 10_000);
+
+    let exact = MetricAggregator::aggregate(&values);
+    let mean = f64::try_from(merged.summary().mean).unwrap();
+    assert_relative_eq!(mean, f64::try_from(exact.mean).unwrap(), epsilon = 1e-9);
+    assert!((merged.percentile(95.0).unwrap() - exact_percentile(&values, 0.95)).abs() < 0.01);
+
+    let report = merged.histogram().unwrap();
+    assert_eq!(report.total_count, 10_000);
+    assert_eq!(report.bins.iter().map(|b| b.count).sum::<usize>(), 10_000);
+}
+
+#[test]
+fn test_streaming_merge_requires_matching_histograms() {
+    let mut plain = StreamingAggregator::from_values(&[1.0]);
+    let with_histogram =
+        StreamingAggregator::new().with_histogram(BucketHistogram::uniform(0.0, 1.0, 2).unwrap());
+
+    assert!(plain.merge(&with_histogram).is_err());
+    assert!(StreamingAggregator::merge_all(&[]).unwrap().is_none());
+}
+
+#[test]
+fn test_streaming_serialization_round_trip() {
+    let mut aggregator = StreamingAggregator::new();
+    aggregator.extend(&uniform_values(5, 2000));
+
+    let json = serde_json::to_string(&aggregator).unwrap();
+    let mut restored: StreamingAggregator = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.count(), aggregator.count());
+    assert_relative_eq!(
+        restored.percentile(90.0).unwrap(),
+        aggregator.percentile(90.0).unwrap(),
+        epsilon = 1e-12
+    );
+
+    // A deserialized partial keeps accumulating and merging
+    restored.push(0.5);
+    restored.merge(&aggregator).unwrap();
+    assert_eq!(restored.count(), 4001);
+}
+
+#[test]
+fn test_streaming_distribution_metric() {
+    let values: Vec<f64> = (1..=100).map(|i| i as f64).collect();
+    let metric = StreamingAggregator::from_values(&values).to_distribution_metric("latency_ms", 3);
+
+    assert_eq!(metric.name, "latency_ms");
+    assert_eq!(metric.step, 3);
+    assert_eq!(metric.count, 100);
+    assert_eq!(metric.min, 1.0);
+    assert_eq!(metric.max, 100.0);
+    assert_relative_eq!(metric.mean, 50.5);
+    assert!((metric.median - 50.5).abs() < 1.0);
+    assert!((metric.percentile_25 - 25.75).abs() < 1.0);
+    assert!((metric.percentile_99 - 99.01).abs() < 1.0);
+}
@@ -0,0 +1,13 @@
+-- Create workflow_checkpoints table for persisting in-flight workflow state
+CREATE TABLE IF NOT EXISTS workflow_checkpoints (
+    workflow_id UUID PRIMARY KEY,
+    name TEXT NOT NULL,
+    status TEXT NOT NULL,
+    state JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Recovery on startup looks up workflows that have not finished
+CREATE INDEX idx_workflow_checkpoints_status ON workflow_checkpoints(status);
+CREATE INDEX idx_workflow_checkpoints_updated_at ON workflow_checkpoints(updated_at DESC);
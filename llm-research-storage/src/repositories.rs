@@ -4,6 +4,7 @@ pub mod model;
 pub mod dataset;
 pub mod prompt;
 pub mod evaluation;
+pub mod workflow;
 
 pub use experiment::*;
 pub use run::*;
@@ -11,3 +12,4 @@ pub use model::*;
 pub use dataset::*;
 pub use prompt::*;
 pub use evaluation::*;
+pub use workflow::*;
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use llm_research_core::Result;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// Serialized workflow state, checkpointed as the workflow progresses
+#[derive(Debug, Clone)]
+pub struct WorkflowCheckpoint {
+    pub workflow_id: Uuid,
+    pub name: String,
+    pub status: String,
+    pub state: serde_json::Value,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub struct WorkflowCheckpointRepository {
+    pool: PgPool,
+}
+
+impl WorkflowCheckpointRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Insert or replace the checkpoint for a workflow
+    pub async fn upsert(&self, checkpoint: &WorkflowCheckpoint) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_checkpoints (workflow_id, name, status, state, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (workflow_id) DO UPDATE
+            SET name = EXCLUDED.name, status = EXCLUDED.status,
+                state = EXCLUDED.state, updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(checkpoint.workflow_id)
+        .bind(&checkpoint.name)
+        .bind(&checkpoint.status)
+        .bind(&checkpoint.state)
+        .bind(checkpoint.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get the checkpoint for a workflow
+    pub async fn get(&self, workflow_id: Uuid) -> Result<Option<WorkflowCheckpoint>> {
+        let row = sqlx::query(
+            r#"
+            SELECT workflow_id, name, status, state, updated_at
+            FROM workflow_checkpoints
+            WHERE workflow_id = $1
+            "#,
+        )
+        .bind(workflow_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(row_to_checkpoint))
+    }
+
+    /// Checkpoints whose status is one of `statuses`, oldest first
+    pub async fn list_by_status(&self, statuses: &[&str]) -> Result<Vec<WorkflowCheckpoint>> {
+        let statuses: Vec<String> = statuses.iter().map(|s| s.to_string()).collect();
+        let rows = sqlx::query(
+            r#"
+            SELECT workflow_id, name, status, state, updated_at
+            FROM workflow_checkpoints
+            WHERE status = ANY($1)
+            ORDER BY updated_at ASC
+            "#,
+        )
+        .bind(&statuses)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(row_to_checkpoint).collect())
+    }
+
+    /// Delete the checkpoint for a workflow
+    pub async fn delete(&self, workflow_id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM workflow_checkpoints WHERE workflow_id = $1")
+            .bind(workflow_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn row_to_checkpoint(row: sqlx::postgres::PgRow) -> WorkflowCheckpoint {
+    WorkflowCheckpoint {
+        workflow_id: row.get("workflow_id"),
+        name: row.get("name"),
+        status: row.get("status"),
+        state: row.get("state"),
+        updated_at: row.get("updated_at"),
+    }
+}
@@ -86,6 +86,10 @@ impl S3Storage {
         Self { client, bucket }
     }
 
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
     pub async fn upload(&self, key: &str, data: Vec<u8>) -> Result<()> {
         self.client
             .put_object()
@@ -112,6 +116,19 @@ impl S3Storage {
         Ok(data)
     }
 
+    /// Reads an object's body as it arrives rather than buffering it whole
+    pub async fn reader(&self, key: &str) -> Result<impl tokio::io::AsyncBufRead + Send + Unpin> {
+        let resp = self.client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await?;
+
+        tracing::info!("Streaming object from S3: {}", key);
+        Ok(resp.body.into_async_read())
+    }
+
     pub async fn delete(&self, key: &str) -> Result<()> {
         self.client
             .delete_object()
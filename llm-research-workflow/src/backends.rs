@@ -0,0 +1,200 @@
+pub mod anthropic;
+pub mod cassette;
+pub mod cohere;
+pub mod openai;
+pub mod simulated;
+mod sse;
+
+pub use anthropic::*;
+pub use cassette::*;
+pub use cohere::*;
+pub use openai::*;
+pub use simulated::*;
+
+use async_trait::async_trait;
+use llm_research_core::{CoreError, ModelParameters, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: Role,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::System,
+            content: content.into(),
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: content.into(),
+        }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::Assistant,
+            content: content.into(),
+        }
+    }
+}
+
+/// One chat completion to request from a model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InferenceRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub parameters: ModelParameters,
+    /// Return the log probability of each generated token, along with this
+    /// many of the most likely alternatives
+    #[serde(default)]
+    pub logprobs: Option<u32>,
+}
+
+impl InferenceRequest {
+    pub fn new(model: impl Into<String>, messages: Vec<ChatMessage>) -> Self {
+        Self {
+            model: model.into(),
+            messages,
+            parameters: ModelParameters::default(),
+            logprobs: None,
+        }
+    }
+
+    pub fn with_parameters(mut self, parameters: ModelParameters) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
+    pub fn with_logprobs(mut self, top_logprobs: u32) -> Self {
+        self.logprobs = Some(top_logprobs);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+}
+
+impl TokenUsage {
+    pub fn total(&self) -> usize {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenLogprob {
+    pub token: String,
+    pub logprob: f64,
+    /// The most likely tokens at this position, most likely first
+    #[serde(default)]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopLogprob {
+    pub token: String,
+    pub logprob: f64,
+}
+
+/// A model's reply to an `InferenceRequest`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Completion {
+    pub text: String,
+    /// The model that answered, as reported by the provider
+    pub model: String,
+    /// Why generation stopped, in the provider's words (e.g. `stop`, `length`)
+    pub finish_reason: Option<String>,
+    pub usage: TokenUsage,
+    pub logprobs: Option<Vec<TokenLogprob>>,
+    /// When the text arrived, for completions that were streamed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<TokenTiming>,
+}
+
+/// When the pieces of a streamed completion arrived, in milliseconds after
+/// the request was sent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenTiming {
+    /// Time to first token
+    pub first_token_ms: f64,
+    /// Arrival of each piece of text the provider sent, usually one token
+    /// each; pieces that arrived together share a timestamp
+    pub arrivals_ms: Vec<f64>,
+}
+
+/// A model provider that completes chat requests. Errors follow
+/// `CoreError::is_retryable`: rate limits, timeouts and server errors can be
+/// retried, rejected requests can't.
+#[async_trait]
+pub trait InferenceBackend: Send + Sync {
+    /// Provider name recorded with each result
+    fn name(&self) -> &str;
+
+    async fn complete(&self, request: &InferenceRequest) -> Result<Completion>;
+
+    /// Like `complete`, but streams the response to record when each token
+    /// arrived. Providers that can't stream complete the request as usual,
+    /// without timing.
+    async fn stream(&self, request: &InferenceRequest) -> Result<Completion> {
+        self.complete(request).await
+    }
+}
+
+/// The error for an unsuccessful HTTP response from `provider`, given the
+/// message the provider gave for it and how long it asked callers to wait
+pub(crate) fn status_error(
+    provider: &str,
+    status: reqwest::StatusCode,
+    message: &str,
+    retry_after: Option<Duration>,
+) -> CoreError {
+    let message = format!("{} returned {}: {}", provider, status, message);
+    match status.as_u16() {
+        401 | 403 => CoreError::Unauthorized(message),
+        404 => CoreError::NotFound(message),
+        429 => CoreError::RateLimited {
+            message,
+            retry_after,
+        },
+        408 | 409 => CoreError::Internal(message),
+        code if code >= 500 => CoreError::Internal(message),
+        _ => CoreError::Validation(message),
+    }
+}
+
+/// How long a response asks callers to wait, from `retry-after-ms` or, in
+/// seconds, `retry-after`
+pub(crate) fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let header =
+        |name: &str| -> Option<f64> { headers.get(name)?.to_str().ok()?.trim().parse().ok() };
+    let seconds = header("retry-after-ms")
+        .map(|ms| ms / 1000.0)
+        .or_else(|| header("retry-after"))?;
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// The error for a request to `provider` that got no response; connection
+/// failures and timeouts may be retried
+pub(crate) fn request_error(provider: &str, error: reqwest::Error) -> CoreError {
+    if error.is_builder() {
+        return CoreError::Validation(format!("Invalid request to {}: {}", provider, error));
+    }
+    CoreError::Internal(format!("Request to {} failed: {}", provider, error))
+}
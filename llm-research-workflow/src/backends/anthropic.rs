@@ -0,0 +1,330 @@
+use async_trait::async_trait;
+use llm_research_core::{CoreError, Result};
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+use super::sse::{parse_event, read_events, StreamRecorder};
+use super::{
+    request_error, retry_after, status_error, Completion, InferenceBackend, InferenceRequest, Role,
+    TokenUsage,
+};
+
+/// Client for Anthropic's Messages API
+pub struct AnthropicBackend {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    version: String,
+}
+
+impl AnthropicBackend {
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.anthropic.com";
+    pub const DEFAULT_VERSION: &'static str = "2023-06-01";
+    /// The API requires `max_tokens`; this is sent when the request has none
+    pub const DEFAULT_MAX_TOKENS: u32 = 1024;
+
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+            version: Self::DEFAULT_VERSION.to_string(),
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// The `anthropic-version` header to send
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    fn body(request: &InferenceRequest) -> serde_json::Value {
+        let parameters = &request.parameters;
+        // System messages go in their own field rather than the conversation
+        let system: Vec<&str> = request
+            .messages
+            .iter()
+            .filter(|m| m.role == Role::System)
+            .map(|m| m.content.as_str())
+            .collect();
+        let messages: Vec<_> = request
+            .messages
+            .iter()
+            .filter(|m| m.role != Role::System)
+            .collect();
+
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "max_tokens": parameters.max_tokens.unwrap_or(Self::DEFAULT_MAX_TOKENS),
+        });
+        if !system.is_empty() {
+            body["system"] = json!(system.join("\n\n"));
+        }
+        let fields = [
+            ("temperature", json!(parameters.temperature)),
+            ("top_p", json!(parameters.top_p)),
+            ("top_k", json!(parameters.top_k)),
+            ("stop_sequences", json!(parameters.stop_sequences)),
+        ];
+        for (name, value) in fields {
+            if !value.is_null() {
+                body[name] = value;
+            }
+        }
+        for (name, value) in &parameters.additional {
+            body[name.as_str()] = value.clone();
+        }
+        body
+    }
+
+    /// Posts `body`, turning an unsuccessful response into an error
+    async fn send(&self, body: &serde_json::Value) -> Result<reqwest::Response> {
+        let mut http = self
+            .client
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("anthropic-version", &self.version)
+            .json(body);
+        if let Some(api_key) = &self.api_key {
+            http = http.header("x-api-key", api_key);
+        }
+
+        let response = http
+            .send()
+            .await
+            .map_err(|e| request_error("anthropic", e))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let retry_after = retry_after(response.headers());
+        let body = response
+            .text()
+            .await
+            .map_err(|e| request_error("anthropic", e))?;
+        match serde_json::from_str::<ErrorBody>(&body) {
+            Ok(ErrorBody { error }) => Err(Self::error(status, error, retry_after)),
+            Err(_) => Err(status_error(
+                "anthropic",
+                status,
+                &body.chars().take(500).collect::<String>(),
+                retry_after,
+            )),
+        }
+    }
+
+    /// Anthropic reports the kind of error in the body; overloaded and rate
+    /// limited requests, and its own failures, are worth retrying
+    fn error(
+        status: reqwest::StatusCode,
+        error: ErrorDetail,
+        retry_after: Option<Duration>,
+    ) -> CoreError {
+        let message = format!(
+            "anthropic returned {} ({}): {}",
+            status, error.kind, error.message
+        );
+        match error.kind.as_str() {
+            "rate_limit_error" => CoreError::RateLimited {
+                message,
+                retry_after,
+            },
+            "overloaded_error" | "api_error" | "timeout_error" => CoreError::Internal(message),
+            "authentication_error" | "permission_error" => CoreError::Unauthorized(message),
+            "not_found_error" => CoreError::NotFound(message),
+            "invalid_request_error" | "request_too_large" | "billing_error" => {
+                CoreError::Validation(message)
+            }
+            _ => status_error("anthropic", status, &error.message, retry_after),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: ErrorDetail,
+}
+
+#[derive(Deserialize)]
+struct ErrorDetail {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    model: String,
+    content: Vec<ContentBlock>,
+    #[serde(default)]
+    stop_reason: Option<String>,
+    usage: Usage,
+}
+
+#[derive(Deserialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct Usage {
+    input_tokens: usize,
+    #[serde(default)]
+    output_tokens: usize,
+    #[serde(default)]
+    cache_creation_input_tokens: Option<usize>,
+    #[serde(default)]
+    cache_read_input_tokens: Option<usize>,
+}
+
+impl Usage {
+    /// Prompt tokens read from or written to the prompt cache are billed
+    /// separately from the rest of the input
+    fn prompt_tokens(&self) -> usize {
+        self.input_tokens
+            + self.cache_creation_input_tokens.unwrap_or(0)
+            + self.cache_read_input_tokens.unwrap_or(0)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    MessageStart {
+        message: StreamMessage,
+    },
+    ContentBlockDelta {
+        delta: TextDelta,
+    },
+    MessageDelta {
+        delta: MessageDelta,
+        usage: OutputUsage,
+    },
+    MessageStop,
+    Error {
+        error: ErrorDetail,
+    },
+    /// Pings and the start and end of content blocks
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct StreamMessage {
+    model: String,
+    usage: Usage,
+}
+
+#[derive(Deserialize)]
+struct TextDelta {
+    /// Empty for deltas of tool input
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct MessageDelta {
+    #[serde(default)]
+    stop_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OutputUsage {
+    output_tokens: usize,
+}
+
+#[async_trait]
+impl InferenceBackend for AnthropicBackend {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    async fn complete(&self, request: &InferenceRequest) -> Result<Completion> {
+        let response = self.send(&Self::body(request)).await?;
+        let body = response
+            .text()
+            .await
+            .map_err(|e| request_error("anthropic", e))?;
+
+        let response: MessagesResponse = serde_json::from_str(&body).map_err(|e| {
+            CoreError::Serialization(format!("Unexpected response from anthropic: {}", e))
+        })?;
+        let text = response
+            .content
+            .iter()
+            .filter(|block| block.kind == "text")
+            .map(|block| block.text.as_str())
+            .collect();
+
+        Ok(Completion {
+            text,
+            model: response.model,
+            finish_reason: response.stop_reason,
+            usage: TokenUsage {
+                prompt_tokens: response.usage.prompt_tokens(),
+                completion_tokens: response.usage.output_tokens,
+            },
+            logprobs: None,
+            timing: None,
+        })
+    }
+
+    async fn stream(&self, request: &InferenceRequest) -> Result<Completion> {
+        let mut body = Self::body(request);
+        body["stream"] = json!(true);
+
+        let mut recorder = StreamRecorder::start();
+        let response = self.send(&body).await?;
+        let status = response.status();
+        let mut model = request.model.clone();
+        let mut finish_reason = None;
+        let mut usage = TokenUsage::default();
+        read_events("anthropic", response, |event| {
+            match parse_event("anthropic", &event)? {
+                StreamEvent::MessageStart { message } => {
+                    model = message.model;
+                    usage.prompt_tokens = message.usage.prompt_tokens();
+                    usage.completion_tokens = message.usage.output_tokens;
+                }
+                StreamEvent::ContentBlockDelta { delta } => recorder.token(&delta.text),
+                StreamEvent::MessageDelta {
+                    delta,
+                    usage: output,
+                } => {
+                    finish_reason = delta.stop_reason;
+                    usage.completion_tokens = output.output_tokens;
+                }
+                StreamEvent::MessageStop => return Ok(false),
+                // e.g. the API became overloaded after it started answering
+                StreamEvent::Error { error } => return Err(Self::error(status, error, None)),
+                StreamEvent::Other => {}
+            }
+            Ok(true)
+        })
+        .await?;
+
+        Ok(Completion {
+            model,
+            finish_reason,
+            usage,
+            logprobs: None,
+            timing: recorder.timing(),
+            text: recorder.text,
+        })
+    }
+}
@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+use llm_research_core::{ContentHash, CoreError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+use super::{Completion, InferenceBackend, InferenceRequest};
+use crate::cache::{hash_json, OutputCache};
+
+/// How a `CassetteBackend` uses its recordings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CassetteMode {
+    /// Replay recorded responses, and call the provider and record its
+    /// response for requests not recorded yet
+    #[default]
+    Record,
+    /// Only replay; a request that was not recorded fails
+    ReplayOnly,
+    /// Always call the provider, without reading or recording anything
+    Passthrough,
+}
+
+/// Key under which the response to `request` from `provider` is recorded
+pub fn cassette_key(provider: &str, request: &InferenceRequest) -> ContentHash {
+    let material = json!({
+        "provider": provider,
+        "model": request.model,
+        "messages": request.messages,
+        "parameters": request.parameters,
+        "logprobs": request.logprobs,
+    });
+    hash_json(&material)
+}
+
+/// A recorded exchange, as stored in the cassette
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    pub provider: String,
+    pub request: InferenceRequest,
+    pub completion: Completion,
+}
+
+/// Records a provider's responses and replays them for identical requests,
+/// so reruns don't call the provider and get exactly the responses of the
+/// run that recorded them. Recordings are kept in any `OutputCache`: a
+/// `FileOutputCache` to keep them on local disk, or an `ArtifactOutputCache`
+/// to keep them in the artifact store.
+pub struct CassetteBackend {
+    provider: String,
+    inner: Option<Arc<dyn InferenceBackend>>,
+    store: Arc<dyn OutputCache>,
+    mode: CassetteMode,
+}
+
+impl CassetteBackend {
+    pub fn new(
+        inner: Arc<dyn InferenceBackend>,
+        store: Arc<dyn OutputCache>,
+        mode: CassetteMode,
+    ) -> Self {
+        Self {
+            provider: inner.name().to_string(),
+            inner: Some(inner),
+            store,
+            mode,
+        }
+    }
+
+    /// Replays the recordings of `provider` without a client for it, so
+    /// runs need neither network access nor API keys
+    pub fn replay(provider: impl Into<String>, store: Arc<dyn OutputCache>) -> Self {
+        Self {
+            provider: provider.into(),
+            inner: None,
+            store,
+            mode: CassetteMode::ReplayOnly,
+        }
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    async fn recorded(&self, key: &ContentHash) -> Result<Option<Completion>> {
+        let Some(entry) = self.store.get(key).await? else {
+            return Ok(None);
+        };
+        let entry: CassetteEntry = serde_json::from_value(entry)?;
+        Ok(Some(entry.completion))
+    }
+
+    fn inner(&self) -> Result<&dyn InferenceBackend> {
+        self.inner.as_deref().ok_or_else(|| {
+            CoreError::Internal(format!("No {} backend to send requests to", self.provider))
+        })
+    }
+
+    /// Replays the response to `request`, or gets it with `call` and records
+    /// it, as the mode allows
+    async fn respond<'a, F>(&'a self, request: &'a InferenceRequest, call: F) -> Result<Completion>
+    where
+        F: FnOnce(&'a dyn InferenceBackend) -> futures::future::BoxFuture<'a, Result<Completion>>,
+    {
+        if self.mode == CassetteMode::Passthrough {
+            return call(self.inner()?).await;
+        }
+
+        let key = cassette_key(&self.provider, request);
+        if let Some(completion) = self.recorded(&key).await? {
+            tracing::debug!("Replaying {} response {}", self.provider, key);
+            return Ok(completion);
+        }
+        if self.mode == CassetteMode::ReplayOnly {
+            return Err(CoreError::NotFound(format!(
+                "No recorded {} response to this {} request (cassette key {}); \
+                 record one before replaying",
+                self.provider, request.model, key
+            )));
+        }
+
+        let completion = call(self.inner()?).await?;
+        let entry = CassetteEntry {
+            provider: self.provider.clone(),
+            request: request.clone(),
+            completion: completion.clone(),
+        };
+        self.store.put(&key, &serde_json::to_value(&entry)?).await?;
+        Ok(completion)
+    }
+}
+
+#[async_trait]
+impl InferenceBackend for CassetteBackend {
+    fn name(&self) -> &str {
+        &self.provider
+    }
+
+    async fn complete(&self, request: &InferenceRequest) -> Result<Completion> {
+        self.respond(request, |inner| inner.complete(request)).await
+    }
+
+    async fn stream(&self, request: &InferenceRequest) -> Result<Completion> {
+        self.respond(request, |inner| inner.stream(request)).await
+    }
+}
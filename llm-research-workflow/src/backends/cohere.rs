@@ -0,0 +1,303 @@
+use async_trait::async_trait;
+use llm_research_core::{CoreError, Result};
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+use super::openai::error_message;
+use super::sse::{parse_event, read_events, StreamRecorder};
+use super::{
+    request_error, retry_after, status_error, Completion, InferenceBackend, InferenceRequest,
+    TokenLogprob, TokenUsage,
+};
+
+/// Client for Cohere's v2 chat API
+pub struct CohereBackend {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl CohereBackend {
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.cohere.com";
+
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    fn body(request: &InferenceRequest) -> serde_json::Value {
+        let parameters = &request.parameters;
+        let mut body = json!({
+            "model": request.model,
+            "messages": request.messages,
+        });
+        let fields = [
+            ("max_tokens", json!(parameters.max_tokens)),
+            ("temperature", json!(parameters.temperature)),
+            ("p", json!(parameters.top_p)),
+            ("k", json!(parameters.top_k)),
+            ("frequency_penalty", json!(parameters.frequency_penalty)),
+            ("presence_penalty", json!(parameters.presence_penalty)),
+            ("stop_sequences", json!(parameters.stop_sequences)),
+            ("seed", json!(parameters.seed)),
+        ];
+        for (name, value) in fields {
+            if !value.is_null() {
+                body[name] = value;
+            }
+        }
+        // Cohere returns the chosen tokens' log probabilities but no
+        // alternatives
+        if request.logprobs.is_some() {
+            body["logprobs"] = json!(true);
+        }
+        for (name, value) in &parameters.additional {
+            body[name.as_str()] = value.clone();
+        }
+        body
+    }
+
+    /// Posts `body`, turning an unsuccessful response into an error
+    async fn send(&self, body: &serde_json::Value) -> Result<reqwest::Response> {
+        let mut http = self
+            .client
+            .post(format!("{}/v2/chat", self.base_url))
+            .json(body);
+        if let Some(api_key) = &self.api_key {
+            http = http.bearer_auth(api_key);
+        }
+
+        let response = http.send().await.map_err(|e| request_error("cohere", e))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let retry_after = retry_after(response.headers());
+        let body = response
+            .text()
+            .await
+            .map_err(|e| request_error("cohere", e))?;
+        Err(Self::error(status, &body, retry_after))
+    }
+
+    fn usage(usage: Option<Usage>) -> Option<TokenUsage> {
+        // Prefer the tokens the model saw over the billed units, which leave
+        // out the prompt template Cohere adds
+        usage
+            .and_then(|u| u.tokens.or(u.billed_units))
+            .map(|u| TokenUsage {
+                prompt_tokens: u.input_tokens as usize,
+                completion_tokens: u.output_tokens as usize,
+            })
+    }
+
+    /// Cohere answers 499 when it cancels a request and 402 when the account
+    /// is out of credit; the first is worth retrying, the second is not
+    fn error(status: reqwest::StatusCode, body: &str, retry_after: Option<Duration>) -> CoreError {
+        let message = error_message(body);
+        match status.as_u16() {
+            499 => CoreError::Internal(format!("cohere returned {}: {}", status, message)),
+            402 => CoreError::Unauthorized(format!("cohere returned {}: {}", status, message)),
+            _ => status_error("cohere", status, &message, retry_after),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    message: ResponseMessage,
+    #[serde(default)]
+    finish_reason: Option<String>,
+    #[serde(default)]
+    usage: Option<Usage>,
+    #[serde(default)]
+    logprobs: Option<Vec<ResponseLogprob>>,
+}
+
+#[derive(Deserialize)]
+struct ResponseMessage {
+    #[serde(default)]
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Deserialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct Usage {
+    #[serde(default)]
+    tokens: Option<UsageTokens>,
+    #[serde(default)]
+    billed_units: Option<UsageTokens>,
+}
+
+#[derive(Deserialize)]
+struct UsageTokens {
+    #[serde(default)]
+    input_tokens: f64,
+    #[serde(default)]
+    output_tokens: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum StreamEvent {
+    ContentDelta {
+        delta: ContentDelta,
+        #[serde(default)]
+        logprobs: Option<ResponseLogprob>,
+    },
+    MessageEnd {
+        delta: MessageEnd,
+    },
+    /// The start and end of the message and its content, and tool calls
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct ContentDelta {
+    message: DeltaMessage,
+}
+
+#[derive(Deserialize)]
+struct DeltaMessage {
+    content: DeltaContent,
+}
+
+#[derive(Deserialize)]
+struct DeltaContent {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct MessageEnd {
+    #[serde(default)]
+    finish_reason: Option<String>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+/// A span of generated text and the log probability of each token in it
+#[derive(Deserialize)]
+struct ResponseLogprob {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    logprobs: Vec<f64>,
+}
+
+#[async_trait]
+impl InferenceBackend for CohereBackend {
+    fn name(&self) -> &str {
+        "cohere"
+    }
+
+    async fn complete(&self, request: &InferenceRequest) -> Result<Completion> {
+        let response = self.send(&Self::body(request)).await?;
+        let body = response
+            .text()
+            .await
+            .map_err(|e| request_error("cohere", e))?;
+
+        let response: ChatResponse = serde_json::from_str(&body).map_err(|e| {
+            CoreError::Serialization(format!("Unexpected response from cohere: {}", e))
+        })?;
+        let text = response
+            .message
+            .content
+            .iter()
+            .filter(|block| block.kind == "text")
+            .map(|block| block.text.as_str())
+            .collect();
+        let usage = Self::usage(response.usage).unwrap_or_default();
+        let logprobs = response.logprobs.map(|spans| {
+            spans
+                .into_iter()
+                .map(|span| TokenLogprob {
+                    token: span.text,
+                    logprob: span.logprobs.iter().sum(),
+                    top_logprobs: Vec::new(),
+                })
+                .collect()
+        });
+
+        Ok(Completion {
+            text,
+            model: request.model.clone(),
+            finish_reason: response.finish_reason,
+            usage,
+            logprobs,
+            timing: None,
+        })
+    }
+
+    async fn stream(&self, request: &InferenceRequest) -> Result<Completion> {
+        let mut body = Self::body(request);
+        body["stream"] = json!(true);
+
+        let mut recorder = StreamRecorder::start();
+        let response = self.send(&body).await?;
+        let mut finish_reason = None;
+        let mut usage = None;
+        let mut logprobs: Option<Vec<TokenLogprob>> = None;
+        read_events("cohere", response, |event| {
+            match parse_event("cohere", &event)? {
+                StreamEvent::ContentDelta {
+                    delta,
+                    logprobs: span,
+                } => {
+                    let text = delta.message.content.text;
+                    recorder.token(&text);
+                    if let Some(span) = span {
+                        logprobs.get_or_insert_with(Vec::new).push(TokenLogprob {
+                            token: text,
+                            logprob: span.logprobs.iter().sum(),
+                            top_logprobs: Vec::new(),
+                        });
+                    }
+                }
+                StreamEvent::MessageEnd { delta } => {
+                    finish_reason = delta.finish_reason;
+                    usage = Self::usage(delta.usage);
+                    return Ok(false);
+                }
+                StreamEvent::Other => {}
+            }
+            Ok(true)
+        })
+        .await?;
+
+        Ok(Completion {
+            model: request.model.clone(),
+            finish_reason,
+            usage: usage.unwrap_or(TokenUsage {
+                prompt_tokens: 0,
+                completion_tokens: recorder.tokens(),
+            }),
+            logprobs,
+            timing: recorder.timing(),
+            text: recorder.text,
+        })
+    }
+}
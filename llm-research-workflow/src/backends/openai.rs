@@ -0,0 +1,294 @@
+use async_trait::async_trait;
+use llm_research_core::{CoreError, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::sse::{parse_event, read_events, StreamRecorder};
+use super::{
+    request_error, retry_after, status_error, Completion, InferenceBackend, InferenceRequest,
+    TokenLogprob, TokenUsage,
+};
+
+/// Client for OpenAI's chat completions API and the servers that implement
+/// it, such as vLLM, llama.cpp server, Ollama and Hugging Face TGI, which
+/// differ only in base URL
+pub struct OpenAiBackend {
+    client: reqwest::Client,
+    name: String,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiBackend {
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.openai.com/v1";
+
+    /// A client for the API under `base_url`, e.g. `http://localhost:8000/v1`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            name: "openai".to_string(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+        }
+    }
+
+    /// Sent as a bearer token; servers without authentication don't need one
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Provider name recorded with results, `openai` by default
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    fn body(request: &InferenceRequest) -> serde_json::Value {
+        let parameters = &request.parameters;
+        let mut body = json!({
+            "model": request.model,
+            "messages": request.messages,
+        });
+        let fields = [
+            ("max_tokens", json!(parameters.max_tokens)),
+            ("temperature", json!(parameters.temperature)),
+            ("top_p", json!(parameters.top_p)),
+            ("top_k", json!(parameters.top_k)),
+            ("frequency_penalty", json!(parameters.frequency_penalty)),
+            ("presence_penalty", json!(parameters.presence_penalty)),
+            ("stop", json!(parameters.stop_sequences)),
+            ("seed", json!(parameters.seed)),
+        ];
+        for (name, value) in fields {
+            if !value.is_null() {
+                body[name] = value;
+            }
+        }
+        if let Some(top_logprobs) = request.logprobs {
+            body["logprobs"] = json!(true);
+            body["top_logprobs"] = json!(top_logprobs);
+        }
+        // Server-specific options, e.g. vLLM's `repetition_penalty`
+        for (name, value) in &parameters.additional {
+            body[name.as_str()] = value.clone();
+        }
+        body
+    }
+
+    /// Posts `body`, turning an unsuccessful response into an error
+    async fn send(&self, body: &serde_json::Value) -> Result<reqwest::Response> {
+        let mut http = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(body);
+        if let Some(api_key) = &self.api_key {
+            http = http.bearer_auth(api_key);
+        }
+
+        let response = http
+            .send()
+            .await
+            .map_err(|e| request_error(&self.name, e))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let retry_after = retry_after(response.headers());
+        let body = response
+            .text()
+            .await
+            .map_err(|e| request_error(&self.name, e))?;
+        Err(status_error(
+            &self.name,
+            status,
+            &error_message(&body),
+            retry_after,
+        ))
+    }
+
+    fn model(&self, request: &InferenceRequest, reported: String) -> String {
+        if reported.is_empty() {
+            request.model.clone()
+        } else {
+            reported
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    #[serde(default)]
+    model: String,
+    choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: ResponseMessage,
+    #[serde(default)]
+    finish_reason: Option<String>,
+    #[serde(default)]
+    logprobs: Option<ChoiceLogprobs>,
+}
+
+#[derive(Default, Deserialize)]
+struct ResponseMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Usage {
+    #[serde(default)]
+    prompt_tokens: usize,
+    #[serde(default)]
+    completion_tokens: usize,
+}
+
+#[derive(Deserialize)]
+struct ChoiceLogprobs {
+    #[serde(default)]
+    content: Option<Vec<TokenLogprob>>,
+}
+
+#[derive(Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct ChunkChoice {
+    #[serde(default)]
+    delta: ResponseMessage,
+    #[serde(default)]
+    finish_reason: Option<String>,
+    #[serde(default)]
+    logprobs: Option<ChoiceLogprobs>,
+}
+
+/// The message of an OpenAI-style error body, `{"error": {"message": ...}}`,
+/// or of the `{"error": "..."}` bodies some compatible servers send
+pub(crate) fn error_message(body: &str) -> String {
+    let parsed: Option<serde_json::Value> = serde_json::from_str(body).ok();
+    let message = parsed.as_ref().and_then(|value| {
+        let error = value.get("error").unwrap_or(value);
+        error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .or_else(|| error.as_str())
+            .map(str::to_string)
+    });
+    message.unwrap_or_else(|| body.chars().take(500).collect())
+}
+
+#[async_trait]
+impl InferenceBackend for OpenAiBackend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn complete(&self, request: &InferenceRequest) -> Result<Completion> {
+        let response = self.send(&Self::body(request)).await?;
+        let body = response
+            .text()
+            .await
+            .map_err(|e| request_error(&self.name, e))?;
+
+        let response: ChatResponse = serde_json::from_str(&body).map_err(|e| {
+            CoreError::Serialization(format!("Unexpected response from {}: {}", self.name, e))
+        })?;
+        let choice = response.choices.into_iter().next().ok_or_else(|| {
+            CoreError::Serialization(format!("{} returned no choices", self.name))
+        })?;
+        let usage = response
+            .usage
+            .map_or_else(TokenUsage::default, |u| TokenUsage {
+                prompt_tokens: u.prompt_tokens,
+                completion_tokens: u.completion_tokens,
+            });
+
+        Ok(Completion {
+            text: choice.message.content.unwrap_or_default(),
+            model: self.model(request, response.model),
+            finish_reason: choice.finish_reason,
+            usage,
+            logprobs: choice.logprobs.and_then(|l| l.content),
+            timing: None,
+        })
+    }
+
+    async fn stream(&self, request: &InferenceRequest) -> Result<Completion> {
+        let mut body = Self::body(request);
+        body["stream"] = json!(true);
+        // Usage comes in a final chunk, if asked for
+        body["stream_options"] = json!({"include_usage": true});
+
+        let mut recorder = StreamRecorder::start();
+        let response = self.send(&body).await?;
+        let mut model = String::new();
+        let mut finish_reason = None;
+        let mut usage = None;
+        let mut logprobs: Option<Vec<TokenLogprob>> = None;
+        read_events(&self.name, response, |event| {
+            if event.data == "[DONE]" {
+                return Ok(false);
+            }
+            let chunk: ChatChunk = parse_event(&self.name, &event)?;
+            // The server failed after it started answering
+            if chunk.error.is_some() {
+                return Err(CoreError::Internal(format!(
+                    "{} stream failed: {}",
+                    self.name,
+                    error_message(&event.data)
+                )));
+            }
+            if !chunk.model.is_empty() {
+                model = chunk.model;
+            }
+            if let Some(u) = chunk.usage {
+                usage = Some(TokenUsage {
+                    prompt_tokens: u.prompt_tokens,
+                    completion_tokens: u.completion_tokens,
+                });
+            }
+            for choice in chunk.choices {
+                recorder.token(choice.delta.content.as_deref().unwrap_or_default());
+                if choice.finish_reason.is_some() {
+                    finish_reason = choice.finish_reason;
+                }
+                if let Some(content) = choice.logprobs.and_then(|l| l.content) {
+                    logprobs.get_or_insert_with(Vec::new).extend(content);
+                }
+            }
+            Ok(true)
+        })
+        .await?;
+
+        Ok(Completion {
+            model: self.model(request, model),
+            finish_reason,
+            usage: usage.unwrap_or(TokenUsage {
+                prompt_tokens: 0,
+                completion_tokens: recorder.tokens(),
+            }),
+            logprobs,
+            timing: recorder.timing(),
+            text: recorder.text,
+        })
+    }
+}
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use llm_research_core::Result;
+use tokio::time::{sleep, Duration};
+
+use super::sse::StreamRecorder;
+use super::{Completion, InferenceBackend, InferenceRequest, Role, TokenUsage};
+
+/// Answers every request with a canned response after a short delay, without
+/// calling a model; for dry runs of workflows and for tests
+#[derive(Debug, Clone, Default)]
+pub struct SimulatedBackend;
+
+impl SimulatedBackend {
+    fn prompt(request: &InferenceRequest) -> &str {
+        request
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.role == Role::User)
+            .map_or("", |m| m.content.as_str())
+    }
+
+    fn delay(prompt: &str) -> Duration {
+        Duration::from_millis(100 + (prompt.len() % 200) as u64)
+    }
+
+    fn completion(request: &InferenceRequest, prompt: &str, text: String) -> Completion {
+        Completion {
+            usage: TokenUsage {
+                prompt_tokens: prompt.len() / 4,
+                completion_tokens: text.len() / 4,
+            },
+            text,
+            model: request.model.clone(),
+            finish_reason: Some("stop".to_string()),
+            logprobs: None,
+            timing: None,
+        }
+    }
+}
+
+#[async_trait]
+impl InferenceBackend for SimulatedBackend {
+    fn name(&self) -> &str {
+        "simulated"
+    }
+
+    async fn complete(&self, request: &InferenceRequest) -> Result<Completion> {
+        let prompt = Self::prompt(request);
+        sleep(Self::delay(prompt)).await;
+
+        let text = format!("Simulated {} response to: {}", request.model, prompt);
+        Ok(Self::completion(request, prompt, text))
+    }
+
+    /// Sends the response a word at a time: the first after half the usual
+    /// delay, the rest spread over the other half
+    async fn stream(&self, request: &InferenceRequest) -> Result<Completion> {
+        let mut recorder = StreamRecorder::start();
+        let prompt = Self::prompt(request);
+        let delay = Self::delay(prompt);
+
+        let text = format!("Simulated {} response to: {}", request.model, prompt);
+        let words: Vec<&str> = text.split_inclusive(' ').collect();
+        let gap = delay / 2 / words.len() as u32;
+        sleep(delay / 2).await;
+        for (i, word) in words.iter().enumerate() {
+            if i > 0 {
+                sleep(gap).await;
+            }
+            recorder.token(word);
+        }
+
+        let timing = recorder.timing();
+        Ok(Completion {
+            timing,
+            ..Self::completion(request, prompt, recorder.text)
+        })
+    }
+}
@@ -0,0 +1,125 @@
+use llm_research_core::{CoreError, Result};
+use serde::de::DeserializeOwned;
+use std::time::Instant;
+
+use super::{request_error, TokenTiming};
+
+/// The data of one event of a `text/event-stream` response
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SseEvent {
+    pub data: String,
+}
+
+/// Splits a `text/event-stream` body into events as its chunks arrive, which
+/// may end anywhere, including inside a line
+#[derive(Debug, Default)]
+pub(crate) struct SseDecoder {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    /// The events completed by `chunk`
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                // A blank line dispatches the event
+                if !self.data.is_empty() {
+                    events.push(SseEvent {
+                        data: self.data.join("\n"),
+                    });
+                    self.data.clear();
+                }
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            // Providers repeat the event type in the data, so other fields
+            // can be ignored
+            if field == "data" {
+                self.data.push(value.to_string());
+            }
+        }
+        events
+    }
+}
+
+/// Reads the events of `response` as they arrive, handing each to
+/// `on_event` until the stream ends or `on_event` returns `false`
+pub(crate) async fn read_events(
+    provider: &str,
+    mut response: reqwest::Response,
+    mut on_event: impl FnMut(SseEvent) -> Result<bool>,
+) -> Result<()> {
+    let mut decoder = SseDecoder::default();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| request_error(provider, e))?
+    {
+        for event in decoder.push(&chunk) {
+            if !on_event(event)? {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Collects the text of a streamed completion with the time each piece of
+/// it arrived
+pub(crate) struct StreamRecorder {
+    start: Instant,
+    pub text: String,
+    arrivals_ms: Vec<f64>,
+}
+
+impl StreamRecorder {
+    /// Starts the clock; call just before sending the request
+    pub fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            text: String::new(),
+            arrivals_ms: Vec::new(),
+        }
+    }
+
+    pub fn token(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.arrivals_ms
+            .push(self.start.elapsed().as_secs_f64() * 1000.0);
+        self.text.push_str(text);
+    }
+
+    /// The number of pieces of text received, for servers that don't report
+    /// usage when streaming
+    pub fn tokens(&self) -> usize {
+        self.arrivals_ms.len()
+    }
+
+    pub fn timing(&self) -> Option<TokenTiming> {
+        let first_token_ms = *self.arrivals_ms.first()?;
+        Some(TokenTiming {
+            first_token_ms,
+            arrivals_ms: self.arrivals_ms.clone(),
+        })
+    }
+}
+
+/// Parses the JSON payload of a stream event
+pub(crate) fn parse_event<T: DeserializeOwned>(provider: &str, event: &SseEvent) -> Result<T> {
+    serde_json::from_str(&event.data).map_err(|e| {
+        CoreError::Serialization(format!("Unexpected stream event from {}: {}", provider, e))
+    })
+}
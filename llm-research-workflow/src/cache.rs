@@ -0,0 +1,225 @@
+use async_trait::async_trait;
+use llm_research_core::{ContentHash, CoreError, Result};
+use llm_research_storage::s3::ArtifactStorage;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+
+use crate::json_file::{io_error, read_json, remove_file, write_json_atomic};
+
+/// What I/O errors for this store's files are reported as
+const STORE: &str = "Output cache";
+
+/// Field of a step output recording how the cache was used for it, as
+/// `{"key": <hash>, "hit": <bool>}`. Not part of what is cached or hashed.
+pub const CACHE_RECORD_FIELD: &str = "_cache";
+
+/// Hash of `value` that is the same for equal values, whatever order their
+/// object keys were inserted in. Relies on serde_json keeping object keys
+/// sorted, so it would break if its `preserve_order` feature were enabled.
+pub fn hash_json(value: &serde_json::Value) -> ContentHash {
+    ContentHash::from_str(&value.to_string())
+}
+
+/// Key under which a step's output is cached: a hash of everything the output
+/// is derived from. `config` is the resolved config and `inputs` the outputs
+/// of the steps it depends on, keyed by step name.
+pub fn cache_key(
+    task_type: &str,
+    config: &serde_json::Value,
+    inputs: &HashMap<String, serde_json::Value>,
+    code_version: &str,
+) -> ContentHash {
+    let inputs: BTreeMap<&String, serde_json::Value> = inputs
+        .iter()
+        .map(|(name, output)| (name, without_cache_record(output)))
+        .collect();
+    let material = json!({
+        "task_type": task_type,
+        "config": config,
+        "inputs": inputs,
+        "code_version": code_version,
+    });
+    hash_json(&material)
+}
+
+/// `output` with its cache record, if any, removed
+pub fn without_cache_record(output: &serde_json::Value) -> serde_json::Value {
+    let mut output = output.clone();
+    if let Some(fields) = output.as_object_mut() {
+        fields.remove(CACHE_RECORD_FIELD);
+    }
+    output
+}
+
+/// `output` with a cache record for `key` added, if it is an object
+pub fn with_cache_record(
+    mut output: serde_json::Value,
+    key: &ContentHash,
+    hit: bool,
+) -> serde_json::Value {
+    if let Some(fields) = output.as_object_mut() {
+        fields.insert(
+            CACHE_RECORD_FIELD.to_string(),
+            json!({"key": key, "hit": hit}),
+        );
+    }
+    output
+}
+
+/// Step outputs memoized by `cache_key`, so a step whose task, config, inputs
+/// and code are unchanged since an earlier run is not run again
+#[async_trait]
+pub trait OutputCache: Send + Sync {
+    async fn get(&self, key: &ContentHash) -> Result<Option<serde_json::Value>>;
+
+    /// Insert or replace the output cached under `key`
+    async fn put(&self, key: &ContentHash, output: &serde_json::Value) -> Result<()>;
+
+    async fn remove(&self, key: &ContentHash) -> Result<()>;
+}
+
+/// Keeps outputs in memory; nothing survives the process
+#[derive(Debug, Default)]
+pub struct InMemoryOutputCache {
+    outputs: RwLock<HashMap<ContentHash, serde_json::Value>>,
+}
+
+impl InMemoryOutputCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.outputs.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.outputs.read().await.is_empty()
+    }
+}
+
+#[async_trait]
+impl OutputCache for InMemoryOutputCache {
+    async fn get(&self, key: &ContentHash) -> Result<Option<serde_json::Value>> {
+        Ok(self.outputs.read().await.get(key).cloned())
+    }
+
+    async fn put(&self, key: &ContentHash, output: &serde_json::Value) -> Result<()> {
+        self.outputs
+            .write()
+            .await
+            .insert(key.clone(), output.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, key: &ContentHash) -> Result<()> {
+        self.outputs.write().await.remove(key);
+        Ok(())
+    }
+}
+
+/// Writes one `<hash>.json` file per output under a directory
+#[derive(Debug, Clone)]
+pub struct FileOutputCache {
+    dir: PathBuf,
+}
+
+impl FileOutputCache {
+    /// Cache outputs under `dir`, creating it if needed
+    pub async fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| io_error(STORE, &dir, e))?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &ContentHash) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+#[async_trait]
+impl OutputCache for FileOutputCache {
+    async fn get(&self, key: &ContentHash) -> Result<Option<serde_json::Value>> {
+        read_json(STORE, &self.path(key)).await
+    }
+
+    async fn put(&self, key: &ContentHash, output: &serde_json::Value) -> Result<()> {
+        write_json_atomic(STORE, &self.path(key), output).await
+    }
+
+    async fn remove(&self, key: &ContentHash) -> Result<()> {
+        remove_file(STORE, &self.path(key)).await
+    }
+}
+
+/// Stores outputs in the artifact store as `<prefix>/<hash>.json` objects
+pub struct ArtifactOutputCache {
+    storage: ArtifactStorage,
+    prefix: String,
+}
+
+impl ArtifactOutputCache {
+    pub fn new(storage: ArtifactStorage, prefix: impl Into<String>) -> Self {
+        Self {
+            storage,
+            prefix: prefix.into(),
+        }
+    }
+
+    fn object_key(&self, key: &ContentHash) -> String {
+        format!("{}/{}.json", self.prefix.trim_end_matches('/'), key)
+    }
+}
+
+fn storage_error(key: &str, err: anyhow::Error) -> CoreError {
+    CoreError::Internal(format!("Output cache storage failed for {}: {}", key, err))
+}
+
+#[async_trait]
+impl OutputCache for ArtifactOutputCache {
+    async fn get(&self, key: &ContentHash) -> Result<Option<serde_json::Value>> {
+        let object_key = self.object_key(key);
+        if !self
+            .storage
+            .exists(&object_key)
+            .await
+            .map_err(|e| storage_error(&object_key, e))?
+        {
+            return Ok(None);
+        }
+        let bytes = self
+            .storage
+            .download(&object_key)
+            .await
+            .map_err(|e| storage_error(&object_key, e))?;
+        Ok(Some(serde_json::from_slice(&bytes)?))
+    }
+
+    async fn put(&self, key: &ContentHash, output: &serde_json::Value) -> Result<()> {
+        let object_key = self.object_key(key);
+        self.storage
+            .upload(
+                &object_key,
+                serde_json::to_vec(output)?,
+                Some("application/json"),
+            )
+            .await
+            .map_err(|e| storage_error(&object_key, e))
+    }
+
+    async fn remove(&self, key: &ContentHash) -> Result<()> {
+        let object_key = self.object_key(key);
+        self.storage
+            .delete(&object_key)
+            .await
+            .map_err(|e| storage_error(&object_key, e))
+    }
+}
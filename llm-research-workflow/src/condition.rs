@@ -0,0 +1,143 @@
+use llm_research_core::{CoreError, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::template::resolve_config;
+
+/// Predicate deciding whether a step runs. Operands are JSON values that may
+/// be, or contain, `${steps.<name>.output...}` and `${params...}` references.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// `left <op> right`
+    Compare {
+        left: serde_json::Value,
+        op: CompareOp,
+        right: serde_json::Value,
+    },
+    /// The value is true, a non-zero number, or a non-empty string, array or object
+    Truthy(serde_json::Value),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Condition {
+    pub fn compare(left: serde_json::Value, op: CompareOp, right: serde_json::Value) -> Self {
+        Self::Compare { left, op, right }
+    }
+
+    /// Evaluate against completed step `outputs`, keyed by step name, and the
+    /// workflow `params`
+    pub fn evaluate(
+        &self,
+        outputs: &HashMap<String, serde_json::Value>,
+        params: &serde_json::Value,
+    ) -> Result<bool> {
+        match self {
+            Self::Compare { left, op, right } => {
+                let left = resolve_config(left, outputs, params)?;
+                let right = resolve_config(right, outputs, params)?;
+                compare(&left, *op, &right)
+            }
+            Self::Truthy(value) => Ok(is_truthy(&resolve_config(value, outputs, params)?)),
+            Self::All(conditions) => {
+                for condition in conditions {
+                    if !condition.evaluate(outputs, params)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Self::Any(conditions) => {
+                for condition in conditions {
+                    if condition.evaluate(outputs, params)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Self::Not(condition) => Ok(!condition.evaluate(outputs, params)?),
+        }
+    }
+}
+
+/// Numbers compare numerically and strings lexically; `eq`/`ne` accept any
+/// two values
+fn compare(left: &serde_json::Value, op: CompareOp, right: &serde_json::Value) -> Result<bool> {
+    let order = || {
+        let ordering = match (left, right) {
+            (serde_json::Value::Number(a), serde_json::Value::Number(b)) => a
+                .as_f64()
+                .zip(b.as_f64())
+                .and_then(|(a, b)| a.partial_cmp(&b)),
+            (serde_json::Value::String(a), serde_json::Value::String(b)) => Some(a.cmp(b)),
+            _ => None,
+        };
+        ordering.ok_or_else(|| {
+            CoreError::Validation(format!(
+                "Cannot compare {} with {} using '{:?}'",
+                left, right, op
+            ))
+        })
+    };
+
+    Ok(match op {
+        CompareOp::Eq => values_equal(left, right),
+        CompareOp::Ne => !values_equal(left, right),
+        CompareOp::Lt => order()? == Ordering::Less,
+        CompareOp::Le => order()? != Ordering::Greater,
+        CompareOp::Gt => order()? == Ordering::Greater,
+        CompareOp::Ge => order()? != Ordering::Less,
+    })
+}
+
+/// Like `==`, but `1` equals `1.0`
+fn values_equal(left: &serde_json::Value, right: &serde_json::Value) -> bool {
+    match (left.as_f64(), right.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => left == right,
+    }
+}
+
+fn is_truthy(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => false,
+        serde_json::Value::Bool(b) => *b,
+        serde_json::Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        serde_json::Value::String(s) => !s.is_empty(),
+        serde_json::Value::Array(items) => !items.is_empty(),
+        serde_json::Value::Object(map) => !map.is_empty(),
+    }
+}
+
+/// When a step runs relative to the rest of the workflow
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepTrigger {
+    /// Once its dependencies have completed or been skipped
+    #[default]
+    OnSuccess,
+    /// Cleanup: after every other step has finished, only if one failed
+    OnFailure,
+    /// Cleanup: after every other step has finished, whatever the outcome
+    Always,
+}
+
+impl StepTrigger {
+    pub fn is_cleanup(&self) -> bool {
+        !matches!(self, Self::OnSuccess)
+    }
+}
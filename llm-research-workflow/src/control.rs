@@ -0,0 +1,135 @@
+use llm_research_core::{CoreError, Result};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+/// Pause flag shared between the engine and the tasks it runs
+#[derive(Debug, Clone)]
+pub struct PauseToken {
+    paused: Arc<watch::Sender<bool>>,
+}
+
+impl PauseToken {
+    pub fn new() -> Self {
+        Self {
+            paused: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    pub fn pause(&self) {
+        self.paused.send_replace(true);
+    }
+
+    pub fn resume(&self) {
+        self.paused.send_replace(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    /// Wait until the token is not paused
+    pub async fn resumed(&self) {
+        self.changed_from(true).await
+    }
+
+    /// Wait until the token's paused flag differs from `paused`
+    pub async fn changed_from(&self, paused: bool) {
+        let mut rx = self.paused.subscribe();
+        // The sender lives as long as `self`, so this cannot fail
+        let _ = rx.wait_for(|current| *current != paused).await;
+    }
+}
+
+impl Default for PauseToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cooperative cancellation and pause signals handed to a running task
+/// through its `TaskContext`. Tasks call `checkpoint` between units of work
+/// (batches, requests) and race long awaits against `cancelled`.
+#[derive(Debug, Clone, Default)]
+pub struct TaskControl {
+    cancellation: CancellationToken,
+    pause: PauseToken,
+}
+
+impl TaskControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A control that is cancelled along with this one but can also be
+    /// cancelled on its own. Pausing is shared.
+    pub fn child(&self) -> Self {
+        Self {
+            cancellation: self.cancellation.child_token(),
+            pause: self.pause.clone(),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// Completes once the control is cancelled
+    pub async fn cancelled(&self) {
+        self.cancellation.cancelled().await
+    }
+
+    pub fn pause(&self) {
+        self.pause.pause();
+    }
+
+    pub fn resume(&self) {
+        self.pause.resume();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause.is_paused()
+    }
+
+    /// Completes once the control is paused if `paused` is false, or
+    /// resumed if it is true
+    pub async fn pause_changed(&self, paused: bool) {
+        self.pause.changed_from(paused).await
+    }
+
+    /// Pause point: waits while paused, then fails if cancelled
+    pub async fn checkpoint(&self) -> Result<()> {
+        if self.is_paused() {
+            tokio::select! {
+                _ = self.cancelled() => {}
+                _ = self.pause.resumed() => {}
+            }
+        }
+
+        if self.is_cancelled() {
+            return Err(cancelled_error());
+        }
+        Ok(())
+    }
+
+    /// Run `future`, abandoning it if the control is cancelled first
+    pub async fn run_until_cancelled<T, F>(&self, future: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        tokio::select! {
+            biased;
+            _ = self.cancelled() => Err(cancelled_error()),
+            result = future => result,
+        }
+    }
+}
+
+fn cancelled_error() -> CoreError {
+    CoreError::InvalidState("Task cancelled".to_string())
+}
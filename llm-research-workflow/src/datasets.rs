@@ -0,0 +1,374 @@
+mod formats;
+
+use llm_research_core::{CoreError, DataSplit, DatasetSample, Result};
+use llm_research_storage::s3::S3Storage;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncRead;
+use tokio::sync::mpsc;
+use tokio_util::io::SyncIoBridge;
+use uuid::Uuid;
+
+use crate::sampling::in_split;
+
+/// Rows read ahead of whoever consumes a `SampleStream`; reading waits once
+/// this many are queued
+const READ_AHEAD: usize = 256;
+
+/// Rows a `synthetic://` source generates
+const SYNTHETIC_SAMPLES: usize = 1000;
+
+/// How a dataset file is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DataFormat {
+    /// One JSON value per line
+    Jsonl,
+    /// Comma-separated values under a header row; every value is a string
+    Csv,
+    Parquet,
+    /// One JSON document listing the rows: a top-level array, a `data`
+    /// field, or the `rows` of a Hugging Face datasets-server response
+    HfJson,
+}
+
+impl DataFormat {
+    /// The format a file name's extension implies, if any
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "jsonl" | "ndjson" => Some(Self::Jsonl),
+            "csv" => Some(Self::Csv),
+            "parquet" => Some(Self::Parquet),
+            "json" => Some(Self::HfJson),
+            _ => None,
+        }
+    }
+}
+
+/// Where a dataset is read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataSource {
+    Local(PathBuf),
+    S3 {
+        bucket: String,
+        key: String,
+    },
+    /// Placeholder samples `"Sample 0"`, `"Sample 1"`, ..., for dry runs
+    Synthetic(String),
+}
+
+impl DataSource {
+    /// Parses `s3://bucket/key`, `synthetic://name`, or a local path, which
+    /// may start with `file://`
+    pub fn parse(source: &str) -> Result<Self> {
+        if let Some(location) = source.strip_prefix("s3://") {
+            return match location.split_once('/') {
+                Some((bucket, key)) if !bucket.is_empty() && !key.is_empty() => Ok(Self::S3 {
+                    bucket: bucket.to_string(),
+                    key: key.to_string(),
+                }),
+                _ => Err(CoreError::Validation(format!(
+                    "S3 data source '{}' must look like s3://bucket/key",
+                    source
+                ))),
+            };
+        }
+        if let Some(name) = source.strip_prefix("synthetic://") {
+            return Ok(Self::Synthetic(name.to_string()));
+        }
+
+        let path = source.strip_prefix("file://").unwrap_or(source);
+        if path.is_empty() {
+            return Err(CoreError::Validation("Data source is empty".to_string()));
+        }
+        Ok(Self::Local(PathBuf::from(path)))
+    }
+
+    /// The format the source's file extension implies, if any
+    pub fn format(&self) -> Option<DataFormat> {
+        match self {
+            Self::Local(path) => DataFormat::from_path(path.to_str()?),
+            Self::S3 { key, .. } => DataFormat::from_path(key),
+            Self::Synthetic(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for DataSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local(path) => write!(f, "{}", path.display()),
+            Self::S3 { bucket, key } => write!(f, "s3://{}/{}", bucket, key),
+            Self::Synthetic(name) => write!(f, "synthetic://{}", name),
+        }
+    }
+}
+
+/// Which fields of a row become a sample's input and expected output.
+/// Fields are top-level keys or dotted paths into nested objects and lists,
+/// such as `answers.text.0`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldMapping {
+    /// Field holding the input. Unset, the input is the whole row less the
+    /// expected output field; set, the row's other fields become metadata.
+    #[serde(default)]
+    pub input: Option<String>,
+    #[serde(default)]
+    pub expected_output: Option<String>,
+}
+
+impl FieldMapping {
+    /// Maps `row`, the dataset's `index`th, into a sample. A mapped field
+    /// missing from the row is an error.
+    pub fn sample(&self, dataset_id: Uuid, index: i64, row: Value) -> Result<DatasetSample> {
+        let field = |name: &String| {
+            lookup(&row, name).cloned().ok_or_else(|| {
+                CoreError::Validation(format!("Row {} has no field '{}'", index, name))
+            })
+        };
+        let expected_output = self.expected_output.as_ref().map(field).transpose()?;
+        let (input, metadata) = match &self.input {
+            Some(name) => {
+                let input = field(name)?;
+                (input, self.unmapped(row))
+            }
+            None => (self.unmapped(row), json!({})),
+        };
+
+        Ok(DatasetSample {
+            id: sample_id(dataset_id, index),
+            ..DatasetSample::new(dataset_id, index, input, expected_output, metadata)
+        })
+    }
+
+    /// The row less the top-level fields the mapping takes from it
+    fn unmapped(&self, row: Value) -> Value {
+        match row {
+            Value::Object(mut fields) => {
+                for name in self.input.iter().chain(&self.expected_output) {
+                    if fields.remove(name).is_none() {
+                        fields.remove(name.split('.').next().unwrap_or(name));
+                    }
+                }
+                Value::Object(fields)
+            }
+            other => other,
+        }
+    }
+}
+
+/// A key of `row`, or else a dotted path into it
+pub(crate) fn lookup<'a>(row: &'a Value, field: &str) -> Option<&'a Value> {
+    row.get(field).or_else(|| {
+        field.split('.').try_fold(row, |value, key| match value {
+            Value::Object(fields) => fields.get(key),
+            Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+            _ => None,
+        })
+    })
+}
+
+/// The dataset a source's samples belong to when none is given, the same
+/// for every load of that source
+pub fn default_dataset_id(source: &str) -> Uuid {
+    Uuid::new_v5(&Uuid::NAMESPACE_URL, source.as_bytes())
+}
+
+/// ID of the dataset's `index`th sample, the same for every load
+pub fn sample_id(dataset_id: Uuid, index: i64) -> Uuid {
+    Uuid::new_v5(&dataset_id, index.to_string().as_bytes())
+}
+
+/// Samples read from a dataset in the background as they are asked for, so
+/// no more than a bounded number of rows are held at once
+#[derive(Debug)]
+pub struct SampleStream {
+    rows: mpsc::Receiver<Result<Value>>,
+    fields: FieldMapping,
+    dataset_id: Uuid,
+    next_index: i64,
+    remaining: Option<usize>,
+    split: Option<(DataSplit, String)>,
+}
+
+impl SampleStream {
+    /// Starts reading `source` as `format`, or the format its extension
+    /// implies. S3 sources are read through `storage`, which must hold
+    /// their bucket.
+    pub async fn open(
+        source: &DataSource,
+        format: Option<DataFormat>,
+        storage: Option<&S3Storage>,
+    ) -> Result<Self> {
+        let (tx, rx) = mpsc::channel(READ_AHEAD);
+        let name = source.to_string();
+        let format = match source {
+            DataSource::Synthetic(_) => None,
+            _ => Some(format.or_else(|| source.format()).ok_or_else(|| {
+                CoreError::Validation(format!(
+                    "Can't tell the format of {}; set `format` to jsonl, csv, parquet or hf-json",
+                    name
+                ))
+            })?),
+        };
+
+        match (source, format) {
+            (DataSource::Synthetic(_), _) | (_, None) => {
+                tokio::spawn(async move {
+                    for i in 0..SYNTHETIC_SAMPLES {
+                        if tx.send(Ok(json!(format!("Sample {}", i)))).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            (DataSource::Local(path), Some(DataFormat::Parquet)) => {
+                let file = tokio::fs::File::open(path)
+                    .await
+                    .map_err(|e| io_error(&name, e))?
+                    .into_std()
+                    .await;
+                tokio::task::spawn_blocking(move || formats::read_parquet(&name, file, tx));
+            }
+            (DataSource::Local(path), Some(format)) => {
+                let file = tokio::fs::File::open(path)
+                    .await
+                    .map_err(|e| io_error(&name, e))?;
+                read_text(name, format, file, tx);
+            }
+            (DataSource::S3 { bucket, key }, Some(format)) => {
+                let storage = match storage {
+                    Some(storage) if storage.bucket() == bucket => storage,
+                    _ => {
+                        return Err(CoreError::Validation(format!(
+                            "No storage for the S3 bucket of {}",
+                            name
+                        )))
+                    }
+                };
+                let mut body = storage
+                    .reader(key)
+                    .await
+                    .map_err(|e| storage_error(&name, e))?;
+
+                if format == DataFormat::Parquet {
+                    // Parquet is read from the end, so spool it to disk
+                    // rather than memory first
+                    let spool = tempfile::tempfile().map_err(|e| io_error(&name, e))?;
+                    let mut spool = tokio::fs::File::from_std(spool);
+                    tokio::io::copy(&mut body, &mut spool)
+                        .await
+                        .map_err(|e| io_error(&name, e))?;
+                    let file = spool.into_std().await;
+                    tokio::task::spawn_blocking(move || formats::read_parquet(&name, file, tx));
+                } else {
+                    read_text(name, format, body, tx);
+                }
+            }
+        }
+
+        Ok(Self {
+            rows: rx,
+            fields: FieldMapping::default(),
+            dataset_id: Uuid::nil(),
+            next_index: 0,
+            remaining: None,
+            split: None,
+        })
+    }
+
+    pub fn with_fields(mut self, fields: FieldMapping) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    pub fn with_dataset_id(mut self, dataset_id: Uuid) -> Self {
+        self.dataset_id = dataset_id;
+        self
+    }
+
+    /// Stop after `limit` samples, if set
+    pub fn with_limit(mut self, limit: Option<usize>) -> Self {
+        self.remaining = limit;
+        self
+    }
+
+    /// Skip samples whose `field` doesn't name `split`. Samples
+    /// keep their index in the whole dataset.
+    pub fn with_split(mut self, split: DataSplit, field: impl Into<String>) -> Self {
+        self.split = Some((split, field.into()));
+        self
+    }
+
+    pub fn dataset_id(&self) -> Uuid {
+        self.dataset_id
+    }
+
+    /// The next sample, or `None` once the dataset or the limit is reached
+    pub async fn next(&mut self) -> Option<Result<DatasetSample>> {
+        if self.remaining == Some(0) {
+            return None;
+        }
+        let sample = loop {
+            let row = match self.rows.recv().await? {
+                Ok(row) => row,
+                Err(e) => return Some(Err(e)),
+            };
+            let index = self.next_index;
+            self.next_index += 1;
+
+            let sample = match self.fields.sample(self.dataset_id, index, row) {
+                Ok(sample) => sample,
+                Err(e) => return Some(Err(e)),
+            };
+            match &self.split {
+                Some((split, field)) if !in_split(&sample, split, field) => continue,
+                _ => break sample,
+            }
+        };
+
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+            if *remaining == 0 {
+                // Nothing more will be read, so let the reader stop
+                self.rows.close();
+            }
+        }
+        Some(Ok(sample))
+    }
+
+    /// Up to `size` more samples; empty once there are none left
+    pub async fn next_batch(&mut self, size: usize) -> Result<Vec<DatasetSample>> {
+        let mut batch = Vec::with_capacity(size.min(READ_AHEAD));
+        while batch.len() < size {
+            match self.next().await {
+                Some(sample) => batch.push(sample?),
+                None => break,
+            }
+        }
+        Ok(batch)
+    }
+}
+
+/// Parses a text format from `input` on a blocking thread
+fn read_text<R>(name: String, format: DataFormat, input: R, rows: mpsc::Sender<Result<Value>>)
+where
+    R: AsyncRead + Send + Unpin + 'static,
+{
+    let input = std::io::BufReader::new(SyncIoBridge::new(input));
+    tokio::task::spawn_blocking(move || formats::read_text(&name, format, input, rows));
+}
+
+fn io_error(source: &str, err: std::io::Error) -> CoreError {
+    if err.kind() == std::io::ErrorKind::NotFound {
+        return CoreError::NotFound(format!("Data source {}", source));
+    }
+    CoreError::Internal(format!("Reading {} failed: {}", source, err))
+}
+
+fn storage_error(source: &str, err: anyhow::Error) -> CoreError {
+    CoreError::Internal(format!("Reading {} from storage failed: {}", source, err))
+}
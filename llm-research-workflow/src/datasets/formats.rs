@@ -0,0 +1,193 @@
+use llm_research_core::{CoreError, Result};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Unexpected, Visitor};
+use serde_json::Value;
+use std::fmt;
+use std::fs::File;
+use std::io::BufRead;
+use tokio::sync::mpsc::Sender;
+
+use super::DataFormat;
+
+/// Sends each row of `input` as it is parsed, stopping early if the receiver
+/// goes away. A failure is sent as the last item. Blocks, so runs on a
+/// blocking thread.
+pub(super) fn read_text(
+    source: &str,
+    format: DataFormat,
+    input: impl BufRead,
+    rows: Sender<Result<Value>>,
+) {
+    let result = match format {
+        DataFormat::Jsonl => read_jsonl(source, input, &rows),
+        DataFormat::Csv => read_csv(source, input, &rows),
+        DataFormat::HfJson => read_hf_json(source, input, &rows),
+        DataFormat::Parquet => Err(CoreError::Internal(
+            "Parquet is not a text format".to_string(),
+        )),
+    };
+    if let Err(e) = result {
+        let _ = rows.blocking_send(Err(e));
+    }
+}
+
+/// Like `read_text`, for a Parquet file
+pub(super) fn read_parquet(source: &str, file: File, rows: Sender<Result<Value>>) {
+    let read = || -> Result<()> {
+        let reader = SerializedFileReader::new(file).map_err(|e| invalid(source, e))?;
+        // Row groups are read from the file one at a time as rows are taken
+        for row in reader.get_row_iter(None).map_err(|e| invalid(source, e))? {
+            let row = row.map_err(|e| invalid(source, e))?;
+            if rows.blocking_send(Ok(row.to_json_value())).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    };
+    if let Err(e) = read() {
+        let _ = rows.blocking_send(Err(e));
+    }
+}
+
+fn read_jsonl(source: &str, input: impl BufRead, rows: &Sender<Result<Value>>) -> Result<()> {
+    for (n, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| super::io_error(source, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let row = serde_json::from_str(&line)
+            .map_err(|e| invalid(source, format!("line {}: {}", n + 1, e)))?;
+        if rows.blocking_send(Ok(row)).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn read_csv(source: &str, input: impl BufRead, rows: &Sender<Result<Value>>) -> Result<()> {
+    let csv_error = |e: csv::Error| {
+        if e.is_io_error() {
+            CoreError::Internal(format!("Reading {} failed: {}", source, e))
+        } else {
+            invalid(source, e)
+        }
+    };
+
+    let mut reader = csv::Reader::from_reader(input);
+    let headers = reader.headers().map_err(csv_error)?.clone();
+    for record in reader.records() {
+        let record = record.map_err(csv_error)?;
+        let row = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(header, value)| (header.to_string(), Value::String(value.to_string())))
+            .collect();
+        if rows.blocking_send(Ok(Value::Object(row))).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn read_hf_json(source: &str, input: impl BufRead, rows: &Sender<Result<Value>>) -> Result<()> {
+    let mut send = |row| rows.blocking_send(Ok(row)).is_ok();
+    let mut deserializer = serde_json::Deserializer::from_reader(input);
+    let parsed = Rows {
+        send: &mut send,
+        top_level: true,
+    }
+    .deserialize(&mut deserializer)
+    .and_then(|()| deserializer.end());
+
+    match parsed {
+        Ok(()) => Ok(()),
+        // Stopped because nobody wants more rows
+        Err(_) if rows.is_closed() => Ok(()),
+        Err(e) if e.is_io() => Err(CoreError::Internal(format!(
+            "Reading {} failed: {}",
+            source, e
+        ))),
+        Err(e) => Err(invalid(source, e)),
+    }
+}
+
+fn invalid(source: &str, err: impl fmt::Display) -> CoreError {
+    CoreError::Validation(format!("Malformed data in {}: {}", source, err))
+}
+
+/// Sends each element of a list of rows as it is parsed, without holding the
+/// list. At the top level it also accepts an object whose `rows` or `data`
+/// field is the list.
+struct Rows<'a> {
+    send: &'a mut dyn FnMut(Value) -> bool,
+    top_level: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for Rows<'_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Rows<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.top_level {
+            f.write_str("a list of rows, or an object with a `rows` or `data` list")
+        } else {
+            f.write_str("a list of rows")
+        }
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while let Some(row) = seq.next_element::<Value>()? {
+            if !(self.send)(unwrap_row(row)) {
+                return Err(de::Error::custom("stopped reading"));
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        if !self.top_level {
+            return Err(de::Error::invalid_type(Unexpected::Map, &self));
+        }
+
+        let send = self.send;
+        let mut found = false;
+        while let Some(key) = map.next_key::<String>()? {
+            if !found && (key == "rows" || key == "data") {
+                map.next_value_seed(Rows {
+                    send: &mut *send,
+                    top_level: false,
+                })?;
+                found = true;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        if !found {
+            return Err(de::Error::missing_field("rows"));
+        }
+        Ok(())
+    }
+}
+
+/// A datasets-server row, `{"row_idx": 0, "row": {...}}`, is its `row`
+fn unwrap_row(row: Value) -> Value {
+    match row {
+        Value::Object(mut fields)
+            if fields.contains_key("row_idx")
+                && fields.get("row").is_some_and(Value::is_object) =>
+        {
+            fields.remove("row").unwrap_or_default()
+        }
+        other => other,
+    }
+}
@@ -0,0 +1,795 @@
+use llm_research_core::{CoreError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::condition::{Condition, StepTrigger};
+use crate::engine::{Workflow, WorkflowStep};
+use crate::fan_out::{FanOut, FanOutTask};
+use crate::pipeline::{Pipeline, PipelineStage, PipelineTask, TaskDAG};
+use crate::registry::TaskRegistry;
+use crate::retry::Backoff;
+use crate::scheduler::{FailurePolicy, ResourceHints};
+use crate::template::{find_references, Reference, ValueType};
+
+/// Syntax of a pipeline definition file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefinitionFormat {
+    Yaml,
+    Toml,
+}
+
+impl DefinitionFormat {
+    /// Format implied by a `.yaml`, `.yml` or `.toml` extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// A pipeline or workflow as written in a YAML or TOML file. Tasks refer to
+/// each other by name; UUIDs are assigned when the definition is turned into
+/// a `Pipeline` or `Workflow`.
+///
+/// Tasks are given either as a flat `tasks` list, ordered only by their
+/// dependencies, or grouped into `stages` that run one after another.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub failure_policy: FailurePolicy,
+    /// Values tasks can refer to as `${params.<name>}`
+    #[serde(default = "empty_config")]
+    pub parameters: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tasks: Vec<TaskDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<StageDefinition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StageDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub parallel: bool,
+    pub tasks: Vec<TaskDefinition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaskDefinition {
+    pub name: String,
+    /// Task type, looked up in the `TaskRegistry`
+    #[serde(rename = "type")]
+    pub task_type: String,
+    #[serde(default = "empty_config")]
+    pub config: serde_json::Value,
+    /// Names of the tasks this one needs the outputs of
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    pub max_retries: Option<usize>,
+    /// Wait before each retry
+    pub backoff: Option<Backoff>,
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub resources: ResourceHints,
+    /// Run the task only if this holds; otherwise it is skipped
+    #[serde(default, deserialize_with = "deserialize_condition")]
+    pub when: Option<Condition>,
+    #[serde(default)]
+    pub trigger: StepTrigger,
+    /// Run the task once per item of a list instead of once
+    pub fan_out: Option<FanOut>,
+    /// Set to `false` to always run the task rather than reuse a cached output
+    pub cache: Option<bool>,
+}
+
+fn empty_config() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+/// serde_yaml only reads enums written as `!tag`s, so a condition is read as
+/// plain data first and converted from that
+fn deserialize_condition<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<Condition>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<serde_json::Value>::deserialize(deserializer)?
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+impl PipelineDefinition {
+    /// All tasks, stage by stage, in the order they were declared
+    pub fn all_tasks(&self) -> impl Iterator<Item = &TaskDefinition> {
+        self.tasks
+            .iter()
+            .chain(self.stages.iter().flat_map(|stage| &stage.tasks))
+    }
+
+    /// Give every task an ID and resolve `depends_on` names to those IDs
+    fn assign_ids(&self) -> Result<HashMap<&str, Uuid>> {
+        let ids: HashMap<&str, Uuid> = self
+            .all_tasks()
+            .map(|task| (task.name.as_str(), Uuid::new_v4()))
+            .collect();
+
+        for task in self.all_tasks() {
+            if let Some(missing) = task
+                .depends_on
+                .iter()
+                .find(|dep| !ids.contains_key(dep.as_str()))
+            {
+                return Err(CoreError::Validation(format!(
+                    "Task '{}' depends on unknown task '{}'",
+                    task.name, missing
+                )));
+            }
+        }
+        Ok(ids)
+    }
+
+    fn pipeline_task(task: &TaskDefinition, ids: &HashMap<&str, Uuid>) -> PipelineTask {
+        if task.resources != ResourceHints::default() || task.cache.is_some() {
+            tracing::warn!(
+                "Task '{}' sets resources or cache, which pipelines ignore; load it as a \
+                 workflow to use them",
+                task.name
+            );
+        }
+
+        PipelineTask {
+            id: ids[task.name.as_str()],
+            name: task.name.clone(),
+            task_type: task.task_type.clone(),
+            config: task.config.clone(),
+            dependencies: task
+                .depends_on
+                .iter()
+                .map(|dep| ids[dep.as_str()])
+                .collect(),
+            max_retries: task.max_retries.unwrap_or_default(),
+            backoff: task.backoff.clone().unwrap_or_default(),
+            timeout_secs: task.timeout_secs,
+        }
+    }
+
+    /// Build a `Pipeline`. A flat task list becomes one parallel stage per
+    /// dependency level, so every task runs after the tasks it depends on.
+    /// Tasks keep their retries, backoff and timeout. Conditions, cleanup
+    /// triggers and fan-outs are only supported by workflows; pipelines have
+    /// no resource limits or output cache, so `resources` and `cache` have no
+    /// effect.
+    pub fn to_pipeline(&self) -> Result<Pipeline> {
+        if let Some(task) = self
+            .all_tasks()
+            .find(|task| task.when.is_some() || task.trigger.is_cleanup() || task.fan_out.is_some())
+        {
+            return Err(CoreError::Validation(format!(
+                "Task '{}' has a condition, trigger or fan-out, which pipelines do not support; load it as a workflow",
+                task.name
+            )));
+        }
+        let ids = self.assign_ids()?;
+
+        let mut stages: Vec<PipelineStage> = self
+            .stages
+            .iter()
+            .map(|stage| PipelineStage {
+                id: Uuid::new_v4(),
+                name: stage.name.clone(),
+                parallel: stage.parallel,
+                tasks: stage
+                    .tasks
+                    .iter()
+                    .map(|task| Self::pipeline_task(task, &ids))
+                    .collect(),
+            })
+            .collect();
+
+        let mut scheduled: HashSet<&str> = HashSet::new();
+        let mut remaining: Vec<&TaskDefinition> = self.tasks.iter().collect();
+        while !remaining.is_empty() {
+            let (ready, blocked): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|task| {
+                task.depends_on
+                    .iter()
+                    .all(|dep| scheduled.contains(dep.as_str()))
+            });
+            if ready.is_empty() {
+                return Err(CoreError::Validation(
+                    "Pipeline contains circular dependencies".to_string(),
+                ));
+            }
+
+            scheduled.extend(ready.iter().map(|task| task.name.as_str()));
+            stages.push(PipelineStage {
+                id: Uuid::new_v4(),
+                name: format!("Stage {}", stages.len() + 1),
+                parallel: true,
+                tasks: ready
+                    .into_iter()
+                    .map(|task| Self::pipeline_task(task, &ids))
+                    .collect(),
+            });
+            remaining = blocked;
+        }
+
+        Ok(Pipeline {
+            id: Uuid::new_v4(),
+            name: self.name.clone(),
+            stages,
+        })
+    }
+
+    /// Build a `Workflow`. Stages only group tasks here: the engine schedules
+    /// steps by their dependencies alone.
+    pub fn to_workflow(&self) -> Result<Workflow> {
+        let ids = self.assign_ids()?;
+
+        let steps = self
+            .all_tasks()
+            .map(|task| {
+                let mut step = WorkflowStep::new(
+                    task.name.clone(),
+                    task.task_type.clone(),
+                    task.config.clone(),
+                )
+                .with_dependencies(
+                    task.depends_on
+                        .iter()
+                        .map(|dep| ids[dep.as_str()])
+                        .collect(),
+                )
+                .with_resources(task.resources.clone());
+                step.id = ids[task.name.as_str()];
+                if let Some(max_retries) = task.max_retries {
+                    step = step.with_max_retries(max_retries);
+                }
+                if let Some(backoff) = &task.backoff {
+                    step = step.with_backoff(backoff.clone());
+                }
+                if let Some(timeout_secs) = task.timeout_secs {
+                    step = step.with_timeout_secs(timeout_secs);
+                }
+                if let Some(when) = &task.when {
+                    step = step.with_condition(when.clone());
+                }
+                if let Some(fan_out) = &task.fan_out {
+                    step = step.with_fan_out(fan_out.clone());
+                }
+                if let Some(cache) = task.cache {
+                    step = step.with_cache(cache);
+                }
+                step.with_trigger(task.trigger)
+            })
+            .collect();
+
+        Ok(Workflow::new(self.name.clone(), steps)
+            .with_failure_policy(self.failure_policy)
+            .with_parameters(self.parameters.clone()))
+    }
+}
+
+/// A problem found in a definition file, at the line it was found on if known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinitionError {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl DefinitionError {
+    fn new(line: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for DefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Parses pipeline definition files and checks them against the task types
+/// a `TaskRegistry` can build
+#[derive(Debug, Clone)]
+pub struct PipelineLoader {
+    registry: TaskRegistry,
+}
+
+impl PipelineLoader {
+    pub fn new(registry: TaskRegistry) -> Self {
+        Self { registry }
+    }
+
+    pub fn registry(&self) -> &TaskRegistry {
+        &self.registry
+    }
+
+    /// Parse and validate `source`, returning every problem found
+    pub fn check(
+        &self,
+        source: &str,
+        format: DefinitionFormat,
+    ) -> std::result::Result<PipelineDefinition, Vec<DefinitionError>> {
+        let definition = parse(source, format).map_err(|e| vec![e])?;
+        let errors = self.validate(&definition, source);
+        if errors.is_empty() {
+            Ok(definition)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Parse and validate `source`
+    pub fn parse(&self, source: &str, format: DefinitionFormat) -> Result<PipelineDefinition> {
+        self.check(source, format)
+            .map_err(|errors| invalid_definition("Invalid pipeline definition", &errors))
+    }
+
+    /// Read, parse and validate a `.yaml`, `.yml` or `.toml` file
+    pub async fn load(&self, path: impl AsRef<Path>) -> Result<PipelineDefinition> {
+        let path = path.as_ref();
+        let format = DefinitionFormat::from_path(path).ok_or_else(|| {
+            CoreError::Validation(format!(
+                "Unsupported pipeline definition file {}: expected .yaml, .yml or .toml",
+                path.display()
+            ))
+        })?;
+        let source = tokio::fs::read_to_string(path).await.map_err(|e| {
+            CoreError::Internal(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+
+        self.check(&source, format).map_err(|errors| {
+            invalid_definition(
+                &format!("Invalid pipeline definition {}", path.display()),
+                &errors,
+            )
+        })
+    }
+
+    pub async fn load_pipeline(&self, path: impl AsRef<Path>) -> Result<Pipeline> {
+        self.load(path).await?.to_pipeline()
+    }
+
+    pub async fn load_workflow(&self, path: impl AsRef<Path>) -> Result<Workflow> {
+        self.load(path).await?.to_workflow()
+    }
+
+    fn validate(&self, definition: &PipelineDefinition, source: &str) -> Vec<DefinitionError> {
+        let mut errors = Vec::new();
+        let lines = TaskLines::locate(definition, source);
+
+        match (definition.tasks.is_empty(), definition.stages.is_empty()) {
+            (true, true) => {
+                errors.push(DefinitionError::new(None, "no `tasks` or `stages` defined"))
+            }
+            (false, false) => errors.push(DefinitionError::new(
+                None,
+                "define either `tasks` or `stages`, not both",
+            )),
+            _ => {}
+        }
+
+        // Tasks declared so far; with stages, a task may only depend on these
+        let mut declared: HashSet<&str> = HashSet::new();
+        for (index, task) in definition.all_tasks().enumerate() {
+            let line = lines.get(index);
+
+            if !declared.insert(task.name.as_str()) {
+                errors.push(DefinitionError::new(
+                    line,
+                    format!("duplicate task name '{}'", task.name),
+                ));
+            }
+            if !self.registry.contains(&task.task_type) {
+                errors.push(DefinitionError::new(
+                    line,
+                    format!(
+                        "task '{}' has unknown type '{}' (registered: {})",
+                        task.name,
+                        task.task_type,
+                        self.registry.task_types().join(", ")
+                    ),
+                ));
+            }
+            if let Some(Err(reason)) = task.backoff.as_ref().map(Backoff::validate) {
+                errors.push(DefinitionError::new(
+                    line,
+                    format!("task '{}' {}", task.name, reason),
+                ));
+            }
+        }
+
+        let names: HashSet<&str> = definition
+            .all_tasks()
+            .map(|task| task.name.as_str())
+            .collect();
+        let unknown = |task: &TaskDefinition, dep: &str, line| {
+            DefinitionError::new(
+                line,
+                format!("task '{}' depends on unknown task '{}'", task.name, dep),
+            )
+        };
+
+        // A flat task list is ordered by its dependencies alone
+        for (index, task) in definition.tasks.iter().enumerate() {
+            for dep in task
+                .depends_on
+                .iter()
+                .filter(|dep| !names.contains(dep.as_str()))
+            {
+                errors.push(unknown(task, dep, lines.get(index)));
+            }
+        }
+
+        // Stages run in order, so a staged task may only depend on tasks that
+        // finish before it starts
+        let mut earlier: HashSet<&str> = HashSet::new();
+        let mut index = definition.tasks.len();
+        for stage in &definition.stages {
+            let before_stage = earlier.clone();
+            for task in &stage.tasks {
+                let line = lines.get(index);
+                let runs_before = if stage.parallel {
+                    &before_stage
+                } else {
+                    &earlier
+                };
+                for dep in &task.depends_on {
+                    if !names.contains(dep.as_str()) {
+                        errors.push(unknown(task, dep, line));
+                    } else if !runs_before.contains(dep.as_str()) {
+                        errors.push(DefinitionError::new(
+                            line,
+                            format!(
+                                "task '{}' depends on '{}', which does not run before it",
+                                task.name, dep
+                            ),
+                        ));
+                    }
+                }
+                earlier.insert(task.name.as_str());
+                index += 1;
+            }
+        }
+
+        // Cleanup tasks run once everything else has finished, so nothing
+        // else can wait on them
+        let triggers: HashMap<&str, StepTrigger> = definition
+            .all_tasks()
+            .map(|task| (task.name.as_str(), task.trigger))
+            .collect();
+        for (index, task) in definition.all_tasks().enumerate() {
+            if task.trigger.is_cleanup() {
+                continue;
+            }
+            for dep in &task.depends_on {
+                if triggers
+                    .get(dep.as_str())
+                    .is_some_and(StepTrigger::is_cleanup)
+                {
+                    errors.push(DefinitionError::new(
+                        lines.get(index),
+                        format!(
+                            "task '{}' depends on cleanup task '{}', which runs after it",
+                            task.name, dep
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            if let Some(error) = find_cycle(definition, &lines) {
+                errors.push(error);
+            }
+        }
+
+        errors.extend(self.check_references(definition, &lines));
+        errors
+    }
+
+    /// Check the `${steps.<name>.output...}` and `${params...}` references in
+    /// task configs and conditions: an output reference must name a task this
+    /// one depends on, directly or not, and fit the output that task's type
+    /// declares; a parameter reference must name a declared parameter
+    fn check_references(
+        &self,
+        definition: &PipelineDefinition,
+        lines: &TaskLines,
+    ) -> Vec<DefinitionError> {
+        let mut errors = Vec::new();
+        let tasks: HashMap<&str, &TaskDefinition> = definition
+            .all_tasks()
+            .map(|task| (task.name.as_str(), task))
+            .collect();
+
+        for (index, task) in definition.all_tasks().enumerate() {
+            let line = lines.get(index);
+            let mut uses = Vec::new();
+            let condition = task
+                .when
+                .as_ref()
+                .map(|when| serde_json::to_value(when).unwrap_or_default());
+            let fan_out = task
+                .fan_out
+                .as_ref()
+                .map(|fan_out| serde_json::json!({ "over": fan_out.over }));
+            let sources = std::iter::once((task.config.clone(), "config field"))
+                .chain(condition.map(|when| (when, "condition field")))
+                .chain(fan_out.map(|over| (over, "fan_out field")));
+            for (value, source) in sources {
+                match find_references(&value) {
+                    Ok(found) => uses.extend(found.into_iter().map(|usage| (usage, source))),
+                    Err(e) => errors.push(DefinitionError::new(
+                        line,
+                        format!("task '{}': {}", task.name, validation_message(e)),
+                    )),
+                }
+            }
+            if let Some(fan_out) = &task.fan_out {
+                if let Err(reason) = fan_out.validate() {
+                    errors.push(DefinitionError::new(
+                        line,
+                        format!("task '{}' {}", task.name, reason),
+                    ));
+                }
+                let whole_reference = matches!(
+                    find_references(&fan_out.over).as_deref(),
+                    Ok([usage]) if !usage.embedded
+                );
+                if !fan_out.over.is_array() && !whole_reference {
+                    errors.push(DefinitionError::new(
+                        line,
+                        format!(
+                            "task '{}' fan_out 'over' must be a list or a reference to one",
+                            task.name
+                        ),
+                    ));
+                }
+            }
+            if uses.is_empty() {
+                continue;
+            }
+
+            let upstream = upstream_tasks(task, &tasks);
+            for (usage, source) in uses {
+                let field = format!("task '{}' {} '{}'", task.name, source, usage.field);
+                let checked = match &usage.reference {
+                    Reference::Param(_) => usage
+                        .reference
+                        .resolve(&HashMap::new(), &definition.parameters)
+                        .map(ValueType::of)
+                        .map_err(|_| format!("{} is not a declared parameter", usage.reference)),
+                    Reference::Output(reference) => {
+                        let Some(producer) = tasks.get(reference.step.as_str()) else {
+                            errors.push(DefinitionError::new(
+                                line,
+                                format!("{} references unknown task '{}'", field, reference.step),
+                            ));
+                            continue;
+                        };
+                        if !upstream.contains(reference.step.as_str()) {
+                            errors.push(DefinitionError::new(
+                                line,
+                                format!(
+                                    "{} references '{}', which it does not depend on",
+                                    field, reference.step
+                                ),
+                            ));
+                            continue;
+                        }
+                        // A fanned-out task outputs what its children did, gathered
+                        if producer.fan_out.is_some() {
+                            FanOutTask::output_schema().check(reference)
+                        } else if let Some(schema) =
+                            self.registry.output_schema(&producer.task_type)
+                        {
+                            schema.check(reference)
+                        } else {
+                            Ok(ValueType::Any)
+                        }
+                    }
+                };
+                let lists_items = source == "fan_out field" && !usage.embedded;
+
+                match checked {
+                    Err(reason) => {
+                        errors.push(DefinitionError::new(line, format!("{}: {}", field, reason)))
+                    }
+                    Ok(value_type) if usage.embedded && !value_type.is_scalar() => {
+                        errors.push(DefinitionError::new(
+                            line,
+                            format!(
+                                "{}: {} is {} and cannot be interpolated into a string",
+                                field, usage.reference, value_type
+                            ),
+                        ))
+                    }
+                    Ok(value_type)
+                        if lists_items
+                            && !matches!(value_type, ValueType::Array | ValueType::Any) =>
+                    {
+                        errors.push(DefinitionError::new(
+                            line,
+                            format!(
+                                "{}: {} is {}, not a list to fan out over",
+                                field, usage.reference, value_type
+                            ),
+                        ))
+                    }
+                    Ok(_) => {}
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+/// Names of the tasks `task` depends on, directly or through other tasks
+fn upstream_tasks<'a>(
+    task: &'a TaskDefinition,
+    tasks: &HashMap<&str, &'a TaskDefinition>,
+) -> HashSet<&'a str> {
+    let mut upstream = HashSet::new();
+    let mut pending: Vec<&str> = task.depends_on.iter().map(String::as_str).collect();
+    while let Some(name) = pending.pop() {
+        if upstream.insert(name) {
+            if let Some(dependency) = tasks.get(name) {
+                pending.extend(dependency.depends_on.iter().map(String::as_str));
+            }
+        }
+    }
+    upstream
+}
+
+fn validation_message(error: CoreError) -> String {
+    match error {
+        CoreError::Validation(message) => message,
+        other => other.to_string(),
+    }
+}
+
+/// Order the tasks with `TaskDAG::topological_sort`; if they cannot be
+/// ordered, report the tasks caught in the cycle
+fn find_cycle(definition: &PipelineDefinition, lines: &TaskLines) -> Option<DefinitionError> {
+    let ids = definition.assign_ids().ok()?;
+    let tasks: Vec<PipelineTask> = definition
+        .all_tasks()
+        .map(|task| PipelineDefinition::pipeline_task(task, &ids))
+        .collect();
+
+    let sorted = TaskDAG::from_tasks(tasks.clone()).and_then(|dag| dag.topological_sort());
+    if sorted.is_ok() {
+        return None;
+    }
+
+    // Peel off tasks with no unresolved dependencies, then tasks nothing
+    // unresolved depends on; what remains forms the cycle
+    let mut cyclic: Vec<&PipelineTask> = tasks.iter().collect();
+    loop {
+        let before = cyclic.len();
+        let members: HashSet<Uuid> = cyclic.iter().map(|task| task.id).collect();
+        cyclic.retain(|task| {
+            task.dependencies.iter().any(|dep| members.contains(dep))
+                && tasks.iter().any(|other| {
+                    members.contains(&other.id) && other.dependencies.contains(&task.id)
+                })
+        });
+        if cyclic.len() == before {
+            break;
+        }
+    }
+
+    let first_id = cyclic.first()?.id;
+    let first = tasks.iter().position(|task| task.id == first_id)?;
+    let names: Vec<&str> = cyclic.iter().map(|task| task.name.as_str()).collect();
+    Some(DefinitionError::new(
+        lines.get(first),
+        format!("circular dependency between tasks {}", names.join(", ")),
+    ))
+}
+
+fn invalid_definition(context: &str, errors: &[DefinitionError]) -> CoreError {
+    let details: Vec<String> = errors.iter().map(|error| format!("  {}", error)).collect();
+    CoreError::Validation(format!("{}:\n{}", context, details.join("\n")))
+}
+
+fn parse(
+    source: &str,
+    format: DefinitionFormat,
+) -> std::result::Result<PipelineDefinition, DefinitionError> {
+    match format {
+        DefinitionFormat::Yaml => serde_yaml::from_str(source).map_err(|e| {
+            let message = e.to_string();
+            match e.location() {
+                Some(location) => {
+                    // Drop the position serde_yaml appends, it is reported as the line
+                    let suffix =
+                        format!(" at line {} column {}", location.line(), location.column());
+                    let message = message.strip_suffix(&suffix).unwrap_or(&message);
+                    DefinitionError::new(Some(location.line()), message)
+                }
+                None => DefinitionError::new(None, message),
+            }
+        }),
+        DefinitionFormat::Toml => toml::from_str(source).map_err(|e| {
+            let line = e
+                .span()
+                .map(|span| source[..span.start].matches('\n').count() + 1);
+            DefinitionError::new(line, e.message().to_string())
+        }),
+    }
+}
+
+/// Line each task is declared on, found by scanning for its `name` key.
+/// Neither parser reports positions for values, but tasks appear in the file
+/// in the same order as in the parsed definition.
+struct TaskLines(Vec<Option<usize>>);
+
+impl TaskLines {
+    fn locate(definition: &PipelineDefinition, source: &str) -> Self {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut cursor = 0;
+        let mut find = |name: &str| {
+            let offset = lines[cursor..]
+                .iter()
+                .position(|line| declares_name(line, name))?;
+            cursor += offset + 1;
+            Some(cursor)
+        };
+
+        let mut found: Vec<Option<usize>> = definition
+            .tasks
+            .iter()
+            .map(|task| find(&task.name))
+            .collect();
+        for stage in &definition.stages {
+            find(&stage.name);
+            found.extend(stage.tasks.iter().map(|task| find(&task.name)));
+        }
+        Self(found)
+    }
+
+    fn get(&self, index: usize) -> Option<usize> {
+        self.0.get(index).copied().flatten()
+    }
+}
+
+/// Whether `line` is `name: <name>` (YAML, possibly a list item) or
+/// `name = "<name>"` (TOML)
+fn declares_name(line: &str, name: &str) -> bool {
+    let line = line.trim_start();
+    let line = line.strip_prefix("- ").unwrap_or(line).trim_start();
+    let Some(rest) = line.strip_prefix("name") else {
+        return false;
+    };
+    let rest = rest.trim_start();
+    let Some(value) = rest.strip_prefix(':').or_else(|| rest.strip_prefix('=')) else {
+        return false;
+    };
+    let value = value.split(" #").next().unwrap_or(value).trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value);
+    value == name
+}
@@ -1,11 +1,27 @@
 use async_trait::async_trait;
-use llm_research_core::{Result, CoreError};
+use futures::FutureExt;
+use llm_research_core::{ContentHash, ReproducibilitySettings, Result, CoreError};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinSet;
 use uuid::Uuid;
 
+use crate::cache::{cache_key, with_cache_record, without_cache_record, OutputCache};
+use crate::condition::{Condition, StepTrigger};
+use crate::control::TaskControl;
+use crate::executor::TaskExecutor;
+use crate::fan_out::{FanOut, FanOutTask};
+use crate::pipeline::{PipelineTask, TaskDAG};
+use crate::registry::TaskRegistry;
+use crate::retry::Backoff;
+use crate::scheduler::{FailurePolicy, ResourceHints, ResourceLimits, ResourceUsage};
+use crate::state_store::WorkflowStateStore;
+use crate::tasks::{Task, TaskContext};
+use crate::template::{find_references, resolve_config, with_prefix, Reference, ValueType};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum WorkflowStatus {
@@ -15,6 +31,34 @@ pub enum WorkflowStatus {
     Completed,
     Failed,
     Cancelled,
+    /// Steps only: not run because of its condition, trigger, or the skip of
+    /// a step it takes output from
+    Skipped,
+}
+
+impl WorkflowStatus {
+    /// Completed, failed, cancelled and skipped workflows and steps cannot
+    /// change status again
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Self::Completed | Self::Failed | Self::Cancelled | Self::Skipped
+        )
+    }
+}
+
+/// Status transition of a workflow or one of its steps, broadcast to
+/// subscribers of `DefaultWorkflowEngine::subscribe`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowEvent {
+    pub workflow_id: Uuid,
+    /// The step that changed status, or `None` for the workflow itself
+    pub step_id: Option<Uuid>,
+    pub name: String,
+    pub status: WorkflowStatus,
+    pub progress: f64, // completed steps, 0.0 to 1.0
+    pub message: Option<String>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +70,22 @@ pub struct Workflow {
     pub error: Option<String>,
     pub started_at: Option<chrono::DateTime<chrono::Utc>>,
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub failure_policy: FailurePolicy,
+    /// Values step configs and conditions refer to as `${params.<name>}`
+    #[serde(default = "empty_parameters")]
+    pub parameters: serde_json::Value,
+    /// Steps to re-run, along with everything downstream of them, rather
+    /// than take from the output cache
+    #[serde(default)]
+    pub invalidated: Vec<String>,
+    /// The experiment's reproducibility settings, handed to every step
+    #[serde(default)]
+    pub reproducibility: ReproducibilitySettings,
+}
+
+fn empty_parameters() -> serde_json::Value {
+    serde_json::json!({})
 }
 
 impl Workflow {
@@ -38,6 +98,77 @@ impl Workflow {
             error: None,
             started_at: None,
             completed_at: None,
+            failure_policy: FailurePolicy::default(),
+            parameters: empty_parameters(),
+            invalidated: Vec::new(),
+            reproducibility: ReproducibilitySettings::default(),
+        }
+    }
+
+    pub fn with_failure_policy(mut self, failure_policy: FailurePolicy) -> Self {
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    pub fn with_parameters(mut self, parameters: serde_json::Value) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
+    pub fn with_invalidated(mut self, step_names: Vec<String>) -> Self {
+        self.invalidated = step_names;
+        self
+    }
+
+    pub fn with_reproducibility(mut self, reproducibility: ReproducibilitySettings) -> Self {
+        self.reproducibility = reproducibility;
+        self
+    }
+
+    /// The reproducibility settings steps run with. With no seed of their
+    /// own, they take the `random_seed` parameter if there is one.
+    pub fn step_reproducibility(&self) -> ReproducibilitySettings {
+        let random_seed = self.reproducibility.random_seed.or_else(|| {
+            self.parameters
+                .get("random_seed")
+                .and_then(serde_json::Value::as_u64)
+        });
+        ReproducibilitySettings {
+            random_seed,
+            ..self.reproducibility.clone()
+        }
+    }
+
+    /// A pending copy of this workflow with new workflow and step IDs, so it
+    /// can run alongside the original on the same engine
+    pub fn instantiate(&self) -> Self {
+        let ids: HashMap<Uuid, Uuid> =
+            self.steps.iter().map(|s| (s.id, Uuid::new_v4())).collect();
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| WorkflowStep {
+                id: ids[&step.id],
+                dependencies: step
+                    .dependencies
+                    .iter()
+                    .map(|dep| ids.get(dep).copied().unwrap_or(*dep))
+                    .collect(),
+                status: WorkflowStatus::Pending,
+                error: None,
+                retry_count: 0,
+                ..step.clone()
+            })
+            .collect();
+
+        Self {
+            id: Uuid::new_v4(),
+            status: WorkflowStatus::Pending,
+            steps,
+            error: None,
+            started_at: None,
+            completed_at: None,
+            ..self.clone()
         }
     }
 }
@@ -53,6 +184,30 @@ pub struct WorkflowStep {
     pub error: Option<String>,
     pub retry_count: usize,
     pub max_retries: usize,
+    /// Wait before each retry; errors that aren't retryable are not retried
+    #[serde(default)]
+    pub backoff: Backoff,
+    /// Limit on each attempt; a step that runs longer is cancelled
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub resources: ResourceHints,
+    /// Run the step only if this holds when it becomes ready, otherwise skip it
+    #[serde(default)]
+    pub condition: Option<Condition>,
+    #[serde(default)]
+    pub trigger: StepTrigger,
+    /// Run the task once per item of a list instead of once
+    #[serde(default)]
+    pub fan_out: Option<FanOut>,
+    /// Reuse the output of an identical earlier run from the engine's output
+    /// cache; turn off for steps whose output should change between runs
+    #[serde(default = "default_cache")]
+    pub cache: bool,
+}
+
+fn default_cache() -> bool {
+    true
 }
 
 impl WorkflowStep {
@@ -67,6 +222,13 @@ impl WorkflowStep {
             error: None,
             retry_count: 0,
             max_retries: 3,
+            backoff: Backoff::default(),
+            timeout_secs: None,
+            resources: ResourceHints::default(),
+            condition: None,
+            trigger: StepTrigger::default(),
+            fan_out: None,
+            cache: true,
         }
     }
 
@@ -79,14 +241,94 @@ impl WorkflowStep {
         self.max_retries = max_retries;
         self
     }
+
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn with_timeout_secs(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = Some(timeout_secs);
+        self
+    }
+
+    pub fn with_resources(mut self, resources: ResourceHints) -> Self {
+        self.resources = resources;
+        self
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    pub fn with_trigger(mut self, trigger: StepTrigger) -> Self {
+        self.trigger = trigger;
+        self
+    }
+
+    pub fn with_fan_out(mut self, fan_out: FanOut) -> Self {
+        self.fan_out = Some(fan_out);
+        self
+    }
+
+    pub fn with_cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowState {
     pub workflow: Workflow,
     pub step_outputs: HashMap<Uuid, serde_json::Value>,
 }
 
+impl WorkflowState {
+    pub fn new(workflow: Workflow) -> Self {
+        Self {
+            workflow,
+            step_outputs: HashMap::new(),
+        }
+    }
+
+    /// Whether `step_id` finished and its output was recorded
+    pub fn is_step_completed(&self, step_id: Uuid) -> bool {
+        self.step_outputs.contains_key(&step_id)
+            && self
+                .workflow
+                .steps
+                .iter()
+                .any(|s| s.id == step_id && s.status == WorkflowStatus::Completed)
+    }
+
+    /// Fraction of steps completed or skipped, 0.0 to 1.0
+    pub fn progress(&self) -> f64 {
+        if self.workflow.steps.is_empty() {
+            return 1.0;
+        }
+        let completed = self
+            .workflow
+            .steps
+            .iter()
+            .filter(|s| s.status == WorkflowStatus::Skipped || self.is_step_completed(s.id))
+            .count();
+        completed as f64 / self.workflow.steps.len() as f64
+    }
+
+    /// Outputs of completed steps, keyed by step name, without their cache
+    /// records
+    pub fn outputs_by_name(&self) -> HashMap<String, serde_json::Value> {
+        self.workflow
+            .steps
+            .iter()
+            .filter_map(|s| {
+                Some((s.name.clone(), without_cache_record(self.step_outputs.get(&s.id)?)))
+            })
+            .collect()
+    }
+}
+
 #[async_trait]
 pub trait WorkflowEngine: Send + Sync {
     async fn execute(&self, workflow: &Workflow) -> Result<WorkflowState>;
@@ -97,223 +339,950 @@ pub trait WorkflowEngine: Send + Sync {
 
 pub struct DefaultWorkflowEngine {
     states: Arc<RwLock<HashMap<Uuid, WorkflowState>>>,
+    registry: Arc<TaskRegistry>,
+    state_store: Option<Arc<dyn WorkflowStateStore>>,
+    /// Controls of the workflows currently being run by this engine
+    controls: Arc<RwLock<HashMap<Uuid, TaskControl>>>,
+    events: broadcast::Sender<WorkflowEvent>,
+    /// Runs each step's task; step IDs are the executor's task IDs
+    executor: Arc<TaskExecutor>,
+    limits: ResourceLimits,
+    output_cache: Option<Arc<dyn OutputCache>>,
+    /// Part of every cache key, so outputs cached by other task code are not reused
+    code_version: String,
+}
+
+/// Where a step's output is cached, and whether a cached output may be used
+struct StepCache {
+    cache: Arc<dyn OutputCache>,
+    key: ContentHash,
+    read: bool,
 }
 
 impl DefaultWorkflowEngine {
+    /// Engine that runs the built-in tasks
     pub fn new() -> Self {
+        Self::with_registry(TaskRegistry::with_builtin_tasks())
+    }
+
+    pub fn with_registry(registry: TaskRegistry) -> Self {
         Self {
             states: Arc::new(RwLock::new(HashMap::new())),
+            registry: Arc::new(registry),
+            state_store: None,
+            controls: Arc::new(RwLock::new(HashMap::new())),
+            events: broadcast::channel(100).0,
+            executor: Arc::new(TaskExecutor::new(ResourceLimits::default().max_concurrency)),
+            limits: ResourceLimits::default(),
+            output_cache: None,
+            code_version: env!("CARGO_PKG_VERSION").to_string(),
         }
     }
 
-    async fn execute_step(
-        &self,
-        step: &mut WorkflowStep,
-        state: &WorkflowState,
-    ) -> Result<serde_json::Value> {
-        // Check dependencies are completed
-        for dep_id in &step.dependencies {
-            if !state.step_outputs.contains_key(dep_id) {
-                return Err(CoreError::InvalidState(format!(
-                    "Dependency step {} not completed",
-                    dep_id
-                )));
+    /// Capacity ready steps are scheduled into
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.executor = Arc::new(TaskExecutor::new(limits.max_concurrency));
+        self.limits = limits;
+        self
+    }
+
+    /// Run at most `max_concurrency` steps at once
+    pub fn with_max_concurrency(self, max_concurrency: usize) -> Self {
+        let limits = ResourceLimits {
+            max_concurrency,
+            ..self.limits.clone()
+        };
+        self.with_resource_limits(limits)
+    }
+
+    pub fn resource_limits(&self) -> &ResourceLimits {
+        &self.limits
+    }
+
+    /// The executor running step tasks, e.g. to track progress or cancel a
+    /// single step with `cancel_task(step.id)`
+    pub fn executor(&self) -> &TaskExecutor {
+        &self.executor
+    }
+
+    /// Checkpoint every state change to `store` so workflows survive a restart
+    pub fn with_state_store(mut self, store: Arc<dyn WorkflowStateStore>) -> Self {
+        self.state_store = Some(store);
+        self
+    }
+
+    /// Skip steps whose task type, resolved config, inputs and code version
+    /// match a run whose output is in `cache`
+    pub fn with_output_cache(mut self, cache: Arc<dyn OutputCache>) -> Self {
+        self.output_cache = Some(cache);
+        self
+    }
+
+    /// Version of the task code, e.g. a git commit; defaults to this crate's
+    /// version. Changing it invalidates every cached output.
+    pub fn with_code_version(mut self, code_version: impl Into<String>) -> Self {
+        self.code_version = code_version.into();
+        self
+    }
+
+    pub fn registry(&self) -> &TaskRegistry {
+        &self.registry
+    }
+
+    /// Receive status transitions of workflows and steps from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<WorkflowEvent> {
+        self.events.subscribe()
+    }
+
+    /// Current state of a workflow, from memory or the state store
+    pub async fn state(&self, workflow_id: Uuid) -> Result<Option<WorkflowState>> {
+        if let Some(state) = self.states.read().await.get(&workflow_id) {
+            return Ok(Some(state.clone()));
+        }
+        match &self.state_store {
+            Some(store) => store.load(workflow_id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Resume every incomplete workflow in the state store. Call this on
+    /// startup: steps that completed before the restart keep their outputs and
+    /// are not run again, steps that were interrupted mid-run start over.
+    /// Paused workflows are loaded but stay paused until `resume` is called.
+    ///
+    /// Returns the final state of each workflow that was run; a workflow that
+    /// fails is reported through its state rather than aborting recovery.
+    pub async fn recover(&self) -> Result<Vec<WorkflowState>> {
+        let store = self.state_store.as_ref().ok_or_else(|| {
+            CoreError::InvalidState("Workflow recovery requires a state store".to_string())
+        })?;
+
+        let mut recovered = Vec::new();
+        for mut state in store.list_incomplete().await? {
+            let workflow_id = state.workflow.id;
+            if state.workflow.status == WorkflowStatus::Paused {
+                self.states.write().await.insert(workflow_id, state);
+                continue;
+            }
+
+            tracing::info!(
+                "Recovering workflow {} ({}/{} steps completed)",
+                state.workflow.name,
+                state.step_outputs.len(),
+                state.workflow.steps.len()
+            );
+            if let Err(e) = self.run(&mut state).await {
+                tracing::warn!("Recovered workflow {} failed: {}", workflow_id, e);
             }
+            recovered.push(state);
         }
 
-        step.status = WorkflowStatus::Running;
+        Ok(recovered)
+    }
 
-        // Simulate step execution with retries
-        let mut last_error = None;
-        for attempt in 0..=step.max_retries {
-            step.retry_count = attempt;
+    /// Record `state` in memory and checkpoint it to the state store
+    async fn checkpoint(&self, state: &WorkflowState) -> Result<()> {
+        self.states
+            .write()
+            .await
+            .insert(state.workflow.id, state.clone());
+        if let Some(store) = &self.state_store {
+            store.save(state).await?;
+        }
+        Ok(())
+    }
+
+    /// Broadcast the status of the workflow, or of `step` if given
+    fn emit(&self, state: &WorkflowState, step: Option<&WorkflowStep>) {
+        let (step_id, name, status, message) = match step {
+            Some(step) => (Some(step.id), &step.name, &step.status, &step.error),
+            None => (None, &state.workflow.name, &state.workflow.status, &state.workflow.error),
+        };
+        // No subscribers is fine
+        let _ = self.events.send(WorkflowEvent {
+            workflow_id: state.workflow.id,
+            step_id,
+            name: name.clone(),
+            status: status.clone(),
+            progress: state.progress(),
+            message: message.clone(),
+            timestamp: chrono::Utc::now(),
+        });
+    }
+
+    /// Mark the workflow failed with `error` and checkpoint it
+    async fn fail(&self, state: &mut WorkflowState, error: CoreError) -> Result<()> {
+        state.workflow.status = WorkflowStatus::Failed;
+        state.workflow.error = Some(error.to_string());
+        state.workflow.completed_at = Some(chrono::Utc::now());
+        self.checkpoint(state).await?;
+        self.emit(state, None);
+        Err(error)
+    }
+
+    /// Mark the workflow cancelled and checkpoint it
+    async fn cancelled(&self, state: &mut WorkflowState) -> Result<()> {
+        state.workflow.status = WorkflowStatus::Cancelled;
+        state.workflow.completed_at = Some(chrono::Utc::now());
+        self.checkpoint(state).await?;
+        self.emit(state, None);
+        tracing::info!("Cancelled workflow: {}", state.workflow.name);
+        Err(CoreError::InvalidState(format!(
+            "Workflow {} was cancelled",
+            state.workflow.id
+        )))
+    }
+
+    /// Stop at a step boundary; `resume` continues from here
+    async fn paused(&self, state: &mut WorkflowState) -> Result<()> {
+        if state.workflow.status != WorkflowStatus::Paused {
+            self.record_pause(state, true).await?;
+        }
+        Ok(())
+    }
+
+    /// Record a pause or resume signalled through the run's control, if the
+    /// status doesn't reflect it yet. Only the run loop records these, so the
+    /// checkpoint never loses steps that finished in the meantime.
+    async fn sync_pause(&self, state: &mut WorkflowState, control: &TaskControl) -> Result<()> {
+        let paused = control.is_paused();
+        if paused != (state.workflow.status == WorkflowStatus::Paused) {
+            self.record_pause(state, paused).await?;
+        }
+        Ok(())
+    }
+
+    async fn record_pause(&self, state: &mut WorkflowState, paused: bool) -> Result<()> {
+        state.workflow.status = if paused {
+            WorkflowStatus::Paused
+        } else {
+            WorkflowStatus::Running
+        };
+        self.checkpoint(state).await?;
+        self.emit(state, None);
+        if paused {
+            tracing::info!("Paused workflow: {}", state.workflow.name);
+        } else {
+            tracing::info!("Resumed workflow: {}", state.workflow.name);
+        }
+        Ok(())
+    }
+
+    /// Run the steps of `state` that have not completed yet, in dependency
+    /// order, until they finish, one fails, or the workflow is paused or
+    /// cancelled
+    async fn run(&self, state: &mut WorkflowState) -> Result<()> {
+        let workflow_id = state.workflow.id;
+        let control = TaskControl::new();
+        self.controls.write().await.insert(workflow_id, control.clone());
+
+        loop {
+            let result = self.run_steps(state, &control).await;
+
+            // A resume that arrives while a paused run is winding down finds
+            // the control still registered, so the run picks it up here
+            let mut controls = self.controls.write().await;
+            if result.is_ok()
+                && state.workflow.status == WorkflowStatus::Paused
+                && !control.is_paused()
+            {
+                continue;
+            }
+            controls.remove(&workflow_id);
+            return result;
+        }
+    }
+
+    async fn run_steps(&self, state: &mut WorkflowState, control: &TaskControl) -> Result<()> {
+        state.workflow.status = WorkflowStatus::Running;
+        state.workflow.error = None;
+        if state.workflow.started_at.is_none() {
+            state.workflow.started_at = Some(chrono::Utc::now());
+        }
+
+        // Steps interrupted mid-run have no output and start over; skipped
+        // steps stay skipped
+        let mut settled_steps = HashSet::new();
+        for i in 0..state.workflow.steps.len() {
+            let step_id = state.workflow.steps[i].id;
+            if state.is_step_completed(step_id)
+                || state.workflow.steps[i].status == WorkflowStatus::Skipped
+            {
+                settled_steps.insert(step_id);
+            } else {
+                state.step_outputs.remove(&step_id);
+                let step = &mut state.workflow.steps[i];
+                step.status = WorkflowStatus::Pending;
+                step.error = None;
+                step.retry_count = 0;
+            }
+        }
+
+        // A cycle would leave its steps waiting on each other forever
+        let dag = match TaskDAG::from_tasks(state.workflow.steps.iter().map(PipelineTask::from)) {
+            Ok(dag) => dag,
+            Err(_) => {
+                let error = CoreError::InvalidState(
+                    "Workflow deadlock: steps have circular dependencies".to_string()
+                );
+                return self.fail(state, error).await;
+            }
+        };
+        let step_index: HashMap<Uuid, usize> = state
+            .workflow
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| (step.id, i))
+            .collect();
+        let invalidated = match Self::invalidated_steps(&state.workflow) {
+            Ok(invalidated) => invalidated,
+            Err(e) => return self.fail(state, e).await,
+        };
+
+        tracing::info!("Executing workflow: {}", state.workflow.name);
+        self.checkpoint(state).await?;
+        self.emit(state, None);
+
+        // Cancelled with the workflow, or on its own to stop running steps fail-fast
+        let steps_control = control.child();
+        // Cleanup steps run even after a fail-fast failure
+        let cleanup_control = control.child();
+        let mut cleanup = false;
+        let mut running = JoinSet::new();
+        let mut usage = ResourceUsage::new(self.limits.clone());
+        let mut started = HashSet::new();
+        let mut failures = Vec::new();
+
+        // Whether the run stopped short because it was paused
+        let stopped_paused = loop {
+            self.sync_pause(state, control).await?;
+
+            // Start every ready step that fits, in workflow order. No new
+            // steps start while paused or after cancellation; after a
+            // fail-fast failure only cleanup steps do.
+            let admitting = !control.is_paused()
+                && !control.is_cancelled()
+                && (cleanup
+                    || (!steps_control.is_cancelled()
+                        && (failures.is_empty()
+                            || state.workflow.failure_policy == FailurePolicy::ContinueOnError)));
+            // Set when a step is settled without running, which may make others ready
+            let mut settled_early = false;
+
+            if admitting {
+                let mut ready: Vec<usize> = if cleanup {
+                    Self::ready_cleanup_steps(state, &settled_steps, &step_index)
+                } else {
+                    dag.get_ready_tasks(&settled_steps)
+                        .into_iter()
+                        .map(|id| step_index[&id])
+                        .filter(|&i| !state.workflow.steps[i].trigger.is_cleanup())
+                        .collect()
+                };
+                ready.retain(|i| !started.contains(&state.workflow.steps[*i].id));
+                ready.sort_unstable();
 
-            // Here we would actually execute the task
-            // For now, return a mock result
-            match Self::execute_task_type(&step.task_type, &step.config, state).await {
+                for i in ready {
+                    match Self::should_run(&state.workflow.steps[i], state) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            let step = &mut state.workflow.steps[i];
+                            started.insert(step.id);
+                            settled_steps.insert(step.id);
+                            step.status = WorkflowStatus::Skipped;
+                            settled_early = true;
+                            self.emit(state, Some(&state.workflow.steps[i]));
+                            self.checkpoint(state).await?;
+                            continue;
+                        }
+                        Err(e) => {
+                            let step = &mut state.workflow.steps[i];
+                            started.insert(step.id);
+                            step.status = WorkflowStatus::Failed;
+                            step.error = Some(e.to_string());
+                            if !cleanup && state.workflow.failure_policy == FailurePolicy::FailFast {
+                                steps_control.cancel();
+                            }
+                            failures.push(e);
+                            settled_early = true;
+                            self.emit(state, Some(&state.workflow.steps[i]));
+                            self.checkpoint(state).await?;
+                            continue;
+                        }
+                    }
+
+                    if !usage.fits(&state.workflow.steps[i].resources) {
+                        continue;
+                    }
+                    usage.acquire(&state.workflow.steps[i].resources);
+
+                    let step = &mut state.workflow.steps[i];
+                    started.insert(step.id);
+                    step.status = WorkflowStatus::Running;
+                    let step = step.clone();
+                    self.emit(state, Some(&step));
+
+                    let step_control = if cleanup { &cleanup_control } else { &steps_control };
+                    let prepared = self
+                        .prepare_step(&step, state)
+                        .map(|(task, context)| (task, context.with_control(step_control.clone())));
+                    let cache = match (&self.output_cache, &prepared) {
+                        (Some(cache), Ok((_, context))) if step.cache => Some(StepCache {
+                            cache: cache.clone(),
+                            key: self.step_cache_key(&step, context, state),
+                            read: !invalidated.contains(&step.id),
+                        }),
+                        _ => None,
+                    };
+                    let execution =
+                        Self::execute_cached(cache, self.executor.clone(), step.clone(), prepared);
+                    running.spawn(async move {
+                        match AssertUnwindSafe(execution).catch_unwind().await {
+                            Ok((step, outcome)) => (i, step, outcome),
+                            Err(_) => {
+                                let error = CoreError::Internal(format!(
+                                    "Step '{}' panicked",
+                                    step.name
+                                ));
+                                let mut step = step;
+                                step.status = WorkflowStatus::Failed;
+                                step.error = Some(error.to_string());
+                                (i, step, Err(error))
+                            }
+                        }
+                    });
+                }
+            }
+            if settled_early {
+                continue;
+            }
+
+            // Wake on a pause or resume as well, so that it is recorded while
+            // steps are still running
+            let paused = state.workflow.status == WorkflowStatus::Paused;
+            let joined = tokio::select! {
+                biased;
+                joined = running.join_next() => joined,
+                _ = control.pause_changed(paused) => continue,
+            };
+            let (i, step, outcome) = match joined {
+                Some(joined) => joined.map_err(|e| {
+                    CoreError::Internal(format!("Step execution aborted: {}", e))
+                })?,
+                None => {
+                    let paused = control.is_paused();
+                    // Once the other steps are done, run the cleanup steps
+                    if !cleanup && !paused && !control.is_cancelled() {
+                        cleanup = true;
+                        continue;
+                    }
+                    break paused;
+                }
+            };
+
+            usage.release(&step.resources);
+            let step_id = step.id;
+            let cancelled = step.status == WorkflowStatus::Cancelled;
+            state.workflow.steps[i] = step;
+
+            match outcome {
                 Ok(output) => {
-                    step.status = WorkflowStatus::Completed;
-                    return Ok(output);
+                    state.step_outputs.insert(step_id, output);
+                    settled_steps.insert(step_id);
                 }
+                // Stopped by the workflow's cancellation or a fail-fast failure
+                Err(_) if cancelled => {}
                 Err(e) => {
-                    last_error = Some(e);
-                    if attempt < step.max_retries {
-                        tracing::warn!(
-                            "Step {} failed, attempt {}/{}",
-                            step.name,
-                            attempt + 1,
-                            step.max_retries + 1
-                        );
-                        tokio::time::sleep(tokio::time::Duration::from_secs(1 << attempt)).await;
+                    if !cleanup && state.workflow.failure_policy == FailurePolicy::FailFast {
+                        steps_control.cancel();
                     }
+                    failures.push(e);
                 }
             }
+
+            self.emit(state, Some(&state.workflow.steps[i]));
+            self.checkpoint(state).await?;
+        };
+
+        if control.is_cancelled() {
+            return self.cancelled(state).await;
+        }
+        if !failures.is_empty() {
+            let error = if failures.len() == 1 {
+                failures.remove(0)
+            } else {
+                CoreError::Internal(format!(
+                    "{} steps failed: {}",
+                    failures.len(),
+                    failures.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; ")
+                ))
+            };
+            return self.fail(state, error).await;
+        }
+        if settled_steps.len() < state.workflow.steps.len() {
+            if stopped_paused {
+                return self.paused(state).await;
+            }
+            let error = CoreError::InvalidState(
+                "Workflow deadlock: no steps can be executed".to_string()
+            );
+            return self.fail(state, error).await;
         }
 
-        let error = last_error.unwrap();
-        step.status = WorkflowStatus::Failed;
-        step.error = Some(error.to_string());
-        Err(error)
+        state.workflow.status = WorkflowStatus::Completed;
+        state.workflow.completed_at = Some(chrono::Utc::now());
+        self.checkpoint(state).await?;
+        self.emit(state, None);
+
+        tracing::info!("Workflow completed: {}", state.workflow.name);
+        Ok(())
     }
 
-    async fn execute_task_type(
-        task_type: &str,
-        config: &serde_json::Value,
-        _state: &WorkflowState,
-    ) -> Result<serde_json::Value> {
-        // Mock implementation - in real system, this would dispatch to actual task executors
-        tracing::info!("Executing task type: {}", task_type);
-        Ok(serde_json::json!({
-            "task_type": task_type,
-            "status": "completed",
-            "config": config,
-        }))
+    /// Cleanup steps whose dependencies are all regular steps, in whatever
+    /// state, or settled cleanup steps
+    fn ready_cleanup_steps(
+        state: &WorkflowState,
+        settled_steps: &HashSet<Uuid>,
+        step_index: &HashMap<Uuid, usize>,
+    ) -> Vec<usize> {
+        let steps = &state.workflow.steps;
+        (0..steps.len())
+            .filter(|&i| steps[i].trigger.is_cleanup() && !settled_steps.contains(&steps[i].id))
+            .filter(|&i| {
+                steps[i].dependencies.iter().all(|dep| match step_index.get(dep) {
+                    Some(&d) => !steps[d].trigger.is_cleanup() || settled_steps.contains(dep),
+                    None => false,
+                })
+            })
+            .collect()
     }
 
-    fn check_dependencies_met(&self, step: &WorkflowStep, state: &WorkflowState) -> bool {
-        step.dependencies
+    /// Whether a ready step runs (`true`) or is skipped (`false`): its trigger
+    /// must match the outcome so far, every step it takes output from must
+    /// have run, and its condition, if any, must hold
+    fn should_run(step: &WorkflowStep, state: &WorkflowState) -> Result<bool> {
+        let any_failed = state
+            .workflow
+            .steps
             .iter()
-            .all(|dep_id| state.step_outputs.contains_key(dep_id))
+            .any(|s| s.status == WorkflowStatus::Failed);
+        if step.trigger == StepTrigger::OnFailure && !any_failed {
+            tracing::info!("Skipping step {}: no step failed", step.name);
+            return Ok(false);
+        }
+
+        let mut references = find_references(&step.config)?;
+        if let Some(condition) = &step.condition {
+            references.extend(find_references(&serde_json::to_value(condition)?)?);
+        }
+        if let Some(fan_out) = &step.fan_out {
+            references.extend(find_references(&fan_out.over)?);
+        }
+        for usage in &references {
+            let Reference::Output(reference) = &usage.reference else {
+                continue;
+            };
+            let skipped = state
+                .workflow
+                .steps
+                .iter()
+                .any(|s| s.name == reference.step && s.status == WorkflowStatus::Skipped);
+            if skipped {
+                tracing::info!(
+                    "Skipping step {}: it uses the output of skipped step {}",
+                    step.name,
+                    reference.step
+                );
+                return Ok(false);
+            }
+        }
+
+        let Some(condition) = &step.condition else {
+            return Ok(true);
+        };
+        let met = condition
+            .evaluate(&state.outputs_by_name(), &state.workflow.parameters)
+            .map_err(|e| with_prefix(e, &format!("Step '{}' condition", step.name)))?;
+        if !met {
+            tracing::info!("Skipping step {}: condition not met", step.name);
+        }
+        Ok(met)
     }
-}
 
-impl Default for DefaultWorkflowEngine {
-    fn default() -> Self {
-        Self::new()
+    /// IDs of the workflow's invalidated steps and the steps downstream of them
+    fn invalidated_steps(workflow: &Workflow) -> Result<HashSet<Uuid>> {
+        let mut invalidated = HashSet::new();
+        for name in &workflow.invalidated {
+            let step = workflow.steps.iter().find(|s| &s.name == name).ok_or_else(|| {
+                CoreError::Validation(format!("Cannot invalidate unknown step '{}'", name))
+            })?;
+            invalidated.insert(step.id);
+        }
+
+        loop {
+            let downstream: Vec<Uuid> = workflow
+                .steps
+                .iter()
+                .filter(|s| !invalidated.contains(&s.id))
+                .filter(|s| s.dependencies.iter().any(|dep| invalidated.contains(dep)))
+                .map(|s| s.id)
+                .collect();
+            if downstream.is_empty() {
+                return Ok(invalidated);
+            }
+            invalidated.extend(downstream);
+        }
     }
-}
 
-#[async_trait]
-impl WorkflowEngine for DefaultWorkflowEngine {
-    async fn execute(&self, workflow: &Workflow) -> Result<WorkflowState> {
-        let mut state = WorkflowState {
-            workflow: workflow.clone(),
-            step_outputs: HashMap::new(),
+    /// Cache key of a prepared step. A fanned-out step is keyed by its items
+    /// as well as the config they are combined with, and a seeded step by its
+    /// seed.
+    fn step_cache_key(
+        &self,
+        step: &WorkflowStep,
+        context: &TaskContext,
+        state: &WorkflowState,
+    ) -> ContentHash {
+        let config = match &step.fan_out {
+            None => context.config.clone(),
+            Some(fan_out) => {
+                let over = resolve_config(
+                    &fan_out.over,
+                    &state.outputs_by_name(),
+                    &state.workflow.parameters,
+                )
+                .unwrap_or_else(|_| fan_out.over.clone());
+                serde_json::json!({
+                    "config": context.config,
+                    "fan_out": FanOut { over, ..fan_out.clone() },
+                })
+            }
+        };
+        let config = match context.reproducibility.random_seed {
+            Some(seed) => serde_json::json!({ "config": config, "random_seed": seed }),
+            None => config,
         };
+        cache_key(&step.task_type, &config, &context.inputs, &self.code_version)
+    }
 
-        state.workflow.status = WorkflowStatus::Running;
-        state.workflow.started_at = Some(chrono::Utc::now());
+    /// `execute_step`, taking the output from `cache` instead if one is cached
+    /// for the step and caching the output otherwise. Cache failures are
+    /// logged and do not fail the step.
+    async fn execute_cached(
+        cache: Option<StepCache>,
+        executor: Arc<TaskExecutor>,
+        mut step: WorkflowStep,
+        prepared: Result<(Arc<dyn Task>, TaskContext)>,
+    ) -> (WorkflowStep, Result<serde_json::Value>) {
+        let Some(StepCache { cache, key, read }) = cache else {
+            return Self::execute_step(executor, step, prepared).await;
+        };
 
-        tracing::info!("Executing workflow: {}", workflow.name);
+        if read {
+            match cache.get(&key).await {
+                Ok(Some(output)) => {
+                    tracing::info!("Step {} reused cached output {}", step.name, key);
+                    step.status = WorkflowStatus::Completed;
+                    step.error = None;
+                    return (step, Ok(with_cache_record(output, &key, true)));
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Output cache lookup failed for step {}: {}", step.name, e),
+            }
+        }
 
-        // Store initial state
-        {
-            let mut states = self.states.write().await;
-            states.insert(workflow.id, state.clone());
+        let (step, outcome) = Self::execute_step(executor, step, prepared).await;
+        let Ok(output) = outcome else {
+            return (step, outcome);
+        };
+        if let Err(e) = cache.put(&key, &output).await {
+            tracing::warn!("Failed to cache output of step {}: {}", step.name, e);
         }
+        (step, Ok(with_cache_record(output, &key, false)))
+    }
 
-        // Execute steps in dependency order
-        let mut completed_steps = std::collections::HashSet::new();
+    /// Run a step's task on `executor`, retrying retryable failures up to
+    /// `max_retries` times after the step's backoff. A task that could not be
+    /// prepared or failed with an error that isn't retryable fails the step
+    /// right away.
+    /// Returns the step with its final status.
+    async fn execute_step(
+        executor: Arc<TaskExecutor>,
+        mut step: WorkflowStep,
+        prepared: Result<(Arc<dyn Task>, TaskContext)>,
+    ) -> (WorkflowStep, Result<serde_json::Value>) {
+        // An unresolvable reference, invalid config or unknown task type will
+        // not fix itself on retry
+        let (task, context) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                step.status = WorkflowStatus::Failed;
+                step.error = Some(e.to_string());
+                return (step, Err(e));
+            }
+        };
+        let control = context.control.clone();
 
-        while completed_steps.len() < state.workflow.steps.len() {
-            let mut made_progress = false;
+        let mut last_error = None;
+        for attempt in 0..=step.max_retries {
+            step.retry_count = attempt;
 
-            for i in 0..state.workflow.steps.len() {
-                let step_id = state.workflow.steps[i].id;
+            tracing::info!("Executing step {} ({})", step.name, step.task_type);
+            // A timed out attempt is cancelled like any other, through its own control
+            let attempt_control = control.child();
+            let timer = step.timeout_secs.map(|secs| {
+                let attempt_control = attempt_control.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(tokio::time::Duration::from_secs(secs)).await;
+                    attempt_control.cancel();
+                })
+            });
 
-                if completed_steps.contains(&step_id) {
-                    continue;
-                }
+            // The executor abandons tasks that don't stop on cancellation themselves
+            let result = executor
+                .execute_one_with_id(
+                    step.id,
+                    task.clone(),
+                    context.clone().with_control(attempt_control.clone()),
+                )
+                .await;
+            if let Some(timer) = timer {
+                timer.abort();
+            }
 
-                // Check if dependencies are met
-                if !self.check_dependencies_met(&state.workflow.steps[i], &state) {
-                    continue;
+            // Failures paired with whether they are worth retrying
+            let outcome = match result {
+                _ if attempt_control.is_cancelled() && !control.is_cancelled() => {
+                    Err((
+                        CoreError::Internal(format!(
+                            "Step '{}' timed out after {}s",
+                            step.name,
+                            step.timeout_secs.unwrap_or_default()
+                        )),
+                        true,
+                    ))
                 }
+                Ok(result) if result.success => Ok(result.output),
+                Ok(result) => Err((
+                    CoreError::Internal(format!(
+                        "Step '{}' failed: {}",
+                        step.name,
+                        result.error.unwrap_or_else(|| "unknown error".to_string())
+                    )),
+                    result.retryable,
+                )),
+                Err(e) => {
+                    let retryable = e.is_retryable();
+                    Err((e, retryable))
+                }
+            };
 
-                // Execute step - clone step to avoid borrow issues
-                let mut step = state.workflow.steps[i].clone();
-                match self.execute_step(&mut step, &state).await {
-                    Ok(output) => {
-                        state.workflow.steps[i] = step;
-                        state.step_outputs.insert(step_id, output);
-                        completed_steps.insert(step_id);
-                        made_progress = true;
+            match outcome {
+                Ok(output) => {
+                    step.status = WorkflowStatus::Completed;
+                    step.error = None;
+                    return (step, Ok(output));
+                }
+                Err((e, _)) if control.is_cancelled() => {
+                    step.status = WorkflowStatus::Cancelled;
+                    step.error = Some(e.to_string());
+                    return (step, Err(e));
+                }
+                Err((e, false)) => {
+                    tracing::warn!("Step {} failed with an error that is not retryable", step.name);
+                    last_error = Some(e);
+                    break;
+                }
+                Err((e, true)) => {
+                    last_error = Some(e);
+                    if let Some(delay) = step.backoff.delay(attempt, step.max_retries) {
+                        tracing::warn!(
+                            "Step {} failed, attempt {}/{}, retrying in {:?}",
+                            step.name,
+                            attempt + 1,
+                            step.max_retries + 1,
+                            delay
+                        );
+                        tokio::select! {
+                            _ = control.cancelled() => {}
+                            _ = tokio::time::sleep(delay) => {}
+                        }
                     }
-                    Err(e) => {
-                        state.workflow.status = WorkflowStatus::Failed;
-                        state.workflow.error = Some(e.to_string());
-                        state.workflow.completed_at = Some(chrono::Utc::now());
+                }
+            }
+        }
 
-                        // Update state
-                        let mut states = self.states.write().await;
-                        states.insert(workflow.id, state.clone());
+        let error = last_error.unwrap();
+        step.status = WorkflowStatus::Failed;
+        step.error = Some(error.to_string());
+        (step, Err(error))
+    }
 
-                        return Err(e);
-                    }
-                }
+    /// The task for `step` and the context to run it in. A fanned-out step
+    /// runs a `FanOutTask` over the items its `over` list resolves to.
+    fn prepare_step(
+        &self,
+        step: &WorkflowStep,
+        state: &WorkflowState,
+    ) -> Result<(Arc<dyn Task>, TaskContext)> {
+        let context = Self::task_context(step, state)?;
+        let Some(fan_out) = &step.fan_out else {
+            let task = self.registry.create(&step.task_type, &context.config)?;
+            return Ok((task, context));
+        };
 
-                // Update state after each step
-                let mut states = self.states.write().await;
-                states.insert(workflow.id, state.clone());
+        let outputs = state.outputs_by_name();
+        let over = resolve_config(&fan_out.over, &outputs, &state.workflow.parameters)
+            .map_err(|e| with_prefix(e, &format!("Step '{}' fan-out", step.name)))?;
+        let items = match over {
+            serde_json::Value::Array(items) => items,
+            other => {
+                return Err(CoreError::Validation(format!(
+                    "Step '{}' fan-out: 'over' is {}, not a list",
+                    step.name,
+                    ValueType::of(&other)
+                )))
             }
+        };
+        let task = FanOutTask::new(
+            &self.registry,
+            &self.executor,
+            &step.task_type,
+            &context.config,
+            fan_out,
+            items,
+        )?;
+        Ok((Arc::new(task), context))
+    }
 
-            if !made_progress {
-                let error = CoreError::InvalidState(
-                    "Workflow deadlock: no steps can be executed".to_string()
-                );
-                state.workflow.status = WorkflowStatus::Failed;
-                state.workflow.error = Some(error.to_string());
-                state.workflow.completed_at = Some(chrono::Utc::now());
-                return Err(error);
-            }
-        }
+    /// Context for running `step`, with `${steps.<name>.output...}`
+    /// references in its config replaced by the outputs of completed steps
+    fn task_context(step: &WorkflowStep, state: &WorkflowState) -> Result<TaskContext> {
+        let inputs = step
+            .dependencies
+            .iter()
+            .filter_map(|dep_id| {
+                let name = state.workflow.steps.iter().find(|s| s.id == *dep_id)?.name.clone();
+                Some((name, without_cache_record(state.step_outputs.get(dep_id)?)))
+            })
+            .collect();
 
-        state.workflow.status = WorkflowStatus::Completed;
-        state.workflow.completed_at = Some(chrono::Utc::now());
+        let config = resolve_config(&step.config, &state.outputs_by_name(), &state.workflow.parameters)
+            .map_err(|e| with_prefix(e, &format!("Step '{}'", step.name)))?;
+
+        Ok(TaskContext::new(state.workflow.id, config)
+            .with_inputs(inputs)
+            .with_reproducibility(state.workflow.step_reproducibility()))
+    }
 
-        // Update final state
-        {
-            let mut states = self.states.write().await;
-            states.insert(workflow.id, state.clone());
+    async fn load_state(&self, workflow_id: Uuid) -> Result<WorkflowState> {
+        self.state(workflow_id)
+            .await?
+            .ok_or_else(|| CoreError::NotFound(format!("Workflow {} not found", workflow_id)))
+    }
+
+    /// Record a status change requested for a workflow with no active run
+    async fn transition(&self, mut state: WorkflowState, status: WorkflowStatus) -> Result<()> {
+        if status == WorkflowStatus::Cancelled {
+            state.workflow.completed_at = Some(chrono::Utc::now());
         }
+        state.workflow.status = status;
+        self.checkpoint(&state).await?;
+        self.emit(&state, None);
+        Ok(())
+    }
+}
+
+impl Default for DefaultWorkflowEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        tracing::info!("Workflow completed: {}", workflow.name);
+#[async_trait]
+impl WorkflowEngine for DefaultWorkflowEngine {
+    async fn execute(&self, workflow: &Workflow) -> Result<WorkflowState> {
+        let mut state = WorkflowState::new(workflow.clone());
+        self.run(&mut state).await?;
         Ok(state)
     }
 
+    /// Pause a pending or running workflow. A running workflow stops before
+    /// its next step; a task in flight waits at its next pause point (e.g.
+    /// between batches) until the workflow is resumed.
     async fn pause(&self, workflow_id: Uuid) -> Result<()> {
-        let mut states = self.states.write().await;
-        if let Some(state) = states.get_mut(&workflow_id) {
-            state.workflow.status = WorkflowStatus::Paused;
+        // The run loop records the pause on its own state
+        if let Some(control) = self.controls.read().await.get(&workflow_id) {
+            control.pause();
             tracing::info!("Paused workflow: {}", workflow_id);
-            Ok(())
-        } else {
-            Err(CoreError::NotFound(format!("Workflow {} not found", workflow_id)))
+            return Ok(());
+        }
+
+        let state = self.load_state(workflow_id).await?;
+        match state.workflow.status {
+            WorkflowStatus::Paused => return Ok(()),
+            WorkflowStatus::Pending | WorkflowStatus::Running => {}
+            ref status => {
+                return Err(CoreError::InvalidState(format!(
+                    "Workflow {} is {:?} and cannot be paused",
+                    workflow_id, status
+                )))
+            }
         }
+
+        self.transition(state, WorkflowStatus::Paused).await?;
+        tracing::info!("Paused workflow: {}", workflow_id);
+        Ok(())
     }
 
+    /// Resume a paused workflow. If its run is still in progress (paused
+    /// inside a step) the run simply continues; otherwise the remaining steps
+    /// are run from the last completed one, and this returns when they finish.
     async fn resume(&self, workflow_id: Uuid) -> Result<()> {
-        let workflow_to_resume = {
-            let states = self.states.read().await;
-            if let Some(state) = states.get(&workflow_id) {
-                if state.workflow.status == WorkflowStatus::Paused {
-                    Some(state.workflow.clone())
-                } else {
-                    return Err(CoreError::InvalidState(format!(
-                        "Workflow {} is not paused",
-                        workflow_id
-                    )));
-                }
-            } else {
-                return Err(CoreError::NotFound(format!("Workflow {} not found", workflow_id)));
+        let not_paused =
+            || CoreError::InvalidState(format!("Workflow {} is not paused", workflow_id));
+
+        // Held while signalling, so the run can't finish without seeing it
+        let controls = self.controls.read().await;
+        if let Some(control) = controls.get(&workflow_id) {
+            if !control.is_paused() {
+                return Err(not_paused());
             }
-        };
+            // The run loop records the resume on its own state
+            control.resume();
+            tracing::info!("Resumed workflow: {}", workflow_id);
+            return Ok(());
+        }
+        drop(controls);
 
-        if let Some(workflow) = workflow_to_resume {
-            self.execute(&workflow).await?;
+        let mut state = self.load_state(workflow_id).await?;
+        if state.workflow.status != WorkflowStatus::Paused {
+            return Err(not_paused());
         }
-        Ok(())
+
+        // Continue from the last completed step rather than starting over
+        self.run(&mut state).await
     }
 
+    /// Cancel a workflow that has not finished. Tasks in flight are signalled
+    /// through their context and abandoned if they don't stop on their own.
     async fn cancel(&self, workflow_id: Uuid) -> Result<()> {
-        let mut states = self.states.write().await;
-        if let Some(state) = states.get_mut(&workflow_id) {
-            state.workflow.status = WorkflowStatus::Cancelled;
-            state.workflow.completed_at = Some(chrono::Utc::now());
-            tracing::info!("Cancelled workflow: {}", workflow_id);
-            Ok(())
-        } else {
-            Err(CoreError::NotFound(format!("Workflow {} not found", workflow_id)))
+        let state = self.load_state(workflow_id).await?;
+        if state.workflow.status.is_terminal() {
+            return Err(CoreError::InvalidState(format!(
+                "Workflow {} is {:?} and cannot be cancelled",
+                workflow_id, state.workflow.status
+            )));
         }
+
+        let active = self.controls.read().await.get(&workflow_id).cloned();
+        match active {
+            // The run loop records the cancellation once the step stops
+            Some(control) => control.cancel(),
+            None => self.transition(state, WorkflowStatus::Cancelled).await?,
+        }
+        tracing::info!("Cancelled workflow: {}", workflow_id);
+        Ok(())
     }
 }
@@ -1,11 +1,13 @@
 use async_trait::async_trait;
 use llm_research_core::Result;
+use llm_research_metrics::StreamingAggregator;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::{Semaphore, RwLock, broadcast};
 use uuid::Uuid;
 
-use crate::tasks::{Task, TaskContext, TaskResult};
+use crate::control::TaskControl;
+use crate::tasks::{merge_reported_aggregates, Task, TaskContext, TaskResult};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskProgress {
@@ -19,7 +21,7 @@ pub struct TaskProgress {
 pub struct TaskExecutor {
     max_concurrency: usize,
     progress_tx: Arc<RwLock<Option<broadcast::Sender<TaskProgress>>>>,
-    cancellation_tokens: Arc<RwLock<std::collections::HashMap<Uuid, tokio_util::sync::CancellationToken>>>,
+    controls: Arc<RwLock<std::collections::HashMap<Uuid, TaskControl>>>,
 }
 
 impl TaskExecutor {
@@ -27,10 +29,23 @@ impl TaskExecutor {
         Self {
             max_concurrency,
             progress_tx: Arc::new(RwLock::new(None)),
-            cancellation_tokens: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            controls: Arc::new(RwLock::new(std::collections::HashMap::new())),
         }
     }
 
+    /// An executor sharing this one's progress tracking and task controls
+    /// that runs at most `max_concurrency` tasks of a batch at once
+    pub fn with_max_concurrency(&self, max_concurrency: usize) -> Self {
+        Self {
+            max_concurrency,
+            ..self.clone_for_task()
+        }
+    }
+
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
     /// Enable progress tracking
     pub async fn enable_progress_tracking(&self) -> broadcast::Receiver<TaskProgress> {
         let (tx, rx) = broadcast::channel(100);
@@ -45,10 +60,11 @@ impl TaskExecutor {
         }
     }
 
-    /// Cancel a task by ID
+    /// Cancel a running task by ID. The task sees the cancellation through
+    /// `TaskContext::control`; one that doesn't stop on its own is abandoned.
     pub async fn cancel_task(&self, task_id: Uuid) {
-        if let Some(token) = self.cancellation_tokens.read().await.get(&task_id) {
-            token.cancel();
+        if let Some(control) = self.controls.read().await.get(&task_id) {
+            control.cancel();
         }
     }
 
@@ -58,12 +74,22 @@ impl TaskExecutor {
         task: Arc<dyn Task>,
         context: TaskContext,
     ) -> Result<TaskResult> {
-        let task_id = Uuid::new_v4();
+        self.execute_one_with_id(Uuid::new_v4(), task, context).await
+    }
+
+    /// Like `execute_one`, under a caller-chosen ID that `cancel_task` accepts.
+    /// The task is also cancelled with the control of `context`.
+    pub async fn execute_one_with_id(
+        &self,
+        task_id: Uuid,
+        task: Arc<dyn Task>,
+        context: TaskContext,
+    ) -> Result<TaskResult> {
         let task_name = task.name().to_string();
 
-        // Create cancellation token
-        let cancel_token = tokio_util::sync::CancellationToken::new();
-        self.cancellation_tokens.write().await.insert(task_id, cancel_token.clone());
+        let control = context.control.child();
+        self.controls.write().await.insert(task_id, control.clone());
+        let context = context.with_control(control.clone());
 
         // Report start
         self.report_progress(TaskProgress {
@@ -76,7 +102,8 @@ impl TaskExecutor {
 
         // Execute task with cancellation support
         let result = tokio::select! {
-            _ = cancel_token.cancelled() => {
+            biased;
+            _ = control.cancelled() => {
                 self.report_progress(TaskProgress {
                     task_id,
                     task_name: task_name.clone(),
@@ -107,14 +134,14 @@ impl TaskExecutor {
                             status: "error".to_string(),
                             message: Some(e.to_string()),
                         }).await;
-                        Ok(TaskResult::failure(e.to_string()))
+                        Ok(TaskResult::from_error(&e))
                     }
                 }
             }
         };
 
-        // Cleanup cancellation token
-        self.cancellation_tokens.write().await.remove(&task_id);
+        // Cleanup task control
+        self.controls.write().await.remove(&task_id);
 
         result
     }
@@ -145,7 +172,7 @@ impl TaskExecutor {
         for handle in handles {
             match handle.await {
                 Ok(Ok(result)) => results.push(result),
-                Ok(Err(e)) => results.push(TaskResult::failure(e.to_string())),
+                Ok(Err(e)) => results.push(TaskResult::from_error(&e)),
                 Err(e) => results.push(TaskResult::failure(format!("Task panicked: {}", e))),
             }
         }
@@ -153,11 +180,26 @@ impl TaskExecutor {
         Ok(results)
     }
 
+    /// Merge the streaming aggregates tasks reported for `metric` under
+    /// `output.aggregates`, e.g. from evaluation tasks run on separate shards.
+    /// Failed tasks and tasks that did not report the metric are skipped.
+    pub fn merge_aggregates(
+        results: &[TaskResult],
+        metric: &str,
+    ) -> Result<Option<StreamingAggregator>> {
+        let outputs = results
+            .iter()
+            .filter(|result| result.success)
+            .map(|result| &result.output);
+
+        merge_reported_aggregates(outputs, metric)
+    }
+
     fn clone_for_task(&self) -> Self {
         Self {
             max_concurrency: self.max_concurrency,
             progress_tx: Arc::clone(&self.progress_tx),
-            cancellation_tokens: Arc::clone(&self.cancellation_tokens),
+            controls: Arc::clone(&self.controls),
         }
     }
 }
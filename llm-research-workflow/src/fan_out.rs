@@ -0,0 +1,208 @@
+use async_trait::async_trait;
+use llm_research_core::{CoreError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+use crate::executor::TaskExecutor;
+use crate::registry::TaskRegistry;
+use crate::tasks::{Task, TaskContext, TaskResult};
+use crate::template::{OutputSchema, ValueType};
+
+/// Runs a step's task once per item of a list known only at runtime, e.g.
+/// the batches of an upstream data loading step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FanOut {
+    /// The list to map over: a `${steps...}` or `${params...}` reference, or
+    /// a literal array
+    pub over: serde_json::Value,
+    /// Config key each child task receives its item under
+    #[serde(default = "default_item_key")]
+    pub item_key: String,
+    /// Most children running at once; defaults to the executor's limit
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+    /// Children that may fail without failing the step
+    #[serde(default)]
+    pub allowed_failures: usize,
+}
+
+fn default_item_key() -> String {
+    "item".to_string()
+}
+
+impl FanOut {
+    pub fn new(over: serde_json::Value) -> Self {
+        Self {
+            over,
+            item_key: default_item_key(),
+            max_concurrency: None,
+            allowed_failures: 0,
+        }
+    }
+
+    pub fn with_item_key(mut self, item_key: impl Into<String>) -> Self {
+        self.item_key = item_key.into();
+        self
+    }
+
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+
+    pub fn with_allowed_failures(mut self, allowed_failures: usize) -> Self {
+        self.allowed_failures = allowed_failures;
+        self
+    }
+
+    /// Why this fan-out can't be run, if it can't
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.max_concurrency == Some(0) {
+            return Err("fan_out max_concurrency must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The task behind a fanned-out step: one `task_type` child per item, run
+/// through `TaskExecutor::execute_batch`. Children that succeeded keep their
+/// output when the step is retried, so a retry only reruns the failures.
+pub struct FanOutTask {
+    children: Vec<Arc<dyn Task>>,
+    executor: TaskExecutor,
+    allowed_failures: usize,
+    outputs: Mutex<Vec<Option<serde_json::Value>>>,
+}
+
+impl FanOutTask {
+    /// Create a child for each of `items` from `config` with the item set
+    /// under `fan_out.item_key`
+    pub fn new(
+        registry: &TaskRegistry,
+        executor: &TaskExecutor,
+        task_type: &str,
+        config: &serde_json::Value,
+        fan_out: &FanOut,
+        items: Vec<serde_json::Value>,
+    ) -> Result<Self> {
+        fan_out.validate().map_err(CoreError::Validation)?;
+        let children = items
+            .into_iter()
+            .map(|item| {
+                let mut config = config.clone();
+                let fields = config.as_object_mut().ok_or_else(|| {
+                    CoreError::Validation(format!(
+                        "Fan-out config must be an object to receive '{}'",
+                        fan_out.item_key
+                    ))
+                })?;
+                fields.insert(fan_out.item_key.clone(), item);
+                registry.create(task_type, &config)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let executor = executor.with_max_concurrency(
+            fan_out
+                .max_concurrency
+                .unwrap_or_else(|| executor.max_concurrency()),
+        );
+
+        Ok(Self {
+            outputs: Mutex::new(vec![None; children.len()]),
+            children,
+            executor,
+            allowed_failures: fan_out.allowed_failures,
+        })
+    }
+
+    /// Fields of the output this task produces
+    pub fn output_schema() -> OutputSchema {
+        OutputSchema::new()
+            .with_field("items", ValueType::Number)
+            .with_field("succeeded", ValueType::Number)
+            .with_field("failed", ValueType::Number)
+            .with_field("outputs", ValueType::Array)
+            .with_field("errors", ValueType::Array)
+    }
+}
+
+/// The outputs gathered by a fan-out step, from its `outputs` field; `None`
+/// if `output` did not come from one. Failed children are left out.
+pub fn fan_out_outputs(output: &serde_json::Value) -> Option<Vec<&serde_json::Value>> {
+    output.get("items")?;
+    let outputs = output.get("outputs")?.as_array()?;
+    Some(outputs.iter().filter(|output| !output.is_null()).collect())
+}
+
+#[async_trait]
+impl Task for FanOutTask {
+    async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
+        let pending: Vec<usize> = {
+            let outputs = self.outputs.lock().unwrap();
+            (0..outputs.len())
+                .filter(|&i| outputs[i].is_none())
+                .collect()
+        };
+        tracing::info!(
+            "Fanning out {} of {} items for experiment: {}",
+            pending.len(),
+            self.children.len(),
+            context.experiment_id
+        );
+
+        let tasks = pending.iter().map(|&i| self.children[i].clone()).collect();
+        let results = self.executor.execute_batch(tasks, context).await?;
+
+        let mut outputs = self.outputs.lock().unwrap();
+        let mut errors = Vec::new();
+        let mut retryable = false;
+        for (&i, result) in pending.iter().zip(results) {
+            if result.success {
+                outputs[i] = Some(result.output);
+            } else {
+                retryable |= result.retryable;
+                errors.push(json!({
+                    "index": i,
+                    "error": result.error.unwrap_or_else(|| "unknown error".to_string()),
+                }));
+            }
+        }
+
+        if errors.len() > self.allowed_failures {
+            let message = format!(
+                "{} of {} items failed (allowed: {}), first: {}",
+                errors.len(),
+                self.children.len(),
+                self.allowed_failures,
+                errors[0]["error"].as_str().unwrap_or_default()
+            );
+            // Retrying only helps if some failed item might succeed next time
+            return Ok(if retryable {
+                TaskResult::failure(message)
+            } else {
+                TaskResult::permanent_failure(message)
+            });
+        }
+        if !errors.is_empty() {
+            tracing::warn!(
+                "{} of {} fanned-out items failed",
+                errors.len(),
+                self.children.len()
+            );
+        }
+
+        Ok(TaskResult::success(json!({
+            "items": self.children.len(),
+            "succeeded": self.children.len() - errors.len(),
+            "failed": errors.len(),
+            "outputs": *outputs,
+            "errors": errors,
+        })))
+    }
+
+    fn name(&self) -> &str {
+        "fan_out"
+    }
+}
@@ -0,0 +1,50 @@
+use llm_research_core::{CoreError, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+
+/// A failed read or write of one of `store`'s files
+pub(crate) fn io_error(store: &str, path: &Path, err: std::io::Error) -> CoreError {
+    CoreError::Internal(format!(
+        "{} I/O failed for {}: {}",
+        store,
+        path.display(),
+        err
+    ))
+}
+
+/// The value stored at `path`, or `None` if there is no file there
+pub(crate) async fn read_json<T: DeserializeOwned>(store: &str, path: &Path) -> Result<Option<T>> {
+    match tokio::fs::read(path).await {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(io_error(store, path, e)),
+    }
+}
+
+/// Write `value` to `path` through a temporary file renamed over it, so a
+/// reader never sees a partly written file
+pub(crate) async fn write_json_atomic<T: Serialize + ?Sized>(
+    store: &str,
+    path: &Path,
+    value: &T,
+) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    let bytes = serde_json::to_vec_pretty(value)?;
+
+    tokio::fs::write(&tmp, bytes)
+        .await
+        .map_err(|e| io_error(store, &tmp, e))?;
+    tokio::fs::rename(&tmp, path)
+        .await
+        .map_err(|e| io_error(store, path, e))
+}
+
+/// Removes `path`, which need not exist
+pub(crate) async fn remove_file(store: &str, path: &Path) -> Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(io_error(store, path, e)),
+    }
+}
@@ -2,8 +2,41 @@ pub mod engine;
 pub mod pipeline;
 pub mod tasks;
 pub mod executor;
+pub mod registry;
+pub mod control;
+pub mod scheduler;
+pub mod definition;
+pub mod template;
+pub mod cache;
+pub mod condition;
+pub mod fan_out;
+pub mod retry;
+pub mod sweep;
+pub mod backends;
+pub mod rate_limit;
+pub mod datasets;
+pub mod sampling;
+pub mod state_store;
+pub mod prompt_store;
+mod json_file;
 
 pub use engine::*;
 pub use pipeline::*;
 pub use tasks::*;
 pub use executor::*;
+pub use registry::*;
+pub use control::*;
+pub use scheduler::*;
+pub use definition::*;
+pub use template::*;
+pub use cache::*;
+pub use condition::*;
+pub use fan_out::*;
+pub use retry::*;
+pub use sweep::*;
+pub use backends::*;
+pub use rate_limit::*;
+pub use datasets::*;
+pub use sampling::*;
+pub use state_store::*;
+pub use prompt_store::*;
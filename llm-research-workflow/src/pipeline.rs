@@ -1,9 +1,18 @@
 use async_trait::async_trait;
+use llm_research_core::retry::retry_if;
 use llm_research_core::{Result, CoreError};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
+use crate::engine::WorkflowStep;
+use crate::registry::TaskRegistry;
+use crate::retry::Backoff;
+use crate::tasks::{Task, TaskContext, TaskResult};
+use crate::template::{resolve_config, with_prefix};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pipeline {
     pub id: Uuid,
@@ -27,6 +36,15 @@ pub struct PipelineTask {
     pub config: serde_json::Value,
     /// Task IDs that must complete before this task can run
     pub dependencies: Vec<Uuid>,
+    /// Retries of a retryable failure; none by default
+    #[serde(default)]
+    pub max_retries: usize,
+    /// Wait before each retry
+    #[serde(default)]
+    pub backoff: Backoff,
+    /// Limit on each attempt
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
 }
 
 impl PipelineTask {
@@ -37,6 +55,9 @@ impl PipelineTask {
             task_type,
             config,
             dependencies: vec![],
+            max_retries: 0,
+            backoff: Backoff::default(),
+            timeout_secs: None,
         }
     }
 
@@ -44,6 +65,36 @@ impl PipelineTask {
         self.dependencies = dependencies;
         self
     }
+
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn with_timeout_secs(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = Some(timeout_secs);
+        self
+    }
+}
+
+impl From<&WorkflowStep> for PipelineTask {
+    fn from(step: &WorkflowStep) -> Self {
+        Self {
+            id: step.id,
+            name: step.name.clone(),
+            task_type: step.task_type.clone(),
+            config: step.config.clone(),
+            dependencies: step.dependencies.clone(),
+            max_retries: step.max_retries,
+            backoff: step.backoff.clone(),
+            timeout_secs: step.timeout_secs,
+        }
+    }
 }
 
 /// Directed Acyclic Graph (DAG) representation for task dependencies
@@ -55,19 +106,25 @@ pub struct TaskDAG {
 
 impl TaskDAG {
     pub fn from_pipeline(pipeline: &Pipeline) -> Result<Self> {
+        Self::from_tasks(pipeline.stages.iter().flat_map(|stage| stage.tasks.iter().cloned()))
+    }
+
+    /// Build the DAG of tasks given in any order, e.g. workflow steps
+    pub fn from_tasks<I>(pipeline_tasks: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = PipelineTask>,
+    {
         let mut tasks = HashMap::new();
         let mut edges: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
 
         // Collect all tasks
-        for stage in &pipeline.stages {
-            for task in &stage.tasks {
-                tasks.insert(task.id, task.clone());
-                edges.entry(task.id).or_insert_with(Vec::new);
+        for task in pipeline_tasks {
+            edges.entry(task.id).or_insert_with(Vec::new);
 
-                for dep_id in &task.dependencies {
-                    edges.entry(*dep_id).or_insert_with(Vec::new).push(task.id);
-                }
+            for dep_id in &task.dependencies {
+                edges.entry(*dep_id).or_insert_with(Vec::new).push(task.id);
             }
+            tasks.insert(task.id, task);
         }
 
         let dag = Self { tasks, edges };
@@ -187,14 +244,61 @@ pub trait PipelineExecutor {
     async fn run(&self, pipeline: &Pipeline) -> Result<HashMap<Uuid, serde_json::Value>>;
 }
 
-pub struct ExperimentPipeline;
+pub struct ExperimentPipeline {
+    registry: Arc<TaskRegistry>,
+}
 
 impl ExperimentPipeline {
+    /// Pipeline executor that runs the built-in tasks
     pub fn new() -> Self {
-        Self
+        Self::with_registry(TaskRegistry::with_builtin_tasks())
+    }
+
+    pub fn with_registry(registry: TaskRegistry) -> Self {
+        Self {
+            registry: Arc::new(registry),
+        }
+    }
+
+    pub fn registry(&self) -> &TaskRegistry {
+        &self.registry
     }
 
     pub fn default_pipeline() -> Pipeline {
+        let load = PipelineTask::new(
+            "load_dataset".to_string(),
+            "data_loading".to_string(),
+            serde_json::json!({
+                "source": "synthetic://default",
+                "batch_size": 100,
+                "stream": false,
+            }),
+        );
+        let inference = PipelineTask::new(
+            "run_inference".to_string(),
+            "inference".to_string(),
+            serde_json::json!({ "provider": "simulated" }),
+        )
+        .with_dependencies(vec![load.id]);
+        let evaluation = PipelineTask::new(
+            "calculate_metrics".to_string(),
+            "evaluation".to_string(),
+            serde_json::json!({}),
+        )
+        .with_dependencies(vec![inference.id]);
+        let aggregation = PipelineTask::new(
+            "aggregate_results".to_string(),
+            "aggregation".to_string(),
+            serde_json::json!({}),
+        )
+        .with_dependencies(vec![evaluation.id]);
+        let report = PipelineTask::new(
+            "generate_report".to_string(),
+            "reporting".to_string(),
+            serde_json::json!({}),
+        )
+        .with_dependencies(vec![aggregation.id]);
+
         Pipeline {
             id: Uuid::new_v4(),
             name: "Default Experiment Pipeline".to_string(),
@@ -203,59 +307,134 @@ impl ExperimentPipeline {
                     id: Uuid::new_v4(),
                     name: "Data Loading".to_string(),
                     parallel: false,
-                    tasks: vec![PipelineTask::new(
-                        "load_dataset".to_string(),
-                        "data_loading".to_string(),
-                        serde_json::json!({}),
-                    )],
+                    tasks: vec![load],
                 },
                 PipelineStage {
                     id: Uuid::new_v4(),
                     name: "Model Inference".to_string(),
                     parallel: true,
-                    tasks: vec![PipelineTask::new(
-                        "run_inference".to_string(),
-                        "inference".to_string(),
-                        serde_json::json!({}),
-                    )],
+                    tasks: vec![inference],
                 },
                 PipelineStage {
                     id: Uuid::new_v4(),
                     name: "Evaluation".to_string(),
                     parallel: true,
-                    tasks: vec![
-                        PipelineTask::new(
-                            "calculate_metrics".to_string(),
-                            "evaluation".to_string(),
-                            serde_json::json!({}),
-                        ),
-                        PipelineTask::new(
-                            "aggregate_results".to_string(),
-                            "aggregation".to_string(),
-                            serde_json::json!({}),
-                        ),
-                    ],
+                    tasks: vec![evaluation],
                 },
                 PipelineStage {
                     id: Uuid::new_v4(),
                     name: "Reporting".to_string(),
                     parallel: false,
-                    tasks: vec![
-                        PipelineTask::new(
-                            "generate_report".to_string(),
-                            "reporting".to_string(),
-                            serde_json::json!({}),
-                        ),
-                        PipelineTask::new(
-                            "save_results".to_string(),
-                            "storage".to_string(),
-                            serde_json::json!({}),
-                        ),
-                    ],
+                    tasks: vec![aggregation, report],
                 },
             ],
         }
     }
+
+    /// Instantiate a pipeline task and build its context from the outputs
+    /// of the tasks it depends on, resolving output references in its config
+    fn prepare(
+        &self,
+        pipeline: &Pipeline,
+        task: &PipelineTask,
+        task_outputs: &HashMap<Uuid, serde_json::Value>,
+    ) -> Result<(Arc<dyn Task>, TaskContext)> {
+        let mut inputs = HashMap::new();
+        for dep_id in &task.dependencies {
+            let output = task_outputs.get(dep_id).ok_or_else(|| {
+                CoreError::InvalidState(format!(
+                    "Task '{}' depends on {} which has not completed",
+                    task.name, dep_id
+                ))
+            })?;
+            let name = pipeline
+                .stages
+                .iter()
+                .flat_map(|stage| &stage.tasks)
+                .find(|t| t.id == *dep_id)
+                .map(|t| t.name.clone())
+                .unwrap_or_else(|| dep_id.to_string());
+            inputs.insert(name, output.clone());
+        }
+
+        let outputs: HashMap<String, serde_json::Value> = pipeline
+            .stages
+            .iter()
+            .flat_map(|stage| &stage.tasks)
+            .filter_map(|t| Some((t.name.clone(), task_outputs.get(&t.id)?.clone())))
+            .collect();
+        let config = resolve_config(&task.config, &outputs, &serde_json::json!({}))
+            .map_err(|e| with_prefix(e, &format!("Task '{}'", task.name)))?;
+
+        let instance = self.registry.create(&task.task_type, &config)?;
+        let context = TaskContext::new(pipeline.id, config).with_inputs(inputs);
+        Ok((instance, context))
+    }
+
+    /// Turn a task's result into its output, or an error naming the task
+    /// paired with whether it is worth retrying
+    fn task_output(
+        task: &PipelineTask,
+        result: Result<TaskResult>,
+    ) -> std::result::Result<serde_json::Value, (CoreError, bool)> {
+        match result {
+            Ok(result) if result.success => Ok(result.output),
+            Ok(result) => Err((
+                CoreError::Internal(format!(
+                    "Task '{}' failed: {}",
+                    task.name,
+                    result.error.unwrap_or_else(|| "unknown error".to_string())
+                )),
+                result.retryable,
+            )),
+            Err(e) => {
+                let retryable = e.is_retryable();
+                Err((e, retryable))
+            }
+        }
+    }
+
+    /// Run a prepared task, retrying retryable failures up to the task's
+    /// `max_retries` times after its backoff, with each attempt limited to
+    /// `timeout_secs`
+    async fn execute_task(
+        task: PipelineTask,
+        instance: Arc<dyn Task>,
+        context: TaskContext,
+    ) -> Result<serde_json::Value> {
+        tracing::info!("Executing task: {}", task.name);
+        let policy = task.backoff.policy(task.max_retries);
+        let task = &task;
+        let attempt = || {
+            let instance = instance.clone();
+            let context = context.clone();
+            async move {
+                let result = match task.timeout_secs {
+                    Some(secs) => tokio::time::timeout(
+                        Duration::from_secs(secs),
+                        instance.execute(context),
+                    )
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(CoreError::Internal(format!(
+                            "Task '{}' timed out after {}s",
+                            task.name, secs
+                        )))
+                    }),
+                    None => instance.execute(context).await,
+                };
+                Self::task_output(task, result)
+            }
+        };
+        retry_if(&policy, attempt, |(_, retryable)| *retryable)
+            .await
+            .map_err(|e| {
+                if e.attempts > 1 {
+                    tracing::warn!("Task {} failed after {} attempts", task.name, e.attempts);
+                }
+                e.error.0
+            })
+    }
 }
 
 impl Default for ExperimentPipeline {
@@ -276,43 +455,27 @@ impl PipelineExecutor for ExperimentPipeline {
 
             if stage.parallel && stage.tasks.len() > 1 {
                 // Execute tasks in parallel
-                let handles: Vec<_> = stage
-                    .tasks
-                    .iter()
-                    .map(|task| {
-                        let task = task.clone();
-                        tokio::spawn(async move {
-                            tracing::info!("Executing task: {}", task.name);
-                            // Mock task execution
-                            (
-                                task.id,
-                                serde_json::json!({
-                                    "task": task.name,
-                                    "status": "completed"
-                                }),
-                            )
-                        })
-                    })
-                    .collect();
-
-                for handle in handles {
-                    let (task_id, output) = handle.await.map_err(|e| {
+                let mut handles = Vec::new();
+                for task in &stage.tasks {
+                    let (instance, context) = self.prepare(pipeline, task, &task_outputs)?;
+                    handles.push((
+                        task,
+                        tokio::spawn(Self::execute_task(task.clone(), instance, context)),
+                    ));
+                }
+
+                for (task, handle) in handles {
+                    let output = handle.await.map_err(|e| {
                         CoreError::Internal(format!("Task failed: {}", e))
-                    })?;
-                    task_outputs.insert(task_id, output);
+                    })??;
+                    task_outputs.insert(task.id, output);
                 }
             } else {
                 // Execute tasks sequentially
                 for task in &stage.tasks {
-                    tracing::info!("Executing task: {}", task.name);
-                    // Mock task execution
-                    task_outputs.insert(
-                        task.id,
-                        serde_json::json!({
-                            "task": task.name,
-                            "status": "completed"
-                        }),
-                    );
+                    let (instance, context) = self.prepare(pipeline, task, &task_outputs)?;
+                    let output = Self::execute_task(task.clone(), instance, context).await?;
+                    task_outputs.insert(task.id, output);
                 }
             }
         }
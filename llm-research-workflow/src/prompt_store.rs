@@ -0,0 +1,173 @@
+use async_trait::async_trait;
+use llm_research_core::{PromptTemplate, Result};
+use llm_research_storage::PromptTemplateRepository;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::json_file::{io_error, read_json, write_json_atomic};
+
+/// What I/O errors for this store's files are reported as
+const STORE: &str = "Prompt template";
+
+/// Where prompt templates are looked up by ID and version
+#[async_trait]
+pub trait PromptTemplateStore: Send + Sync {
+    /// Insert the template, or replace the same version of it
+    async fn save(&self, template: &PromptTemplate) -> Result<()>;
+
+    /// The template's `version`, or its latest version if unset
+    async fn get(&self, id: Uuid, version: Option<i32>) -> Result<Option<PromptTemplate>>;
+}
+
+/// Keeps every saved version in memory; nothing survives the process
+#[derive(Debug, Default)]
+pub struct InMemoryPromptTemplateStore {
+    templates: RwLock<HashMap<Uuid, BTreeMap<i32, PromptTemplate>>>,
+}
+
+impl InMemoryPromptTemplateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PromptTemplateStore for InMemoryPromptTemplateStore {
+    async fn save(&self, template: &PromptTemplate) -> Result<()> {
+        self.templates
+            .write()
+            .await
+            .entry(template.id)
+            .or_default()
+            .insert(template.version, template.clone());
+        Ok(())
+    }
+
+    async fn get(&self, id: Uuid, version: Option<i32>) -> Result<Option<PromptTemplate>> {
+        let templates = self.templates.read().await;
+        let Some(versions) = templates.get(&id) else {
+            return Ok(None);
+        };
+        Ok(match version {
+            Some(version) => versions.get(&version).cloned(),
+            None => versions.values().next_back().cloned(),
+        })
+    }
+}
+
+/// Writes each version of a template to `<id>/<version>.json` under a
+/// directory, so templates can be kept alongside workflow definitions
+#[derive(Debug, Clone)]
+pub struct FilePromptTemplateStore {
+    dir: PathBuf,
+}
+
+impl FilePromptTemplateStore {
+    /// Store templates under `dir`, creating it if needed
+    pub async fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| io_error(STORE, &dir, e))?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, id: Uuid, version: i32) -> PathBuf {
+        self.dir
+            .join(id.to_string())
+            .join(format!("{}.json", version))
+    }
+
+    /// The highest version saved for `id`, if any
+    async fn latest_version(&self, id: Uuid) -> Result<Option<i32>> {
+        let dir = self.dir.join(id.to_string());
+        let mut entries = match tokio::fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(io_error(STORE, &dir, e)),
+        };
+
+        let mut latest = None;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| io_error(STORE, &dir, e))?
+        {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(version) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<i32>().ok())
+            {
+                latest = latest.max(Some(version));
+            }
+        }
+        Ok(latest)
+    }
+}
+
+#[async_trait]
+impl PromptTemplateStore for FilePromptTemplateStore {
+    async fn save(&self, template: &PromptTemplate) -> Result<()> {
+        let path = self.path(template.id, template.version);
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| io_error(STORE, dir, e))?;
+        }
+        write_json_atomic(STORE, &path, template).await
+    }
+
+    async fn get(&self, id: Uuid, version: Option<i32>) -> Result<Option<PromptTemplate>> {
+        let version = match version {
+            Some(version) => version,
+            None => match self.latest_version(id).await? {
+                Some(version) => version,
+                None => return Ok(None),
+            },
+        };
+        read_json(STORE, &self.path(id, version)).await
+    }
+}
+
+/// Reads templates from the `prompt_templates` table, which holds only the
+/// current version of each
+pub struct PostgresPromptTemplateStore {
+    repository: PromptTemplateRepository,
+}
+
+impl PostgresPromptTemplateStore {
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self {
+            repository: PromptTemplateRepository::new(pool),
+        }
+    }
+}
+
+#[async_trait]
+impl PromptTemplateStore for PostgresPromptTemplateStore {
+    async fn save(&self, template: &PromptTemplate) -> Result<()> {
+        match self.repository.get_by_id(&template.id).await? {
+            Some(_) => self.repository.update(template).await?,
+            None => self.repository.create(template).await?,
+        };
+        Ok(())
+    }
+
+    async fn get(&self, id: Uuid, version: Option<i32>) -> Result<Option<PromptTemplate>> {
+        Ok(self
+            .repository
+            .get_by_id(&id)
+            .await?
+            .filter(|template| version.is_none_or(|version| template.version == version)))
+    }
+}
@@ -0,0 +1,360 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{sleep, Instant};
+
+/// Budgets a provider enforces on one API key. Unset budgets are unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RateLimits {
+    pub requests_per_minute: Option<usize>,
+    /// Prompt and completion tokens together
+    pub tokens_per_minute: Option<usize>,
+    /// Most requests to have outstanding at once
+    pub max_in_flight: Option<usize>,
+}
+
+/// A key's shared limiter, and whether its limits were set by `configure`
+/// rather than by the tasks using it
+#[derive(Debug)]
+struct SharedLimiter {
+    limiter: Arc<RateLimiter>,
+    configured: bool,
+}
+
+type SharedLimiters = HashMap<String, SharedLimiter>;
+
+/// Longest pause after a rate limit response that didn't say how long to
+/// wait
+const MAX_RATE_LIMIT_PAUSE: Duration = Duration::from_secs(60);
+
+/// How much of its per-minute budget a bucket holds, so that a new or idle
+/// limiter lets only a few seconds' worth of requests go at once
+const BURST: Duration = Duration::from_secs(5);
+
+/// A bucket holding a few seconds' budget, and at least one request's,
+/// refilled continuously, so a full bucket allows a short burst and is then
+/// paced at its rate
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    level: f64,
+    per_second: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(per_minute: usize, now: Instant) -> Self {
+        let per_second = per_minute.max(1) as f64 / 60.0;
+        let capacity = Self::capacity(per_second);
+        Self {
+            capacity,
+            level: capacity,
+            per_second,
+            updated: now,
+        }
+    }
+
+    fn capacity(per_second: f64) -> f64 {
+        (per_second * BURST.as_secs_f64()).max(1.0)
+    }
+
+    /// Refill at `per_minute` from now on, keeping no more than the new
+    /// capacity
+    fn resize(&mut self, per_minute: usize, now: Instant) {
+        self.refill(now);
+        self.per_second = per_minute.max(1) as f64 / 60.0;
+        self.capacity = Self::capacity(self.per_second);
+        self.level = self.level.min(self.capacity);
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.level = (self.level + elapsed * self.per_second).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// How long until `cost` may be taken. Costs above the capacity only
+    /// wait for a full bucket, and take it into debt.
+    fn wait(&self, cost: f64) -> Duration {
+        let needed = cost.min(self.capacity) - self.level;
+        if needed <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(needed / self.per_second)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    limits: RateLimits,
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+    /// No requests start before this, after the provider refused one
+    paused_until: Option<Instant>,
+    /// Rate limit responses since the last success
+    rate_limited: u32,
+    in_flight: Option<Arc<Semaphore>>,
+    /// In-flight slots to take away as they are released, after the limit
+    /// was lowered while they were in use
+    in_flight_debt: usize,
+}
+
+/// Admits requests to a provider within its requests and tokens per minute
+/// and in-flight budgets, and holds all of them back when the provider
+/// answers that they are coming too fast
+#[derive(Debug)]
+pub struct RateLimiter {
+    state: Mutex<State>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        let now = Instant::now();
+        Self {
+            state: Mutex::new(State {
+                limits,
+                requests: limits.requests_per_minute.map(|n| Bucket::new(n, now)),
+                tokens: limits.tokens_per_minute.map(|n| Bucket::new(n, now)),
+                paused_until: None,
+                rate_limited: 0,
+                in_flight: limits
+                    .max_in_flight
+                    .map(|n| Arc::new(Semaphore::new(n.max(1)))),
+                in_flight_debt: 0,
+            }),
+        }
+    }
+
+    /// The limiter of everyone in this process using `key`, typically one
+    /// per provider and API key, so that concurrent tasks share its budgets.
+    /// Its limits are the ones last declared for the key, unless they were
+    /// set with `configure`.
+    pub fn shared(key: &str, limits: RateLimits) -> Arc<Self> {
+        let mut shared = shared_limiters();
+        let entry = shared
+            .entry(key.to_string())
+            .or_insert_with(|| SharedLimiter {
+                limiter: Arc::new(Self::new(limits)),
+                configured: false,
+            });
+        if !entry.configured {
+            entry.limiter.set_limits(limits);
+        }
+        Arc::clone(&entry.limiter)
+    }
+
+    /// Sets the limits of `key`'s shared limiter, raising or lowering them,
+    /// for tasks already using it and those that follow. Limits the tasks
+    /// declare are ignored until the key is `reset`.
+    pub fn configure(key: &str, limits: RateLimits) -> Arc<Self> {
+        let mut shared = shared_limiters();
+        let entry = shared
+            .entry(key.to_string())
+            .or_insert_with(|| SharedLimiter {
+                limiter: Arc::new(Self::new(limits)),
+                configured: true,
+            });
+        entry.configured = true;
+        entry.limiter.set_limits(limits);
+        Arc::clone(&entry.limiter)
+    }
+
+    /// Forgets `key`'s shared limiter, along with any limits configured for
+    /// it. Tasks holding it keep using it; the next to ask for the key get a
+    /// new one with the limits they declare.
+    pub fn reset(key: &str) {
+        shared_limiters().remove(key);
+    }
+
+    /// The budgets currently enforced
+    pub fn limits(&self) -> RateLimits {
+        self.state().limits
+    }
+
+    /// Enforce `limits` from now on, raising or lowering each budget. Spent
+    /// budget stays spent, and requests in flight keep their slots until
+    /// they finish.
+    pub fn set_limits(&self, limits: RateLimits) {
+        let mut state = self.state();
+        if state.limits == limits {
+            return;
+        }
+        let now = Instant::now();
+
+        state.requests = resized(state.requests.take(), limits.requests_per_minute, now);
+        state.tokens = resized(state.tokens.take(), limits.tokens_per_minute, now);
+        let semaphore = state.in_flight.clone();
+        match (semaphore, state.limits.max_in_flight, limits.max_in_flight) {
+            (Some(semaphore), Some(current), Some(n)) => {
+                let (current, n) = (current.max(1), n.max(1));
+                if n < current {
+                    let excess = current - n;
+                    let forgotten = semaphore.forget_permits(excess);
+                    state.in_flight_debt += excess - forgotten;
+                } else {
+                    // Slots still owed from a lower limit are forgiven first
+                    let extra = n - current;
+                    let forgiven = extra.min(state.in_flight_debt);
+                    state.in_flight_debt -= forgiven;
+                    semaphore.add_permits(extra - forgiven);
+                }
+            }
+            (_, _, n) => {
+                state.in_flight = n.map(|n| Arc::new(Semaphore::new(n.max(1))));
+                state.in_flight_debt = 0;
+            }
+        }
+        state.limits = limits;
+    }
+
+    /// Waits until a request expected to use `estimated_tokens` fits the
+    /// budgets, and takes it from them
+    pub async fn acquire(self: &Arc<Self>, estimated_tokens: usize) -> RatePermit {
+        let semaphore = self.state().in_flight.clone();
+        let in_flight = match semaphore {
+            Some(semaphore) => Some(
+                semaphore
+                    .acquire_owned()
+                    .await
+                    .expect("in-flight semaphore is never closed"),
+            ),
+            None => None,
+        };
+
+        let tokens = estimated_tokens as f64;
+        loop {
+            let wait = {
+                let mut state = self.state();
+                let now = Instant::now();
+                let mut wait = state
+                    .paused_until
+                    .map_or(Duration::ZERO, |until| until.saturating_duration_since(now));
+                if let Some(bucket) = &mut state.requests {
+                    bucket.refill(now);
+                    wait = wait.max(bucket.wait(1.0));
+                }
+                if let Some(bucket) = &mut state.tokens {
+                    bucket.refill(now);
+                    wait = wait.max(bucket.wait(tokens));
+                }
+
+                if wait.is_zero() {
+                    if let Some(bucket) = &mut state.requests {
+                        bucket.level -= 1.0;
+                    }
+                    if let Some(bucket) = &mut state.tokens {
+                        bucket.level -= tokens;
+                    }
+                    break;
+                }
+                wait
+            };
+            sleep(wait).await;
+        }
+
+        RatePermit {
+            limiter: Arc::clone(self),
+            estimated_tokens,
+            in_flight,
+        }
+    }
+
+    /// Holds back every request for `retry_after`, or, if the provider
+    /// didn't say, for a pause that doubles from a second with each rate
+    /// limit response until a request succeeds
+    pub fn rate_limited(&self, retry_after: Option<Duration>) {
+        let mut state = self.state();
+        state.rate_limited += 1;
+        let pause = retry_after.unwrap_or_else(|| {
+            Duration::from_secs(1 << (state.rate_limited - 1).min(6)).min(MAX_RATE_LIMIT_PAUSE)
+        });
+        let until = Instant::now() + pause;
+        state.paused_until = Some(
+            state
+                .paused_until
+                .map_or(until, |current| current.max(until)),
+        );
+    }
+
+    /// When requests may start again after a rate limit response, if that is
+    /// still ahead
+    pub fn paused_until(&self) -> Option<Instant> {
+        self.state()
+            .paused_until
+            .filter(|until| *until > Instant::now())
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A request admitted by a `RateLimiter`, which keeps its in-flight slot
+/// until dropped
+#[derive(Debug)]
+pub struct RatePermit {
+    limiter: Arc<RateLimiter>,
+    estimated_tokens: usize,
+    in_flight: Option<OwnedSemaphorePermit>,
+}
+
+impl RatePermit {
+    /// Settles the tokens budget with the tokens the request actually used,
+    /// rather than its estimate
+    pub fn complete(self, tokens_used: usize) {
+        let mut state = self.limiter.state();
+        state.rate_limited = 0;
+        if let Some(bucket) = &mut state.tokens {
+            bucket.level += self.estimated_tokens as f64 - tokens_used as f64;
+            bucket.level = bucket.level.min(bucket.capacity);
+        }
+    }
+
+    /// The provider refused the request as over its limits
+    pub fn rate_limited(self, retry_after: Option<Duration>) {
+        self.limiter.rate_limited(retry_after);
+    }
+}
+
+impl Drop for RatePermit {
+    fn drop(&mut self) {
+        let mut state = self.limiter.state();
+        if state.in_flight_debt == 0 {
+            return;
+        }
+        // Only slots of the current semaphore pay off its debt
+        let current = match (&self.in_flight, &state.in_flight) {
+            (Some(permit), Some(semaphore)) => Arc::ptr_eq(permit.semaphore(), semaphore),
+            _ => false,
+        };
+        if current {
+            if let Some(permit) = self.in_flight.take() {
+                permit.forget();
+                state.in_flight_debt -= 1;
+            }
+        }
+    }
+}
+
+fn shared_limiters() -> std::sync::MutexGuard<'static, SharedLimiters> {
+    static SHARED: OnceLock<Mutex<SharedLimiters>> = OnceLock::new();
+    SHARED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// `bucket` refilling at `per_minute`, or a new one if there was none
+fn resized(bucket: Option<Bucket>, per_minute: Option<usize>, now: Instant) -> Option<Bucket> {
+    let per_minute = per_minute?;
+    Some(match bucket {
+        Some(mut bucket) => {
+            bucket.resize(per_minute, now);
+            bucket
+        }
+        None => Bucket::new(per_minute, now),
+    })
+}
@@ -0,0 +1,124 @@
+use llm_research_core::{CoreError, Result};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::template::OutputSchema;
+use crate::tasks::{
+    AggregationConfig, AggregationTask, DataLoadingConfig, DataLoadingTask, EvaluationConfig,
+    EvaluationTask, InferenceConfig, InferenceTask, LlmJudgeConfig, LlmJudgeTask,
+    PromptRenderingConfig, PromptRenderingTask, ReduceConfig, ReduceTask, ReportingConfig,
+    ReportingTask, Task,
+};
+
+/// Builds a task from a step's `config`
+pub type TaskFactory = Arc<dyn Fn(&serde_json::Value) -> Result<Arc<dyn Task>> + Send + Sync>;
+
+/// Maps `task_type` strings used in workflow steps and pipeline tasks to
+/// factories for the tasks that implement them
+#[derive(Clone, Default)]
+pub struct TaskRegistry {
+    factories: HashMap<String, TaskFactory>,
+    output_schemas: HashMap<String, OutputSchema>,
+}
+
+impl TaskRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with the tasks shipped in this crate: `data_loading`,
+    /// `prompt_rendering`, `inference`, `evaluation`, `llm_judge`,
+    /// `aggregation`, `reduce` and `reporting`
+    pub fn with_builtin_tasks() -> Self {
+        let mut registry = Self::new();
+        registry.register_config::<DataLoadingConfig, _, _>("data_loading", DataLoadingTask::new);
+        registry.register_config::<PromptRenderingConfig, _, _>(
+            "prompt_rendering",
+            PromptRenderingTask::new,
+        );
+        registry.register_config::<InferenceConfig, _, _>("inference", InferenceTask::new);
+        registry.register_config::<EvaluationConfig, _, _>("evaluation", EvaluationTask::new);
+        registry.register_config::<LlmJudgeConfig, _, _>("llm_judge", LlmJudgeTask::new);
+        registry.register_config::<AggregationConfig, _, _>("aggregation", AggregationTask::new);
+        registry.register_config::<ReduceConfig, _, _>("reduce", ReduceTask::new);
+        registry.register_config::<ReportingConfig, _, _>("reporting", ReportingTask::new);
+
+        registry.register_output_schema("data_loading", DataLoadingTask::output_schema());
+        registry.register_output_schema("prompt_rendering", PromptRenderingTask::output_schema());
+        registry.register_output_schema("inference", InferenceTask::output_schema());
+        registry.register_output_schema("evaluation", EvaluationTask::output_schema());
+        registry.register_output_schema("llm_judge", LlmJudgeTask::output_schema());
+        registry.register_output_schema("aggregation", AggregationTask::output_schema());
+        registry.register_output_schema("reduce", ReduceTask::output_schema());
+        registry.register_output_schema("reporting", ReportingTask::output_schema());
+        registry
+    }
+
+    /// Register (or replace) the factory for `task_type`
+    pub fn register<F>(&mut self, task_type: impl Into<String>, factory: F)
+    where
+        F: Fn(&serde_json::Value) -> Result<Arc<dyn Task>> + Send + Sync + 'static,
+    {
+        self.factories.insert(task_type.into(), Arc::new(factory));
+    }
+
+    /// Register a task built from a config type deserialized from the step config
+    pub fn register_config<C, T, F>(&mut self, task_type: impl Into<String>, build: F)
+    where
+        C: DeserializeOwned,
+        T: Task + 'static,
+        F: Fn(C) -> T + Send + Sync + 'static,
+    {
+        let task_type = task_type.into();
+        let name = task_type.clone();
+        self.register(task_type, move |config| {
+            let config = serde_json::from_value::<C>(config.clone()).map_err(|e| {
+                CoreError::Validation(format!("Invalid config for task type '{}': {}", name, e))
+            })?;
+            Ok(Arc::new(build(config)) as Arc<dyn Task>)
+        });
+    }
+
+    /// Declare the output fields of `task_type`, so references to its output
+    /// can be checked when a definition is loaded
+    pub fn register_output_schema(&mut self, task_type: impl Into<String>, schema: OutputSchema) {
+        self.output_schemas.insert(task_type.into(), schema);
+    }
+
+    pub fn output_schema(&self, task_type: &str) -> Option<&OutputSchema> {
+        self.output_schemas.get(task_type)
+    }
+
+    pub fn contains(&self, task_type: &str) -> bool {
+        self.factories.contains_key(task_type)
+    }
+
+    /// Registered task types, sorted
+    pub fn task_types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = self.factories.keys().map(String::as_str).collect();
+        types.sort_unstable();
+        types
+    }
+
+    /// Instantiate the task for `task_type` from a step config
+    pub fn create(&self, task_type: &str, config: &serde_json::Value) -> Result<Arc<dyn Task>> {
+        let factory = self.factories.get(task_type).ok_or_else(|| {
+            CoreError::NotFound(format!(
+                "Unknown task type '{}' (registered: {})",
+                task_type,
+                self.task_types().join(", ")
+            ))
+        })?;
+        factory(config)
+    }
+}
+
+impl std::fmt::Debug for TaskRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskRegistry")
+            .field("task_types", &self.task_types())
+            .finish()
+    }
+}
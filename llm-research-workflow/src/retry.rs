@@ -0,0 +1,200 @@
+use llm_research_core::retry::{
+    ConstantBackoff, ExponentialBackoff, LinearBackoff, RetryConfig, RetryPolicy,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub use llm_research_core::retry::JitterStrategy;
+
+/// How long a failed step or request waits before each retry. Only
+/// retryable errors (see `CoreError::is_retryable`) are retried at all.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Backoff {
+    /// `initial_delay_ms * multiplier^attempt`, capped at `max_delay_ms`
+    Exponential {
+        #[serde(default = "default_initial_delay_ms")]
+        initial_delay_ms: u64,
+        #[serde(default = "default_multiplier")]
+        multiplier: f64,
+        #[serde(default = "default_max_delay_ms")]
+        max_delay_ms: u64,
+        #[serde(default)]
+        jitter: JitterStrategy,
+    },
+    /// `initial_delay_ms + increment_ms * attempt`, capped at `max_delay_ms`
+    Linear {
+        #[serde(default = "default_initial_delay_ms")]
+        initial_delay_ms: u64,
+        #[serde(default = "default_initial_delay_ms")]
+        increment_ms: u64,
+        #[serde(default = "default_max_delay_ms")]
+        max_delay_ms: u64,
+        #[serde(default)]
+        jitter: JitterStrategy,
+    },
+    /// The same delay before every retry
+    Constant {
+        #[serde(default = "default_initial_delay_ms")]
+        delay_ms: u64,
+        #[serde(default)]
+        jitter: JitterStrategy,
+    },
+}
+
+fn default_initial_delay_ms() -> u64 {
+    1000
+}
+
+fn default_multiplier() -> f64 {
+    2.0
+}
+
+fn default_max_delay_ms() -> u64 {
+    60_000
+}
+
+impl Default for Backoff {
+    /// Exponential from one second, doubling up to a minute, with full jitter
+    fn default() -> Self {
+        Self::exponential(Duration::from_millis(default_initial_delay_ms()))
+    }
+}
+
+impl Backoff {
+    pub fn exponential(initial_delay: Duration) -> Self {
+        Self::Exponential {
+            initial_delay_ms: initial_delay.as_millis() as u64,
+            multiplier: default_multiplier(),
+            max_delay_ms: default_max_delay_ms(),
+            jitter: JitterStrategy::default(),
+        }
+    }
+
+    pub fn linear(initial_delay: Duration, increment: Duration) -> Self {
+        Self::Linear {
+            initial_delay_ms: initial_delay.as_millis() as u64,
+            increment_ms: increment.as_millis() as u64,
+            max_delay_ms: default_max_delay_ms(),
+            jitter: JitterStrategy::default(),
+        }
+    }
+
+    pub fn constant(delay: Duration) -> Self {
+        Self::Constant {
+            delay_ms: delay.as_millis() as u64,
+            jitter: JitterStrategy::default(),
+        }
+    }
+
+    pub fn with_jitter(mut self, strategy: JitterStrategy) -> Self {
+        match &mut self {
+            Self::Exponential { jitter, .. }
+            | Self::Linear { jitter, .. }
+            | Self::Constant { jitter, .. } => *jitter = strategy,
+        }
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        match &mut self {
+            Self::Exponential { max_delay_ms, .. } | Self::Linear { max_delay_ms, .. } => {
+                *max_delay_ms = max_delay.as_millis() as u64
+            }
+            Self::Constant { .. } => {}
+        }
+        self
+    }
+
+    /// Delay before retry number `attempt + 1`, or `None` once `max_retries`
+    /// retries have been made
+    pub fn delay(&self, attempt: usize, max_retries: usize) -> Option<Duration> {
+        self.policy(max_retries).next_delay(attempt)
+    }
+
+    /// The resilience policy this backoff describes, allowing `max_retries`
+    /// retries
+    pub fn policy(&self, max_retries: usize) -> BackoffPolicy {
+        match *self {
+            Self::Exponential {
+                initial_delay_ms,
+                multiplier,
+                max_delay_ms,
+                jitter,
+            } => BackoffPolicy::Exponential(ExponentialBackoff::with_jitter(
+                RetryConfig {
+                    max_attempts: max_retries,
+                    initial_delay: Duration::from_millis(initial_delay_ms),
+                    max_delay: Duration::from_millis(max_delay_ms),
+                    multiplier,
+                    jitter: jitter != JitterStrategy::None,
+                },
+                jitter,
+            )),
+            Self::Linear {
+                initial_delay_ms,
+                increment_ms,
+                max_delay_ms,
+                jitter,
+            } => BackoffPolicy::Linear(LinearBackoff::with_jitter(
+                max_retries,
+                Duration::from_millis(initial_delay_ms),
+                Duration::from_millis(increment_ms),
+                Duration::from_millis(max_delay_ms),
+                jitter,
+            )),
+            Self::Constant { delay_ms, jitter } => BackoffPolicy::Constant(
+                ConstantBackoff::with_jitter(max_retries, Duration::from_millis(delay_ms), jitter),
+            ),
+        }
+    }
+
+    /// Why this backoff can't be used, if it can't
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Self::Exponential { multiplier, .. } if multiplier.is_nan() || multiplier < 1.0 => Err(
+                format!("backoff multiplier must be at least 1, got {}", multiplier),
+            ),
+            Self::Exponential {
+                initial_delay_ms,
+                max_delay_ms,
+                ..
+            }
+            | Self::Linear {
+                initial_delay_ms,
+                max_delay_ms,
+                ..
+            } if max_delay_ms < initial_delay_ms => Err(format!(
+                "backoff max_delay_ms ({}) is less than initial_delay_ms ({})",
+                max_delay_ms, initial_delay_ms
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A `Backoff` as one of the resilience module's retry policies
+#[derive(Debug, Clone)]
+pub enum BackoffPolicy {
+    Exponential(ExponentialBackoff),
+    Linear(LinearBackoff),
+    Constant(ConstantBackoff),
+}
+
+impl RetryPolicy for BackoffPolicy {
+    fn next_delay(&self, attempt: usize) -> Option<Duration> {
+        match self {
+            Self::Exponential(policy) => policy.next_delay(attempt),
+            Self::Linear(policy) => policy.next_delay(attempt),
+            Self::Constant(policy) => policy.next_delay(attempt),
+        }
+    }
+
+    fn max_attempts(&self) -> usize {
+        match self {
+            Self::Exponential(policy) => policy.max_attempts(),
+            Self::Linear(policy) => policy.max_attempts(),
+            Self::Constant(policy) => policy.max_attempts(),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use llm_research_core::{
+    CoreError, DataSplit, DatasetSample, ExperimentRun, ReproducibilitySettings, Result,
+    SampleConfig, SampleSize, SampleStrategy,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Key of a run's metadata holding its `SampleSelection`
+pub const SAMPLE_SELECTION_KEY: &str = "sample_selection";
+
+/// Metadata field a sample's split is read from unless configured otherwise
+pub const DEFAULT_SPLIT_FIELD: &str = "split";
+
+/// The name a split goes by in sample metadata
+pub fn split_name(split: &DataSplit) -> &str {
+    match split {
+        DataSplit::Train => "train",
+        DataSplit::Validation => "validation",
+        DataSplit::Test => "test",
+        DataSplit::Custom(name) => name,
+    }
+}
+
+/// A field of the sample's metadata, or of its input when the input is the
+/// whole row
+pub fn sample_field<'a>(sample: &'a DatasetSample, field: &str) -> Option<&'a serde_json::Value> {
+    sample
+        .metadata
+        .get(field)
+        .or_else(|| sample.input.get(field))
+}
+
+/// Whether `sample`'s `field` puts it in `split`
+pub fn in_split(sample: &DatasetSample, split: &DataSplit, field: &str) -> bool {
+    sample_field(sample, field).and_then(|value| value.as_str()) == Some(split_name(split))
+}
+
+/// Which samples a run used, and how they were chosen, so the run can be
+/// repeated on exactly those samples. The data loading task puts it in its
+/// output as `selection`, and trial runs of a sweep record it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampleSelection {
+    pub seed: u64,
+    pub split: Option<DataSplit>,
+    pub strategy: SampleStrategy,
+    pub size: SampleSize,
+    pub stratify_by: Option<String>,
+    /// In dataset order
+    pub sample_ids: Vec<Uuid>,
+}
+
+impl SampleSelection {
+    /// Stores the selection in the run's metadata
+    pub fn record(&self, run: &mut ExperimentRun) -> Result<()> {
+        run.metadata.insert(
+            SAMPLE_SELECTION_KEY.to_string(),
+            serde_json::to_value(self)?,
+        );
+        Ok(())
+    }
+
+    /// The selection recorded on `run`, if any
+    pub fn from_run(run: &ExperimentRun) -> Result<Option<Self>> {
+        run.metadata
+            .get(SAMPLE_SELECTION_KEY)
+            .map(|value| serde_json::from_value(value.clone()))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// The selection in a data loading step's output, if it sampled
+    pub fn from_output(output: &serde_json::Value) -> Result<Option<Self>> {
+        match output.get("selection") {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(selection) => Ok(Some(serde_json::from_value(selection.clone())?)),
+        }
+    }
+}
+
+/// Selects samples from a split of a dataset by a `SampleConfig`. The same
+/// seed over the same samples selects the same ones.
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    config: SampleConfig,
+    seed: u64,
+    split: Option<DataSplit>,
+    split_field: String,
+}
+
+impl Sampler {
+    /// Seeded by the config's own seed, else the experiment's random seed,
+    /// else a fresh one, which the selection records
+    pub fn new(config: SampleConfig, reproducibility: &ReproducibilitySettings) -> Self {
+        let seed = config
+            .seed
+            .or(reproducibility.random_seed)
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        Self {
+            config,
+            seed,
+            split: None,
+            split_field: DEFAULT_SPLIT_FIELD.to_string(),
+        }
+    }
+
+    /// Only select samples whose `field` names `split`
+    pub fn with_split(mut self, split: DataSplit, field: impl Into<String>) -> Self {
+        self.split = Some(split);
+        self.split_field = field.into();
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn config(&self) -> &SampleConfig {
+        &self.config
+    }
+
+    /// Whether selecting needs every sample of the split at once, rather
+    /// than taking them from the front as they are read
+    pub fn needs_all_samples(&self) -> bool {
+        !matches!(
+            (&self.config.strategy, &self.config.size),
+            (
+                SampleStrategy::Sequential,
+                SampleSize::All | SampleSize::Count(_)
+            )
+        )
+    }
+
+    /// The selected samples, in dataset order
+    pub fn select(&self, samples: Vec<DatasetSample>) -> Result<Vec<DatasetSample>> {
+        let samples: Vec<DatasetSample> = match &self.split {
+            Some(split) => samples
+                .into_iter()
+                .filter(|sample| in_split(sample, split, &self.split_field))
+                .collect(),
+            None => samples,
+        };
+        let wanted = self.size_of(samples.len())?;
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        let mut keep = match &self.config.strategy {
+            SampleStrategy::Sequential => (0..wanted).collect(),
+            SampleStrategy::Random => {
+                rand::seq::index::sample(&mut rng, samples.len(), wanted).into_vec()
+            }
+            SampleStrategy::Stratified => self.stratified(&samples, wanted, &mut rng)?,
+            SampleStrategy::Custom(name) => {
+                return Err(CoreError::Validation(format!(
+                    "Unsupported sample strategy '{}'",
+                    name
+                )))
+            }
+        };
+        keep.sort_unstable();
+
+        let mut keep = keep.into_iter().peekable();
+        Ok(samples
+            .into_iter()
+            .enumerate()
+            .filter(|(position, _)| {
+                let kept = keep.peek() == Some(position);
+                if kept {
+                    keep.next();
+                }
+                kept
+            })
+            .map(|(_, sample)| sample)
+            .collect())
+    }
+
+    /// What to record about having selected `sample_ids`
+    pub fn selection(&self, sample_ids: Vec<Uuid>) -> SampleSelection {
+        SampleSelection {
+            seed: self.seed,
+            split: self.split.clone(),
+            strategy: self.config.strategy.clone(),
+            size: self.config.size.clone(),
+            stratify_by: self.config.stratify_by.clone(),
+            sample_ids,
+        }
+    }
+
+    /// How many of `available` samples to select
+    pub fn size_of(&self, available: usize) -> Result<usize> {
+        match self.config.size {
+            SampleSize::All => Ok(available),
+            SampleSize::Count(count) => Ok(count.min(available)),
+            SampleSize::Percentage(percent) if percent <= 100 => {
+                Ok((available * percent as usize + 50) / 100)
+            }
+            SampleSize::Percentage(percent) => Err(CoreError::Validation(format!(
+                "Sample percentage {} is over 100",
+                percent
+            ))),
+        }
+    }
+
+    /// Positions of `wanted` samples drawn from each stratum in proportion
+    /// to its size, by largest remainder
+    fn stratified(
+        &self,
+        samples: &[DatasetSample],
+        wanted: usize,
+        rng: &mut StdRng,
+    ) -> Result<Vec<usize>> {
+        let field = self.config.stratify_by.as_deref().ok_or_else(|| {
+            CoreError::Validation("Stratified sampling needs `stratify_by`".to_string())
+        })?;
+
+        // Ordered by stratum so allocation and draws don't depend on hashing
+        let mut strata: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (position, sample) in samples.iter().enumerate() {
+            let stratum = match sample_field(sample, field) {
+                Some(serde_json::Value::String(value)) => value.clone(),
+                Some(value) => value.to_string(),
+                None => {
+                    return Err(CoreError::Validation(format!(
+                        "Sample {} has no field '{}' to stratify by",
+                        sample.index, field
+                    )))
+                }
+            };
+            strata.entry(stratum).or_default().push(position);
+        }
+
+        let total = samples.len().max(1);
+        let mut quotas: Vec<(usize, usize)> = strata
+            .values()
+            .map(|members| {
+                let share = members.len() * wanted;
+                (share / total, share % total)
+            })
+            .collect();
+        let short = wanted - quotas.iter().map(|(quota, _)| quota).sum::<usize>();
+        let mut by_remainder: Vec<usize> = (0..quotas.len()).collect();
+        by_remainder.sort_by(|a, b| quotas[*b].1.cmp(&quotas[*a].1).then(a.cmp(b)));
+        for &stratum in by_remainder.iter().take(short) {
+            quotas[stratum].0 += 1;
+        }
+
+        let mut keep = Vec::with_capacity(wanted);
+        for (members, (quota, _)) in strata.values().zip(quotas) {
+            keep.extend(
+                rand::seq::index::sample(rng, members.len(), quota)
+                    .into_iter()
+                    .map(|i| members[i]),
+            );
+        }
+        Ok(keep)
+    }
+}
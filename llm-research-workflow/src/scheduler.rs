@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+/// What the engine does with the rest of a workflow once a step fails
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// Cancel running steps and start no new ones
+    #[default]
+    FailFast,
+    /// Keep running every step that does not depend on a failed one; the
+    /// workflow still fails once they finish
+    ContinueOnError,
+}
+
+/// Resources a step occupies while it runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceHints {
+    /// Concurrency slots, more than one for steps that are parallel themselves
+    pub slots: usize,
+    pub gpus: usize,
+    pub memory_mb: u64,
+}
+
+impl ResourceHints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_slots(mut self, slots: usize) -> Self {
+        self.slots = slots;
+        self
+    }
+
+    pub fn with_gpus(mut self, gpus: usize) -> Self {
+        self.gpus = gpus;
+        self
+    }
+
+    pub fn with_memory_mb(mut self, memory_mb: u64) -> Self {
+        self.memory_mb = memory_mb;
+        self
+    }
+
+    fn none() -> Self {
+        Self {
+            slots: 0,
+            gpus: 0,
+            memory_mb: 0,
+        }
+    }
+}
+
+impl Default for ResourceHints {
+    fn default() -> Self {
+        Self {
+            slots: 1,
+            gpus: 0,
+            memory_mb: 0,
+        }
+    }
+}
+
+/// Capacity the engine schedules ready steps into
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// Concurrency slots shared by running steps
+    pub max_concurrency: usize,
+    pub gpus: usize,
+    /// Memory budget across running steps, unlimited if `None`
+    pub memory_mb: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn new(max_concurrency: usize) -> Self {
+        Self {
+            max_concurrency,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_gpus(mut self, gpus: usize) -> Self {
+        self.gpus = gpus;
+        self
+    }
+
+    pub fn with_memory_mb(mut self, memory_mb: u64) -> Self {
+        self.memory_mb = Some(memory_mb);
+        self
+    }
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 4,
+            gpus: 0,
+            memory_mb: None,
+        }
+    }
+}
+
+/// Resources held by the steps currently running
+#[derive(Debug)]
+pub(crate) struct ResourceUsage {
+    limits: ResourceLimits,
+    in_use: ResourceHints,
+    running: usize,
+}
+
+impl ResourceUsage {
+    pub(crate) fn new(limits: ResourceLimits) -> Self {
+        Self {
+            limits,
+            in_use: ResourceHints::none(),
+            running: 0,
+        }
+    }
+
+    /// Whether a step needing `hints` fits next to the running steps. A step
+    /// that exceeds the limits on its own still runs, alone.
+    pub(crate) fn fits(&self, hints: &ResourceHints) -> bool {
+        if self.running == 0 {
+            return true;
+        }
+
+        let memory_fits = match self.limits.memory_mb {
+            Some(limit) => self.in_use.memory_mb + hints.memory_mb <= limit,
+            None => true,
+        };
+        self.in_use.slots + hints.slots <= self.limits.max_concurrency
+            && self.in_use.gpus + hints.gpus <= self.limits.gpus
+            && memory_fits
+    }
+
+    pub(crate) fn acquire(&mut self, hints: &ResourceHints) {
+        self.in_use.slots += hints.slots;
+        self.in_use.gpus += hints.gpus;
+        self.in_use.memory_mb += hints.memory_mb;
+        self.running += 1;
+    }
+
+    pub(crate) fn release(&mut self, hints: &ResourceHints) {
+        self.in_use.slots -= hints.slots;
+        self.in_use.gpus -= hints.gpus;
+        self.in_use.memory_mb -= hints.memory_mb;
+        self.running -= 1;
+    }
+}
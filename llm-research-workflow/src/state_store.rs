@@ -0,0 +1,226 @@
+use async_trait::async_trait;
+use llm_research_core::Result;
+use llm_research_storage::{WorkflowCheckpoint, WorkflowCheckpointRepository};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::engine::{WorkflowState, WorkflowStatus};
+use crate::json_file::{io_error, read_json, remove_file, write_json_atomic};
+
+/// What I/O errors for this store's files are reported as
+const STORE: &str = "Workflow state";
+
+/// Statuses of workflows that have not reached a terminal state
+const INCOMPLETE_STATUSES: [WorkflowStatus; 3] = [
+    WorkflowStatus::Pending,
+    WorkflowStatus::Running,
+    WorkflowStatus::Paused,
+];
+
+fn is_incomplete(status: &WorkflowStatus) -> bool {
+    !status.is_terminal()
+}
+
+/// Sorts `states` into the order `list_incomplete` returns them in
+fn oldest_first(mut states: Vec<WorkflowState>) -> Vec<WorkflowState> {
+    states.sort_by_key(|state| (state.workflow.started_at, state.workflow.id));
+    states
+}
+
+fn status_str(status: &WorkflowStatus) -> &'static str {
+    match status {
+        WorkflowStatus::Pending => "pending",
+        WorkflowStatus::Running => "running",
+        WorkflowStatus::Paused => "paused",
+        WorkflowStatus::Completed => "completed",
+        WorkflowStatus::Failed => "failed",
+        WorkflowStatus::Cancelled => "cancelled",
+        WorkflowStatus::Skipped => "skipped",
+    }
+}
+
+/// Durable storage for workflow checkpoints. The engine saves the full
+/// `WorkflowState` (step statuses and outputs) after every step so that an
+/// interrupted workflow can continue from its last completed step.
+#[async_trait]
+pub trait WorkflowStateStore: Send + Sync {
+    /// Insert or replace the checkpoint for `state.workflow.id`
+    async fn save(&self, state: &WorkflowState) -> Result<()>;
+
+    async fn load(&self, workflow_id: Uuid) -> Result<Option<WorkflowState>>;
+
+    /// Workflows that are pending, running or paused, ordered by
+    /// `started_at` with ones never started first, so that recovery resumes
+    /// them in the same order whatever the store
+    async fn list_incomplete(&self) -> Result<Vec<WorkflowState>>;
+
+    async fn delete(&self, workflow_id: Uuid) -> Result<()>;
+}
+
+/// Keeps checkpoints in memory; nothing survives the process
+#[derive(Debug, Default)]
+pub struct InMemoryWorkflowStateStore {
+    states: RwLock<HashMap<Uuid, WorkflowState>>,
+}
+
+impl InMemoryWorkflowStateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl WorkflowStateStore for InMemoryWorkflowStateStore {
+    async fn save(&self, state: &WorkflowState) -> Result<()> {
+        self.states
+            .write()
+            .await
+            .insert(state.workflow.id, state.clone());
+        Ok(())
+    }
+
+    async fn load(&self, workflow_id: Uuid) -> Result<Option<WorkflowState>> {
+        Ok(self.states.read().await.get(&workflow_id).cloned())
+    }
+
+    async fn list_incomplete(&self) -> Result<Vec<WorkflowState>> {
+        Ok(oldest_first(
+            self.states
+                .read()
+                .await
+                .values()
+                .filter(|state| is_incomplete(&state.workflow.status))
+                .cloned()
+                .collect(),
+        ))
+    }
+
+    async fn delete(&self, workflow_id: Uuid) -> Result<()> {
+        self.states.write().await.remove(&workflow_id);
+        Ok(())
+    }
+}
+
+/// Writes one `<workflow_id>.json` file per workflow under a directory.
+/// Files are written to a temporary path and renamed into place, so a crash
+/// mid-write leaves the previous checkpoint intact.
+#[derive(Debug, Clone)]
+pub struct FileWorkflowStateStore {
+    dir: PathBuf,
+}
+
+impl FileWorkflowStateStore {
+    /// Store checkpoints under `dir`, creating it if needed
+    pub async fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| io_error(STORE, &dir, e))?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, workflow_id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", workflow_id))
+    }
+}
+
+#[async_trait]
+impl WorkflowStateStore for FileWorkflowStateStore {
+    async fn save(&self, state: &WorkflowState) -> Result<()> {
+        write_json_atomic(STORE, &self.path(state.workflow.id), state).await
+    }
+
+    async fn load(&self, workflow_id: Uuid) -> Result<Option<WorkflowState>> {
+        read_json(STORE, &self.path(workflow_id)).await
+    }
+
+    async fn list_incomplete(&self) -> Result<Vec<WorkflowState>> {
+        let mut entries = tokio::fs::read_dir(&self.dir)
+            .await
+            .map_err(|e| io_error(STORE, &self.dir, e))?;
+
+        let mut states = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| io_error(STORE, &self.dir, e))?
+        {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(state) = read_json::<WorkflowState>(STORE, &path).await? {
+                if is_incomplete(&state.workflow.status) {
+                    states.push(state);
+                }
+            }
+        }
+
+        Ok(oldest_first(states))
+    }
+
+    async fn delete(&self, workflow_id: Uuid) -> Result<()> {
+        remove_file(STORE, &self.path(workflow_id)).await
+    }
+}
+
+/// Stores checkpoints in the `workflow_checkpoints` table
+pub struct PostgresWorkflowStateStore {
+    repository: WorkflowCheckpointRepository,
+}
+
+impl PostgresWorkflowStateStore {
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self {
+            repository: WorkflowCheckpointRepository::new(pool),
+        }
+    }
+}
+
+fn from_checkpoint(checkpoint: WorkflowCheckpoint) -> Result<WorkflowState> {
+    Ok(serde_json::from_value(checkpoint.state)?)
+}
+
+#[async_trait]
+impl WorkflowStateStore for PostgresWorkflowStateStore {
+    async fn save(&self, state: &WorkflowState) -> Result<()> {
+        let checkpoint = WorkflowCheckpoint {
+            workflow_id: state.workflow.id,
+            name: state.workflow.name.clone(),
+            status: status_str(&state.workflow.status).to_string(),
+            state: serde_json::to_value(state)?,
+            updated_at: chrono::Utc::now(),
+        };
+        self.repository.upsert(&checkpoint).await
+    }
+
+    async fn load(&self, workflow_id: Uuid) -> Result<Option<WorkflowState>> {
+        self.repository
+            .get(workflow_id)
+            .await?
+            .map(from_checkpoint)
+            .transpose()
+    }
+
+    async fn list_incomplete(&self) -> Result<Vec<WorkflowState>> {
+        let statuses: Vec<&str> = INCOMPLETE_STATUSES.iter().map(status_str).collect();
+        let states = self
+            .repository
+            .list_by_status(&statuses)
+            .await?
+            .into_iter()
+            .map(from_checkpoint)
+            .collect::<Result<_>>()?;
+        Ok(oldest_first(states))
+    }
+
+    async fn delete(&self, workflow_id: Uuid) -> Result<()> {
+        self.repository.delete(workflow_id).await
+    }
+}
@@ -0,0 +1,619 @@
+pub mod hyperband;
+pub mod space;
+pub mod tpe;
+
+pub use hyperband::*;
+pub use space::*;
+pub use tpe::*;
+
+use async_trait::async_trait;
+use futures::stream::{self, FuturesUnordered, StreamExt};
+use llm_research_core::{
+    CoreError, ExperimentId, ExperimentParameters, ExperimentRun, ParameterValue, Result, RunError,
+    RunId, RunMetrics, RunStatus, ScalarMetricPoint, ScalarMetricSeries, SearchStrategy, UserId,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::engine::{DefaultWorkflowEngine, Workflow, WorkflowEngine, WorkflowState};
+use crate::sampling::SampleSelection;
+use crate::template::resolve_config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Goal {
+    Maximize,
+    Minimize,
+}
+
+/// The metric a sweep optimizes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Objective {
+    pub metric: String,
+    pub goal: Goal,
+}
+
+impl Objective {
+    pub fn maximize(metric: impl Into<String>) -> Self {
+        Self {
+            metric: metric.into(),
+            goal: Goal::Maximize,
+        }
+    }
+
+    pub fn minimize(metric: impl Into<String>) -> Self {
+        Self {
+            metric: metric.into(),
+            goal: Goal::Minimize,
+        }
+    }
+
+    /// The metric's value in `metrics`: the last point of its scalar series
+    /// at or before `budget`, or else its custom metric
+    pub fn value(&self, metrics: &RunMetrics, budget: Option<u64>) -> Option<f64> {
+        let value = match metrics.scalars.iter().find(|s| s.name == self.metric) {
+            Some(series) => series
+                .points
+                .iter()
+                .filter(|p| p.step <= budget.unwrap_or(u64::MAX))
+                .max_by_key(|p| p.step)
+                .map(|p| p.value),
+            None => metrics.custom_metrics.get(&self.metric)?.as_f64(),
+        };
+        value.filter(|v| v.is_finite())
+    }
+
+    /// `value` oriented so that higher is better
+    fn score(&self, value: f64) -> f64 {
+        match self.goal {
+            Goal::Maximize => value,
+            Goal::Minimize => -value,
+        }
+    }
+}
+
+/// One configuration to evaluate
+#[derive(Debug, Clone)]
+pub struct Trial {
+    pub number: u32,
+    /// The trial's run, a child of the sweep's run
+    pub run_id: RunId,
+    /// Fixed and swept parameters
+    pub parameters: HashMap<String, ParameterValue>,
+    /// Resources to spend on the trial in Hyperband sweeps. A trial that
+    /// survives a round is run again with a larger budget; runners may resume
+    /// from where the smaller budget left off.
+    pub budget: Option<u64>,
+}
+
+/// Evaluates trials, e.g. by training or prompting a model with their
+/// parameters and measuring it
+#[async_trait]
+pub trait TrialRunner: Send + Sync {
+    async fn run_trial(&self, trial: &Trial) -> Result<RunMetrics>;
+
+    /// `run_trial`, also recording on the trial's run what it ran on, such as
+    /// the samples it used. The sweep runs every trial through this.
+    async fn run_trial_on(&self, trial: &Trial, _run: &mut ExperimentRun) -> Result<RunMetrics> {
+        self.run_trial(trial).await
+    }
+}
+
+/// A trial's run and how it did
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrialRecord {
+    pub run: ExperimentRun,
+    /// The budget of the trial's last evaluation
+    pub budget: Option<u64>,
+    /// The objective metric, if the trial completed
+    pub value: Option<f64>,
+    /// Stopped by Hyperband before reaching the full budget
+    pub early_stopped: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BestTrial {
+    pub run_id: RunId,
+    pub parameters: HashMap<String, ParameterValue>,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepReport {
+    /// The parent of every trial's run, which records the best configuration
+    pub sweep_run: ExperimentRun,
+    pub trials: Vec<TrialRecord>,
+    /// The best trial that ran to completion on the full budget
+    pub best: Option<BestTrial>,
+}
+
+/// A hyperparameter sweep over an experiment's search spaces, using its
+/// search strategy: every combination for grid search, `max_trials` draws for
+/// random search, a tree-structured Parzen estimator for Bayesian search and
+/// successive halving of randomly drawn trials for Hyperband.
+pub struct Sweep {
+    experiment_id: ExperimentId,
+    created_by: UserId,
+    name: String,
+    parameters: ExperimentParameters,
+    objective: Objective,
+    seed: Option<u64>,
+    tpe: TpeConfig,
+    hyperband: HyperbandConfig,
+    first_run_number: u32,
+}
+
+impl Sweep {
+    pub fn new(
+        experiment_id: ExperimentId,
+        created_by: UserId,
+        parameters: ExperimentParameters,
+        objective: Objective,
+    ) -> Self {
+        Self {
+            experiment_id,
+            created_by,
+            name: "sweep".to_string(),
+            parameters,
+            objective,
+            seed: None,
+            tpe: TpeConfig::default(),
+            hyperband: HyperbandConfig::default(),
+            first_run_number: 1,
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Seed for random, Bayesian and Hyperband sweeps; the same seed draws
+    /// the same trials
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_tpe(mut self, tpe: TpeConfig) -> Self {
+        self.tpe = tpe;
+        self
+    }
+
+    pub fn with_hyperband(mut self, hyperband: HyperbandConfig) -> Self {
+        self.hyperband = hyperband;
+        self
+    }
+
+    /// Run number of the sweep's own run; trials are numbered after it
+    pub fn with_first_run_number(mut self, run_number: u32) -> Self {
+        self.first_run_number = run_number;
+        self
+    }
+
+    pub async fn run(&self, runner: Arc<dyn TrialRunner>) -> Result<SweepReport> {
+        let space = ParameterSpace::new(&self.parameters.search_spaces)?;
+        let strategy = self
+            .parameters
+            .search_strategy
+            .clone()
+            .unwrap_or(SearchStrategy::Grid);
+        let seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let mut sweep_run = ExperimentRun::new(
+            self.experiment_id,
+            self.first_run_number,
+            self.name.clone(),
+            self.created_by,
+        )
+        .with_parameters(self.parameters.fixed.clone());
+        sweep_run.metadata.insert(
+            "search_strategy".to_string(),
+            serde_json::to_value(&strategy)?,
+        );
+        sweep_run
+            .metadata
+            .insert("seed".to_string(), serde_json::json!(seed));
+        sweep_run.start();
+
+        let mut sweep = SweepState {
+            sweep: self,
+            runner: runner.as_ref(),
+            sweep_run_id: sweep_run.id,
+            trials: Vec::new(),
+        };
+        match strategy {
+            SearchStrategy::Grid => {
+                let mut grid = space.grid()?;
+                if let Some(max_trials) = self.parameters.max_trials {
+                    grid.truncate(max_trials as usize);
+                }
+                sweep.run_batch(grid, None).await;
+            }
+            SearchStrategy::Random => {
+                let trials = (0..self.max_trials("random")?)
+                    .map(|_| space.sample(&mut rng))
+                    .collect();
+                sweep.run_batch(trials, None).await;
+            }
+            SearchStrategy::Bayesian => {
+                let max_trials = self.max_trials("Bayesian")?;
+                sweep.run_bayesian(&space, max_trials, &mut rng).await;
+            }
+            SearchStrategy::Hyperband => {
+                self.hyperband.validate()?;
+                sweep.run_hyperband(&space, &mut rng).await;
+            }
+            SearchStrategy::Custom(name) => {
+                return Err(CoreError::Validation(format!(
+                    "Unsupported search strategy '{}'",
+                    name
+                )))
+            }
+        }
+        let trials = sweep.trials;
+
+        let best = trials
+            .iter()
+            .filter(|t| !t.early_stopped && t.run.status == RunStatus::Completed)
+            .filter_map(|t| Some((t, t.value?)))
+            .max_by(|a, b| {
+                self.objective
+                    .score(a.1)
+                    .total_cmp(&self.objective.score(b.1))
+            })
+            .map(|(t, value)| BestTrial {
+                run_id: t.run.id,
+                parameters: t.run.parameters.clone(),
+                value,
+            });
+
+        match &best {
+            Some(best) => {
+                sweep_run
+                    .metrics
+                    .custom_metrics
+                    .insert(self.objective.metric.clone(), serde_json::json!(best.value));
+                sweep_run.metadata.insert(
+                    "best_run_id".to_string(),
+                    serde_json::to_value(best.run_id)?,
+                );
+                sweep_run.metadata.insert(
+                    "best_parameters".to_string(),
+                    serde_json::to_value(&best.parameters)?,
+                );
+                sweep_run.complete();
+            }
+            None => sweep_run.fail(run_error(&CoreError::InvalidState(format!(
+                "No trial of sweep '{}' reported {}",
+                self.name, self.objective.metric
+            )))),
+        }
+        tracing::info!(
+            "Sweep {} finished {} trials, best {}: {:?}",
+            self.name,
+            trials.len(),
+            self.objective.metric,
+            best.as_ref().map(|b| b.value)
+        );
+
+        Ok(SweepReport {
+            sweep_run,
+            trials,
+            best,
+        })
+    }
+
+    fn max_trials(&self, strategy: &str) -> Result<u32> {
+        self.parameters
+            .max_trials
+            .ok_or_else(|| CoreError::Validation(format!("A {} sweep needs max_trials", strategy)))
+    }
+
+    fn concurrency(&self) -> usize {
+        self.parameters.concurrent_trials.unwrap_or(1).max(1) as usize
+    }
+}
+
+/// The trials of one `Sweep::run`
+struct SweepState<'a> {
+    sweep: &'a Sweep,
+    runner: &'a dyn TrialRunner,
+    sweep_run_id: RunId,
+    trials: Vec<TrialRecord>,
+}
+
+impl SweepState<'_> {
+    /// A pending record for the next trial
+    fn record(&self, index: usize, swept: TrialParameters) -> TrialRecord {
+        let number = self.sweep.first_run_number + 1 + index as u32;
+        let mut parameters = self.sweep.parameters.fixed.clone();
+        parameters.extend(swept);
+        let run = ExperimentRun::new(
+            self.sweep.experiment_id,
+            number,
+            format!(
+                "{}-trial-{}",
+                self.sweep.name,
+                number - self.sweep.first_run_number
+            ),
+            self.sweep.created_by,
+        )
+        .with_parent(self.sweep_run_id)
+        .with_parameters(parameters);
+        TrialRecord {
+            run,
+            budget: None,
+            value: None,
+            early_stopped: false,
+        }
+    }
+
+    /// Run a trial (again) at `budget` and record how it did
+    async fn evaluate(&self, mut record: TrialRecord, budget: Option<u64>) -> TrialRecord {
+        let trial = Trial {
+            number: record.run.run_number,
+            run_id: record.run.id,
+            parameters: record.run.parameters.clone(),
+            budget,
+        };
+        if record.run.started_at.is_none() {
+            record.run.start();
+        } else {
+            record.run.status = RunStatus::Running;
+        }
+        record.budget = budget;
+
+        let result = self.runner.run_trial_on(&trial, &mut record.run).await;
+        record.value = None;
+        match result {
+            Ok(metrics) => {
+                record.value = self.sweep.objective.value(&metrics, budget);
+                record.run.metrics = metrics;
+                match record.value {
+                    Some(_) => record.run.complete(),
+                    None => record.run.fail(run_error(&CoreError::Validation(format!(
+                        "Trial did not report {}",
+                        self.sweep.objective.metric
+                    )))),
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Trial {} failed: {}", trial.number, e);
+                record.run.fail(run_error(&e));
+            }
+        }
+        record
+    }
+
+    /// Evaluate `records` at `budget`, `concurrent_trials` at a time
+    async fn evaluate_all(
+        &self,
+        records: Vec<TrialRecord>,
+        budget: Option<u64>,
+    ) -> Vec<TrialRecord> {
+        stream::iter(records)
+            .map(|record| self.evaluate(record, budget))
+            .buffered(self.sweep.concurrency())
+            .collect()
+            .await
+    }
+
+    async fn run_batch(&mut self, trials: Vec<TrialParameters>, budget: Option<u64>) {
+        let start = self.trials.len();
+        let records = trials
+            .into_iter()
+            .enumerate()
+            .map(|(i, swept)| self.record(start + i, swept))
+            .collect();
+        let records = self.evaluate_all(records, budget).await;
+        self.trials.extend(records);
+    }
+
+    /// Suggest each trial from those finished so far, keeping up to
+    /// `concurrent_trials` in flight
+    async fn run_bayesian(&mut self, space: &ParameterSpace, max_trials: u32, rng: &mut StdRng) {
+        let mut sampler = TpeSampler::new(self.sweep.tpe.clone());
+        let mut in_flight = FuturesUnordered::new();
+        let mut finished = Vec::new();
+        let mut started = 0;
+
+        loop {
+            while started < max_trials as usize && in_flight.len() < self.sweep.concurrency() {
+                let record = self.record(started, sampler.suggest(space, rng));
+                in_flight.push(self.evaluate(record, None));
+                started += 1;
+            }
+            let Some(record) = in_flight.next().await else {
+                break;
+            };
+            if let Some(value) = record.value {
+                let swept = space
+                    .parameters()
+                    .iter()
+                    .filter_map(|(name, _)| {
+                        Some((name.clone(), record.run.parameters.get(name)?.clone()))
+                    })
+                    .collect();
+                sampler.observe(swept, self.sweep.objective.score(value));
+            }
+            finished.push(record);
+        }
+        drop(in_flight);
+
+        finished.sort_by_key(|r| r.run.run_number);
+        self.trials.extend(finished);
+    }
+
+    /// Successive halving in each bracket: run every trial on the rung's
+    /// budget and promote the best `1/eta` of them to the next rung. Trials
+    /// left behind are early-stopped.
+    async fn run_hyperband(&mut self, space: &ParameterSpace, rng: &mut StdRng) {
+        let max_trials = self.sweep.parameters.max_trials.map(|n| n as usize);
+        for rungs in self.sweep.hyperband.brackets() {
+            let remaining = max_trials.map_or(usize::MAX, |n| n.saturating_sub(self.trials.len()));
+            let count = rungs[0].trials.min(remaining);
+            if count == 0 {
+                break;
+            }
+
+            let start = self.trials.len();
+            let mut survivors: Vec<TrialRecord> = (0..count)
+                .map(|i| self.record(start + i, space.sample(rng)))
+                .collect();
+            let mut bracket = Vec::new();
+            for (i, rung) in rungs.iter().enumerate() {
+                let evaluated = self.evaluate_all(survivors, Some(rung.budget)).await;
+                let (mut completed, failed): (Vec<_>, Vec<_>) =
+                    evaluated.into_iter().partition(|r| r.value.is_some());
+                bracket.extend(failed);
+
+                let promoted = rungs.get(i + 1).map_or(completed.len(), |next| next.trials);
+                let objective = &self.sweep.objective;
+                completed.sort_by(|a, b| {
+                    let score = |r: &TrialRecord| objective.score(r.value.unwrap_or_default());
+                    score(b).total_cmp(&score(a))
+                });
+                let stopped = completed.split_off(promoted.min(completed.len()));
+                bracket.extend(stopped.into_iter().map(|mut record| {
+                    record.early_stopped = true;
+                    record
+                }));
+                survivors = completed;
+            }
+            bracket.extend(survivors);
+            bracket.sort_by_key(|r| r.run.run_number);
+            self.trials.extend(bracket);
+        }
+    }
+}
+
+fn run_error(error: &CoreError) -> RunError {
+    RunError {
+        error_type: "trial_failed".to_string(),
+        message: error.to_string(),
+        stacktrace: None,
+        occurred_at: chrono::Utc::now(),
+        is_retryable: error.is_retryable(),
+        metadata: HashMap::new(),
+    }
+}
+
+/// Runs each trial as a fresh copy of a workflow whose `${params...}` are the
+/// workflow's own parameters overlaid with the trial's, plus `budget` in
+/// Hyperband sweeps, and reads the trial's metrics from step outputs. The
+/// samples its data loading steps select are recorded on the trial's run.
+pub struct WorkflowTrialRunner {
+    engine: Arc<DefaultWorkflowEngine>,
+    workflow: Workflow,
+    /// Metric names and the `${steps...}` references they are read from
+    metrics: Vec<(String, String)>,
+}
+
+impl WorkflowTrialRunner {
+    pub fn new(engine: Arc<DefaultWorkflowEngine>, workflow: Workflow) -> Self {
+        Self {
+            engine,
+            workflow,
+            metrics: Vec::new(),
+        }
+    }
+
+    /// Record `reference`, e.g. `${steps.evaluate.output.accuracy}`, as the
+    /// metric `name`. A number is recorded at the trial's budget, and a list
+    /// of numbers as the metric's value after each of steps 1, 2, ...
+    pub fn with_metric(mut self, name: impl Into<String>, reference: impl Into<String>) -> Self {
+        self.metrics.push((name.into(), reference.into()));
+        self
+    }
+
+    /// The selection made by the first data loading step that sampled
+    fn selection(state: &WorkflowState) -> Result<Option<SampleSelection>> {
+        for step in &state.workflow.steps {
+            if step.task_type != "data_loading" {
+                continue;
+            }
+            let Some(output) = state.step_outputs.get(&step.id) else {
+                continue;
+            };
+            if let Some(selection) = SampleSelection::from_output(output)? {
+                return Ok(Some(selection));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Run `trial`'s workflow and read its metrics from the outputs
+    async fn execute(&self, trial: &Trial) -> Result<(WorkflowState, RunMetrics)> {
+        let mut params = match &self.workflow.parameters {
+            serde_json::Value::Object(map) => map.clone(),
+            _ => serde_json::Map::new(),
+        };
+        for (name, value) in &trial.parameters {
+            params.insert(name.clone(), serde_json::to_value(value)?);
+        }
+        if let Some(budget) = trial.budget {
+            params.insert("budget".to_string(), serde_json::json!(budget));
+        }
+        let params = serde_json::Value::Object(params);
+
+        let workflow = self.workflow.instantiate().with_parameters(params.clone());
+        let state = self.engine.execute(&workflow).await?;
+        let outputs = state.outputs_by_name();
+
+        let mut metrics = RunMetrics::default();
+        for (name, reference) in &self.metrics {
+            let value = resolve_config(&serde_json::json!(reference), &outputs, &params)?;
+            let values: Vec<(u64, f64)> = match &value {
+                serde_json::Value::Array(items) => items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| item.as_f64().map(|v| (i as u64 + 1, v)))
+                    .collect::<Option<_>>(),
+                other => other.as_f64().map(|v| vec![(trial.budget.unwrap_or(0), v)]),
+            }
+            .ok_or_else(|| {
+                CoreError::Validation(format!(
+                    "Metric '{}' is {}, not a number or list of numbers",
+                    name, value
+                ))
+            })?;
+
+            let now = chrono::Utc::now();
+            metrics.scalars.push(ScalarMetricSeries {
+                name: name.clone(),
+                points: values
+                    .into_iter()
+                    .map(|(step, value)| ScalarMetricPoint {
+                        step,
+                        value,
+                        timestamp: now,
+                    })
+                    .collect(),
+                unit: None,
+                tags: HashMap::new(),
+            });
+        }
+        Ok((state, metrics))
+    }
+}
+
+#[async_trait]
+impl TrialRunner for WorkflowTrialRunner {
+    async fn run_trial(&self, trial: &Trial) -> Result<RunMetrics> {
+        let (_, metrics) = self.execute(trial).await?;
+        Ok(metrics)
+    }
+
+    async fn run_trial_on(&self, trial: &Trial, run: &mut ExperimentRun) -> Result<RunMetrics> {
+        let (state, metrics) = self.execute(trial).await?;
+        if let Some(selection) = Self::selection(&state)? {
+            selection.record(run)?;
+        }
+        Ok(metrics)
+    }
+}
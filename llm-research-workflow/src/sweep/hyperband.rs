@@ -0,0 +1,79 @@
+use llm_research_core::{CoreError, Result};
+use serde::{Deserialize, Serialize};
+
+/// Settings of Hyperband sweeps. Trials are given a budget, in whatever unit
+/// the trial runner counts resources (epochs, samples, ...), and only the
+/// best `1/eta` of the trials at each budget go on to the next.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HyperbandConfig {
+    pub min_budget: u64,
+    pub max_budget: u64,
+    pub eta: u64,
+}
+
+impl Default for HyperbandConfig {
+    fn default() -> Self {
+        Self {
+            min_budget: 1,
+            max_budget: 81,
+            eta: 3,
+        }
+    }
+}
+
+/// One round of successive halving: `trials` trials run at `budget`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rung {
+    pub trials: usize,
+    pub budget: u64,
+}
+
+impl HyperbandConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.eta < 2 {
+            return Err(CoreError::Validation(format!(
+                "Hyperband eta must be at least 2, got {}",
+                self.eta
+            )));
+        }
+        if self.min_budget == 0 || self.min_budget > self.max_budget {
+            return Err(CoreError::Validation(format!(
+                "Hyperband budgets must satisfy 0 < min_budget <= max_budget, got {} and {}",
+                self.min_budget, self.max_budget
+            )));
+        }
+        Ok(())
+    }
+
+    /// The brackets of successive halving Hyperband runs, most exploratory
+    /// first: each starts more trials on a smaller budget than the next
+    pub fn brackets(&self) -> Vec<Vec<Rung>> {
+        let eta = self.eta as f64;
+        // A budget too large for a u64 is past any `max_budget`
+        let mut s_max = 0;
+        while self
+            .eta
+            .checked_pow(s_max + 1)
+            .and_then(|scale| self.min_budget.checked_mul(scale))
+            .is_some_and(|budget| budget <= self.max_budget)
+        {
+            s_max += 1;
+        }
+
+        (0..=s_max)
+            .rev()
+            .map(|s| {
+                let trials = ((s_max + 1) as f64 / (s + 1) as f64 * eta.powi(s as i32)).ceil();
+                let budget = self.max_budget as f64 / eta.powi(s as i32);
+                (0..=s)
+                    .map(|i| Rung {
+                        trials: ((trials / eta.powi(i as i32)).floor() as usize).max(1),
+                        budget: ((budget * eta.powi(i as i32)).round() as u64)
+                            .clamp(self.min_budget, self.max_budget),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
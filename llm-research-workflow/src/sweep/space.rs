@@ -0,0 +1,305 @@
+use llm_research_core::{CoreError, ParameterValue, Result, SearchSpace};
+use rand::Rng;
+use std::collections::HashMap;
+
+/// A trial's values for the swept parameters, by parameter name
+pub type TrialParameters = HashMap<String, ParameterValue>;
+
+/// The values one swept parameter can take, read from a `SearchSpace`.
+/// `values` lists the choices when `distribution` is unset or `choice`, and
+/// the distribution's two bounds (or mean and standard deviation) otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Domain {
+    Choice(Vec<ParameterValue>),
+    Uniform { low: f64, high: f64 },
+    LogUniform { low: f64, high: f64 },
+    IntUniform { low: i64, high: i64 },
+    Normal { mean: f64, std_dev: f64 },
+}
+
+impl Domain {
+    pub fn from_space(space: &SearchSpace) -> Result<Self> {
+        let invalid = |reason: String| {
+            CoreError::Validation(format!(
+                "Search space '{}': {}",
+                space.parameter_name, reason
+            ))
+        };
+        let distribution = space.distribution.as_deref().unwrap_or("choice");
+        if distribution == "choice" || distribution == "categorical" {
+            if space.values.is_empty() {
+                return Err(invalid("no values to choose from".to_string()));
+            }
+            return Ok(Self::Choice(space.values.clone()));
+        }
+
+        let [first, second] = space.values.as_slice() else {
+            return Err(invalid(format!(
+                "a {} distribution takes 2 values, got {}",
+                distribution,
+                space.values.len()
+            )));
+        };
+        let (Some(a), Some(b)) = (as_f64(first), as_f64(second)) else {
+            return Err(invalid(format!(
+                "a {} distribution takes numeric values",
+                distribution
+            )));
+        };
+        let bounded = |low: f64, high: f64| {
+            if low < high {
+                Ok(())
+            } else {
+                Err(invalid(format!(
+                    "lower bound {} is not below {}",
+                    low, high
+                )))
+            }
+        };
+
+        match distribution {
+            "uniform" => bounded(a, b).map(|_| Self::Uniform { low: a, high: b }),
+            "log-uniform" | "log_uniform" | "loguniform" => {
+                if a <= 0.0 {
+                    return Err(invalid("log-uniform bounds must be positive".to_string()));
+                }
+                bounded(a, b).map(|_| Self::LogUniform { low: a, high: b })
+            }
+            "int-uniform" | "int_uniform" | "randint" => {
+                let (ParameterValue::Integer(low), ParameterValue::Integer(high)) = (first, second)
+                else {
+                    return Err(invalid(
+                        "int-uniform bounds must be integers".to_string(),
+                    ));
+                };
+                bounded(a, b).map(|_| Self::IntUniform {
+                    low: *low,
+                    high: *high,
+                })
+            }
+            "normal" => {
+                if b <= 0.0 {
+                    return Err(invalid(
+                        "normal standard deviation must be positive".to_string(),
+                    ));
+                }
+                Ok(Self::Normal {
+                    mean: a,
+                    std_dev: b,
+                })
+            }
+            other => Err(invalid(format!(
+                "unknown distribution '{}' (expected choice, uniform, log-uniform, int-uniform or normal)",
+                other
+            ))),
+        }
+    }
+
+    /// Every value, for a grid search; `None` for continuous distributions
+    pub fn values(&self) -> Option<Vec<ParameterValue>> {
+        match self {
+            Self::Choice(values) => Some(values.clone()),
+            Self::IntUniform { low, high } => {
+                Some((*low..=*high).map(ParameterValue::Integer).collect())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> ParameterValue {
+        match self {
+            Self::Choice(values) => values[rng.gen_range(0..values.len())].clone(),
+            Self::IntUniform { low, high } => ParameterValue::Integer(rng.gen_range(*low..=*high)),
+            _ => self.value_at(rng.gen::<f64>()),
+        }
+    }
+
+    /// The value at `u` in `[0, 1]` along the domain, the inverse of
+    /// `position`. Choices are indexed in order.
+    pub fn value_at(&self, u: f64) -> ParameterValue {
+        let u = u.clamp(0.0, 1.0);
+        match self {
+            Self::Choice(values) => {
+                let index = ((u * values.len() as f64) as usize).min(values.len() - 1);
+                values[index].clone()
+            }
+            Self::Uniform { low, high } => ParameterValue::Float(low + u * (high - low)),
+            Self::LogUniform { low, high } => {
+                ParameterValue::Float((low.ln() + u * (high.ln() - low.ln())).exp())
+            }
+            Self::IntUniform { low, high } => {
+                let span = (high - low + 1) as f64;
+                ParameterValue::Integer((*low + (u * span) as i64).min(*high))
+            }
+            Self::Normal { mean, std_dev } => {
+                ParameterValue::Float(mean + std_dev * inverse_normal_cdf(u))
+            }
+        }
+    }
+
+    /// Where `value` lies along the domain, in `[0, 1]`; `None` if it is
+    /// not a value of this domain
+    pub fn position(&self, value: &ParameterValue) -> Option<f64> {
+        match self {
+            Self::Choice(values) => {
+                let index = values.iter().position(|v| v == value)?;
+                Some((index as f64 + 0.5) / values.len() as f64)
+            }
+            Self::Uniform { low, high } => Some((as_f64(value)? - low) / (high - low)),
+            Self::LogUniform { low, high } => {
+                Some((as_f64(value)?.ln() - low.ln()) / (high.ln() - low.ln()))
+            }
+            Self::IntUniform { low, high } => {
+                let span = (high - low + 1) as f64;
+                Some((as_f64(value)? - *low as f64 + 0.5) / span)
+            }
+            Self::Normal { mean, std_dev } => Some(normal_cdf((as_f64(value)? - mean) / std_dev)),
+        }
+        .map(|u| u.clamp(0.0, 1.0))
+    }
+
+    pub fn is_categorical(&self) -> bool {
+        matches!(self, Self::Choice(_))
+    }
+}
+
+fn as_f64(value: &ParameterValue) -> Option<f64> {
+    match value {
+        ParameterValue::Integer(i) => Some(*i as f64),
+        ParameterValue::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / std::f64::consts::SQRT_2))
+}
+
+/// Abramowitz and Stegun 7.1.26; accurate to about 1e-7
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    if x < 0.0 {
+        -y
+    } else {
+        y
+    }
+}
+
+/// Acklam's rational approximation, clamped away from the infinite tails
+fn inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    let p = p.clamp(1e-9, 1.0 - 1e-9);
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p < 0.02425 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// The swept parameters of an experiment and their domains
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterSpace {
+    parameters: Vec<(String, Domain)>,
+}
+
+impl ParameterSpace {
+    pub fn new(spaces: &[SearchSpace]) -> Result<Self> {
+        let mut parameters: Vec<(String, Domain)> = Vec::new();
+        for space in spaces {
+            if parameters
+                .iter()
+                .any(|(name, _)| name == &space.parameter_name)
+            {
+                return Err(CoreError::Validation(format!(
+                    "Parameter '{}' has more than one search space",
+                    space.parameter_name
+                )));
+            }
+            parameters.push((space.parameter_name.clone(), Domain::from_space(space)?));
+        }
+        Ok(Self { parameters })
+    }
+
+    pub fn parameters(&self) -> &[(String, Domain)] {
+        &self.parameters
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parameters.is_empty()
+    }
+
+    /// Every combination of the parameters' values, the first parameter
+    /// varying slowest
+    pub fn grid(&self) -> Result<Vec<TrialParameters>> {
+        let mut grid = vec![TrialParameters::new()];
+        for (name, domain) in &self.parameters {
+            let values = domain.values().ok_or_else(|| {
+                CoreError::Validation(format!(
+                    "Parameter '{}' has a continuous distribution, which a grid search can't enumerate",
+                    name
+                ))
+            })?;
+            grid = grid
+                .into_iter()
+                .flat_map(|trial| {
+                    values.iter().map(move |value| {
+                        let mut trial = trial.clone();
+                        trial.insert(name.clone(), value.clone());
+                        trial
+                    })
+                })
+                .collect();
+        }
+        Ok(grid)
+    }
+
+    /// Draw each parameter independently from its domain
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> TrialParameters {
+        self.parameters
+            .iter()
+            .map(|(name, domain)| (name.clone(), domain.sample(rng)))
+            .collect()
+    }
+}
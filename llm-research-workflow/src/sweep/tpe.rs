@@ -0,0 +1,186 @@
+use llm_research_core::ParameterValue;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::space::{Domain, ParameterSpace, TrialParameters};
+
+/// Settings of the tree-structured Parzen estimator behind Bayesian sweeps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TpeConfig {
+    /// Trials drawn at random before the estimator takes over
+    pub startup_trials: usize,
+    /// Fraction of the trials so far counted as good
+    pub gamma: f64,
+    /// Values drawn from the good density per parameter, of which the one
+    /// most likely good rather than bad is suggested
+    pub candidates: usize,
+}
+
+impl Default for TpeConfig {
+    fn default() -> Self {
+        Self {
+            startup_trials: 10,
+            gamma: 0.25,
+            candidates: 24,
+        }
+    }
+}
+
+/// Suggests trials by modelling the density of parameter values among good
+/// trials, l(x), and among the rest, g(x), and picking values where l/g is
+/// highest. Each parameter is modelled on its own, continuous ones as a
+/// Gaussian mixture over their position in the domain plus a uniform prior.
+pub struct TpeSampler {
+    config: TpeConfig,
+    /// Parameters of finished trials with their scores, higher being better
+    observations: Vec<(TrialParameters, f64)>,
+}
+
+impl TpeSampler {
+    pub fn new(config: TpeConfig) -> Self {
+        Self {
+            config,
+            observations: Vec::new(),
+        }
+    }
+
+    pub fn observe(&mut self, parameters: TrialParameters, score: f64) {
+        self.observations.push((parameters, score));
+    }
+
+    pub fn suggest<R: Rng>(&self, space: &ParameterSpace, rng: &mut R) -> TrialParameters {
+        if self.observations.len() < self.config.startup_trials.max(2) {
+            return space.sample(rng);
+        }
+
+        let mut ranked: Vec<&(TrialParameters, f64)> = self.observations.iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let n_good =
+            ((self.config.gamma * ranked.len() as f64).ceil() as usize).clamp(1, ranked.len() - 1);
+        let (good, bad) = ranked.split_at(n_good);
+
+        space
+            .parameters()
+            .iter()
+            .map(|(name, domain)| {
+                let values = |trials: &[&(TrialParameters, f64)]| -> Vec<ParameterValue> {
+                    trials
+                        .iter()
+                        .filter_map(|(parameters, _)| parameters.get(name).cloned())
+                        .collect()
+                };
+                let value = match domain {
+                    Domain::Choice(choices) => {
+                        Self::suggest_choice(choices, &values(good), &values(bad))
+                    }
+                    _ => self.suggest_numeric(domain, &values(good), &values(bad), rng),
+                };
+                (name.clone(), value)
+            })
+            .collect()
+    }
+
+    /// The choice with the highest ratio of (smoothed) good to bad frequency
+    fn suggest_choice(
+        choices: &[ParameterValue],
+        good: &[ParameterValue],
+        bad: &[ParameterValue],
+    ) -> ParameterValue {
+        let frequency = |values: &[ParameterValue], choice: &ParameterValue| {
+            (values.iter().filter(|v| *v == choice).count() as f64 + 1.0)
+                / (values.len() + choices.len()) as f64
+        };
+        choices
+            .iter()
+            .map(|choice| (choice, frequency(good, choice) / frequency(bad, choice)))
+            .fold(
+                None,
+                |best: Option<(&ParameterValue, f64)>, (choice, ratio)| match best {
+                    Some((_, best_ratio)) if best_ratio >= ratio => best,
+                    _ => Some((choice, ratio)),
+                },
+            )
+            .map(|(choice, _)| choice.clone())
+            .unwrap_or_else(|| choices[0].clone())
+    }
+
+    fn suggest_numeric<R: Rng>(
+        &self,
+        domain: &Domain,
+        good: &[ParameterValue],
+        bad: &[ParameterValue],
+        rng: &mut R,
+    ) -> ParameterValue {
+        let good = Parzen::new(good.iter().filter_map(|v| domain.position(v)).collect());
+        let bad = Parzen::new(bad.iter().filter_map(|v| domain.position(v)).collect());
+
+        let best = (0..self.config.candidates.max(1))
+            .map(|_| good.sample(rng))
+            .map(|x| (x, good.density(x) / bad.density(x)))
+            .fold((0.5, f64::NEG_INFINITY), |best, candidate| {
+                if candidate.1 > best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            });
+        domain.value_at(best.0)
+    }
+}
+
+/// Adaptive Parzen estimator over `[0, 1]`, as in the original TPE: a
+/// Gaussian on each observation, as wide as the gap to its farthest
+/// neighbour, plus a wide prior Gaussian centred on the domain
+struct Parzen {
+    /// Centre and standard deviation of each component
+    components: Vec<(f64, f64)>,
+}
+
+impl Parzen {
+    fn new(mut centers: Vec<f64>) -> Self {
+        centers.sort_by(f64::total_cmp);
+        // Narrow enough to home in on an optimum, never so narrow that it
+        // stops exploring around it
+        let min_width = 1.0 / (centers.len() + 1).min(100) as f64;
+
+        let mut components: Vec<(f64, f64)> = centers
+            .iter()
+            .enumerate()
+            .map(|(i, &center)| {
+                let left = center - if i == 0 { 0.0 } else { centers[i - 1] };
+                let right = centers.get(i + 1).copied().unwrap_or(1.0) - center;
+                (center, left.max(right).clamp(min_width, 1.0))
+            })
+            .collect();
+        components.push((0.5, 1.0));
+        Self { components }
+    }
+
+    fn density(&self, x: f64) -> f64 {
+        let total: f64 = self
+            .components
+            .iter()
+            .map(|&(center, width)| {
+                let z = (x - center) / width;
+                (-0.5 * z * z).exp() / (width * (2.0 * std::f64::consts::PI).sqrt())
+            })
+            .sum();
+        total / self.components.len() as f64
+    }
+
+    fn sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        let (center, width) = self.components[rng.gen_range(0..self.components.len())];
+        // Redraw values outside the domain, giving up after a few tries
+        let mut x = center;
+        for _ in 0..8 {
+            // Box-Muller
+            let (u1, u2): (f64, f64) = (rng.gen::<f64>().max(f64::MIN_POSITIVE), rng.gen());
+            x = center + width * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+            if (0.0..=1.0).contains(&x) {
+                break;
+            }
+        }
+        x.clamp(0.0, 1.0)
+    }
+}
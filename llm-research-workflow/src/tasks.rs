@@ -1,21 +1,73 @@
 pub mod data_loading;
+pub mod prompt_rendering;
 pub mod inference;
 pub mod evaluation;
+pub mod llm_judge;
 pub mod reporting;
+pub mod aggregation;
+pub mod reduce;
 
 pub use data_loading::*;
+pub use prompt_rendering::*;
 pub use inference::*;
 pub use evaluation::*;
+pub use llm_judge::*;
 pub use reporting::*;
+pub use aggregation::*;
+pub use reduce::*;
 
 use async_trait::async_trait;
-use llm_research_core::Result;
+use llm_research_core::{CoreError, ReproducibilitySettings, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::control::TaskControl;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskContext {
     pub experiment_id: uuid::Uuid,
     pub config: serde_json::Value,
+    /// Outputs of upstream steps, keyed by step name
+    #[serde(default)]
+    pub inputs: HashMap<String, serde_json::Value>,
+    /// The experiment's reproducibility settings, such as the seed that
+    /// tasks selecting at random draw from
+    #[serde(default)]
+    pub reproducibility: ReproducibilitySettings,
+    /// Cancellation and pause signals from whoever is running the task
+    #[serde(skip)]
+    pub control: TaskControl,
+}
+
+impl TaskContext {
+    pub fn new(experiment_id: uuid::Uuid, config: serde_json::Value) -> Self {
+        Self {
+            experiment_id,
+            config,
+            inputs: HashMap::new(),
+            reproducibility: ReproducibilitySettings::default(),
+            control: TaskControl::new(),
+        }
+    }
+
+    pub fn with_inputs(mut self, inputs: HashMap<String, serde_json::Value>) -> Self {
+        self.inputs = inputs;
+        self
+    }
+
+    pub fn with_reproducibility(mut self, reproducibility: ReproducibilitySettings) -> Self {
+        self.reproducibility = reproducibility;
+        self
+    }
+
+    pub fn with_control(mut self, control: TaskControl) -> Self {
+        self.control = control;
+        self
+    }
+
+    pub fn input(&self, step_name: &str) -> Option<&serde_json::Value> {
+        self.inputs.get(step_name)
+    }
 }
 
 #[async_trait]
@@ -29,6 +81,13 @@ pub struct TaskResult {
     pub success: bool,
     pub output: serde_json::Value,
     pub error: Option<String>,
+    /// Whether a failed task may succeed if run again
+    #[serde(default = "default_retryable")]
+    pub retryable: bool,
+}
+
+fn default_retryable() -> bool {
+    true
 }
 
 impl TaskResult {
@@ -37,6 +96,7 @@ impl TaskResult {
             success: true,
             output,
             error: None,
+            retryable: true,
         }
     }
 
@@ -45,6 +105,23 @@ impl TaskResult {
             success: false,
             output: serde_json::Value::Null,
             error: Some(error),
+            retryable: true,
+        }
+    }
+
+    /// A failure that running the task again won't fix, e.g. invalid input
+    pub fn permanent_failure(error: String) -> Self {
+        Self {
+            retryable: false,
+            ..Self::failure(error)
+        }
+    }
+
+    /// The failure a task's `error` amounts to, retryable if the error is
+    pub fn from_error(error: &CoreError) -> Self {
+        Self {
+            retryable: error.is_retryable(),
+            ..Self::failure(error.to_string())
         }
     }
 }
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use llm_research_core::Result;
+use llm_research_metrics::StreamingAggregator;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+use super::{Task, TaskContext, TaskResult};
+use crate::template::{OutputSchema, ValueType};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AggregationConfig {
+    pub metrics: Vec<String>,
+}
+
+impl Default for AggregationConfig {
+    fn default() -> Self {
+        Self {
+            metrics: vec!["accuracy".to_string(), "bleu".to_string(), "rouge".to_string()],
+        }
+    }
+}
+
+/// Merges the streaming aggregates reported by upstream evaluation steps
+pub struct AggregationTask {
+    config: AggregationConfig,
+}
+
+impl AggregationTask {
+    pub fn new(config: AggregationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Fields of the output this task produces
+    pub fn output_schema() -> OutputSchema {
+        OutputSchema::new()
+            .with_field("sources", ValueType::Number)
+            .with_field("metrics", ValueType::Object)
+            .with_field("aggregates", ValueType::Object)
+    }
+}
+
+/// Merge the `StreamingAggregator`s reported for `metric` under `aggregates`
+/// in each output. Outputs that did not report the metric are skipped.
+pub fn merge_reported_aggregates<'a, I>(
+    outputs: I,
+    metric: &str,
+) -> Result<Option<StreamingAggregator>>
+where
+    I: IntoIterator<Item = &'a serde_json::Value>,
+{
+    let parts = outputs
+        .into_iter()
+        .filter_map(|output| output.get("aggregates")?.get(metric).cloned())
+        .map(|value| serde_json::from_value(value).map_err(Into::into))
+        .collect::<Result<Vec<StreamingAggregator>>>()?;
+
+    StreamingAggregator::merge_all(&parts)
+}
+
+#[async_trait]
+impl Task for AggregationTask {
+    async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
+        tracing::info!(
+            "Aggregating {} upstream results for experiment: {}",
+            context.inputs.len(),
+            context.experiment_id
+        );
+
+        let mut aggregates = BTreeMap::new();
+        let mut metric_values = serde_json::Map::new();
+
+        for metric in &self.config.metrics {
+            if let Some(merged) = merge_reported_aggregates(context.inputs.values(), metric)? {
+                let agg = merged.summary();
+                metric_values.insert(metric.clone(), json!({
+                    "count": agg.count,
+                    "mean": agg.mean,
+                    "median": agg.median,
+                    "std_dev": agg.std_dev,
+                    "min": agg.min,
+                    "max": agg.max,
+                    "p95": agg.p95,
+                    "p99": agg.p99,
+                }));
+                aggregates.insert(metric.clone(), merged);
+            }
+        }
+
+        let output = json!({
+            "sources": context.inputs.len(),
+            "metrics": metric_values,
+            "aggregates": aggregates,
+        });
+
+        Ok(TaskResult::success(output))
+    }
+
+    fn name(&self) -> &str {
+        "aggregation"
+    }
+}
@@ -1,89 +1,308 @@
 use async_trait::async_trait;
-use futures::stream::{self, StreamExt};
-use llm_research_core::Result;
+use llm_research_core::{
+    CoreError, DataSplit, DatasetSample, ReproducibilitySettings, Result, SampleConfig, SampleSize,
+    SampleStrategy,
+};
+use llm_research_storage::s3::{create_client, create_client_with_config, S3Config, S3Storage};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use uuid::Uuid;
 
 use super::{Task, TaskContext, TaskResult};
+use crate::control::TaskControl;
+use crate::datasets::{default_dataset_id, DataFormat, DataSource, FieldMapping, SampleStream};
+use crate::sampling::{Sampler, DEFAULT_SPLIT_FIELD};
+use crate::template::{OutputSchema, ValueType};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DataLoadingConfig {
+    /// A local path, `s3://bucket/key`, or `synthetic://name` for placeholder
+    /// samples
     pub source: String,
     pub batch_size: usize,
+    /// Read the dataset a batch at a time and hand each batch to the task's
+    /// sink, rather than returning the samples in the output
     pub stream: bool,
+    /// Most samples to read, after picking out the split
     pub limit: Option<usize>,
+    /// How the source is laid out; inferred from its extension if unset
+    pub format: Option<DataFormat>,
+    pub fields: FieldMapping,
+    /// Dataset the samples belong to; derived from `source` if unset
+    pub dataset_id: Option<Uuid>,
+    /// Client settings for S3 sources, such as an endpoint; the bucket comes
+    /// from the source. Unset, the environment's AWS configuration is used.
+    pub s3: Option<S3Config>,
+    /// Only samples in this split, as named by their `split_field`
+    pub split: Option<DataSplit>,
+    /// Field of the sample's metadata, or of its input if that is the whole
+    /// row, naming its split; `split` if unset
+    pub split_field: Option<String>,
+    /// Select some of the samples rather than loading them all. Strategies
+    /// other than taking them in order hold the split in memory to select
+    /// from.
+    pub sample: Option<SampleConfig>,
+    /// Seeds the selection unless `sample` sets its own seed; the
+    /// experiment's seed if unset
+    pub random_seed: Option<u64>,
+}
+
+impl Default for DataLoadingConfig {
+    fn default() -> Self {
+        Self {
+            source: String::new(),
+            batch_size: 100,
+            stream: false,
+            limit: None,
+            format: None,
+            fields: FieldMapping::default(),
+            dataset_id: None,
+            s3: None,
+            split: None,
+            split_field: None,
+            sample: None,
+            random_seed: None,
+        }
+    }
 }
 
 pub struct DataLoadingTask {
     config: DataLoadingConfig,
+    storage: Option<Arc<S3Storage>>,
+    sink: Option<mpsc::Sender<Vec<DatasetSample>>>,
 }
 
 impl DataLoadingTask {
     pub fn new(config: DataLoadingConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            storage: None,
+            sink: None,
+        }
     }
 
-    /// Load data in batches
-    async fn load_batched(&self) -> Result<Vec<serde_json::Value>> {
-        tracing::info!(
-            "Loading data from {} in batches of {}",
-            self.config.source,
-            self.config.batch_size
-        );
+    /// Read S3 sources through `storage` instead of a client built from the
+    /// config
+    pub fn with_storage(mut self, storage: Arc<S3Storage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
 
-        // Mock implementation - in real system, would load from S3, database, etc.
-        let total_samples = self.config.limit.unwrap_or(1000);
-        let mut batches = Vec::new();
+    /// Where batches go when streaming
+    pub fn with_sink(mut self, sink: mpsc::Sender<Vec<DatasetSample>>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
 
-        for batch_start in (0..total_samples).step_by(self.config.batch_size) {
-            let batch_end = (batch_start + self.config.batch_size).min(total_samples);
-            let batch_size = batch_end - batch_start;
+    /// Fields of the output this task produces
+    pub fn output_schema() -> OutputSchema {
+        OutputSchema::new()
+            .with_field("source", ValueType::String)
+            .with_field("dataset_id", ValueType::String)
+            .with_field("batches_loaded", ValueType::Number)
+            .with_field("total_samples", ValueType::Number)
+            .with_field("batch_size", ValueType::Number)
+            .with_field("streaming", ValueType::Bool)
+            .with_field("batches", ValueType::Array)
+            .with_field("selection", ValueType::Object)
+    }
 
-            let batch = json!({
-                "batch_id": batch_start / self.config.batch_size,
-                "start": batch_start,
-                "end": batch_end,
-                "size": batch_size,
-                "samples": (batch_start..batch_end)
-                    .map(|i| json!({"id": i, "text": format!("Sample {}", i)}))
-                    .collect::<Vec<_>>()
-            });
+    pub fn dataset_id(&self) -> Uuid {
+        self.config
+            .dataset_id
+            .unwrap_or_else(|| default_dataset_id(&self.config.source))
+    }
+
+    /// Starts reading the configured samples, for callers that consume them
+    /// as they arrive
+    pub async fn open(&self) -> Result<SampleStream> {
+        let source = DataSource::parse(&self.config.source)?;
+        let storage = match &source {
+            DataSource::S3 { bucket, .. } => Some(self.storage(bucket).await?),
+            _ => None,
+        };
+
+        let mut limit = self.config.limit;
+        if let Some(SampleConfig {
+            strategy: SampleStrategy::Sequential,
+            size: SampleSize::Count(count),
+            ..
+        }) = &self.config.sample
+        {
+            // The selection is the first samples, so there's no need to
+            // read past them
+            limit = Some(limit.map_or(*count, |limit| limit.min(*count)));
+        }
 
-            batches.push(batch);
+        let mut samples = SampleStream::open(&source, self.config.format, storage.as_deref())
+            .await?
+            .with_fields(self.config.fields.clone())
+            .with_dataset_id(self.dataset_id())
+            .with_limit(limit);
+        if let Some(split) = &self.config.split {
+            samples = samples.with_split(split.clone(), self.split_field());
+        }
+        Ok(samples)
+    }
 
-            // Simulate some loading time
-            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+    /// Every selected sample at once, for callers that need them together.
+    /// `context` supplies the experiment's seed and the task's control.
+    pub async fn samples(&self, context: &TaskContext) -> Result<Vec<DatasetSample>> {
+        self.batch_size()?;
+        let mut samples = self.open().await?;
+        let all = self.read_all(&mut samples, &context.control).await?;
+        match self.sampler(&context.reproducibility) {
+            Some(sampler) if sampler.needs_all_samples() => sampler.select(all),
+            _ => Ok(all),
         }
+    }
 
-        Ok(batches)
+    /// The rest of `samples`, read a batch at a time, pausing or stopping
+    /// between batches
+    async fn read_all(
+        &self,
+        samples: &mut SampleStream,
+        control: &TaskControl,
+    ) -> Result<Vec<DatasetSample>> {
+        let mut all = Vec::new();
+        loop {
+            control.checkpoint().await?;
+            let batch = samples.next_batch(self.config.batch_size).await?;
+            if batch.is_empty() {
+                return Ok(all);
+            }
+            all.extend(batch);
+        }
     }
 
-    /// Load data as a stream
-    async fn load_streaming(&self) -> Result<Vec<serde_json::Value>> {
-        tracing::info!("Loading data from {} as stream", self.config.source);
+    fn batch_size(&self) -> Result<usize> {
+        match self.config.batch_size {
+            0 => Err(CoreError::Validation(
+                "Data loading batch_size must be at least 1".to_string(),
+            )),
+            batch_size => Ok(batch_size),
+        }
+    }
 
-        let total_samples = self.config.limit.unwrap_or(1000);
+    fn split_field(&self) -> &str {
+        self.config
+            .split_field
+            .as_deref()
+            .unwrap_or(DEFAULT_SPLIT_FIELD)
+    }
 
-        // Create a stream of data
-        let data_stream = stream::iter(0..total_samples)
-            .map(|i| json!({"id": i, "text": format!("Sample {}", i)}))
-            .chunks(self.config.batch_size);
+    /// What selects the samples, if the config picks a split or a sample.
+    /// The config's seed takes the place of the experiment's.
+    fn sampler(&self, reproducibility: &ReproducibilitySettings) -> Option<Sampler> {
+        if self.config.split.is_none() && self.config.sample.is_none() {
+            return None;
+        }
+        let reproducibility = ReproducibilitySettings {
+            random_seed: self.config.random_seed.or(reproducibility.random_seed),
+            ..reproducibility.clone()
+        };
+        let sampler = Sampler::new(
+            self.config.sample.clone().unwrap_or_default(),
+            &reproducibility,
+        );
+        Some(match &self.config.split {
+            Some(split) => sampler.with_split(split.clone(), self.split_field()),
+            None => sampler,
+        })
+    }
 
-        let batches: Vec<Vec<serde_json::Value>> = data_stream.collect().await;
+    async fn storage(&self, bucket: &str) -> Result<Arc<S3Storage>> {
+        if let Some(storage) = &self.storage {
+            return Ok(Arc::clone(storage));
+        }
 
-        let result = batches
-            .into_iter()
-            .enumerate()
-            .map(|(idx, samples)| {
-                json!({
-                    "batch_id": idx,
-                    "size": samples.len(),
-                    "samples": samples
+        let client = match &self.config.s3 {
+            Some(config) => {
+                create_client_with_config(&S3Config {
+                    bucket: bucket.to_string(),
+                    ..config.clone()
                 })
-            })
-            .collect();
+                .await
+            }
+            None => create_client().await,
+        }
+        .map_err(|e| CoreError::Internal(format!("Failed to create S3 client: {}", e)))?;
+        Ok(Arc::new(S3Storage::new(client, bucket.to_string())))
+    }
+
+    /// Load data in batches, pausing or stopping between batches, along with
+    /// the selection made if sampling. Streaming hands each batch to the
+    /// sink and keeps only its size.
+    async fn load(
+        &self,
+        context: &TaskContext,
+    ) -> Result<(Vec<serde_json::Value>, Option<serde_json::Value>)> {
+        let control = &context.control;
+        tracing::info!(
+            "Loading data from {} in batches of {}{}",
+            self.config.source,
+            self.config.batch_size,
+            if self.config.stream {
+                " as a stream"
+            } else {
+                ""
+            }
+        );
+        let batch_size = self.batch_size()?;
 
-        Ok(result)
+        let sampler = self.sampler(&context.reproducibility);
+        let mut samples = self.open().await?;
+        let mut selected = match &sampler {
+            Some(sampler) if sampler.needs_all_samples() => {
+                let all = self.read_all(&mut samples, control).await?;
+                Some(sampler.select(all)?.into_iter())
+            }
+            _ => None,
+        };
+
+        let mut batches = Vec::new();
+        let mut sample_ids = Vec::new();
+        let mut batch_start = 0;
+
+        loop {
+            control.checkpoint().await?;
+            let batch: Vec<DatasetSample> = match &mut selected {
+                Some(selected) => selected.by_ref().take(batch_size).collect(),
+                None => samples.next_batch(batch_size).await?,
+            };
+            if batch.is_empty() {
+                break;
+            }
+            let batch_end = batch_start + batch.len();
+            if sampler.is_some() {
+                sample_ids.extend(batch.iter().map(|sample| sample.id));
+            }
+
+            let mut summary = json!({
+                "batch_id": batches.len(),
+                "start": batch_start,
+                "end": batch_end,
+                "size": batch.len(),
+            });
+            if !self.config.stream {
+                summary["samples"] = serde_json::to_value(&batch)?;
+            } else if let Some(sink) = &self.sink {
+                sink.send(batch)
+                    .await
+                    .map_err(|_| CoreError::Internal("Data loading sink was closed".to_string()))?;
+            }
+
+            batches.push(summary);
+            batch_start = batch_end;
+        }
+
+        let selection = sampler
+            .map(|sampler| serde_json::to_value(sampler.selection(sample_ids)))
+            .transpose()?;
+        Ok((batches, selection))
     }
 }
 
@@ -96,11 +315,7 @@ impl Task for DataLoadingTask {
             self.config.source
         );
 
-        let batches = if self.config.stream {
-            self.load_streaming().await?
-        } else {
-            self.load_batched().await?
-        };
+        let (batches, selection) = self.load(&context).await?;
 
         let total_samples: usize = batches
             .iter()
@@ -111,11 +326,13 @@ impl Task for DataLoadingTask {
 
         let output = json!({
             "source": self.config.source,
+            "dataset_id": self.dataset_id(),
             "batches_loaded": batches.len(),
             "total_samples": total_samples,
             "batch_size": self.config.batch_size,
             "streaming": self.config.stream,
             "batches": batches,
+            "selection": selection,
         });
 
         Ok(TaskResult::success(output))
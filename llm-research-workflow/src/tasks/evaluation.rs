@@ -1,19 +1,26 @@
 use async_trait::async_trait;
-use llm_research_core::{MetricCalculator, Result};
+use llm_research_core::{CoreError, MetricCalculator, Result};
 use llm_research_metrics::{
     AccuracyCalculator, BleuCalculator, RougeCalculator, ComparisonMode,
-    MetricAggregator, MetricInput,
+    MetricInput, SequentialConfig, SequentialDecision, SequentialResult, SequentialTest,
+    StreamingAggregator,
 };
 use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::BTreeMap;
 
 use super::{Task, TaskContext, TaskResult};
+use crate::control::TaskControl;
+use crate::template::{OutputSchema, ValueType};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EvaluationConfig {
     pub metrics: Vec<String>,
     pub batch_size: usize,
+    /// Sequential test consulted between batches to stop once the result is clear
+    pub early_stopping: Option<EarlyStoppingConfig>,
 }
 
 impl Default for EvaluationConfig {
@@ -21,10 +28,32 @@ impl Default for EvaluationConfig {
         Self {
             metrics: vec!["accuracy".to_string(), "bleu".to_string(), "rouge".to_string()],
             batch_size: 100,
+            early_stopping: None,
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EarlyStoppingConfig {
+    /// Metric monitored between batches: "accuracy", "bleu" or "rouge"
+    pub metric: String,
+    /// Reference score the metric is tested against, e.g. the incumbent model's
+    pub baseline: f64,
+    #[serde(default)]
+    pub test: SequentialConfig,
+}
+
+/// Why and when an evaluation run stopped before exhausting its samples
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EarlyStoppingOutcome {
+    pub stopped: bool,
+    pub reason: SequentialDecision,
+    pub metric: String,
+    pub samples_evaluated: usize,
+    pub samples_available: usize,
+    pub last_look: Option<SequentialResult>,
+}
+
 pub struct EvaluationTask {
     config: EvaluationConfig,
 }
@@ -34,6 +63,17 @@ impl EvaluationTask {
         Self { config }
     }
 
+    /// Fields of the output this task produces
+    pub fn output_schema() -> OutputSchema {
+        OutputSchema::new()
+            .with_field("metrics_calculated", ValueType::Array)
+            .with_field("total_samples", ValueType::Number)
+            .with_field("batches_processed", ValueType::Number)
+            .with_field("metrics", ValueType::Object)
+            .with_field("aggregates", ValueType::Object)
+            .with_field("early_stopping", ValueType::Any)
+    }
+
     /// Evaluate a batch of predictions
     async fn evaluate_batch(
         &self,
@@ -83,19 +123,73 @@ impl EvaluationTask {
         })
     }
 
-    /// Process evaluation in batches
+    /// Process evaluation in batches, consulting the sequential test (if any)
+    /// after each batch. Batches are pause points.
     async fn evaluate_batched(
         &self,
         pairs: Vec<(String, String)>,
-    ) -> Result<Vec<BatchEvaluationResult>> {
+        control: &TaskControl,
+    ) -> Result<(Vec<BatchEvaluationResult>, Option<EarlyStoppingOutcome>)> {
         let mut results = Vec::new();
 
+        let mut monitor = match &self.config.early_stopping {
+            Some(early_stopping) => {
+                if !self.config.metrics.contains(&early_stopping.metric) {
+                    return Err(CoreError::Validation(format!(
+                        "Early stopping metric '{}' is not among the evaluated metrics",
+                        early_stopping.metric
+                    )));
+                }
+                Some((early_stopping, SequentialTest::new(early_stopping.test.clone())?))
+            }
+            None => None,
+        };
+        let mut monitored_scores = Vec::new();
+        let mut last_look = None;
+
         for chunk in pairs.chunks(self.config.batch_size) {
+            control.checkpoint().await?;
             let batch_result = self.evaluate_batch(chunk).await?;
-            results.push(batch_result);
+
+            if let Some((early_stopping, test)) = monitor.as_mut() {
+                monitored_scores.extend_from_slice(batch_result.scores(&early_stopping.metric));
+                let look = test.update_one_sample(&monitored_scores, early_stopping.baseline);
+                results.push(batch_result);
+
+                if look.decision.should_stop() {
+                    tracing::info!(
+                        "Stopping evaluation early after {} samples: {:?}",
+                        monitored_scores.len(),
+                        look.decision
+                    );
+                    return Ok((
+                        results,
+                        Some(EarlyStoppingOutcome {
+                            stopped: true,
+                            reason: look.decision,
+                            metric: early_stopping.metric.clone(),
+                            samples_evaluated: monitored_scores.len(),
+                            samples_available: pairs.len(),
+                            last_look: Some(look),
+                        }),
+                    ));
+                }
+                last_look = Some(look);
+            } else {
+                results.push(batch_result);
+            }
         }
 
-        Ok(results)
+        let outcome = monitor.map(|(early_stopping, _)| EarlyStoppingOutcome {
+            stopped: false,
+            reason: SequentialDecision::Continue,
+            metric: early_stopping.metric.clone(),
+            samples_evaluated: monitored_scores.len(),
+            samples_available: pairs.len(),
+            last_look,
+        });
+
+        Ok((results, outcome))
     }
 }
 
@@ -106,6 +200,17 @@ struct BatchEvaluationResult {
     rouge_scores: Vec<f64>,
 }
 
+impl BatchEvaluationResult {
+    fn scores(&self, metric: &str) -> &[f64] {
+        match metric {
+            "accuracy" => &self.accuracy_scores,
+            "bleu" => &self.bleu_scores,
+            "rouge" => &self.rouge_scores,
+            _ => &[],
+        }
+    }
+}
+
 #[async_trait]
 impl Task for EvaluationTask {
     async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
@@ -127,32 +232,29 @@ impl Task for EvaluationTask {
             })
             .collect();
 
-        let batch_results = self.evaluate_batched(pairs).await?;
-
-        // Aggregate results
-        let all_accuracy: Vec<f64> = batch_results
-            .iter()
-            .flat_map(|r| r.accuracy_scores.iter())
-            .copied()
-            .collect();
+        let (batch_results, early_stopping) = self.evaluate_batched(pairs, &context.control).await?;
 
-        let all_bleu: Vec<f64> = batch_results
-            .iter()
-            .flat_map(|r| r.bleu_scores.iter())
-            .copied()
-            .collect();
-
-        let all_rouge: Vec<f64> = batch_results
-            .iter()
-            .flat_map(|r| r.rouge_scores.iter())
-            .copied()
-            .collect();
+        // Aggregate each batch on its own and merge the partial results, so
+        // the same aggregates can be combined across workers downstream
+        let mut aggregates: BTreeMap<String, StreamingAggregator> = BTreeMap::new();
+        for batch in &batch_results {
+            for metric in ["accuracy", "bleu", "rouge"] {
+                let scores = batch.scores(metric);
+                if scores.is_empty() {
+                    continue;
+                }
+                aggregates
+                    .entry(metric.to_string())
+                    .or_default()
+                    .merge(&StreamingAggregator::from_values(scores))?;
+            }
+        }
 
         let mut metrics_calculated = Vec::new();
         let mut metric_values = serde_json::Map::new();
 
-        if !all_accuracy.is_empty() {
-            let agg = MetricAggregator::aggregate(&all_accuracy);
+        if let Some(aggregate) = aggregates.get("accuracy") {
+            let agg = aggregate.summary();
             metrics_calculated.push("accuracy");
             metric_values.insert("accuracy".to_string(), json!({
                 "mean": agg.mean,
@@ -160,34 +262,44 @@ impl Task for EvaluationTask {
                 "std_dev": agg.std_dev,
                 "min": agg.min,
                 "max": agg.max,
+                "p95": agg.p95,
+                "p99": agg.p99,
             }));
         }
 
-        if !all_bleu.is_empty() {
-            let agg = MetricAggregator::aggregate(&all_bleu);
+        if let Some(aggregate) = aggregates.get("bleu") {
+            let agg = aggregate.summary();
             metrics_calculated.push("bleu");
             metric_values.insert("bleu".to_string(), json!({
                 "mean": agg.mean,
                 "median": agg.median,
                 "std_dev": agg.std_dev,
+                "p95": agg.p95,
+                "p99": agg.p99,
             }));
         }
 
-        if !all_rouge.is_empty() {
-            let agg = MetricAggregator::aggregate(&all_rouge);
+        if let Some(aggregate) = aggregates.get("rouge") {
+            let agg = aggregate.summary();
             metrics_calculated.push("rouge");
             metric_values.insert("rouge_l".to_string(), json!({
                 "mean": agg.mean,
                 "median": agg.median,
                 "std_dev": agg.std_dev,
+                "p95": agg.p95,
+                "p99": agg.p99,
             }));
         }
 
+        let total_samples = aggregates.get("accuracy").map_or(0, |a| a.count());
+
         let output = json!({
             "metrics_calculated": metrics_calculated,
-            "total_samples": all_accuracy.len(),
+            "total_samples": total_samples,
             "batches_processed": batch_results.len(),
             "metrics": metric_values,
+            "aggregates": aggregates,
+            "early_stopping": early_stopping,
         });
 
         Ok(TaskResult::success(output))
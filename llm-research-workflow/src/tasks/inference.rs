@@ -1,32 +1,119 @@
 use async_trait::async_trait;
-use llm_research_core::Result;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use llm_research_core::retry::retry_if;
+use llm_research_core::{CoreError, ModelParameters, Result};
+use llm_research_metrics::LatencyMetrics;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::Semaphore;
-use tokio::time::{sleep, Duration, Instant};
+use tokio::time::{Duration, Instant};
 
 use super::{Task, TaskContext, TaskResult};
+use crate::backends::{
+    AnthropicBackend, CassetteBackend, CassetteMode, ChatMessage, CohereBackend, Completion,
+    InferenceBackend, InferenceRequest, OpenAiBackend, SimulatedBackend, TokenLogprob,
+};
+use crate::cache::{FileOutputCache, OutputCache};
+use crate::control::TaskControl;
+use crate::rate_limit::{RateLimiter, RateLimits};
+use crate::retry::Backoff;
+use crate::template::{OutputSchema, ValueType};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Requests an inference task has under way at once when its limits don't
+/// cap them
+const DEFAULT_MAX_IN_FLIGHT: usize = 64;
+
+/// How many requests a task keeps under way at once through `rate_limiter`
+pub(crate) fn request_concurrency(rate_limiter: &RateLimiter) -> usize {
+    rate_limiter
+        .limits()
+        .max_in_flight
+        .unwrap_or(DEFAULT_MAX_IN_FLIGHT)
+        .max(1)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum InferenceProvider {
     OpenAI,
     Anthropic,
     Cohere,
+    /// Hugging Face's OpenAI-compatible router or a TGI endpoint
     HuggingFace,
+    /// An OpenAI-compatible server such as vLLM, llama.cpp server or Ollama
     Local,
+    /// Canned responses without calling a model, for dry runs
+    Simulated,
+}
+
+impl InferenceProvider {
+    /// Name recorded with results from the provider
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::OpenAI => "openai",
+            Self::Anthropic => "anthropic",
+            Self::Cohere => "cohere",
+            Self::HuggingFace => "huggingface",
+            Self::Local => "local",
+            Self::Simulated => "simulated",
+        }
+    }
+}
+
+/// Where and how to record and replay responses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CassetteConfig {
+    /// Directory holding the recordings
+    pub dir: PathBuf,
+    #[serde(default)]
+    pub mode: CassetteMode,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct InferenceConfig {
     pub provider: InferenceProvider,
     pub model: String,
+    /// Endpoint of the provider's API; defaults to the provider's public API,
+    /// or `http://localhost:8000/v1` for `local`
+    pub base_url: Option<String>,
+    /// Environment variable holding the API key; defaults to `OPENAI_API_KEY`,
+    /// `ANTHROPIC_API_KEY`, `CO_API_KEY` or `HF_TOKEN` by provider
+    pub api_key_env: Option<String>,
+    /// Prompts to complete, e.g. `${steps.render.output.prompts}`
+    pub prompts: Vec<String>,
+    /// Sent as the system message before each prompt
+    pub system_prompt: Option<String>,
     pub max_tokens: usize,
-    pub temperature: f32,
+    pub temperature: f64,
+    /// Sampling parameters to send instead of `max_tokens` and `temperature`
+    pub parameters: Option<ModelParameters>,
+    /// Record each generated token's log probability, along with this many
+    /// of the most likely alternatives
+    pub logprobs: Option<u32>,
+    /// Requests per minute
     pub rate_limit_per_minute: usize,
+    /// Prompt and completion tokens per minute
+    pub tokens_per_minute: Option<usize>,
+    /// Most requests to have outstanding at once
+    pub max_in_flight: Option<usize>,
+    /// Tasks with the same key share their rate limits, as requests made
+    /// with one API key do, enforcing the limits declared last unless
+    /// `RateLimiter::configure` set them. Defaults to the provider, endpoint
+    /// and API key variable; each simulated task has its own.
+    pub rate_limit_key: Option<String>,
     pub max_retries: usize,
+    /// Wait before each retry of a failed request
+    pub backoff: Backoff,
+    /// Limit on each request, after which it fails and may be retried
     pub timeout_seconds: u64,
+    /// Stream responses to record the time to first token and when each
+    /// token arrived
+    pub stream: bool,
+    /// Record responses, or replay recorded ones instead of calling the
+    /// provider
+    pub cassette: Option<CassetteConfig>,
 }
 
 impl Default for InferenceConfig {
@@ -34,127 +121,334 @@ impl Default for InferenceConfig {
         Self {
             provider: InferenceProvider::OpenAI,
             model: "gpt-4".to_string(),
+            base_url: None,
+            api_key_env: None,
+            prompts: Vec::new(),
+            system_prompt: None,
             max_tokens: 1000,
             temperature: 0.7,
+            parameters: None,
+            logprobs: None,
             rate_limit_per_minute: 60,
+            tokens_per_minute: None,
+            max_in_flight: Some(16),
+            rate_limit_key: None,
             max_retries: 3,
+            backoff: Backoff::default(),
             timeout_seconds: 30,
+            stream: false,
+            cassette: None,
+        }
+    }
+}
+
+impl InferenceConfig {
+    /// The sampling parameters sent with each request
+    pub fn model_parameters(&self) -> ModelParameters {
+        self.parameters.clone().unwrap_or_else(|| ModelParameters {
+            temperature: Some(self.temperature),
+            max_tokens: Some(self.max_tokens as u32),
+            ..Default::default()
+        })
+    }
+
+    pub fn rate_limits(&self) -> RateLimits {
+        RateLimits {
+            requests_per_minute: Some(self.rate_limit_per_minute),
+            tokens_per_minute: self.tokens_per_minute,
+            max_in_flight: self.max_in_flight,
+        }
+    }
+
+    /// The limiter for requests to the configured provider, shared with
+    /// other tasks that use the same key
+    pub fn rate_limiter(&self) -> Arc<RateLimiter> {
+        let key = match &self.rate_limit_key {
+            Some(key) => key.clone(),
+            None if self.provider == InferenceProvider::Simulated => {
+                return Arc::new(RateLimiter::new(self.rate_limits()))
+            }
+            None => format!(
+                "{}|{}|{}",
+                self.provider.name(),
+                self.base_url.as_deref().unwrap_or_default(),
+                self.api_key_env.as_deref().unwrap_or_default()
+            ),
+        };
+        RateLimiter::shared(&key, self.rate_limits())
+    }
+
+    /// The client for the configured provider
+    pub fn backend(&self) -> Result<Arc<dyn InferenceBackend>> {
+        let base_url = |default: &str| self.base_url.clone().unwrap_or_else(|| default.to_string());
+        Ok(match self.provider {
+            InferenceProvider::Simulated => Arc::new(SimulatedBackend),
+            InferenceProvider::Anthropic => {
+                let backend = AnthropicBackend::new(base_url(AnthropicBackend::DEFAULT_BASE_URL));
+                match self.api_key(Some("ANTHROPIC_API_KEY"))? {
+                    Some(api_key) => Arc::new(backend.with_api_key(api_key)),
+                    None => Arc::new(backend),
+                }
+            }
+            InferenceProvider::Cohere => {
+                let backend = CohereBackend::new(base_url(CohereBackend::DEFAULT_BASE_URL));
+                match self.api_key(Some("CO_API_KEY"))? {
+                    Some(api_key) => Arc::new(backend.with_api_key(api_key)),
+                    None => Arc::new(backend),
+                }
+            }
+            _ => {
+                let (default_url, default_key_env) = match self.provider {
+                    InferenceProvider::OpenAI => {
+                        (OpenAiBackend::DEFAULT_BASE_URL, Some("OPENAI_API_KEY"))
+                    }
+                    InferenceProvider::HuggingFace => {
+                        ("https://router.huggingface.co/v1", Some("HF_TOKEN"))
+                    }
+                    _ => ("http://localhost:8000/v1", None),
+                };
+                let backend =
+                    OpenAiBackend::new(base_url(default_url)).with_name(self.provider.name());
+                match self.api_key(default_key_env)? {
+                    Some(api_key) => Arc::new(backend.with_api_key(api_key)),
+                    None => Arc::new(backend),
+                }
+            }
+        })
+    }
+
+    /// The API key for the provider, read from `api_key_env` or the
+    /// provider's usual variable
+    fn api_key(&self, default_key_env: Option<&str>) -> Result<Option<String>> {
+        let Some(key_env) = self.api_key_env.as_deref().or(default_key_env) else {
+            return Ok(None);
+        };
+        match std::env::var(key_env) {
+            Ok(api_key) => Ok(Some(api_key)),
+            // Self-hosted servers behind a custom URL often need no key
+            Err(_) if self.api_key_env.is_none() && self.base_url.is_some() => Ok(None),
+            Err(_) => Err(CoreError::Unauthorized(format!(
+                "Set {} to the API key for {}",
+                key_env,
+                self.provider.name()
+            ))),
         }
     }
 }
 
 pub struct InferenceTask {
     config: InferenceConfig,
+    /// Overrides the backend built from the config
+    backend: Option<Arc<dyn InferenceBackend>>,
 }
 
 impl InferenceTask {
     pub fn new(config: InferenceConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            backend: None,
+        }
     }
 
-    /// Execute inference with rate limiting
-    async fn execute_with_rate_limit(
-        &self,
-        prompts: &[String],
-    ) -> Result<Vec<InferenceResult>> {
-        let rate_limiter = Arc::new(Semaphore::new(self.config.rate_limit_per_minute));
-        let mut handles = Vec::new();
-
-        for (idx, prompt) in prompts.iter().enumerate() {
-            let rate_limiter = Arc::clone(&rate_limiter);
-            let config = self.config.clone();
-            let prompt = prompt.clone();
-
-            let handle = tokio::spawn(async move {
-                // Acquire rate limit permit
-                let _permit = rate_limiter.acquire().await.unwrap();
-
-                let start = Instant::now();
-                let result = Self::execute_single_inference(&config, &prompt, idx).await;
-                let latency = start.elapsed().as_millis() as u64;
-
-                // Release permit after minimum delay (to maintain rate limit)
-                let min_delay = Duration::from_millis(60_000 / config.rate_limit_per_minute as u64);
-                if start.elapsed() < min_delay {
-                    sleep(min_delay - start.elapsed()).await;
+    /// Send requests to `backend` rather than the configured provider
+    pub fn with_backend(mut self, backend: Arc<dyn InferenceBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// The backend to send requests to, recording or replaying responses if
+    /// the config asks to
+    pub(crate) async fn backend(&self) -> Result<Arc<dyn InferenceBackend>> {
+        let cassette = match &self.config.cassette {
+            Some(cassette) if cassette.mode != CassetteMode::Passthrough => cassette,
+            _ => {
+                return match &self.backend {
+                    Some(backend) => Ok(Arc::clone(backend)),
+                    None => self.config.backend(),
                 }
+            }
+        };
 
-                (result, latency)
-            });
+        let store: Arc<dyn OutputCache> = Arc::new(FileOutputCache::new(&cassette.dir).await?);
+        let backend = match (&self.backend, cassette.mode) {
+            (Some(backend), mode) => CassetteBackend::new(Arc::clone(backend), store, mode),
+            // Replaying needs no client, and so no API key
+            (None, CassetteMode::ReplayOnly) => {
+                CassetteBackend::replay(self.config.provider.name(), store)
+            }
+            (None, mode) => CassetteBackend::new(self.config.backend()?, store, mode),
+        };
+        Ok(Arc::new(backend))
+    }
 
-            handles.push(handle);
-        }
+    /// Fields of the output this task produces
+    pub fn output_schema() -> OutputSchema {
+        OutputSchema::new()
+            .with_field("provider", ValueType::String)
+            .with_field("model", ValueType::String)
+            .with_field("predictions_generated", ValueType::Number)
+            .with_field("total_tokens", ValueType::Number)
+            .with_field("avg_latency_ms", ValueType::Number)
+            .with_field("total_duration_ms", ValueType::Number)
+            .with_field("latency", ValueType::Object)
+            .with_field("results", ValueType::Array)
+    }
 
-        let mut results = Vec::new();
-        for handle in handles {
-            let (result, latency) = handle.await.map_err(|e| {
-                llm_research_core::CoreError::Internal(format!("Inference task failed: {}", e))
-            })?;
-            let mut result = result?;
-            result.latency_ms = latency;
-            results.push(result);
+    /// The limiter requests wait for
+    pub(crate) fn rate_limiter(&self) -> Arc<RateLimiter> {
+        match &self.config.cassette {
+            // Replays never reach the provider
+            Some(cassette) if cassette.mode == CassetteMode::ReplayOnly => {
+                Arc::new(RateLimiter::new(RateLimits::default()))
+            }
+            _ => self.config.rate_limiter(),
         }
+    }
 
-        Ok(results)
+    /// Execute inference within the rate limits. Only as many requests as
+    /// may be in flight are started at a time, so a long prompt list isn't
+    /// queued up on the limiter all at once. Each request waits while the
+    /// task is paused, and in-flight requests are abandoned on cancellation
+    /// or once one fails.
+    async fn execute_with_rate_limit(
+        &self,
+        backend: &Arc<dyn InferenceBackend>,
+        prompts: &[String],
+        control: &TaskControl,
+    ) -> Result<Vec<InferenceResult>> {
+        let rate_limiter = self.rate_limiter();
+        let concurrency = request_concurrency(&rate_limiter);
+        let rate_limiter = &rate_limiter;
+
+        stream::iter(0..prompts.len())
+            .map(|idx| self.execute_controlled(backend, rate_limiter, &prompts[idx], idx, control))
+            .buffered(concurrency)
+            .try_collect()
+            .await
     }
 
-    /// Execute single inference with retries
-    async fn execute_single_inference(
-        config: &InferenceConfig,
+    /// Execute single inference of `prompt` once the task isn't paused,
+    /// unless it is cancelled first
+    async fn execute_controlled(
+        &self,
+        backend: &Arc<dyn InferenceBackend>,
+        rate_limiter: &Arc<RateLimiter>,
         prompt: &str,
         index: usize,
+        control: &TaskControl,
     ) -> Result<InferenceResult> {
-        let mut last_error = None;
-
-        for attempt in 0..=config.max_retries {
-            match Self::call_provider(config, prompt, index).await {
-                Ok(result) => return Ok(result),
-                Err(e) => {
-                    last_error = Some(e);
-                    if attempt < config.max_retries {
-                        tracing::warn!(
-                            "Inference attempt {} failed, retrying...",
-                            attempt + 1
-                        );
-                        sleep(Duration::from_secs(2_u64.pow(attempt as u32))).await;
-                    }
-                }
-            }
-        }
-
-        Err(last_error.unwrap())
+        control.checkpoint().await?;
+        control
+            .run_until_cancelled(Self::execute_single_inference(
+                &self.config,
+                &**backend,
+                rate_limiter,
+                prompt,
+                index,
+            ))
+            .await
     }
 
-    /// Mock call to inference provider
-    async fn call_provider(
+    /// Execute single inference of `prompt`, after the system prompt if any
+    async fn execute_single_inference(
         config: &InferenceConfig,
+        backend: &dyn InferenceBackend,
+        rate_limiter: &Arc<RateLimiter>,
         prompt: &str,
         index: usize,
     ) -> Result<InferenceResult> {
-        // Simulate API call
-        sleep(Duration::from_millis(100 + (index % 200) as u64)).await;
-
-        // Mock response based on provider
-        let response = match config.provider {
-            InferenceProvider::OpenAI => format!("OpenAI {} response to: {}", config.model, prompt),
-            InferenceProvider::Anthropic => format!("Claude {} response to: {}", config.model, prompt),
-            InferenceProvider::Cohere => format!("Cohere response to: {}", prompt),
-            InferenceProvider::HuggingFace => format!("HF {} response to: {}", config.model, prompt),
-            InferenceProvider::Local => format!("Local model response to: {}", prompt),
-        };
-
-        let tokens_used = prompt.len() / 4 + response.len() / 4;
+        let mut messages = Vec::new();
+        if let Some(system_prompt) = &config.system_prompt {
+            messages.push(ChatMessage::system(system_prompt.as_str()));
+        }
+        messages.push(ChatMessage::user(prompt));
+        let (completion, latency) =
+            Self::send(config, backend, rate_limiter, messages, index).await?;
 
         Ok(InferenceResult {
             index,
             prompt: prompt.to_string(),
-            response,
-            tokens_used,
-            latency_ms: 0, // Will be set by caller
-            provider: format!("{:?}", config.provider),
-            model: config.model.clone(),
+            response: completion.text,
+            tokens_used: completion.usage.total(),
+            prompt_tokens: completion.usage.prompt_tokens,
+            completion_tokens: completion.usage.completion_tokens,
+            finish_reason: completion.finish_reason,
+            logprobs: completion.logprobs,
+            ttft_ms: completion.timing.as_ref().map(|t| t.first_token_ms),
+            token_times_ms: completion.timing.map(|t| t.arrivals_ms).unwrap_or_default(),
+            latency_ms: latency.as_millis() as u64,
+            provider: backend.name().to_string(),
+            model: completion.model,
         })
     }
+
+    /// Send `messages` to the model with the configured parameters, and how
+    /// long the answer took. Timeouts and other retryable errors are retried
+    /// after the configured backoff. Each attempt waits for the rate limiter,
+    /// which holds every request back after a rate limit response.
+    pub(crate) async fn send(
+        config: &InferenceConfig,
+        backend: &dyn InferenceBackend,
+        rate_limiter: &Arc<RateLimiter>,
+        messages: Vec<ChatMessage>,
+        index: usize,
+    ) -> Result<(Completion, Duration)> {
+        let policy = config.backoff.policy(config.max_retries);
+        let timeout = Duration::from_secs(config.timeout_seconds);
+
+        let mut request = InferenceRequest::new(config.model.as_str(), messages)
+            .with_parameters(config.model_parameters());
+        request.logprobs = config.logprobs;
+        let request = &request;
+        let estimated_tokens = estimate_tokens(request);
+
+        let attempt = || async move {
+            let permit = rate_limiter.acquire(estimated_tokens).await;
+            let start = Instant::now();
+            let call = if config.stream {
+                backend.stream(request)
+            } else {
+                backend.complete(request)
+            };
+            let result = tokio::time::timeout(timeout, call)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(CoreError::Internal(format!(
+                        "Inference request {} timed out after {}s",
+                        index, config.timeout_seconds
+                    )))
+                });
+            match &result {
+                Ok(completion) => permit.complete(completion.usage.total()),
+                Err(CoreError::RateLimited { retry_after, .. }) => {
+                    permit.rate_limited(*retry_after)
+                }
+                // Assume the failed request used its estimate
+                Err(_) => {}
+            }
+            result.map(|completion| (completion, start.elapsed()))
+        };
+        retry_if(&policy, attempt, CoreError::is_retryable)
+            .await
+            .map_err(|e| {
+                tracing::warn!("Inference request {} failed after {} attempts", index, e.attempts);
+                e.error
+            })
+    }
+
+    /// The configured prompts, or placeholder prompts for a simulated run
+    fn prompts(&self) -> Result<Vec<String>> {
+        if !self.config.prompts.is_empty() {
+            return Ok(self.config.prompts.clone());
+        }
+        if self.backend.is_none() && self.config.provider == InferenceProvider::Simulated {
+            return Ok((0..10).map(|i| format!("Test prompt {}", i)).collect());
+        }
+        Err(CoreError::Validation(
+            "Inference has no prompts; set `prompts`, e.g. to the output of a rendering step"
+                .to_string(),
+        ))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -163,27 +457,63 @@ pub struct InferenceResult {
     pub prompt: String,
     pub response: String,
     pub tokens_used: usize,
+    #[serde(default)]
+    pub prompt_tokens: usize,
+    #[serde(default)]
+    pub completion_tokens: usize,
+    /// Why generation stopped, e.g. `stop` or `length`
+    #[serde(default)]
+    pub finish_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<Vec<TokenLogprob>>,
+    /// Time to first token of a streamed request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttft_ms: Option<f64>,
+    /// When each token of a streamed response arrived, in milliseconds after
+    /// the request was sent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub token_times_ms: Vec<f64>,
     pub latency_ms: u64,
     pub provider: String,
     pub model: String,
 }
 
+/// Tokens `request` may use, for the tokens per minute budget: roughly four
+/// characters per prompt token, plus the most it may generate
+fn estimate_tokens(request: &InferenceRequest) -> usize {
+    let prompt_chars: usize = request.messages.iter().map(|m| m.content.len()).sum();
+    prompt_chars / 4 + request.parameters.max_tokens.unwrap_or(0) as usize
+}
+
+/// Latency percentiles of `results`, with the mean time to first token of
+/// those that were streamed and the rate completion tokens were generated
+pub fn latency_metrics(results: &[InferenceResult]) -> LatencyMetrics {
+    let latencies: Vec<f64> = results.iter().map(|r| r.latency_ms as f64).collect();
+    let mut metrics = LatencyMetrics::from_measurements(&latencies);
+
+    let ttfts: Vec<f64> = results.iter().filter_map(|r| r.ttft_ms).collect();
+    if !ttfts.is_empty() {
+        metrics = metrics.with_ttft(ttfts.iter().sum::<f64>() / ttfts.len() as f64);
+    }
+    let completion_tokens = results.iter().map(|r| r.completion_tokens).sum();
+    metrics.with_throughput(completion_tokens, latencies.iter().sum())
+}
+
 #[async_trait]
 impl Task for InferenceTask {
     async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
+        let backend = self.backend().await?;
         tracing::info!(
-            "Running inference for experiment: {} using {:?}",
+            "Running inference for experiment: {} using {}",
             context.experiment_id,
-            self.config.provider
+            backend.name()
         );
 
-        // Mock prompts - in real system, these would come from data loading task
-        let prompts: Vec<String> = (0..10)
-            .map(|i| format!("Test prompt {}", i))
-            .collect();
-
+        let prompts = self.prompts()?;
         let start = Instant::now();
-        let results = self.execute_with_rate_limit(&prompts).await?;
+        let results = self
+            .execute_with_rate_limit(&backend, &prompts, &context.control)
+            .await?;
         let total_duration = start.elapsed();
 
         let total_tokens: usize = results.iter().map(|r| r.tokens_used).sum();
@@ -191,12 +521,13 @@ impl Task for InferenceTask {
             / results.len() as f64;
 
         let output = json!({
-            "provider": format!("{:?}", self.config.provider),
+            "provider": backend.name(),
             "model": self.config.model,
             "predictions_generated": results.len(),
             "total_tokens": total_tokens,
             "avg_latency_ms": avg_latency,
             "total_duration_ms": total_duration.as_millis(),
+            "latency": latency_metrics(&results),
             "results": results,
         });
 
@@ -0,0 +1,665 @@
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use llm_research_core::{CoreError, Evaluation, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::inference::request_concurrency;
+use super::{InferenceConfig, InferenceResult, InferenceTask, Task, TaskContext, TaskResult};
+use crate::backends::{ChatMessage, InferenceBackend};
+use crate::control::TaskControl;
+use crate::rate_limit::RateLimiter;
+use crate::template::{OutputSchema, ValueType};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JudgeMode {
+    /// Score each response on the rubric's scale
+    #[default]
+    Pointwise,
+    /// Pick the better of each response and its counterpart in `baseline`
+    Pairwise,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RubricCriterion {
+    pub name: String,
+    pub description: String,
+}
+
+/// What the judge is told to look for, and how to score it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rubric {
+    pub instructions: String,
+    /// Aspects to weigh, each scored on its own as well in pointwise mode
+    pub criteria: Vec<RubricCriterion>,
+    /// Lowest pointwise score
+    pub scale_min: u32,
+    /// Highest pointwise score
+    pub scale_max: u32,
+}
+
+impl Default for Rubric {
+    fn default() -> Self {
+        Self {
+            instructions: "Assess how well the response answers the prompt, considering \
+                           accuracy, completeness and clarity."
+                .to_string(),
+            criteria: Vec::new(),
+            scale_min: 1,
+            scale_max: 10,
+        }
+    }
+}
+
+impl Rubric {
+    fn criteria_list(&self) -> String {
+        self.criteria
+            .iter()
+            .map(|criterion| format!("- {}: {}\n", criterion.name, criterion.description))
+            .collect()
+    }
+
+    fn pointwise_prompt(&self) -> String {
+        let mut prompt = format!("You are an impartial judge. {}\n", self.instructions);
+        let mut criteria = String::new();
+        if !self.criteria.is_empty() {
+            prompt.push_str(&format!(
+                "\nScore each of these criteria from {} to {} as well:\n{}",
+                self.scale_min,
+                self.scale_max,
+                self.criteria_list()
+            ));
+            let scores: Vec<String> = self
+                .criteria
+                .iter()
+                .map(|criterion| format!("\"{}\": <score>", criterion.name))
+                .collect();
+            criteria = format!("\"criteria\": {{{}}}, ", scores.join(", "));
+        }
+        prompt.push_str(&format!(
+            "\nReply with only a JSON object: {{\"score\": <{} to {}>, {}\"rationale\": \
+             \"<your reasoning>\"}}",
+            self.scale_min, self.scale_max, criteria
+        ));
+        prompt
+    }
+
+    fn pairwise_prompt(&self) -> String {
+        let mut prompt = format!(
+            "You are an impartial judge comparing two responses to the same prompt. {}\n",
+            self.instructions
+        );
+        if !self.criteria.is_empty() {
+            prompt.push_str(&format!("\nConsider:\n{}", self.criteria_list()));
+        }
+        prompt.push_str(
+            "\nThe order the responses are shown in must not affect your verdict. Reply with \
+             only a JSON object: {\"winner\": \"A\", \"B\" or \"tie\", \"rationale\": \
+             \"<your reasoning>\"}",
+        );
+        prompt
+    }
+
+    /// Why `verdict` doesn't fit the rubric, if it doesn't
+    fn check(&self, verdict: &ScoreVerdict) -> std::result::Result<(), String> {
+        let in_scale =
+            |score: f64| score >= self.scale_min as f64 && score <= self.scale_max as f64;
+        if !in_scale(verdict.score) {
+            return Err(format!(
+                "score {} is outside {} to {}",
+                verdict.score, self.scale_min, self.scale_max
+            ));
+        }
+        for criterion in &self.criteria {
+            match verdict.criteria.get(&criterion.name) {
+                Some(score) if in_scale(*score) => {}
+                Some(score) => {
+                    return Err(format!(
+                        "score {} for '{}' is outside {} to {}",
+                        score, criterion.name, self.scale_min, self.scale_max
+                    ))
+                }
+                None => return Err(format!("no score for '{}'", criterion.name)),
+            }
+        }
+        Ok(())
+    }
+
+    /// `score` moved to 0 for the lowest score and 1 for the highest
+    fn normalize(&self, score: f64) -> f64 {
+        let range = self.scale_max.saturating_sub(self.scale_min).max(1) as f64;
+        (score - self.scale_min as f64) / range
+    }
+}
+
+/// A pointwise judgment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreVerdict {
+    pub score: f64,
+    pub rationale: String,
+    /// Score of each rubric criterion
+    #[serde(default)]
+    pub criteria: BTreeMap<String, f64>,
+}
+
+/// Which of two responses a judge preferred
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preference {
+    #[serde(alias = "A")]
+    A,
+    #[serde(alias = "B")]
+    B,
+    #[serde(alias = "Tie", alias = "TIE")]
+    Tie,
+}
+
+impl Preference {
+    /// The same preference with the responses shown the other way round
+    pub fn swapped(self) -> Self {
+        match self {
+            Self::A => Self::B,
+            Self::B => Self::A,
+            Self::Tie => Self::Tie,
+        }
+    }
+
+    /// 1 for a win for A, 0 for a loss, and a half for a tie
+    pub fn score(self) -> f64 {
+        match self {
+            Self::A => 1.0,
+            Self::B => 0.0,
+            Self::Tie => 0.5,
+        }
+    }
+}
+
+/// A pairwise judgment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreferenceVerdict {
+    pub winner: Preference,
+    pub rationale: String,
+}
+
+/// The JSON object in a judge's reply, which may be wrapped in prose or a
+/// code fence
+pub fn parse_verdict<T: DeserializeOwned>(reply: &str) -> std::result::Result<T, String> {
+    let object = reply
+        .find('{')
+        .zip(reply.rfind('}'))
+        .filter(|(start, end)| start < end)
+        .map(|(start, end)| &reply[start..=end])
+        .ok_or_else(|| "no JSON object".to_string())?;
+    serde_json::from_str(object).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmJudgeConfig {
+    /// The judge model; its `prompts` and `system_prompt` are unused
+    pub judge: InferenceConfig,
+    #[serde(default)]
+    pub mode: JudgeMode,
+    #[serde(default)]
+    pub rubric: Rubric,
+    /// Responses to judge, e.g. `${steps.infer.output.results}`
+    pub results: Vec<InferenceResult>,
+    /// In pairwise mode, the responses `results` are compared with, to the
+    /// same prompts in the same order
+    #[serde(default)]
+    pub baseline: Vec<InferenceResult>,
+    /// The samples the prompts were rendered from, in prompt order, e.g.
+    /// `${steps.render.output.render_log}`. Unset, each evaluation gets a
+    /// fresh sample ID and the judge sees no reference answer.
+    #[serde(default)]
+    pub samples: Vec<JudgedSample>,
+    /// Judge each pair in both orders, so that a judge favouring one
+    /// position can't decide the outcome
+    #[serde(default = "default_swap_positions")]
+    pub swap_positions: bool,
+    /// Times to ask again when the judge's reply isn't a valid verdict
+    #[serde(default = "default_parse_retries")]
+    pub parse_retries: usize,
+}
+
+fn default_swap_positions() -> bool {
+    true
+}
+
+fn default_parse_retries() -> usize {
+    2
+}
+
+/// A rendered sample whose response is judged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JudgedSample {
+    pub sample_id: Uuid,
+    #[serde(default)]
+    pub expected_output: Option<serde_json::Value>,
+}
+
+/// Has a model judge responses against a rubric, storing its verdict and
+/// rationale with an `Evaluation` of each
+pub struct LlmJudgeTask {
+    config: LlmJudgeConfig,
+    judge: InferenceTask,
+}
+
+impl LlmJudgeTask {
+    pub fn new(config: LlmJudgeConfig) -> Self {
+        Self {
+            judge: InferenceTask::new(config.judge.clone()),
+            config,
+        }
+    }
+
+    /// Send judge requests to `backend` rather than the configured provider
+    pub fn with_backend(mut self, backend: Arc<dyn InferenceBackend>) -> Self {
+        self.judge = self.judge.with_backend(backend);
+        self
+    }
+
+    /// Fields of the output this task produces
+    pub fn output_schema() -> OutputSchema {
+        OutputSchema::new()
+            .with_field("mode", ValueType::String)
+            .with_field("judge_model", ValueType::String)
+            .with_field("judgments", ValueType::Number)
+            .with_field("parse_retries", ValueType::Number)
+            .with_field("summary", ValueType::Object)
+            .with_field("evaluations", ValueType::Array)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let config = &self.config;
+        if config.results.is_empty() {
+            return Err(CoreError::Validation(
+                "Nothing to judge; set `results`, e.g. to the output of an inference step"
+                    .to_string(),
+            ));
+        }
+        if !config.samples.is_empty() && config.samples.len() != config.results.len() {
+            return Err(CoreError::Validation(format!(
+                "Judging {} responses but given {} samples",
+                config.results.len(),
+                config.samples.len()
+            )));
+        }
+        if config.rubric.scale_min >= config.rubric.scale_max {
+            return Err(CoreError::Validation(
+                "Rubric scale_min must be below scale_max".to_string(),
+            ));
+        }
+        if config.mode == JudgeMode::Pairwise {
+            if config.baseline.len() != config.results.len() {
+                return Err(CoreError::Validation(format!(
+                    "Pairwise judging needs a baseline response for each of the {} responses; \
+                     got {}",
+                    config.results.len(),
+                    config.baseline.len()
+                )));
+            }
+            if let Some((i, _)) = config
+                .results
+                .iter()
+                .zip(&config.baseline)
+                .enumerate()
+                .find(|(_, (result, baseline))| result.prompt != baseline.prompt)
+            {
+                return Err(CoreError::Validation(format!(
+                    "Response {} and its baseline answer different prompts",
+                    i
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Asks the judge until its reply parses as a verdict that `check`
+    /// accepts, telling it what was wrong each time. Returns the verdict and
+    /// the number of requests it took.
+    async fn ask<T, F>(
+        &self,
+        backend: &Arc<dyn InferenceBackend>,
+        rate_limiter: &Arc<RateLimiter>,
+        system_prompt: String,
+        prompt: String,
+        index: usize,
+        check: F,
+    ) -> Result<(T, usize)>
+    where
+        T: DeserializeOwned,
+        F: Fn(&T) -> std::result::Result<(), String>,
+    {
+        let mut messages = vec![
+            ChatMessage::system(system_prompt),
+            ChatMessage::user(prompt),
+        ];
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let (completion, _) = InferenceTask::send(
+                &self.config.judge,
+                &**backend,
+                rate_limiter,
+                messages.clone(),
+                index,
+            )
+            .await?;
+            let problem = match parse_verdict::<T>(&completion.text) {
+                Ok(verdict) => match check(&verdict) {
+                    Ok(()) => return Ok((verdict, attempts)),
+                    Err(problem) => problem,
+                },
+                Err(problem) => problem,
+            };
+
+            if attempts > self.config.parse_retries {
+                return Err(CoreError::Internal(format!(
+                    "Judge gave no valid verdict for response {} in {} attempts: {}",
+                    index, attempts, problem
+                )));
+            }
+            tracing::warn!(
+                "Judge verdict for response {} was malformed: {}",
+                index,
+                problem
+            );
+            messages.push(ChatMessage::assistant(completion.text));
+            messages.push(ChatMessage::user(format!(
+                "That reply isn't a valid verdict ({}). Reply with only the JSON object.",
+                problem
+            )));
+        }
+    }
+
+    /// Judge the response at `index` once the task isn't paused, unless it
+    /// is cancelled first
+    async fn judge_controlled(
+        &self,
+        backend: &Arc<dyn InferenceBackend>,
+        rate_limiter: &Arc<RateLimiter>,
+        index: usize,
+        control: &TaskControl,
+    ) -> Result<(serde_json::Value, usize)> {
+        control.checkpoint().await?;
+        control
+            .run_until_cancelled(async {
+                match self.config.mode {
+                    JudgeMode::Pointwise => self.pointwise(backend, rate_limiter, index).await,
+                    JudgeMode::Pairwise => self.pairwise(backend, rate_limiter, index).await,
+                }
+            })
+            .await
+    }
+
+    /// Scores the `index`th response, returning the evaluation's metrics and
+    /// the requests made
+    async fn pointwise(
+        &self,
+        backend: &Arc<dyn InferenceBackend>,
+        rate_limiter: &Arc<RateLimiter>,
+        index: usize,
+    ) -> Result<(serde_json::Value, usize)> {
+        let result = &self.config.results[index];
+        let prompt = judge_prompt(
+            &result.prompt,
+            self.reference(index).as_deref(),
+            &[("Response", &result.response)],
+        );
+        let rubric = &self.config.rubric;
+        let (verdict, attempts): (ScoreVerdict, _) = self
+            .ask(
+                backend,
+                rate_limiter,
+                rubric.pointwise_prompt(),
+                prompt,
+                index,
+                |verdict| rubric.check(verdict),
+            )
+            .await?;
+
+        let metrics = json!({
+            "judge_score": verdict.score,
+            "judge_normalized_score": rubric.normalize(verdict.score),
+            "judge_criteria": verdict.criteria,
+            "judge_rationale": verdict.rationale,
+            "judge_model": self.config.judge.model,
+            "judge_attempts": attempts,
+        });
+        Ok((metrics, attempts))
+    }
+
+    /// Compares the `index`th response, A, with its baseline, B, in both
+    /// orders if swapping positions. Disagreeing verdicts cancel out.
+    async fn pairwise(
+        &self,
+        backend: &Arc<dyn InferenceBackend>,
+        rate_limiter: &Arc<RateLimiter>,
+        index: usize,
+    ) -> Result<(serde_json::Value, usize)> {
+        let a = &self.config.results[index];
+        let b = &self.config.baseline[index];
+        let reference = self.reference(index);
+        let orders: &[bool] = if self.config.swap_positions {
+            &[false, true]
+        } else {
+            &[false]
+        };
+
+        let mut verdicts = Vec::new();
+        let mut attempts = 0;
+        for &swapped in orders {
+            let (first, second) = if swapped { (b, a) } else { (a, b) };
+            let prompt = judge_prompt(
+                &a.prompt,
+                reference.as_deref(),
+                &[
+                    ("Response A", &first.response),
+                    ("Response B", &second.response),
+                ],
+            );
+            let (verdict, tries): (PreferenceVerdict, _) = self
+                .ask(
+                    backend,
+                    rate_limiter,
+                    self.config.rubric.pairwise_prompt(),
+                    prompt,
+                    index,
+                    |_| Ok(()),
+                )
+                .await?;
+            attempts += tries;
+            let winner = if swapped {
+                verdict.winner.swapped()
+            } else {
+                verdict.winner
+            };
+            verdicts.push((swapped, winner, verdict.rationale));
+        }
+
+        let score = verdicts
+            .iter()
+            .map(|(_, winner, _)| winner.score())
+            .sum::<f64>()
+            / verdicts.len() as f64;
+        let preference = if score > 0.5 {
+            Preference::A
+        } else if score < 0.5 {
+            Preference::B
+        } else {
+            Preference::Tie
+        };
+        let consistent = verdicts
+            .iter()
+            .all(|(_, winner, _)| *winner == verdicts[0].1);
+
+        let metrics = json!({
+            "judge_preference": preference,
+            "judge_score": score,
+            "judge_consistent": consistent,
+            "judge_verdicts": verdicts
+                .iter()
+                .map(|(swapped, winner, rationale)| json!({
+                    "baseline_first": swapped,
+                    "winner": winner,
+                    "rationale": rationale,
+                }))
+                .collect::<Vec<_>>(),
+            "judge_model": self.config.judge.model,
+            "judge_attempts": attempts,
+        });
+        Ok((metrics, attempts))
+    }
+
+    /// The expected output of the `index`th response's sample, if known
+    fn reference(&self, index: usize) -> Option<String> {
+        match self.config.samples.get(index)?.expected_output.as_ref()? {
+            serde_json::Value::String(text) => Some(text.clone()),
+            value => Some(value.to_string()),
+        }
+    }
+}
+
+/// The request to judge `responses`, each under its label
+fn judge_prompt(prompt: &str, reference: Option<&str>, responses: &[(&str, &str)]) -> String {
+    let mut text = format!("[Prompt]\n{}\n", prompt);
+    if let Some(reference) = reference {
+        text.push_str(&format!("\n[Reference answer]\n{}\n", reference));
+    }
+    for (label, response) in responses {
+        text.push_str(&format!("\n[{}]\n{}\n", label, response));
+    }
+    text
+}
+
+/// Averages and spread of the judgments in `metrics`
+fn summarize(mode: JudgeMode, metrics: &[serde_json::Value]) -> serde_json::Value {
+    let values = |key: &str| -> Vec<f64> {
+        metrics
+            .iter()
+            .filter_map(|m| m.get(key).and_then(|v| v.as_f64()))
+            .collect()
+    };
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len().max(1) as f64;
+
+    match mode {
+        JudgeMode::Pointwise => {
+            let scores = values("judge_score");
+            let mut criteria: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+            for m in metrics {
+                if let Some(scores) = m["judge_criteria"].as_object() {
+                    for (name, score) in scores {
+                        criteria
+                            .entry(name.clone())
+                            .or_default()
+                            .extend(score.as_f64());
+                    }
+                }
+            }
+            json!({
+                "mean_score": mean(&scores),
+                "min_score": scores.iter().copied().fold(f64::INFINITY, f64::min),
+                "max_score": scores.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                "mean_normalized_score": mean(&values("judge_normalized_score")),
+                "criteria": criteria
+                    .iter()
+                    .map(|(name, scores)| (name.clone(), json!(mean(scores))))
+                    .collect::<serde_json::Map<_, _>>(),
+            })
+        }
+        JudgeMode::Pairwise => {
+            let count = |preference: Preference| {
+                metrics
+                    .iter()
+                    .filter(|m| m["judge_preference"] == json!(preference))
+                    .count()
+            };
+            json!({
+                "wins": count(Preference::A),
+                "losses": count(Preference::B),
+                "ties": count(Preference::Tie),
+                "win_rate": mean(&values("judge_score")),
+                "inconsistent": metrics
+                    .iter()
+                    .filter(|m| m["judge_consistent"] == json!(false))
+                    .count(),
+            })
+        }
+    }
+}
+
+#[async_trait]
+impl Task for LlmJudgeTask {
+    async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
+        self.validate()?;
+        let backend = self.judge.backend().await?;
+        let rate_limiter = self.judge.rate_limiter();
+        tracing::info!(
+            "Judging {} responses for experiment: {} with {} {}",
+            self.config.results.len(),
+            context.experiment_id,
+            backend.name(),
+            self.config.judge.model
+        );
+
+        let judgments: Vec<(serde_json::Value, usize)> = stream::iter(0..self.config.results.len())
+            .map(|index| self.judge_controlled(&backend, &rate_limiter, index, &context.control))
+            .buffered(request_concurrency(&rate_limiter))
+            .try_collect()
+            .await?;
+
+        let requests: usize = judgments.iter().map(|(_, attempts)| attempts).sum();
+        let per_response = match self.config.mode {
+            JudgeMode::Pairwise if self.config.swap_positions => 2,
+            _ => 1,
+        };
+        let metrics: Vec<serde_json::Value> =
+            judgments.into_iter().map(|(metrics, _)| metrics).collect();
+        let summary = summarize(self.config.mode, &metrics);
+
+        let evaluations: Vec<Evaluation> = self
+            .config
+            .results
+            .iter()
+            .zip(metrics)
+            .enumerate()
+            .map(|(index, (result, metrics))| {
+                Evaluation::new(
+                    context.experiment_id,
+                    self.config
+                        .samples
+                        .get(index)
+                        .map_or_else(Uuid::new_v4, |sample| sample.sample_id),
+                    result.prompt.clone(),
+                    result.response.clone(),
+                    self.reference(index),
+                    result.latency_ms as i64,
+                    result.tokens_used as i32,
+                    None,
+                    metrics,
+                )
+            })
+            .collect();
+
+        let output = json!({
+            "mode": self.config.mode,
+            "judge_model": self.config.judge.model,
+            "judgments": evaluations.len(),
+            "parse_retries": requests - evaluations.len() * per_response,
+            "summary": summary,
+            "evaluations": evaluations,
+        });
+
+        Ok(TaskResult::success(output))
+    }
+
+    fn name(&self) -> &str {
+        "llm_judge"
+    }
+}
@@ -0,0 +1,300 @@
+use async_trait::async_trait;
+use llm_research_core::{CoreError, DatasetSample, PromptTemplate, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::{DataLoadingConfig, DataLoadingTask, Task, TaskContext, TaskResult};
+use crate::datasets::lookup;
+use crate::prompt_store::{FilePromptTemplateStore, PromptTemplateStore};
+use crate::template::{OutputSchema, ValueType};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptRenderingConfig {
+    pub template_id: Uuid,
+    /// The template's latest version if unset
+    #[serde(default)]
+    pub template_version: Option<i32>,
+    /// Directory of a `FilePromptTemplateStore` to find the template in,
+    /// unless the task is given a store
+    #[serde(default)]
+    pub template_dir: Option<PathBuf>,
+    /// Batches of a data loading step that kept its samples, e.g.
+    /// `${steps.load.output.batches}`
+    pub batches: Vec<SampleBatch>,
+    /// Template variables filled from an input field of another name, or a
+    /// dotted path into the input. Other variables are filled from the input
+    /// field of the same name, or from the whole input if it isn't an object
+    /// and the variable is `input`.
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    #[serde(default)]
+    pub few_shot: Option<FewShotConfig>,
+}
+
+/// The samples of a batch a data loading step returned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SampleBatch {
+    pub samples: Vec<DatasetSample>,
+}
+
+/// Worked examples put into every prompt, rendered one after another into a
+/// template variable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FewShotConfig {
+    /// Where the examples come from, usually the train split of the same
+    /// source. Select a few with `sample` or `limit`.
+    pub examples: DataLoadingConfig,
+    /// Template variable the examples fill
+    #[serde(default = "default_few_shot_variable")]
+    pub variable: String,
+    /// Template for each example, filled like the prompt's with
+    /// `expected_output` as well
+    #[serde(default = "default_example_template")]
+    pub template: String,
+    #[serde(default = "default_example_separator")]
+    pub separator: String,
+}
+
+fn default_few_shot_variable() -> String {
+    "examples".to_string()
+}
+
+fn default_example_template() -> String {
+    "{{input}}\n{{expected_output}}".to_string()
+}
+
+fn default_example_separator() -> String {
+    "\n\n".to_string()
+}
+
+/// Renders a stored `PromptTemplate` for each loaded sample, the step
+/// between loading data and inference
+pub struct PromptRenderingTask {
+    config: PromptRenderingConfig,
+    /// Overrides the store found from the config
+    store: Option<Arc<dyn PromptTemplateStore>>,
+}
+
+impl PromptRenderingTask {
+    pub fn new(config: PromptRenderingConfig) -> Self {
+        Self {
+            config,
+            store: None,
+        }
+    }
+
+    /// Look templates up in `store` rather than `template_dir`
+    pub fn with_store(mut self, store: Arc<dyn PromptTemplateStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Fields of the output this task produces
+    pub fn output_schema() -> OutputSchema {
+        OutputSchema::new()
+            .with_field("template_id", ValueType::String)
+            .with_field("template_version", ValueType::Number)
+            .with_field("samples_rendered", ValueType::Number)
+            .with_field("prompts", ValueType::Array)
+            .with_field("render_log", ValueType::Array)
+            .with_field("few_shot_examples", ValueType::Array)
+    }
+
+    /// The configured version of the template
+    async fn template(&self) -> Result<PromptTemplate> {
+        let store: Arc<dyn PromptTemplateStore> = match (&self.store, &self.config.template_dir) {
+            (Some(store), _) => Arc::clone(store),
+            (None, Some(dir)) => Arc::new(FilePromptTemplateStore::new(dir).await?),
+            (None, None) => {
+                return Err(CoreError::Validation(
+                    "Prompt rendering has no template store; set `template_dir`".to_string(),
+                ))
+            }
+        };
+
+        let id = self.config.template_id;
+        let version = self.config.template_version;
+        store.get(id, version).await?.ok_or_else(|| {
+            CoreError::NotFound(match version {
+                Some(version) => format!("Prompt template {} version {}", id, version),
+                None => format!("Prompt template {}", id),
+            })
+        })
+    }
+
+    /// Values for the template's variables taken from `sample`'s input
+    fn variables(&self, sample: &DatasetSample) -> Result<Map<String, Value>> {
+        let mut variables = match &sample.input {
+            Value::Object(fields) => fields.clone(),
+            input => Map::from_iter([("input".to_string(), input.clone())]),
+        };
+        for (variable, field) in &self.config.variables {
+            let value = lookup(&sample.input, field).ok_or_else(|| {
+                CoreError::Validation(format!(
+                    "Sample {} has no input field '{}' for template variable '{}'",
+                    sample.index, field, variable
+                ))
+            })?;
+            variables.insert(variable.clone(), value.clone());
+        }
+        Ok(variables)
+    }
+
+    /// The few-shot examples, and their text to put in each prompt. None
+    /// may be among the samples being rendered.
+    async fn few_shot(
+        &self,
+        config: &FewShotConfig,
+        template: &PromptTemplate,
+        context: &TaskContext,
+    ) -> Result<(Vec<DatasetSample>, String)> {
+        if !template.variables.contains(&config.variable) {
+            return Err(CoreError::Validation(format!(
+                "Prompt template '{}' has no {{{{{}}}}} to put few-shot examples in",
+                template.name, config.variable
+            )));
+        }
+
+        let examples = DataLoadingTask::new(config.examples.clone())
+            .samples(context)
+            .await?;
+        if examples.is_empty() {
+            return Err(CoreError::Validation(format!(
+                "No few-shot examples in {}",
+                config.examples.source
+            )));
+        }
+        let rendered: HashSet<Uuid> = self
+            .config
+            .batches
+            .iter()
+            .flat_map(|batch| &batch.samples)
+            .map(|sample| sample.id)
+            .collect();
+
+        let example_template = PromptTemplate::new(
+            "few-shot example".to_string(),
+            None,
+            config.template.clone(),
+        );
+        let mut texts = Vec::with_capacity(examples.len());
+        for example in &examples {
+            if rendered.contains(&example.id) {
+                return Err(CoreError::Validation(format!(
+                    "Few-shot example {} is also a sample being rendered; take the examples \
+                     from another split",
+                    example.index
+                )));
+            }
+            let mut variables = self.variables(example)?;
+            if let Some(expected_output) = &example.expected_output {
+                variables.insert("expected_output".to_string(), expected_output.clone());
+            }
+            texts.push(render(
+                &example_template,
+                example,
+                &variables,
+                "few-shot example",
+            )?);
+        }
+        Ok((examples, texts.join(&config.separator)))
+    }
+}
+
+/// `template` filled in for `sample`, naming the fields there were to fill
+/// it with if one is missing
+fn render(
+    template: &PromptTemplate,
+    sample: &DatasetSample,
+    variables: &Map<String, Value>,
+    what: &str,
+) -> Result<String> {
+    template
+        .render(&Value::Object(variables.clone()))
+        .map_err(|e| {
+            let mut available: Vec<&str> = variables.keys().map(String::as_str).collect();
+            available.sort_unstable();
+            CoreError::Validation(format!(
+                "Can't render {} {} with prompt template '{}' version {}: {} (has {})",
+                what,
+                sample.index,
+                template.name,
+                template.version,
+                e,
+                available.join(", ")
+            ))
+        })
+}
+
+#[async_trait]
+impl Task for PromptRenderingTask {
+    async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
+        let template = self.template().await?;
+        tracing::info!(
+            "Rendering prompt template '{}' version {} for experiment: {}",
+            template.name,
+            template.version,
+            context.experiment_id
+        );
+
+        let few_shot = match &self.config.few_shot {
+            Some(config) => Some((
+                config,
+                self.few_shot(config, &template, &context).await?,
+            )),
+            None => None,
+        };
+
+        let mut prompts = Vec::new();
+        let mut render_log = Vec::new();
+        for batch in &self.config.batches {
+            context.control.checkpoint().await?;
+            for sample in &batch.samples {
+                let mut variables = self.variables(sample)?;
+                if let Some((config, (_, examples))) = &few_shot {
+                    variables.insert(config.variable.clone(), json!(examples));
+                }
+                let prompt = render(&template, sample, &variables, "sample")?;
+
+                // The examples are the same for every sample, so are logged once
+                let used: Map<String, Value> = template
+                    .variables
+                    .iter()
+                    .filter(|name| few_shot.iter().all(|(config, _)| config.variable != **name))
+                    .filter_map(|name| Some((name.clone(), variables.get(name)?.clone())))
+                    .collect();
+                render_log.push(json!({
+                    "sample_id": sample.id,
+                    "index": sample.index,
+                    "variables": used,
+                    "expected_output": sample.expected_output,
+                    "prompt_chars": prompt.chars().count(),
+                }));
+                prompts.push(prompt);
+            }
+        }
+
+        let few_shot_examples: Vec<Uuid> = few_shot
+            .iter()
+            .flat_map(|(_, (examples, _))| examples.iter().map(|example| example.id))
+            .collect();
+        let output = json!({
+            "template_id": template.id,
+            "template_version": template.version,
+            "samples_rendered": prompts.len(),
+            "prompts": prompts,
+            "render_log": render_log,
+            "few_shot_examples": few_shot_examples,
+        });
+
+        Ok(TaskResult::success(output))
+    }
+
+    fn name(&self) -> &str {
+        "prompt_rendering"
+    }
+}
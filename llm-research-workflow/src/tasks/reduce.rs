@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use llm_research_core::{CoreError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{merge_reported_aggregates, Task, TaskContext, TaskResult};
+use crate::fan_out::fan_out_outputs;
+use crate::template::{OutputSchema, ValueType};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReduceConfig {
+    /// Dotted path of the value to take from each output, e.g.
+    /// `metrics.accuracy`; the whole output if unset
+    pub field: Option<String>,
+    /// Concatenate list values rather than collecting each as one value
+    pub flatten: bool,
+}
+
+/// Gathers the outputs of upstream steps into one list. The children of a
+/// fanned-out step count as separate outputs, so this is the reduce half of
+/// a fan-out; their streaming aggregates are merged as well.
+pub struct ReduceTask {
+    config: ReduceConfig,
+}
+
+impl ReduceTask {
+    pub fn new(config: ReduceConfig) -> Self {
+        Self { config }
+    }
+
+    /// Fields of the output this task produces
+    pub fn output_schema() -> OutputSchema {
+        OutputSchema::new()
+            .with_field("count", ValueType::Number)
+            .with_field("failed", ValueType::Number)
+            .with_field("values", ValueType::Array)
+            .with_field("aggregates", ValueType::Object)
+    }
+
+    fn select<'a>(
+        &self,
+        output: &'a serde_json::Value,
+        step: &str,
+    ) -> Result<&'a serde_json::Value> {
+        let Some(field) = &self.config.field else {
+            return Ok(output);
+        };
+        field
+            .split('.')
+            .try_fold(output, |value, key| match value {
+                serde_json::Value::Array(items) => {
+                    key.parse::<usize>().ok().and_then(|i| items.get(i))
+                }
+                _ => value.get(key),
+            })
+            .ok_or_else(|| {
+                CoreError::Validation(format!(
+                    "Output of step '{}' has no field '{}'",
+                    step, field
+                ))
+            })
+    }
+}
+
+#[async_trait]
+impl Task for ReduceTask {
+    async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
+        // Upstream outputs in step name order, fanned-out children in item order
+        let steps: BTreeMap<&String, &serde_json::Value> = context.inputs.iter().collect();
+        let mut outputs = Vec::new();
+        let mut failed = 0;
+        for (step, output) in steps {
+            match fan_out_outputs(output) {
+                Some(children) => {
+                    failed += output["failed"].as_u64().unwrap_or(0);
+                    outputs.extend(children.into_iter().map(|child| (step, child)));
+                }
+                None => outputs.push((step, output)),
+            }
+        }
+        tracing::info!(
+            "Reducing {} outputs for experiment: {}",
+            outputs.len(),
+            context.experiment_id
+        );
+
+        let mut values = Vec::new();
+        for (step, output) in &outputs {
+            match self.select(output, step)? {
+                serde_json::Value::Array(items) if self.config.flatten => {
+                    values.extend(items.iter().cloned())
+                }
+                value => values.push(value.clone()),
+            }
+        }
+
+        let metrics: BTreeSet<&String> = outputs
+            .iter()
+            .filter_map(|(_, output)| output.get("aggregates")?.as_object())
+            .flat_map(|aggregates| aggregates.keys())
+            .collect();
+        let mut aggregates = BTreeMap::new();
+        for metric in metrics {
+            let parts = outputs.iter().map(|(_, output)| *output);
+            if let Some(merged) = merge_reported_aggregates(parts, metric)? {
+                aggregates.insert(metric.clone(), merged);
+            }
+        }
+
+        Ok(TaskResult::success(json!({
+            "count": values.len(),
+            "failed": failed,
+            "values": values,
+            "aggregates": aggregates,
+        })))
+    }
+
+    fn name(&self) -> &str {
+        "reduce"
+    }
+}
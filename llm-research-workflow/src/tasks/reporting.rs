@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 use super::{Task, TaskContext, TaskResult};
+use crate::template::{OutputSchema, ValueType};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -14,6 +15,7 @@ pub enum ReportFormat {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ReportingConfig {
     pub formats: Vec<ReportFormat>,
     pub include_charts: bool,
@@ -39,6 +41,15 @@ impl ReportingTask {
         Self { config }
     }
 
+    /// Fields of the output this task produces
+    pub fn output_schema() -> OutputSchema {
+        OutputSchema::new()
+            .with_field("report_generated", ValueType::Bool)
+            .with_field("formats", ValueType::Array)
+            .with_field("reports", ValueType::Array)
+            .with_field("include_charts", ValueType::Bool)
+    }
+
     /// Generate JSON report
     fn generate_json_report(&self, experiment_id: &uuid::Uuid, data: &serde_json::Value) -> String {
         let report = json!({
@@ -174,7 +185,7 @@ impl Task for ReportingTask {
             context.experiment_id
         );
 
-        // Mock data - in real system, this would come from previous tasks
+        // Sample data used when the report has no upstream steps
         let mock_data = json!({
             "metrics": {
                 "accuracy": {
@@ -200,13 +211,28 @@ impl Task for ReportingTask {
             "provider": "OpenAI"
         });
 
+        // Report on upstream outputs, merged in step-name order
+        let report_data = if context.inputs.is_empty() {
+            mock_data
+        } else {
+            let mut names: Vec<&String> = context.inputs.keys().collect();
+            names.sort();
+            let mut merged = serde_json::Map::new();
+            for name in names {
+                if let Some(output) = context.inputs[name].as_object() {
+                    merged.extend(output.clone());
+                }
+            }
+            serde_json::Value::Object(merged)
+        };
+
         let mut generated_reports = Vec::new();
 
         for format in &self.config.formats {
             let content = match format {
-                ReportFormat::Json => self.generate_json_report(&context.experiment_id, &mock_data),
-                ReportFormat::Html => self.generate_html_report(&context.experiment_id, &mock_data),
-                ReportFormat::Markdown => self.generate_markdown_report(&context.experiment_id, &mock_data),
+                ReportFormat::Json => self.generate_json_report(&context.experiment_id, &report_data),
+                ReportFormat::Html => self.generate_html_report(&context.experiment_id, &report_data),
+                ReportFormat::Markdown => self.generate_markdown_report(&context.experiment_id, &report_data),
             };
 
             let storage_location = self.save_report(format, &content, &context.experiment_id).await?;
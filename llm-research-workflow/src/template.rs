@@ -0,0 +1,457 @@
+use llm_research_core::{CoreError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// A `${steps.<name>.output.<path>}` reference to an earlier step's output.
+/// Numeric path segments index into arrays; an empty path is the whole output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputRef {
+    pub step: String,
+    pub path: Vec<String>,
+}
+
+impl OutputRef {
+    /// Parse the expression between `${` and `}`
+    pub fn parse(expression: &str) -> Result<Self> {
+        match Reference::parse(expression)? {
+            Reference::Output(reference) => Ok(reference),
+            Reference::Param(_) => Err(invalid_reference(expression, "not a step output")),
+        }
+    }
+
+    /// The referenced value inside `output`
+    pub fn lookup<'a>(&self, output: &'a serde_json::Value) -> Result<&'a serde_json::Value> {
+        lookup_path(
+            output,
+            &self.path,
+            &format!("output of step '{}'", self.step),
+            self,
+        )
+    }
+}
+
+impl fmt::Display for OutputRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${{steps.{}.output", self.step)?;
+        for segment in &self.path {
+            write!(f, ".{}", segment)?;
+        }
+        f.write_str("}")
+    }
+}
+
+/// What a `${...}` expression refers to: a step output, or a workflow
+/// parameter written `${params.<path>}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reference {
+    Output(OutputRef),
+    Param(Vec<String>),
+}
+
+impl Reference {
+    /// Parse the expression between `${` and `}`
+    pub fn parse(expression: &str) -> Result<Self> {
+        let invalid = |reason: &str| invalid_reference(expression, reason);
+
+        let mut segments = expression.trim().split('.');
+        let reference = match segments.next() {
+            Some("params") => {
+                let path: Vec<String> = segments.map(str::to_string).collect();
+                if path.is_empty() {
+                    return Err(invalid("missing parameter name"));
+                }
+                Self::Param(path)
+            }
+            Some("steps") => {
+                let step = match segments.next() {
+                    Some(step) if !step.is_empty() => step.to_string(),
+                    _ => return Err(invalid("missing step name")),
+                };
+                if segments.next() != Some("output") {
+                    return Err(invalid("step name must be followed by 'output'"));
+                }
+                Self::Output(OutputRef {
+                    step,
+                    path: segments.map(str::to_string).collect(),
+                })
+            }
+            _ => return Err(invalid("must start with 'steps' or 'params'")),
+        };
+
+        if reference.path().iter().any(String::is_empty) {
+            return Err(invalid("empty path segment"));
+        }
+        Ok(reference)
+    }
+
+    pub fn path(&self) -> &[String] {
+        match self {
+            Self::Output(reference) => &reference.path,
+            Self::Param(path) => path,
+        }
+    }
+
+    /// The referenced value among completed step `outputs`, keyed by step
+    /// name, and workflow `params`
+    pub fn resolve<'a>(
+        &self,
+        outputs: &'a HashMap<String, serde_json::Value>,
+        params: &'a serde_json::Value,
+    ) -> Result<&'a serde_json::Value> {
+        match self {
+            Self::Output(reference) => {
+                let output = outputs.get(&reference.step).ok_or_else(|| {
+                    CoreError::Validation(format!(
+                        "{}: step '{}' has no output",
+                        reference, reference.step
+                    ))
+                })?;
+                reference.lookup(output)
+            }
+            Self::Param(path) => lookup_path(params, path, "workflow parameters", self),
+        }
+    }
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Output(reference) => reference.fmt(f),
+            Self::Param(path) => write!(f, "${{params.{}}}", path.join(".")),
+        }
+    }
+}
+
+fn invalid_reference(expression: &str, reason: &str) -> CoreError {
+    CoreError::Validation(format!(
+        "Invalid reference '${{{}}}': {}, expected ${{steps.<name>.output.<path>}} or ${{params.<name>}}",
+        expression, reason
+    ))
+}
+
+/// Follow `path` from `root`, naming `root` as `root_name` in errors
+fn lookup_path<'a>(
+    root: &'a serde_json::Value,
+    path: &[String],
+    root_name: &str,
+    reference: &dyn fmt::Display,
+) -> Result<&'a serde_json::Value> {
+    let mut value = root;
+    for (depth, segment) in path.iter().enumerate() {
+        let found = match value {
+            serde_json::Value::Object(map) => map.get(segment),
+            serde_json::Value::Array(items) => {
+                segment.parse::<usize>().ok().and_then(|i| items.get(i))
+            }
+            _ => None,
+        };
+        value = found.ok_or_else(|| {
+            let parent = if depth == 0 {
+                root_name.to_string()
+            } else {
+                format!("'{}'", path[..depth].join("."))
+            };
+            let detail = match value {
+                serde_json::Value::Object(map) => format!(
+                    "has no key '{}' (available: {})",
+                    segment,
+                    map.keys().cloned().collect::<Vec<_>>().join(", ")
+                ),
+                serde_json::Value::Array(items) => {
+                    format!("has no index '{}' ({} items)", segment, items.len())
+                }
+                other => format!("is {} and has no field '{}'", ValueType::of(other), segment),
+            };
+            CoreError::Validation(format!("{}: {} {}", reference, parent, detail))
+        })?;
+    }
+    Ok(value)
+}
+
+/// A reference found in a config, and where
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateUse {
+    pub reference: Reference,
+    /// Config field holding the reference, e.g. `dataset.path` or `prompts[0]`
+    pub field: String,
+    /// Whether the reference is part of a longer string, so its value must be
+    /// a scalar, rather than the whole value of the field
+    pub embedded: bool,
+}
+
+/// Every reference in `config`. Write `$${` for a literal `${`.
+pub fn find_references(config: &serde_json::Value) -> Result<Vec<TemplateUse>> {
+    let mut uses = Vec::new();
+    walk(config, String::new(), &mut |field, text| {
+        let parts = split_template(text)?;
+        let embedded = parts.len() > 1;
+        for part in parts {
+            if let Part::Reference(reference) = part {
+                uses.push(TemplateUse {
+                    reference,
+                    field: field.to_string(),
+                    embedded,
+                });
+            }
+        }
+        Ok(())
+    })?;
+    Ok(uses)
+}
+
+/// Replace the references in `config` with values from `outputs`, the
+/// outputs of completed steps keyed by step name, and the workflow `params`.
+/// A field that is exactly one reference takes the referenced value as is;
+/// references inside longer strings are interpolated and must be scalars.
+pub fn resolve_config(
+    config: &serde_json::Value,
+    outputs: &HashMap<String, serde_json::Value>,
+    params: &serde_json::Value,
+) -> Result<serde_json::Value> {
+    let mut resolved = config.clone();
+    resolve_in_place(&mut resolved, String::new(), outputs, params)?;
+    Ok(resolved)
+}
+
+fn resolve_in_place(
+    value: &mut serde_json::Value,
+    field: String,
+    outputs: &HashMap<String, serde_json::Value>,
+    params: &serde_json::Value,
+) -> Result<()> {
+    match value {
+        serde_json::Value::String(text) => {
+            if let Some(replacement) = resolve_string(text, &field, outputs, params)? {
+                *value = replacement;
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                resolve_in_place(item, format!("{}[{}]", field, i), outputs, params)?;
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                resolve_in_place(item, child_field(&field, key), outputs, params)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The value replacing `text`, or `None` if it has no references or escapes
+fn resolve_string(
+    text: &str,
+    field: &str,
+    outputs: &HashMap<String, serde_json::Value>,
+    params: &serde_json::Value,
+) -> Result<Option<serde_json::Value>> {
+    if !text.contains("${") {
+        return Ok(None);
+    }
+    let in_field = |e: CoreError| with_prefix(e, &format!("Config field '{}'", field));
+
+    let parts = split_template(text).map_err(in_field)?;
+    if let [Part::Reference(reference)] = parts.as_slice() {
+        return reference
+            .resolve(outputs, params)
+            .map(|value| Some(value.clone()))
+            .map_err(in_field);
+    }
+
+    let mut interpolated = String::new();
+    for part in &parts {
+        match part {
+            Part::Text(text) => interpolated.push_str(text),
+            Part::Reference(reference) => {
+                match reference.resolve(outputs, params).map_err(in_field)? {
+                    serde_json::Value::String(s) => interpolated.push_str(s),
+                    value @ (serde_json::Value::Array(_) | serde_json::Value::Object(_)) => {
+                        return Err(in_field(CoreError::Validation(format!(
+                            "{} is {} and cannot be interpolated into a string",
+                            reference,
+                            ValueType::of(value)
+                        ))));
+                    }
+                    scalar => interpolated.push_str(&scalar.to_string()),
+                }
+            }
+        }
+    }
+    Ok(Some(serde_json::Value::String(interpolated)))
+}
+
+/// Put `prefix` in front of a validation error's message
+pub(crate) fn with_prefix(error: CoreError, prefix: &str) -> CoreError {
+    match error {
+        CoreError::Validation(message) => CoreError::Validation(format!("{}: {}", prefix, message)),
+        other => other,
+    }
+}
+
+enum Part {
+    Text(String),
+    Reference(Reference),
+}
+
+/// Split a string into literal text and references
+fn split_template(text: &str) -> Result<Vec<Part>> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            literal.push_str(&rest[..start - 1]);
+            literal.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        literal.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| {
+            CoreError::Validation(format!("Unterminated reference in '{}'", text))
+        })?;
+        if !literal.is_empty() {
+            parts.push(Part::Text(std::mem::take(&mut literal)));
+        }
+        parts.push(Part::Reference(Reference::parse(
+            &rest[start + 2..start + end],
+        )?));
+        rest = &rest[start + end + 1..];
+    }
+
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        parts.push(Part::Text(literal));
+    }
+    Ok(parts)
+}
+
+fn walk<F>(value: &serde_json::Value, field: String, visit: &mut F) -> Result<()>
+where
+    F: FnMut(&str, &str) -> Result<()>,
+{
+    match value {
+        serde_json::Value::String(text) if text.contains("${") => {
+            visit(&field, text)?;
+        }
+        serde_json::Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                walk(item, format!("{}[{}]", field, i), visit)?;
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (key, item) in map {
+                walk(item, child_field(&field, key), visit)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn child_field(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+/// JSON type of a task output field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueType {
+    String,
+    Number,
+    Bool,
+    Array,
+    Object,
+    Null,
+    /// Not known until the task runs
+    Any,
+}
+
+impl ValueType {
+    pub fn of(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::String(_) => Self::String,
+            serde_json::Value::Number(_) => Self::Number,
+            serde_json::Value::Bool(_) => Self::Bool,
+            serde_json::Value::Array(_) => Self::Array,
+            serde_json::Value::Object(_) => Self::Object,
+            serde_json::Value::Null => Self::Null,
+        }
+    }
+
+    /// Whether a value of this type can be interpolated into a string
+    pub fn is_scalar(&self) -> bool {
+        !matches!(self, Self::Array | Self::Object)
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::String => "a string",
+            Self::Number => "a number",
+            Self::Bool => "a boolean",
+            Self::Array => "an array",
+            Self::Object => "an object",
+            Self::Null => "null",
+            Self::Any => "any value",
+        })
+    }
+}
+
+/// Top-level fields a task type puts in its output, used to check references
+/// before anything runs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputSchema {
+    fields: BTreeMap<String, ValueType>,
+}
+
+impl OutputSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_field(mut self, name: impl Into<String>, value_type: ValueType) -> Self {
+        self.fields.insert(name.into(), value_type);
+        self
+    }
+
+    pub fn field(&self, name: &str) -> Option<ValueType> {
+        self.fields.get(name).copied()
+    }
+
+    pub fn field_names(&self) -> Vec<&str> {
+        self.fields.keys().map(String::as_str).collect()
+    }
+
+    /// Type of the value `reference` points to, or why it cannot exist
+    pub fn check(&self, reference: &OutputRef) -> std::result::Result<ValueType, String> {
+        let Some((first, rest)) = reference.path.split_first() else {
+            return Ok(ValueType::Object);
+        };
+        let value_type = self.field(first).ok_or_else(|| {
+            format!(
+                "step '{}' has no output '{}' (available: {})",
+                reference.step,
+                first,
+                self.field_names().join(", ")
+            )
+        })?;
+
+        match (value_type, rest.first()) {
+            (_, None) => Ok(value_type),
+            (ValueType::Array | ValueType::Object | ValueType::Any, Some(_)) => Ok(ValueType::Any),
+            (_, Some(next)) => Err(format!(
+                "output '{}' of step '{}' is {} and has no field '{}'",
+                first, reference.step, value_type, next
+            )),
+        }
+    }
+}
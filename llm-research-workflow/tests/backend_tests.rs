@@ -0,0 +1,884 @@
+use llm_research_core::{CoreError, ModelParameters};
+use llm_research_workflow::*;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn chat_response(content: &str) -> serde_json::Value {
+    json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "model": "llama-3-8b-instruct",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": content},
+            "finish_reason": "stop",
+        }],
+        "usage": {"prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15},
+    })
+}
+
+fn request(prompt: &str) -> InferenceRequest {
+    InferenceRequest::new("llama-3-8b-instruct", vec![ChatMessage::user(prompt)])
+}
+
+async fn stub(status: u16, body: serde_json::Value) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(status).set_body_json(body))
+        .mount(&server)
+        .await;
+    server
+}
+
+// ===== OpenAI-Compatible Backend Tests =====
+
+#[tokio::test]
+async fn test_openai_backend_completion() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(header("authorization", "Bearer sk-test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chat_response("Paris")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let backend = OpenAiBackend::new(format!("{}/v1/", server.uri())).with_api_key("sk-test");
+    let completion = backend
+        .complete(&request("Capital of France?"))
+        .await
+        .unwrap();
+
+    assert_eq!(backend.name(), "openai");
+    assert_eq!(completion.text, "Paris");
+    assert_eq!(completion.model, "llama-3-8b-instruct");
+    assert_eq!(completion.finish_reason.as_deref(), Some("stop"));
+    assert_eq!(completion.usage.prompt_tokens, 12);
+    assert_eq!(completion.usage.completion_tokens, 3);
+    assert_eq!(completion.usage.total(), 15);
+    assert!(completion.logprobs.is_none());
+}
+
+#[tokio::test]
+async fn test_openai_backend_maps_model_parameters() {
+    let server = stub(200, chat_response("ok")).await;
+    let parameters = ModelParameters {
+        temperature: Some(0.0),
+        max_tokens: Some(64),
+        top_p: Some(0.9),
+        top_k: None,
+        frequency_penalty: None,
+        presence_penalty: Some(0.5),
+        stop_sequences: Some(vec!["\n\n".to_string()]),
+        seed: Some(42),
+        additional: HashMap::from([("repetition_penalty".to_string(), json!(1.1))]),
+    };
+    let request = InferenceRequest::new(
+        "llama-3-8b-instruct",
+        vec![ChatMessage::system("Be brief."), ChatMessage::user("Hi")],
+    )
+    .with_parameters(parameters)
+    .with_logprobs(2);
+
+    OpenAiBackend::new(format!("{}/v1", server.uri()))
+        .complete(&request)
+        .await
+        .unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&received[0].body).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "model": "llama-3-8b-instruct",
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": "Hi"},
+            ],
+            "temperature": 0.0,
+            "max_tokens": 64,
+            "top_p": 0.9,
+            "presence_penalty": 0.5,
+            "stop": ["\n\n"],
+            "seed": 42,
+            "logprobs": true,
+            "top_logprobs": 2,
+            "repetition_penalty": 1.1,
+        })
+    );
+    assert!(received[0].headers.get("authorization").is_none());
+}
+
+#[tokio::test]
+async fn test_openai_backend_logprobs() {
+    let mut response = chat_response("Yes");
+    response["choices"][0]["logprobs"] = json!({
+        "content": [{
+            "token": "Yes",
+            "logprob": -0.01,
+            "bytes": [89, 101, 115],
+            "top_logprobs": [
+                {"token": "Yes", "logprob": -0.01, "bytes": [89, 101, 115]},
+                {"token": "No", "logprob": -4.6, "bytes": [78, 111]},
+            ],
+        }],
+    });
+    let server = stub(200, response).await;
+
+    let completion = OpenAiBackend::new(format!("{}/v1", server.uri()))
+        .complete(&request("Is water wet?").with_logprobs(2))
+        .await
+        .unwrap();
+
+    let logprobs = completion.logprobs.unwrap();
+    assert_eq!(logprobs.len(), 1);
+    assert_eq!(logprobs[0].token, "Yes");
+    assert_eq!(logprobs[0].logprob, -0.01);
+    assert_eq!(logprobs[0].top_logprobs[1].token, "No");
+}
+
+#[tokio::test]
+async fn test_openai_backend_error_classification() {
+    let cases = [
+        (
+            429,
+            json!({"error": {"message": "Rate limit reached"}}),
+            true,
+        ),
+        (503, json!({"error": "model is loading"}), true),
+        (
+            400,
+            json!({"error": {"message": "max_tokens is too large"}}),
+            false,
+        ),
+        (
+            401,
+            json!({"error": {"message": "Incorrect API key"}}),
+            false,
+        ),
+        (404, json!({"error": {"message": "model not found"}}), false),
+    ];
+
+    for (status, body, retryable) in cases {
+        let server = stub(status, body.clone()).await;
+        let error = OpenAiBackend::new(format!("{}/v1", server.uri()))
+            .complete(&request("Hi"))
+            .await
+            .unwrap_err();
+        assert_eq!(error.is_retryable(), retryable, "status {}", status);
+
+        let message = body["error"]["message"]
+            .as_str()
+            .or(body["error"].as_str())
+            .unwrap();
+        assert!(error.to_string().contains(message), "{}", error);
+    }
+
+    let unauthorized = stub(401, json!({"error": {"message": "Incorrect API key"}})).await;
+    let error = OpenAiBackend::new(format!("{}/v1", unauthorized.uri()))
+        .complete(&request("Hi"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, CoreError::Unauthorized(_)));
+}
+
+#[tokio::test]
+async fn test_openai_backend_malformed_response() {
+    let server = stub(200, json!({"unexpected": true})).await;
+    let error = OpenAiBackend::new(format!("{}/v1", server.uri()))
+        .complete(&request("Hi"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, CoreError::Serialization(_)));
+    assert!(!error.is_retryable());
+}
+
+#[tokio::test]
+async fn test_openai_backend_connection_refused_is_retryable() {
+    let error = OpenAiBackend::new("http://127.0.0.1:9/v1")
+        .complete(&request("Hi"))
+        .await
+        .unwrap_err();
+    assert!(error.is_retryable());
+}
+
+// ===== Anthropic Backend Tests =====
+
+fn messages_response(text: &str) -> serde_json::Value {
+    json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "model": "claude-sonnet-4-5",
+        "content": [{"type": "text", "text": text}],
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": {
+            "input_tokens": 20,
+            "output_tokens": 4,
+            "cache_read_input_tokens": 100,
+        },
+    })
+}
+
+async fn anthropic_stub(status: u16, body: serde_json::Value) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(ResponseTemplate::new(status).set_body_json(body))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn test_anthropic_backend_completion() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("x-api-key", "sk-ant-test"))
+        .and(header(
+            "anthropic-version",
+            AnthropicBackend::DEFAULT_VERSION,
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(messages_response("Paris")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let parameters = ModelParameters {
+        temperature: Some(0.0),
+        max_tokens: None,
+        top_p: None,
+        top_k: Some(40),
+        frequency_penalty: None,
+        presence_penalty: None,
+        stop_sequences: Some(vec!["\n\nHuman:".to_string()]),
+        seed: None,
+        additional: HashMap::new(),
+    };
+    let request = InferenceRequest::new(
+        "claude-sonnet-4-5",
+        vec![
+            ChatMessage::system("Answer in one word."),
+            ChatMessage::user("Capital of France?"),
+        ],
+    )
+    .with_parameters(parameters);
+    let backend = AnthropicBackend::new(server.uri()).with_api_key("sk-ant-test");
+    let completion = backend.complete(&request).await.unwrap();
+
+    assert_eq!(backend.name(), "anthropic");
+    assert_eq!(completion.text, "Paris");
+    assert_eq!(completion.model, "claude-sonnet-4-5");
+    assert_eq!(completion.finish_reason.as_deref(), Some("end_turn"));
+    // Cached prompt tokens count towards the prompt
+    assert_eq!(completion.usage.prompt_tokens, 120);
+    assert_eq!(completion.usage.completion_tokens, 4);
+
+    let received = server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&received[0].body).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "model": "claude-sonnet-4-5",
+            "system": "Answer in one word.",
+            "messages": [{"role": "user", "content": "Capital of France?"}],
+            "max_tokens": AnthropicBackend::DEFAULT_MAX_TOKENS,
+            "temperature": 0.0,
+            "top_k": 40,
+            "stop_sequences": ["\n\nHuman:"],
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_anthropic_backend_error_classification() {
+    let error_body = |kind: &str, message: &str| json!({"type": "error", "error": {"type": kind, "message": message}});
+    let cases = [
+        (529, error_body("overloaded_error", "Overloaded"), true),
+        (429, error_body("rate_limit_error", "Too many tokens"), true),
+        (500, error_body("api_error", "Internal server error"), true),
+        (
+            400,
+            error_body("invalid_request_error", "prompt is too long"),
+            false,
+        ),
+        (
+            413,
+            error_body("request_too_large", "Request exceeds the maximum size"),
+            false,
+        ),
+        (
+            401,
+            error_body("authentication_error", "invalid x-api-key"),
+            false,
+        ),
+    ];
+
+    for (status, body, retryable) in cases {
+        let server = anthropic_stub(status, body.clone()).await;
+        let error = AnthropicBackend::new(server.uri())
+            .complete(&request("Hi"))
+            .await
+            .unwrap_err();
+        assert_eq!(error.is_retryable(), retryable, "status {}", status);
+        let message = body["error"]["message"].as_str().unwrap();
+        assert!(error.to_string().contains(message), "{}", error);
+    }
+
+    let unauthorized =
+        anthropic_stub(401, error_body("authentication_error", "invalid x-api-key")).await;
+    let error = AnthropicBackend::new(unauthorized.uri())
+        .complete(&request("Hi"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, CoreError::Unauthorized(_)));
+
+    // Proxies in front of the API may answer with bodies of their own
+    let gateway = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+        .mount(&gateway)
+        .await;
+    let error = AnthropicBackend::new(gateway.uri())
+        .complete(&request("Hi"))
+        .await
+        .unwrap_err();
+    assert!(error.is_retryable());
+}
+
+// ===== Cohere Backend Tests =====
+
+fn cohere_response(text: &str) -> serde_json::Value {
+    json!({
+        "id": "c14c80c3",
+        "finish_reason": "COMPLETE",
+        "message": {
+            "role": "assistant",
+            "content": [{"type": "text", "text": text}],
+        },
+        "usage": {
+            "billed_units": {"input_tokens": 5, "output_tokens": 1},
+            "tokens": {"input_tokens": 71, "output_tokens": 1},
+        },
+        "logprobs": [{"token_ids": [1, 2], "text": text, "logprobs": [-0.25, -0.5]}],
+    })
+}
+
+async fn cohere_stub(status: u16, body: serde_json::Value) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/chat"))
+        .respond_with(ResponseTemplate::new(status).set_body_json(body))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn test_cohere_backend_completion() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/chat"))
+        .and(header("authorization", "Bearer co-test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(cohere_response("Paris")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let parameters = ModelParameters {
+        temperature: Some(0.3),
+        max_tokens: Some(8),
+        top_p: Some(0.75),
+        top_k: None,
+        frequency_penalty: None,
+        presence_penalty: None,
+        stop_sequences: Some(vec!["\n".to_string()]),
+        seed: Some(7),
+        additional: HashMap::new(),
+    };
+    let request = InferenceRequest::new(
+        "command-r-plus",
+        vec![
+            ChatMessage::system("Answer in one word."),
+            ChatMessage::user("Capital of France?"),
+        ],
+    )
+    .with_parameters(parameters)
+    .with_logprobs(0);
+    let backend = CohereBackend::new(server.uri()).with_api_key("co-test");
+    let completion = backend.complete(&request).await.unwrap();
+
+    assert_eq!(backend.name(), "cohere");
+    assert_eq!(completion.text, "Paris");
+    assert_eq!(completion.model, "command-r-plus");
+    assert_eq!(completion.finish_reason.as_deref(), Some("COMPLETE"));
+    assert_eq!(completion.usage.prompt_tokens, 71);
+    assert_eq!(completion.usage.completion_tokens, 1);
+    let logprobs = completion.logprobs.unwrap();
+    assert_eq!(logprobs[0].token, "Paris");
+    assert_eq!(logprobs[0].logprob, -0.75);
+
+    let received = server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&received[0].body).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "model": "command-r-plus",
+            "messages": [
+                {"role": "system", "content": "Answer in one word."},
+                {"role": "user", "content": "Capital of France?"},
+            ],
+            "max_tokens": 8,
+            "temperature": 0.3,
+            "p": 0.75,
+            "stop_sequences": ["\n"],
+            "seed": 7,
+            "logprobs": true,
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_cohere_backend_error_classification() {
+    let cases = [
+        (429, "You are using a Trial key, which is limited", true),
+        (499, "request cancelled", true),
+        (503, "service unavailable", true),
+        (400, "invalid request: message must not be empty", false),
+        (402, "please add a payment method", false),
+        (
+            422,
+            "unknown field: parameter foo is not a valid field",
+            false,
+        ),
+    ];
+
+    for (status, message, retryable) in cases {
+        let server = cohere_stub(status, json!({"message": message})).await;
+        let error = CohereBackend::new(server.uri())
+            .complete(&request("Hi"))
+            .await
+            .unwrap_err();
+        assert_eq!(error.is_retryable(), retryable, "status {}", status);
+        assert!(error.to_string().contains(message), "{}", error);
+    }
+}
+
+#[tokio::test]
+async fn test_inference_task_against_anthropic_stub() {
+    let server = anthropic_stub(200, messages_response("Paris")).await;
+    let config: InferenceConfig = serde_json::from_value(json!({
+        "provider": "anthropic",
+        "base_url": server.uri(),
+        "model": "claude-sonnet-4-5",
+        "prompts": ["Capital of France?"],
+        "system_prompt": "Answer in one word.",
+        "rate_limit_per_minute": 60000,
+    }))
+    .unwrap();
+    let result = InferenceTask::new(config)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap();
+
+    assert_eq!(result.output["provider"], "anthropic");
+    assert_eq!(result.output["results"][0]["response"], "Paris");
+    assert_eq!(result.output["total_tokens"], 124);
+}
+
+// ===== Streaming Tests =====
+
+fn sse(events: &[serde_json::Value]) -> String {
+    events
+        .iter()
+        .map(|event| format!("data: {}\n\n", event))
+        .collect()
+}
+
+async fn sse_stub(route: &str, body: String) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(route))
+        .and(body_partial_json(json!({"stream": true})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn assert_timing(completion: &Completion, tokens: usize) {
+    let timing = completion.timing.as_ref().unwrap();
+    assert_eq!(timing.arrivals_ms.len(), tokens);
+    assert_eq!(timing.first_token_ms, timing.arrivals_ms[0]);
+    assert!(timing.arrivals_ms.windows(2).all(|w| w[0] <= w[1]));
+}
+
+#[tokio::test]
+async fn test_openai_backend_stream() {
+    let chunk = |content: &str| {
+        json!({
+            "model": "llama-3-8b-instruct",
+            "choices": [{"index": 0, "delta": {"content": content}, "finish_reason": null}],
+        })
+    };
+    let mut body = sse(&[
+        json!({"model": "llama-3-8b-instruct", "choices": [{"index": 0, "delta": {"role": "assistant"}}]}),
+        chunk("The capital"),
+        chunk(" is"),
+        chunk(" Paris."),
+        json!({"model": "llama-3-8b-instruct", "choices": [{"index": 0, "delta": {}, "finish_reason": "stop"}]}),
+        json!({"model": "llama-3-8b-instruct", "choices": [], "usage": {"prompt_tokens": 12, "completion_tokens": 4}}),
+    ]);
+    body.push_str("data: [DONE]\n\n");
+    let server = sse_stub("/v1/chat/completions", body).await;
+
+    let completion = OpenAiBackend::new(format!("{}/v1", server.uri()))
+        .stream(&request("Capital of France?"))
+        .await
+        .unwrap();
+
+    assert_eq!(completion.text, "The capital is Paris.");
+    assert_eq!(completion.finish_reason.as_deref(), Some("stop"));
+    assert_eq!(completion.usage.prompt_tokens, 12);
+    assert_eq!(completion.usage.completion_tokens, 4);
+    assert_timing(&completion, 3);
+
+    let received = server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&received[0].body).unwrap();
+    assert_eq!(body["stream_options"], json!({"include_usage": true}));
+}
+
+#[tokio::test]
+async fn test_openai_backend_stream_without_usage() {
+    // Some servers ignore `stream_options`, end without `[DONE]` and split
+    // lines with CRLF
+    let body = sse(&[
+        json!({"choices": [{"delta": {"content": "Par"}}]}),
+        json!({"choices": [{"delta": {"content": "is"}, "finish_reason": "length"}]}),
+    ])
+    .replace('\n', "\r\n");
+    let server = sse_stub(
+        "/v1/chat/completions",
+        format!(": keep-alive\r\n\r\n{}", body),
+    )
+    .await;
+
+    let completion = OpenAiBackend::new(format!("{}/v1", server.uri()))
+        .stream(&request("Capital of France?"))
+        .await
+        .unwrap();
+
+    assert_eq!(completion.text, "Paris");
+    assert_eq!(completion.model, "llama-3-8b-instruct");
+    assert_eq!(completion.finish_reason.as_deref(), Some("length"));
+    assert_eq!(completion.usage.completion_tokens, 2);
+    assert_timing(&completion, 2);
+}
+
+#[tokio::test]
+async fn test_anthropic_backend_stream() {
+    let delta = |text: &str| json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": text}});
+    let body = sse(&[
+        json!({
+            "type": "message_start",
+            "message": {
+                "id": "msg_01",
+                "model": "claude-sonnet-4-5",
+                "content": [],
+                "usage": {"input_tokens": 20, "output_tokens": 1, "cache_read_input_tokens": 100},
+            },
+        }),
+        json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+        json!({"type": "ping"}),
+        delta("Par"),
+        delta("is"),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"}, "usage": {"output_tokens": 2}}),
+        json!({"type": "message_stop"}),
+    ]);
+    let server = sse_stub("/v1/messages", body).await;
+
+    let completion = AnthropicBackend::new(server.uri())
+        .stream(&request("Capital of France?"))
+        .await
+        .unwrap();
+
+    assert_eq!(completion.text, "Paris");
+    assert_eq!(completion.model, "claude-sonnet-4-5");
+    assert_eq!(completion.finish_reason.as_deref(), Some("end_turn"));
+    assert_eq!(completion.usage.prompt_tokens, 120);
+    assert_eq!(completion.usage.completion_tokens, 2);
+    assert_timing(&completion, 2);
+}
+
+#[tokio::test]
+async fn test_anthropic_backend_stream_error_is_retryable() {
+    let body = sse(&[
+        json!({
+            "type": "message_start",
+            "message": {"model": "claude-sonnet-4-5", "usage": {"input_tokens": 20}},
+        }),
+        json!({"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}),
+    ]);
+    let server = sse_stub("/v1/messages", body).await;
+
+    let error = AnthropicBackend::new(server.uri())
+        .stream(&request("Hi"))
+        .await
+        .unwrap_err();
+    assert!(error.is_retryable());
+    assert!(error.to_string().contains("Overloaded"));
+}
+
+#[tokio::test]
+async fn test_cohere_backend_stream() {
+    let delta = |text: &str, logprob: f64| {
+        json!({
+            "type": "content-delta",
+            "index": 0,
+            "delta": {"message": {"content": {"text": text}}},
+            "logprobs": {"token_ids": [1], "logprobs": [logprob]},
+        })
+    };
+    let body = sse(&[
+        json!({"type": "message-start", "id": "c14c80c3", "delta": {"message": {"role": "assistant"}}}),
+        json!({"type": "content-start", "index": 0, "delta": {"message": {"content": {"type": "text", "text": ""}}}}),
+        delta("Par", -0.5),
+        delta("is", -0.25),
+        json!({"type": "content-end", "index": 0}),
+        json!({
+            "type": "message-end",
+            "delta": {
+                "finish_reason": "COMPLETE",
+                "usage": {"tokens": {"input_tokens": 71, "output_tokens": 2}},
+            },
+        }),
+    ]);
+    let server = sse_stub("/v2/chat", body).await;
+
+    let completion = CohereBackend::new(server.uri())
+        .stream(&request("Capital of France?").with_logprobs(0))
+        .await
+        .unwrap();
+
+    assert_eq!(completion.text, "Paris");
+    assert_eq!(completion.finish_reason.as_deref(), Some("COMPLETE"));
+    assert_eq!(completion.usage.prompt_tokens, 71);
+    assert_eq!(completion.usage.completion_tokens, 2);
+    let logprobs = completion.logprobs.as_ref().unwrap();
+    assert_eq!(logprobs[1].token, "is");
+    assert_eq!(logprobs[1].logprob, -0.25);
+    assert_timing(&completion, 2);
+}
+
+#[tokio::test]
+async fn test_stream_records_token_arrival() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Send the events slowly, breaking one across writes
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![0; 65536];
+        let _ = socket.read(&mut request).await.unwrap();
+        let writes = [
+            "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\n",
+            "data: {\"choices\": [{\"delta\": {\"content\": \"Par\"}}]}\n\n",
+            "data: {\"choices\": [{\"delta\": {\"con",
+            "tent\": \"is\"}, \"finish_reason\": \"stop\"}]}\n\ndata: [DONE]\n\n",
+        ];
+        for (i, write) in writes.iter().enumerate() {
+            if i > 1 {
+                tokio::time::sleep(Duration::from_millis(150)).await;
+            }
+            socket.write_all(write.as_bytes()).await.unwrap();
+            socket.flush().await.unwrap();
+        }
+    });
+
+    let completion = OpenAiBackend::new(format!("http://{}/v1", address))
+        .stream(&request("Capital of France?"))
+        .await
+        .unwrap();
+
+    assert_eq!(completion.text, "Paris");
+    let timing = completion.timing.unwrap();
+    assert_eq!(timing.arrivals_ms.len(), 2);
+    assert!(timing.first_token_ms < 150.0, "{:?}", timing);
+    assert!(
+        timing.arrivals_ms[1] - timing.first_token_ms >= 250.0,
+        "{:?}",
+        timing
+    );
+}
+
+#[tokio::test]
+async fn test_simulated_backend_stream() {
+    let backend = SimulatedBackend;
+    let streamed = backend
+        .stream(&request("Capital of France?"))
+        .await
+        .unwrap();
+    let completed = backend
+        .complete(&request("Capital of France?"))
+        .await
+        .unwrap();
+
+    assert_eq!(streamed.text, completed.text);
+    assert_eq!(streamed.usage, completed.usage);
+    assert!(completed.timing.is_none());
+    let timing = streamed.timing.unwrap();
+    assert_eq!(timing.arrivals_ms.len(), streamed.text.split(' ').count());
+    assert!(timing.first_token_ms < *timing.arrivals_ms.last().unwrap());
+}
+
+#[tokio::test]
+async fn test_inference_task_streaming_latency() {
+    let config = InferenceConfig {
+        provider: InferenceProvider::Simulated,
+        prompts: vec![
+            "Capital of France?".to_string(),
+            "Capital of Spain?".to_string(),
+        ],
+        rate_limit_per_minute: 60_000,
+        stream: true,
+        ..Default::default()
+    };
+    let result = InferenceTask::new(config)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap();
+
+    let results: Vec<InferenceResult> =
+        serde_json::from_value(result.output["results"].clone()).unwrap();
+    for result in &results {
+        let ttft = result.ttft_ms.unwrap();
+        assert_eq!(result.token_times_ms[0], ttft);
+        assert!(ttft < result.latency_ms as f64);
+    }
+    let latency = latency_metrics(&results);
+    let mean_ttft = results.iter().map(|r| r.ttft_ms.unwrap()).sum::<f64>() / 2.0;
+    assert_eq!(latency.ttft, Some(mean_ttft));
+    assert!(latency.tokens_per_second.unwrap() > 0.0);
+    assert_eq!(result.output["latency"]["ttft"], json!(mean_ttft));
+
+    // Without streaming there is no time to first token
+    let config = InferenceConfig {
+        provider: InferenceProvider::Simulated,
+        prompts: vec!["Capital of France?".to_string()],
+        ..Default::default()
+    };
+    let result = InferenceTask::new(config)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap();
+    assert!(result.output["results"][0].get("ttft_ms").is_none());
+    assert!(result.output["latency"]["ttft"].is_null());
+}
+
+// ===== InferenceTask Backend Tests =====
+
+#[tokio::test]
+async fn test_inference_task_against_local_server() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(body_partial_json(json!({
+            "model": "llama-3-8b-instruct",
+            "temperature": 0.2,
+            "max_tokens": 16,
+            "messages": [
+                {"role": "system", "content": "Answer in one word."},
+                {"role": "user", "content": "Capital of France?"},
+            ],
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chat_response("Paris")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let config: InferenceConfig = serde_json::from_value(json!({
+        "provider": "local",
+        "base_url": format!("{}/v1", server.uri()),
+        "model": "llama-3-8b-instruct",
+        "prompts": ["Capital of France?"],
+        "system_prompt": "Answer in one word.",
+        "temperature": 0.2,
+        "max_tokens": 16,
+        "rate_limit_per_minute": 60000,
+    }))
+    .unwrap();
+    let result = InferenceTask::new(config)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap();
+
+    let output = result.output;
+    assert_eq!(output["provider"], "local");
+    assert_eq!(output["predictions_generated"], 1);
+    assert_eq!(output["total_tokens"], 15);
+    let prediction = &output["results"][0];
+    assert_eq!(prediction["response"], "Paris");
+    assert_eq!(prediction["model"], "llama-3-8b-instruct");
+    assert_eq!(prediction["prompt_tokens"], 12);
+    assert_eq!(prediction["completion_tokens"], 3);
+    assert_eq!(prediction["finish_reason"], "stop");
+}
+
+#[tokio::test]
+async fn test_inference_task_retries_server_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("upstream crashed"))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chat_response("Paris")))
+        .mount(&server)
+        .await;
+
+    let config = InferenceConfig {
+        prompts: vec!["Capital of France?".to_string()],
+        rate_limit_per_minute: 60_000,
+        backoff: Backoff::constant(Duration::from_millis(10)).with_jitter(JitterStrategy::None),
+        ..Default::default()
+    };
+    let backend = Arc::new(OpenAiBackend::new(format!("{}/v1", server.uri())));
+    let result = InferenceTask::new(config)
+        .with_backend(backend)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap();
+
+    assert_eq!(result.output["results"][0]["response"], "Paris");
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_inference_task_does_not_retry_rejected_requests() {
+    let server = stub(400, json!({"error": {"message": "prompt is too long"}})).await;
+    let config = InferenceConfig {
+        prompts: vec!["Capital of France?".to_string()],
+        backoff: Backoff::constant(Duration::from_millis(10)),
+        ..Default::default()
+    };
+    let backend = Arc::new(OpenAiBackend::new(format!("{}/v1", server.uri())));
+    let error = InferenceTask::new(config)
+        .with_backend(backend)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap_err();
+
+    assert!(error.to_string().contains("prompt is too long"));
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
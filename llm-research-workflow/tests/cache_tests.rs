@@ -0,0 +1,458 @@
+use async_trait::async_trait;
+use llm_research_core::{ContentHash, Result};
+use llm_research_workflow::*;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Echoes its config and inputs, counting runs per task type
+struct CountingTask {
+    task_type: String,
+    config: serde_json::Value,
+    runs: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+#[async_trait]
+impl Task for CountingTask {
+    async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
+        *self
+            .runs
+            .lock()
+            .unwrap()
+            .entry(self.task_type.clone())
+            .or_default() += 1;
+        if self.config["fail"] == true {
+            return Ok(TaskResult::failure("judge unavailable".to_string()));
+        }
+        Ok(TaskResult::success(json!({
+            "config": self.config,
+            "inputs": context.inputs,
+        })))
+    }
+
+    fn name(&self) -> &str {
+        &self.task_type
+    }
+}
+
+struct Counted {
+    cache: Arc<InMemoryOutputCache>,
+    runs: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl Counted {
+    fn new() -> Self {
+        Self {
+            cache: Arc::new(InMemoryOutputCache::new()),
+            runs: Default::default(),
+        }
+    }
+
+    /// Engine counting its runs here and caching outputs in `self.cache`
+    fn engine(&self) -> DefaultWorkflowEngine {
+        self.engine_with(self.cache.clone())
+    }
+
+    fn engine_with(&self, cache: Arc<dyn OutputCache>) -> DefaultWorkflowEngine {
+        let mut registry = TaskRegistry::new();
+        for task_type in ["load", "infer", "report"] {
+            let runs = self.runs.clone();
+            registry.register(task_type, move |config| {
+                Ok(Arc::new(CountingTask {
+                    task_type: task_type.to_string(),
+                    config: config.clone(),
+                    runs: runs.clone(),
+                }) as Arc<dyn Task>)
+            });
+        }
+        DefaultWorkflowEngine::with_registry(registry).with_output_cache(cache)
+    }
+
+    fn runs(&self, task_type: &str) -> usize {
+        self.runs
+            .lock()
+            .unwrap()
+            .get(task_type)
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// load -> infer -> report
+fn pipeline(report_format: &str) -> Workflow {
+    let load = WorkflowStep::new(
+        "load".to_string(),
+        "load".to_string(),
+        json!({"path": "data/cnn.jsonl"}),
+    );
+    let infer = WorkflowStep::new(
+        "infer".to_string(),
+        "infer".to_string(),
+        json!({"model": "gpt-4", "dataset": "${steps.load.output.config.path}"}),
+    )
+    .with_dependencies(vec![load.id]);
+    let report = WorkflowStep::new(
+        "report".to_string(),
+        "report".to_string(),
+        json!({"format": report_format}),
+    )
+    .with_dependencies(vec![infer.id]);
+    Workflow::new("Cached".to_string(), vec![load, infer, report])
+}
+
+fn cache_hit(state: &WorkflowState, name: &str) -> bool {
+    let step = state
+        .workflow
+        .steps
+        .iter()
+        .find(|s| s.name == name)
+        .unwrap();
+    state.step_outputs[&step.id][CACHE_RECORD_FIELD]["hit"] == true
+}
+
+// ===== Cache Key Tests =====
+
+#[test]
+fn test_hash_json_ignores_key_order() {
+    let mut forward = serde_json::Map::new();
+    forward.insert("a".to_string(), json!(1));
+    forward.insert("b".to_string(), json!({"x": 1, "y": 2}));
+    let mut backward = serde_json::Map::new();
+    backward.insert("b".to_string(), json!({"y": 2, "x": 1}));
+    backward.insert("a".to_string(), json!(1));
+
+    assert_eq!(
+        hash_json(&serde_json::Value::Object(forward)),
+        hash_json(&serde_json::Value::Object(backward))
+    );
+    assert_ne!(hash_json(&json!({"a": 1})), hash_json(&json!({"a": 2})));
+}
+
+#[test]
+fn test_cache_key_is_deterministic() {
+    let mut inputs = HashMap::new();
+    inputs.insert("load".to_string(), json!({"path": "a", "rows": 3}));
+    inputs.insert("split".to_string(), json!({"seed": 7}));
+    let config = json!({"model": "gpt-4", "temperature": 0.2});
+
+    let key = cache_key("inference", &config, &inputs, "1.0.0");
+    let reordered: HashMap<String, serde_json::Value> = [
+        ("split".to_string(), json!({"seed": 7})),
+        ("load".to_string(), json!({"rows": 3, "path": "a"})),
+    ]
+    .into();
+
+    assert_eq!(key, cache_key("inference", &config, &reordered, "1.0.0"));
+    assert_eq!(key.as_str().len(), 64);
+}
+
+#[test]
+fn test_cache_key_covers_task_config_inputs_and_code() {
+    let inputs: HashMap<String, serde_json::Value> =
+        [("load".to_string(), json!({"path": "a"}))].into();
+    let config = json!({"model": "gpt-4"});
+    let key = cache_key("inference", &config, &inputs, "1.0.0");
+
+    assert_ne!(key, cache_key("evaluation", &config, &inputs, "1.0.0"));
+    assert_ne!(
+        key,
+        cache_key("inference", &json!({"model": "gpt-3.5"}), &inputs, "1.0.0")
+    );
+    assert_ne!(
+        key,
+        cache_key("inference", &config, &HashMap::new(), "1.0.0")
+    );
+    assert_ne!(key, cache_key("inference", &config, &inputs, "1.0.1"));
+}
+
+#[test]
+fn test_cache_key_ignores_cache_records() {
+    let plain: HashMap<String, serde_json::Value> =
+        [("load".to_string(), json!({"path": "a"}))].into();
+    let recorded: HashMap<String, serde_json::Value> = [(
+        "load".to_string(),
+        with_cache_record(json!({"path": "a"}), &ContentHash::from_str("x"), true),
+    )]
+    .into();
+
+    assert_eq!(
+        cache_key("inference", &json!({}), &plain, "1.0.0"),
+        cache_key("inference", &json!({}), &recorded, "1.0.0")
+    );
+}
+
+// ===== Store Tests =====
+
+#[tokio::test]
+async fn test_in_memory_cache_roundtrip() {
+    let cache = InMemoryOutputCache::new();
+    let key = ContentHash::from_str("step");
+
+    assert_eq!(cache.get(&key).await.unwrap(), None);
+    cache.put(&key, &json!({"rows": 3})).await.unwrap();
+    assert_eq!(cache.get(&key).await.unwrap(), Some(json!({"rows": 3})));
+    assert_eq!(cache.len().await, 1);
+
+    cache.remove(&key).await.unwrap();
+    assert!(cache.is_empty().await);
+}
+
+#[tokio::test]
+async fn test_file_cache_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = FileOutputCache::new(dir.path().join("cache"))
+        .await
+        .unwrap();
+    let key = ContentHash::from_str("step");
+
+    assert_eq!(cache.get(&key).await.unwrap(), None);
+    cache.put(&key, &json!({"rows": 3})).await.unwrap();
+    assert!(cache.dir().join(format!("{}.json", key)).exists());
+    assert_eq!(cache.get(&key).await.unwrap(), Some(json!({"rows": 3})));
+
+    cache.remove(&key).await.unwrap();
+    cache.remove(&key).await.unwrap();
+    assert_eq!(cache.get(&key).await.unwrap(), None);
+}
+
+// ===== Engine Caching Tests =====
+
+#[tokio::test]
+async fn test_rerun_reuses_cached_outputs() {
+    let counted = Counted::new();
+
+    let first = counted.engine().execute(&pipeline("html")).await.unwrap();
+    let second = counted.engine().execute(&pipeline("html")).await.unwrap();
+
+    for task_type in ["load", "infer", "report"] {
+        assert_eq!(counted.runs(task_type), 1, "{}", task_type);
+        assert!(!cache_hit(&first, task_type));
+        assert!(cache_hit(&second, task_type));
+    }
+    assert_eq!(second.workflow.status, WorkflowStatus::Completed);
+    assert!(second
+        .workflow
+        .steps
+        .iter()
+        .all(|s| s.status == WorkflowStatus::Completed));
+
+    // Same output apart from the cache record
+    let report = second.workflow.steps[2].id;
+    assert_eq!(
+        without_cache_record(&second.step_outputs[&report]),
+        without_cache_record(&first.step_outputs[&first.workflow.steps[2].id])
+    );
+    assert_eq!(
+        first.step_outputs[&first.workflow.steps[2].id][CACHE_RECORD_FIELD]["key"],
+        second.step_outputs[&report][CACHE_RECORD_FIELD]["key"]
+    );
+}
+
+#[tokio::test]
+async fn test_changing_report_step_only_reruns_report() {
+    let counted = Counted::new();
+    counted.engine().execute(&pipeline("html")).await.unwrap();
+
+    let state = counted
+        .engine()
+        .execute(&pipeline("markdown"))
+        .await
+        .unwrap();
+
+    assert!(cache_hit(&state, "load"));
+    assert!(cache_hit(&state, "infer"));
+    assert!(!cache_hit(&state, "report"));
+    assert_eq!(counted.runs("infer"), 1);
+    assert_eq!(counted.runs("report"), 2);
+}
+
+#[tokio::test]
+async fn test_changed_upstream_output_reruns_downstream() {
+    let counted = Counted::new();
+    counted.engine().execute(&pipeline("html")).await.unwrap();
+
+    let mut workflow = pipeline("html");
+    workflow.steps[0].config = json!({"path": "data/xsum.jsonl"});
+    let state = counted.engine().execute(&workflow).await.unwrap();
+
+    assert!(!cache_hit(&state, "load"));
+    assert!(!cache_hit(&state, "infer"));
+    assert!(!cache_hit(&state, "report"));
+    assert_eq!(counted.runs("report"), 2);
+}
+
+#[tokio::test]
+async fn test_code_version_change_misses() {
+    let counted = Counted::new();
+    counted
+        .engine()
+        .with_code_version("abc123")
+        .execute(&pipeline("html"))
+        .await
+        .unwrap();
+
+    let state = counted
+        .engine()
+        .with_code_version("def456")
+        .execute(&pipeline("html"))
+        .await
+        .unwrap();
+
+    assert!(!cache_hit(&state, "load"));
+    assert_eq!(counted.runs("load"), 2);
+}
+
+#[tokio::test]
+async fn test_invalidated_step_and_downstream_rerun() {
+    let counted = Counted::new();
+    counted.engine().execute(&pipeline("html")).await.unwrap();
+
+    let workflow = pipeline("html").with_invalidated(vec!["infer".to_string()]);
+    let state = counted.engine().execute(&workflow).await.unwrap();
+
+    assert!(cache_hit(&state, "load"));
+    assert!(!cache_hit(&state, "infer"));
+    assert!(!cache_hit(&state, "report"));
+    assert_eq!(counted.runs("load"), 1);
+    assert_eq!(counted.runs("infer"), 2);
+    assert_eq!(counted.runs("report"), 2);
+
+    // The re-run refreshed the cache
+    let state = counted.engine().execute(&pipeline("html")).await.unwrap();
+    assert!(cache_hit(&state, "report"));
+}
+
+#[tokio::test]
+async fn test_invalidating_unknown_step_fails() {
+    let counted = Counted::new();
+    let workflow = pipeline("html").with_invalidated(vec!["inference".to_string()]);
+
+    let error = counted.engine().execute(&workflow).await.unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Cannot invalidate unknown step 'inference'"));
+    assert_eq!(counted.runs("load"), 0);
+}
+
+#[tokio::test]
+async fn test_uncached_step_always_runs() {
+    let counted = Counted::new();
+    let mut workflow = pipeline("html");
+    workflow.steps[2].cache = false;
+
+    counted.engine().execute(&workflow).await.unwrap();
+    let state = counted.engine().execute(&workflow).await.unwrap();
+
+    assert!(cache_hit(&state, "infer"));
+    let report = &state.step_outputs[&state.workflow.steps[2].id];
+    assert!(report.get(CACHE_RECORD_FIELD).is_none());
+    assert_eq!(counted.runs("report"), 2);
+}
+
+#[tokio::test]
+async fn test_failed_steps_are_not_cached() {
+    let counted = Counted::new();
+    let mut workflow = pipeline("html");
+    workflow.steps[2].config = json!({"fail": true});
+    workflow.steps[2].max_retries = 0;
+
+    assert!(counted.engine().execute(&workflow).await.is_err());
+    assert!(counted.engine().execute(&workflow).await.is_err());
+
+    assert_eq!(counted.runs("report"), 2);
+    assert_eq!(counted.cache.len().await, 2);
+}
+
+#[tokio::test]
+async fn test_tasks_do_not_see_cache_records() {
+    let counted = Counted::new();
+    counted.engine().execute(&pipeline("html")).await.unwrap();
+
+    let mut workflow = pipeline("html");
+    workflow.steps[1].config["whole_load"] = json!("${steps.load.output}");
+    let state = counted.engine().execute(&workflow).await.unwrap();
+
+    let infer = &state.step_outputs[&state.workflow.steps[1].id];
+    assert!(infer["inputs"]["load"].get(CACHE_RECORD_FIELD).is_none());
+    assert!(infer["config"]["whole_load"]
+        .get(CACHE_RECORD_FIELD)
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_fan_out_cached_by_items() {
+    let counted = Counted::new();
+    let fanned = |items: serde_json::Value| {
+        let step = WorkflowStep::new("infer".to_string(), "infer".to_string(), json!({}))
+            .with_fan_out(FanOut::new(items).with_item_key("model"));
+        Workflow::new("Fan Out".to_string(), vec![step])
+    };
+
+    counted
+        .engine()
+        .execute(&fanned(json!(["a", "b"])))
+        .await
+        .unwrap();
+    let same = counted
+        .engine()
+        .execute(&fanned(json!(["a", "b"])))
+        .await
+        .unwrap();
+    let more = counted
+        .engine()
+        .execute(&fanned(json!(["a", "b", "c"])))
+        .await
+        .unwrap();
+
+    assert!(cache_hit(&same, "infer"));
+    assert!(!cache_hit(&more, "infer"));
+    assert_eq!(counted.runs("infer"), 5);
+}
+
+#[tokio::test]
+async fn test_file_cache_survives_engines() {
+    let dir = tempfile::tempdir().unwrap();
+    let counted = Counted::new();
+    let cache = || async { Arc::new(FileOutputCache::new(dir.path()).await.unwrap()) };
+
+    counted
+        .engine_with(cache().await)
+        .execute(&pipeline("html"))
+        .await
+        .unwrap();
+    let state = counted
+        .engine_with(cache().await)
+        .execute(&pipeline("html"))
+        .await
+        .unwrap();
+
+    assert!(cache_hit(&state, "report"));
+    assert_eq!(counted.runs("report"), 1);
+}
+
+#[tokio::test]
+async fn test_no_cache_by_default() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut registry = TaskRegistry::new();
+    let counter = runs.clone();
+    registry.register("load", move |config| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Ok(Arc::new(CountingTask {
+            task_type: "load".to_string(),
+            config: config.clone(),
+            runs: Default::default(),
+        }) as Arc<dyn Task>)
+    });
+    let engine = DefaultWorkflowEngine::with_registry(registry);
+    let step = WorkflowStep::new("load".to_string(), "load".to_string(), json!({}));
+    let workflow = Workflow::new("Uncached".to_string(), vec![step]);
+
+    engine.execute(&workflow).await.unwrap();
+    let state = engine.execute(&workflow).await.unwrap();
+
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+    assert!(state.step_outputs[&state.workflow.steps[0].id]
+        .get(CACHE_RECORD_FIELD)
+        .is_none());
+}
@@ -0,0 +1,237 @@
+use llm_research_core::{CoreError, ModelParameters};
+use llm_research_workflow::*;
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn request(prompt: &str) -> InferenceRequest {
+    InferenceRequest::new("llama-3-8b-instruct", vec![ChatMessage::user(prompt)])
+}
+
+async fn stub(answer: &str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "model": "llama-3-8b-instruct",
+            "choices": [{"message": {"role": "assistant", "content": answer}, "finish_reason": "stop"}],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 3},
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn openai(server: &MockServer) -> Arc<dyn InferenceBackend> {
+    Arc::new(OpenAiBackend::new(format!("{}/v1", server.uri())).with_name("local"))
+}
+
+// ===== Cassette Key Tests =====
+
+#[test]
+fn test_cassette_key_covers_request() {
+    let base = cassette_key("openai", &request("Hi"));
+    assert_eq!(base, cassette_key("openai", &request("Hi")));
+
+    assert_ne!(base, cassette_key("anthropic", &request("Hi")));
+    assert_ne!(base, cassette_key("openai", &request("Hello")));
+    let mut other_model = request("Hi");
+    other_model.model = "gpt-4o".to_string();
+    assert_ne!(base, cassette_key("openai", &other_model));
+    let with_system = InferenceRequest::new(
+        "llama-3-8b-instruct",
+        vec![ChatMessage::system("Be brief."), ChatMessage::user("Hi")],
+    );
+    assert_ne!(base, cassette_key("openai", &with_system));
+    let parameters = ModelParameters {
+        temperature: Some(0.0),
+        ..Default::default()
+    };
+    assert_ne!(
+        base,
+        cassette_key("openai", &request("Hi").with_parameters(parameters))
+    );
+    assert_ne!(
+        base,
+        cassette_key("openai", &request("Hi").with_logprobs(1))
+    );
+}
+
+// ===== Cassette Mode Tests =====
+
+#[tokio::test]
+async fn test_cassette_records_then_replays() {
+    let server = stub("Paris").await;
+    let store = Arc::new(InMemoryOutputCache::new());
+    let cassette = CassetteBackend::new(openai(&server), store.clone(), CassetteMode::Record);
+
+    let recorded = cassette
+        .complete(&request("Capital of France?"))
+        .await
+        .unwrap();
+    let replayed = cassette
+        .complete(&request("Capital of France?"))
+        .await
+        .unwrap();
+
+    assert_eq!(cassette.name(), "local");
+    assert_eq!(recorded.text, "Paris");
+    assert_eq!(replayed, recorded);
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    assert_eq!(store.len().await, 1);
+
+    let key = cassette_key("local", &request("Capital of France?"));
+    let entry: CassetteEntry =
+        serde_json::from_value(store.get(&key).await.unwrap().unwrap()).unwrap();
+    assert_eq!(entry.provider, "local");
+    assert_eq!(entry.request, request("Capital of France?"));
+    assert_eq!(entry.completion, recorded);
+
+    // Streaming replays the same recording
+    let streamed = cassette
+        .stream(&request("Capital of France?"))
+        .await
+        .unwrap();
+    assert_eq!(streamed, recorded);
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_cassette_replay_only_fails_on_miss() {
+    let server = stub("Paris").await;
+    let store = Arc::new(InMemoryOutputCache::new());
+    CassetteBackend::new(openai(&server), store.clone(), CassetteMode::Record)
+        .complete(&request("Capital of France?"))
+        .await
+        .unwrap();
+
+    let replay = CassetteBackend::new(openai(&server), store.clone(), CassetteMode::ReplayOnly);
+    let replayed = replay
+        .complete(&request("Capital of France?"))
+        .await
+        .unwrap();
+    assert_eq!(replayed.text, "Paris");
+
+    let error = replay
+        .complete(&request("Capital of Spain?"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, CoreError::NotFound(_)));
+    assert!(!error.is_retryable());
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    assert_eq!(store.len().await, 1);
+
+    // No client is needed to replay
+    let offline = CassetteBackend::replay("local", store);
+    assert_eq!(offline.mode(), CassetteMode::ReplayOnly);
+    assert_eq!(
+        offline
+            .complete(&request("Capital of France?"))
+            .await
+            .unwrap(),
+        replayed
+    );
+}
+
+#[tokio::test]
+async fn test_cassette_passthrough_neither_reads_nor_records() {
+    let server = stub("Paris").await;
+    let store = Arc::new(InMemoryOutputCache::new());
+    CassetteBackend::new(openai(&server), store.clone(), CassetteMode::Record)
+        .complete(&request("Capital of France?"))
+        .await
+        .unwrap();
+
+    let passthrough =
+        CassetteBackend::new(openai(&server), store.clone(), CassetteMode::Passthrough);
+    passthrough
+        .complete(&request("Capital of France?"))
+        .await
+        .unwrap();
+    passthrough
+        .complete(&request("Capital of Spain?"))
+        .await
+        .unwrap();
+
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    assert_eq!(store.len().await, 1);
+}
+
+#[test]
+fn test_cassette_mode_serialization() {
+    assert_eq!(
+        serde_json::to_value(CassetteMode::ReplayOnly).unwrap(),
+        json!("replay-only")
+    );
+    let config: CassetteConfig = serde_json::from_value(json!({"dir": "cassettes"})).unwrap();
+    assert_eq!(config.mode, CassetteMode::Record);
+}
+
+// ===== InferenceTask Cassette Tests =====
+
+#[tokio::test]
+async fn test_inference_task_replays_offline() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = stub("Paris").await;
+    let prompts = json!(["Capital of France?", "Capital of Italy?"]);
+
+    let record: InferenceConfig = serde_json::from_value(json!({
+        "provider": "openai",
+        "base_url": format!("{}/v1", server.uri()),
+        "model": "llama-3-8b-instruct",
+        "prompts": prompts,
+        "rate_limit_per_minute": 60000,
+        "cassette": {"dir": dir.path(), "mode": "record"},
+    }))
+    .unwrap();
+    let recorded = InferenceTask::new(record)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap();
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    drop(server);
+
+    // Without the server or an API key
+    let replay: InferenceConfig = serde_json::from_value(json!({
+        "provider": "openai",
+        "api_key_env": "RESEARCH_LAB_TEST_UNSET_KEY",
+        "model": "llama-3-8b-instruct",
+        "prompts": prompts,
+        "rate_limit_per_minute": 60000,
+        "cassette": {"dir": dir.path(), "mode": "replay-only"},
+    }))
+    .unwrap();
+    let replayed = InferenceTask::new(replay.clone())
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap();
+
+    let responses = |output: &serde_json::Value| -> Vec<serde_json::Value> {
+        output["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                json!([
+                    r["provider"],
+                    r["response"],
+                    r["prompt_tokens"],
+                    r["completion_tokens"]
+                ])
+            })
+            .collect()
+    };
+    assert_eq!(replayed.output["provider"], "openai");
+    assert_eq!(responses(&replayed.output), responses(&recorded.output));
+
+    // A prompt that was never recorded
+    let mut missing = replay;
+    missing.prompts = vec!["Capital of Spain?".to_string()];
+    let error = InferenceTask::new(missing)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, CoreError::NotFound(_)));
+}
@@ -0,0 +1,172 @@
+use llm_research_workflow::*;
+use serde_json::json;
+use std::collections::HashMap;
+
+fn outputs() -> HashMap<String, serde_json::Value> {
+    let mut outputs = HashMap::new();
+    outputs.insert(
+        "evaluate".to_string(),
+        json!({"accuracy": 0.82, "model": "gpt-4", "failures": []}),
+    );
+    outputs
+}
+
+fn params() -> serde_json::Value {
+    json!({"threshold": 0.9, "judge": true, "models": ["gpt-4", "claude"]})
+}
+
+fn holds(condition: &Condition) -> bool {
+    condition.evaluate(&outputs(), &params()).unwrap()
+}
+
+// ===== Comparison Tests =====
+
+#[test]
+fn test_compare_numbers() {
+    let accuracy = json!("${steps.evaluate.output.accuracy}");
+    let threshold = json!("${params.threshold}");
+
+    assert!(holds(&Condition::compare(
+        accuracy.clone(),
+        CompareOp::Lt,
+        threshold.clone()
+    )));
+    assert!(holds(&Condition::compare(
+        accuracy.clone(),
+        CompareOp::Le,
+        json!(0.82)
+    )));
+    assert!(!holds(&Condition::compare(
+        accuracy.clone(),
+        CompareOp::Gt,
+        threshold.clone()
+    )));
+    assert!(holds(&Condition::compare(
+        threshold,
+        CompareOp::Ge,
+        accuracy
+    )));
+}
+
+#[test]
+fn test_compare_equality() {
+    assert!(holds(&Condition::compare(
+        json!("${steps.evaluate.output.model}"),
+        CompareOp::Eq,
+        json!("gpt-4"),
+    )));
+    assert!(holds(&Condition::compare(
+        json!("${params.models}"),
+        CompareOp::Ne,
+        json!([]),
+    )));
+    // Integers and floats with the same value are equal
+    assert!(holds(&Condition::compare(
+        json!(1),
+        CompareOp::Eq,
+        json!(1.0)
+    )));
+}
+
+#[test]
+fn test_compare_strings_lexically() {
+    assert!(holds(&Condition::compare(
+        json!("claude"),
+        CompareOp::Lt,
+        json!("gpt-4")
+    )));
+}
+
+#[test]
+fn test_compare_incompatible_types() {
+    let condition = Condition::compare(
+        json!("${steps.evaluate.output.model}"),
+        CompareOp::Gt,
+        json!(1),
+    );
+
+    let message = condition
+        .evaluate(&outputs(), &params())
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("Cannot compare \"gpt-4\" with 1"));
+}
+
+// ===== Combinator Tests =====
+
+#[test]
+fn test_truthy() {
+    assert!(holds(&Condition::Truthy(json!("${params.judge}"))));
+    assert!(!holds(&Condition::Truthy(json!(
+        "${steps.evaluate.output.failures}"
+    ))));
+    assert!(!holds(&Condition::Truthy(json!(0))));
+    assert!(!holds(&Condition::Truthy(json!(""))));
+    assert!(!holds(&Condition::Truthy(json!(null))));
+    assert!(holds(&Condition::Truthy(json!({"key": "value"}))));
+}
+
+#[test]
+fn test_all_any_not() {
+    let yes = Condition::Truthy(json!(true));
+    let no = Condition::Truthy(json!(false));
+
+    assert!(holds(&Condition::All(vec![yes.clone(), yes.clone()])));
+    assert!(!holds(&Condition::All(vec![yes.clone(), no.clone()])));
+    assert!(holds(&Condition::Any(vec![no.clone(), yes.clone()])));
+    assert!(!holds(&Condition::Any(vec![])));
+    assert!(holds(&Condition::All(vec![])));
+    assert!(holds(&Condition::Not(Box::new(no))));
+}
+
+#[test]
+fn test_missing_reference_is_an_error() {
+    let condition = Condition::Truthy(json!("${steps.judge.output.score}"));
+
+    let message = condition
+        .evaluate(&outputs(), &params())
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("step 'judge' has no output"));
+}
+
+// ===== Serialization Tests =====
+
+#[test]
+fn test_condition_serialization() {
+    let condition: Condition = serde_json::from_value(json!({
+        "any": [
+            {"compare": {"left": "${steps.evaluate.output.accuracy}", "op": "lt", "right": 0.9}},
+            {"not": {"truthy": "${params.judge}"}},
+        ]
+    }))
+    .unwrap();
+
+    assert_eq!(
+        condition,
+        Condition::Any(vec![
+            Condition::compare(
+                json!("${steps.evaluate.output.accuracy}"),
+                CompareOp::Lt,
+                json!(0.9)
+            ),
+            Condition::Not(Box::new(Condition::Truthy(json!("${params.judge}")))),
+        ])
+    );
+    assert!(holds(&condition));
+}
+
+#[test]
+fn test_step_trigger_default_and_serialization() {
+    assert_eq!(StepTrigger::default(), StepTrigger::OnSuccess);
+    assert!(!StepTrigger::OnSuccess.is_cleanup());
+    assert!(StepTrigger::OnFailure.is_cleanup());
+    assert_eq!(
+        serde_json::to_string(&StepTrigger::OnFailure).unwrap(),
+        "\"on_failure\""
+    );
+    assert_eq!(
+        serde_json::from_str::<StepTrigger>("\"always\"").unwrap(),
+        StepTrigger::Always
+    );
+}
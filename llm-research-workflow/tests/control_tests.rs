@@ -0,0 +1,119 @@
+use llm_research_core::Result;
+use llm_research_workflow::*;
+use std::time::Duration;
+
+// ===== PauseToken Tests =====
+
+#[tokio::test]
+async fn test_pause_token_toggles() {
+    let token = PauseToken::new();
+    assert!(!token.is_paused());
+
+    token.pause();
+    assert!(token.is_paused());
+    assert!(token.clone().is_paused());
+
+    token.resume();
+    assert!(!token.is_paused());
+}
+
+#[tokio::test]
+async fn test_pause_token_resumed_wakes_waiter() {
+    let token = PauseToken::new();
+    token.pause();
+
+    let waiter = token.clone();
+    let handle = tokio::spawn(async move { waiter.resumed().await });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!handle.is_finished());
+
+    token.resume();
+    tokio::time::timeout(Duration::from_secs(1), handle)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+// ===== TaskControl Tests =====
+
+#[tokio::test]
+async fn test_checkpoint_passes_when_running() {
+    let control = TaskControl::new();
+    assert!(control.checkpoint().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_checkpoint_fails_once_cancelled() {
+    let control = TaskControl::new();
+    control.cancel();
+
+    assert!(control.is_cancelled());
+    let error = control.checkpoint().await.unwrap_err();
+    assert!(error.to_string().contains("cancelled"));
+}
+
+#[tokio::test]
+async fn test_checkpoint_waits_while_paused() {
+    let control = TaskControl::new();
+    control.pause();
+
+    let task_control = control.clone();
+    let handle = tokio::spawn(async move { task_control.checkpoint().await });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!handle.is_finished());
+
+    control.resume();
+    assert!(handle.await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn test_cancel_releases_paused_checkpoint() {
+    let control = TaskControl::new();
+    control.pause();
+
+    let task_control = control.clone();
+    let handle = tokio::spawn(async move { task_control.checkpoint().await });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    control.cancel();
+
+    assert!(handle.await.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn test_child_control() {
+    let parent = TaskControl::new();
+    let first = parent.child();
+    let second = parent.child();
+
+    first.cancel();
+    assert!(first.is_cancelled());
+    assert!(!second.is_cancelled());
+    assert!(!parent.is_cancelled());
+
+    parent.pause();
+    assert!(second.is_paused());
+
+    parent.cancel();
+    assert!(second.is_cancelled());
+}
+
+#[tokio::test]
+async fn test_run_until_cancelled() {
+    let control = TaskControl::new();
+    let value: Result<u32> = control.run_until_cancelled(async { Ok(7) }).await;
+    assert_eq!(value.unwrap(), 7);
+
+    let canceller = control.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        canceller.cancel();
+    });
+
+    let result: Result<()> = control
+        .run_until_cancelled(async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        })
+        .await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,520 @@
+use llm_research_core::CoreError;
+use llm_research_workflow::*;
+use parquet::data_type::{ByteArray, ByteArrayType, Int32Type};
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn write(dir: &Path, name: &str, contents: impl AsRef<[u8]>) -> String {
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+/// A Parquet file of (question, answer, label) rows
+fn parquet_file(rows: &[(&str, &str, i32)]) -> Vec<u8> {
+    let schema = parse_message_type(
+        "message qa {
+            REQUIRED BYTE_ARRAY question (UTF8);
+            REQUIRED BYTE_ARRAY answer (UTF8);
+            REQUIRED INT32 label;
+        }",
+    )
+    .unwrap();
+    let mut bytes = Vec::new();
+    let mut file =
+        SerializedFileWriter::new(&mut bytes, Arc::new(schema), Default::default()).unwrap();
+    let mut group = file.next_row_group().unwrap();
+    for column in 0..2 {
+        let values: Vec<ByteArray> = rows
+            .iter()
+            .map(|row| ByteArray::from(if column == 0 { row.0 } else { row.1 }))
+            .collect();
+        let mut writer = group.next_column().unwrap().unwrap();
+        writer
+            .typed::<ByteArrayType>()
+            .write_batch(&values, None, None)
+            .unwrap();
+        writer.close().unwrap();
+    }
+    let labels: Vec<i32> = rows.iter().map(|row| row.2).collect();
+    let mut writer = group.next_column().unwrap().unwrap();
+    writer
+        .typed::<Int32Type>()
+        .write_batch(&labels, None, None)
+        .unwrap();
+    writer.close().unwrap();
+    group.close().unwrap();
+    file.close().unwrap();
+    bytes
+}
+
+fn qa_fields() -> FieldMapping {
+    FieldMapping {
+        input: Some("question".to_string()),
+        expected_output: Some("answer".to_string()),
+    }
+}
+
+async fn load(config: DataLoadingConfig) -> serde_json::Value {
+    DataLoadingTask::new(config)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap()
+        .output
+}
+
+async fn load_error(config: DataLoadingConfig) -> CoreError {
+    DataLoadingTask::new(config)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap_err()
+}
+
+fn samples(output: &serde_json::Value) -> Vec<serde_json::Value> {
+    output["batches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|batch| batch["samples"].as_array().unwrap().clone())
+        .collect()
+}
+
+// ===== Source Tests =====
+
+#[test]
+fn test_data_source_parse() {
+    assert_eq!(
+        DataSource::parse("s3://datasets/qa/train.parquet").unwrap(),
+        DataSource::S3 {
+            bucket: "datasets".to_string(),
+            key: "qa/train.parquet".to_string(),
+        }
+    );
+    assert_eq!(
+        DataSource::parse("file:///data/train.jsonl").unwrap(),
+        DataSource::Local("/data/train.jsonl".into())
+    );
+    assert_eq!(
+        DataSource::parse("synthetic://smoke").unwrap(),
+        DataSource::Synthetic("smoke".to_string())
+    );
+    assert!(DataSource::parse("s3://datasets").is_err());
+    assert!(DataSource::parse("").is_err());
+
+    let formats: Vec<_> = [
+        "a.jsonl",
+        "a.NDJSON",
+        "a.csv",
+        "a.parquet",
+        "a.json",
+        "a.txt",
+    ]
+    .iter()
+    .map(|path| DataFormat::from_path(path))
+    .collect();
+    assert_eq!(
+        formats,
+        vec![
+            Some(DataFormat::Jsonl),
+            Some(DataFormat::Jsonl),
+            Some(DataFormat::Csv),
+            Some(DataFormat::Parquet),
+            Some(DataFormat::HfJson),
+            None,
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(DataFormat::HfJson).unwrap(),
+        json!("hf-json")
+    );
+}
+
+// ===== Field Mapping Tests =====
+
+#[test]
+fn test_field_mapping() {
+    let dataset_id = Uuid::new_v4();
+    let row = json!({"question": "2+2?", "answer": "4", "source": "arith"});
+
+    let sample = qa_fields().sample(dataset_id, 3, row.clone()).unwrap();
+    assert_eq!(sample.dataset_id, dataset_id);
+    assert_eq!(sample.index, 3);
+    assert_eq!(sample.input, json!("2+2?"));
+    assert_eq!(sample.expected_output, Some(json!("4")));
+    assert_eq!(sample.metadata, json!({"source": "arith"}));
+    // IDs are the same on every load
+    assert_eq!(sample.id, sample_id(dataset_id, 3));
+    assert_ne!(sample.id, sample_id(dataset_id, 4));
+
+    // Without an input field, the input is the rest of the row
+    let fields = FieldMapping {
+        expected_output: Some("answer".to_string()),
+        ..Default::default()
+    };
+    let sample = fields.sample(dataset_id, 0, row.clone()).unwrap();
+    assert_eq!(sample.input, json!({"question": "2+2?", "source": "arith"}));
+    assert_eq!(sample.metadata, json!({}));
+
+    let error = FieldMapping {
+        input: Some("prompt".to_string()),
+        ..Default::default()
+    }
+    .sample(dataset_id, 7, row)
+    .unwrap_err();
+    assert!(matches!(error, CoreError::Validation(_)));
+    assert!(error.to_string().contains("Row 7 has no field 'prompt'"));
+}
+
+#[test]
+fn test_field_mapping_dotted_paths() {
+    let fields = FieldMapping {
+        input: Some("question".to_string()),
+        expected_output: Some("answers.text.0".to_string()),
+    };
+    let sample = fields
+        .sample(
+            Uuid::new_v4(),
+            0,
+            json!({
+                "question": "Where is the Louvre?",
+                "answers": {"text": ["Paris", "in Paris"], "answer_start": [21, 18]},
+                "title": "Louvre",
+            }),
+        )
+        .unwrap();
+    assert_eq!(sample.expected_output, Some(json!("Paris")));
+    assert_eq!(sample.metadata, json!({"title": "Louvre"}));
+
+    // A key containing dots is taken as it is
+    let fields = FieldMapping {
+        input: Some("prompt.text".to_string()),
+        ..Default::default()
+    };
+    let sample = fields
+        .sample(Uuid::new_v4(), 0, json!({"prompt.text": "Hi"}))
+        .unwrap();
+    assert_eq!(sample.input, json!("Hi"));
+}
+
+// ===== Format Tests =====
+
+#[tokio::test]
+async fn test_load_jsonl() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = write(
+        dir.path(),
+        "qa.jsonl",
+        "{\"question\": \"2+2?\", \"answer\": \"4\"}\n\n{\"question\": \"3+3?\", \"answer\": \"6\"}\n{\"question\": \"4+4?\", \"answer\": \"8\"}\n",
+    );
+
+    let output = load(DataLoadingConfig {
+        source: source.clone(),
+        batch_size: 2,
+        fields: qa_fields(),
+        ..Default::default()
+    })
+    .await;
+
+    assert_eq!(output["total_samples"], 3);
+    assert_eq!(output["batches_loaded"], 2);
+    assert_eq!(output["batches"][1]["start"], 2);
+    let dataset_id = default_dataset_id(&source);
+    assert_eq!(output["dataset_id"], json!(dataset_id));
+
+    let samples = samples(&output);
+    let inputs: Vec<_> = samples.iter().map(|s| s["input"].clone()).collect();
+    assert_eq!(inputs, vec![json!("2+2?"), json!("3+3?"), json!("4+4?")]);
+    assert_eq!(samples[2]["expected_output"], "8");
+    assert_eq!(samples[2]["index"], 2);
+    assert_eq!(samples[2]["id"], json!(sample_id(dataset_id, 2)));
+}
+
+#[tokio::test]
+async fn test_load_csv() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = write(
+        dir.path(),
+        "qa.csv",
+        "question,answer,topic\n\"Capital of France, in one word?\",Paris,geography\n2+2?,4,arithmetic\n",
+    );
+
+    let output = load(DataLoadingConfig {
+        source,
+        fields: qa_fields(),
+        ..Default::default()
+    })
+    .await;
+
+    let samples = samples(&output);
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[0]["input"], "Capital of France, in one word?");
+    assert_eq!(samples[0]["expected_output"], "Paris");
+    assert_eq!(samples[1]["metadata"], json!({"topic": "arithmetic"}));
+}
+
+#[tokio::test]
+async fn test_load_parquet() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = write(
+        dir.path(),
+        "qa.parquet",
+        parquet_file(&[("2+2?", "4", 0), ("3+3?", "6", 1)]),
+    );
+
+    let output = load(DataLoadingConfig {
+        source,
+        fields: qa_fields(),
+        ..Default::default()
+    })
+    .await;
+
+    let samples = samples(&output);
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[1]["input"], "3+3?");
+    assert_eq!(samples[1]["expected_output"], "6");
+    assert_eq!(samples[1]["metadata"], json!({"label": 1}));
+}
+
+#[tokio::test]
+async fn test_load_hf_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let rows = json!([
+        {"question": "2+2?", "answer": "4"},
+        {"question": "3+3?", "answer": "6"},
+    ]);
+    let documents = [
+        rows.clone(),
+        json!({"version": "1.0", "data": rows}),
+        // A datasets-server /rows response
+        json!({
+            "features": [{"feature_idx": 0, "name": "question"}],
+            "rows": [
+                {"row_idx": 0, "row": {"question": "2+2?", "answer": "4"}, "truncated_cells": []},
+                {"row_idx": 1, "row": {"question": "3+3?", "answer": "6"}, "truncated_cells": []},
+            ],
+            "num_rows_total": 2,
+        }),
+    ];
+
+    for (i, document) in documents.iter().enumerate() {
+        let source = write(dir.path(), &format!("qa-{}.json", i), document.to_string());
+        let output = load(DataLoadingConfig {
+            source,
+            fields: qa_fields(),
+            ..Default::default()
+        })
+        .await;
+
+        let samples = samples(&output);
+        assert_eq!(samples.len(), 2, "document {}", i);
+        assert_eq!(samples[1]["input"], "3+3?");
+        assert_eq!(samples[1]["expected_output"], "6");
+    }
+
+    let source = write(dir.path(), "other.json", r#"{"items": []}"#);
+    let error = load_error(DataLoadingConfig {
+        source,
+        ..Default::default()
+    })
+    .await;
+    assert!(matches!(error, CoreError::Validation(_)));
+}
+
+#[tokio::test]
+async fn test_format_from_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = write(dir.path(), "qa.txt", "\"2+2?\"\n\"3+3?\"\n");
+
+    let error = load_error(DataLoadingConfig {
+        source: source.clone(),
+        ..Default::default()
+    })
+    .await;
+    assert!(error.to_string().contains("Can't tell the format"));
+
+    let output = load(DataLoadingConfig {
+        source,
+        format: Some(DataFormat::Jsonl),
+        ..Default::default()
+    })
+    .await;
+    assert_eq!(samples(&output)[1]["input"], "3+3?");
+}
+
+// ===== Error Tests =====
+
+#[tokio::test]
+async fn test_load_malformed_row() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = write(
+        dir.path(),
+        "bad.jsonl",
+        "{\"question\": \"2+2?\"}\n{\"question\": \n",
+    );
+
+    let error = load_error(DataLoadingConfig {
+        source,
+        ..Default::default()
+    })
+    .await;
+    assert!(matches!(error, CoreError::Validation(_)));
+    assert!(error.to_string().contains("line 2"));
+    assert!(!error.is_retryable());
+}
+
+#[tokio::test]
+async fn test_load_missing_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let error = load_error(DataLoadingConfig {
+        source: dir
+            .path()
+            .join("missing.jsonl")
+            .to_str()
+            .unwrap()
+            .to_string(),
+        ..Default::default()
+    })
+    .await;
+    assert!(matches!(error, CoreError::NotFound(_)));
+}
+
+// ===== Streaming Tests =====
+
+#[tokio::test]
+async fn test_streaming_hands_batches_to_sink() {
+    let dir = tempfile::tempdir().unwrap();
+    let rows: String = (0..10)
+        .map(|i| format!("{{\"question\": \"q{}\", \"answer\": \"a{}\"}}\n", i, i))
+        .collect();
+    let source = write(dir.path(), "qa.jsonl", rows);
+
+    let (tx, mut rx) = mpsc::channel(1);
+    let task = DataLoadingTask::new(DataLoadingConfig {
+        source,
+        batch_size: 4,
+        stream: true,
+        fields: qa_fields(),
+        ..Default::default()
+    })
+    .with_sink(tx);
+    let handle = tokio::spawn(async move {
+        task.execute(TaskContext::new(Uuid::new_v4(), json!({})))
+            .await
+    });
+
+    let mut sizes = Vec::new();
+    let mut inputs = Vec::new();
+    while let Some(batch) = rx.recv().await {
+        sizes.push(batch.len());
+        inputs.extend(batch.into_iter().map(|sample| sample.input));
+    }
+    let output = handle.await.unwrap().unwrap().output;
+
+    assert_eq!(sizes, vec![4, 4, 2]);
+    assert_eq!(inputs[9], "q9");
+    assert_eq!(output["total_samples"], 10);
+    assert_eq!(output["streaming"], true);
+    // Only sizes are kept, not the samples
+    assert_eq!(output["batches"][2]["size"], 2);
+    assert!(output["batches"][2].get("samples").is_none());
+}
+
+#[tokio::test]
+async fn test_sample_stream_stops_at_limit() {
+    let dir = tempfile::tempdir().unwrap();
+    let rows: String = (0..100_000).map(|i| format!("{}\n", i)).collect();
+    let source = write(dir.path(), "numbers.jsonl", rows);
+
+    let task = DataLoadingTask::new(DataLoadingConfig {
+        source,
+        limit: Some(5),
+        ..Default::default()
+    });
+    let mut stream = task.open().await.unwrap();
+    assert_eq!(stream.dataset_id(), task.dataset_id());
+
+    let batch = stream.next_batch(100).await.unwrap();
+    let inputs: Vec<_> = batch.iter().map(|sample| sample.input.clone()).collect();
+    assert_eq!(
+        inputs,
+        vec![json!(0), json!(1), json!(2), json!(3), json!(4)]
+    );
+    assert!(stream.next().await.is_none());
+}
+
+// ===== S3 Tests =====
+
+async fn s3_stub(key: &str, body: Vec<u8>) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/datasets/{}", key)))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(body))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn s3_config(server: &MockServer) -> serde_json::Value {
+    json!({
+        "endpoint": server.uri(),
+        "bucket": "unused",
+        "region": "us-east-1",
+        "access_key": "test",
+        "secret_key": "test",
+    })
+}
+
+#[tokio::test]
+async fn test_load_from_s3() {
+    let server = s3_stub(
+        "qa/train.jsonl",
+        b"{\"question\": \"2+2?\", \"answer\": \"4\"}\n".to_vec(),
+    )
+    .await;
+    let config: DataLoadingConfig = serde_json::from_value(json!({
+        "source": "s3://datasets/qa/train.jsonl",
+        "batch_size": 10,
+        "stream": false,
+        "limit": null,
+        "fields": {"input": "question", "expected_output": "answer"},
+        "s3": s3_config(&server),
+    }))
+    .unwrap();
+
+    let samples = samples(&load(config).await);
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0]["input"], "2+2?");
+    assert_eq!(samples[0]["expected_output"], "4");
+}
+
+#[tokio::test]
+async fn test_load_parquet_from_s3() {
+    let server = s3_stub(
+        "qa/train.parquet",
+        parquet_file(&[("2+2?", "4", 0), ("3+3?", "6", 1), ("4+4?", "8", 0)]),
+    )
+    .await;
+    let config: DataLoadingConfig = serde_json::from_value(json!({
+        "source": "s3://datasets/qa/train.parquet",
+        "batch_size": 2,
+        "stream": false,
+        "limit": 2,
+        "fields": {"input": "question"},
+        "s3": s3_config(&server),
+    }))
+    .unwrap();
+
+    let output = load(config).await;
+    assert_eq!(output["total_samples"], 2);
+    assert_eq!(
+        samples(&output)[1]["metadata"],
+        json!({"answer": "6", "label": 1})
+    );
+}
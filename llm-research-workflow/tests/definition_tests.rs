@@ -0,0 +1,989 @@
+use async_trait::async_trait;
+use llm_research_core::Result;
+use llm_research_workflow::*;
+use std::sync::Arc;
+
+/// Echoes its config and the outputs of the steps it depends on
+struct EchoTask {
+    config: serde_json::Value,
+}
+
+#[async_trait]
+impl Task for EchoTask {
+    async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
+        Ok(TaskResult::success(serde_json::json!({
+            "config": self.config,
+            "inputs": context.inputs,
+        })))
+    }
+
+    fn name(&self) -> &str {
+        "echo"
+    }
+}
+
+fn registry() -> TaskRegistry {
+    let mut registry = TaskRegistry::new();
+    registry.register("echo", |config| {
+        Ok(Arc::new(EchoTask {
+            config: config.clone(),
+        }) as Arc<dyn Task>)
+    });
+    registry
+}
+
+fn loader() -> PipelineLoader {
+    PipelineLoader::new(registry())
+}
+
+const YAML_WORKFLOW: &str = r#"name: summarization-eval
+failure_policy: continue_on_error
+tasks:
+  - name: load_dataset
+    type: echo
+    config:
+      path: data/cnn.jsonl
+  - name: run_inference
+    type: echo
+    depends_on: [load_dataset]
+    max_retries: 1
+    timeout_secs: 600
+    resources:
+      gpus: 1
+  - name: score
+    type: echo
+    depends_on: [run_inference, load_dataset]
+"#;
+
+const TOML_PIPELINE: &str = r#"name = "summarization-eval"
+
+[[stages]]
+name = "load"
+
+[[stages.tasks]]
+name = "load_dataset"
+type = "echo"
+config = { path = "data/cnn.jsonl" }
+
+[[stages]]
+name = "infer"
+parallel = true
+
+[[stages.tasks]]
+name = "model_a"
+type = "echo"
+depends_on = ["load_dataset"]
+
+[[stages.tasks]]
+name = "model_b"
+type = "echo"
+depends_on = ["load_dataset"]
+"#;
+
+fn step<'a>(workflow: &'a Workflow, name: &str) -> &'a WorkflowStep {
+    workflow
+        .steps
+        .iter()
+        .find(|step| step.name == name)
+        .unwrap()
+}
+
+fn errors(source: &str, format: DefinitionFormat) -> Vec<DefinitionError> {
+    loader().check(source, format).unwrap_err()
+}
+
+// ===== Parsing Tests =====
+
+#[test]
+fn test_parse_yaml_definition() {
+    let definition = loader()
+        .parse(YAML_WORKFLOW, DefinitionFormat::Yaml)
+        .unwrap();
+
+    assert_eq!(definition.name, "summarization-eval");
+    assert_eq!(definition.failure_policy, FailurePolicy::ContinueOnError);
+    assert_eq!(definition.tasks.len(), 3);
+    assert_eq!(definition.tasks[0].config["path"], "data/cnn.jsonl");
+    assert_eq!(definition.tasks[1].depends_on, vec!["load_dataset"]);
+    assert_eq!(definition.tasks[1].resources.gpus, 1);
+    assert_eq!(definition.tasks[1].resources.slots, 1);
+    // Tasks without a config get an empty one
+    assert_eq!(definition.tasks[2].config, serde_json::json!({}));
+}
+
+#[test]
+fn test_parse_toml_definition() {
+    let definition = loader()
+        .parse(TOML_PIPELINE, DefinitionFormat::Toml)
+        .unwrap();
+
+    assert_eq!(definition.stages.len(), 2);
+    assert!(!definition.stages[0].parallel);
+    assert!(definition.stages[1].parallel);
+    assert_eq!(definition.stages[1].tasks[1].name, "model_b");
+    assert_eq!(definition.failure_policy, FailurePolicy::FailFast);
+}
+
+#[test]
+fn test_format_from_path() {
+    use std::path::Path;
+
+    assert_eq!(
+        DefinitionFormat::from_path(Path::new("eval.yaml")),
+        Some(DefinitionFormat::Yaml)
+    );
+    assert_eq!(
+        DefinitionFormat::from_path(Path::new("eval.yml")),
+        Some(DefinitionFormat::Yaml)
+    );
+    assert_eq!(
+        DefinitionFormat::from_path(Path::new("eval.toml")),
+        Some(DefinitionFormat::Toml)
+    );
+    assert_eq!(DefinitionFormat::from_path(Path::new("eval.json")), None);
+}
+
+// ===== Conversion Tests =====
+
+#[test]
+fn test_definition_to_workflow() {
+    let definition = loader()
+        .parse(YAML_WORKFLOW, DefinitionFormat::Yaml)
+        .unwrap();
+    let workflow = definition.to_workflow().unwrap();
+
+    assert_eq!(workflow.name, "summarization-eval");
+    assert_eq!(workflow.failure_policy, FailurePolicy::ContinueOnError);
+
+    let load = step(&workflow, "load_dataset");
+    let infer = step(&workflow, "run_inference");
+    let score = step(&workflow, "score");
+    assert!(load.dependencies.is_empty());
+    assert_eq!(infer.dependencies, vec![load.id]);
+    assert_eq!(score.dependencies, vec![infer.id, load.id]);
+
+    assert_eq!(infer.max_retries, 1);
+    assert_eq!(infer.timeout_secs, Some(600));
+    assert_eq!(infer.resources.gpus, 1);
+    // Unset retries and timeouts keep the step defaults
+    assert_eq!(load.max_retries, 3);
+    assert_eq!(load.timeout_secs, None);
+}
+
+#[test]
+fn test_staged_definition_to_pipeline() {
+    let definition = loader()
+        .parse(TOML_PIPELINE, DefinitionFormat::Toml)
+        .unwrap();
+    let pipeline = definition.to_pipeline().unwrap();
+
+    assert_eq!(pipeline.stages.len(), 2);
+    assert_eq!(pipeline.stages[0].name, "load");
+    assert!(pipeline.stages[1].parallel);
+
+    let load_id = pipeline.stages[0].tasks[0].id;
+    for task in &pipeline.stages[1].tasks {
+        assert_eq!(task.dependencies, vec![load_id]);
+    }
+    assert!(TaskDAG::from_pipeline(&pipeline).is_ok());
+}
+
+#[test]
+fn test_flat_definition_to_pipeline_stages_by_level() {
+    let definition = loader()
+        .parse(YAML_WORKFLOW, DefinitionFormat::Yaml)
+        .unwrap();
+    let pipeline = definition.to_pipeline().unwrap();
+
+    let names: Vec<Vec<&str>> = pipeline
+        .stages
+        .iter()
+        .map(|stage| stage.tasks.iter().map(|task| task.name.as_str()).collect())
+        .collect();
+    assert_eq!(
+        names,
+        vec![vec!["load_dataset"], vec!["run_inference"], vec!["score"]]
+    );
+    assert!(pipeline.stages.iter().all(|stage| stage.parallel));
+
+    // Retries and timeouts carry over to the pipeline's tasks
+    let inference = &pipeline.stages[1].tasks[0];
+    assert_eq!(inference.max_retries, 1);
+    assert_eq!(inference.timeout_secs, Some(600));
+    assert_eq!(pipeline.stages[0].tasks[0].max_retries, 0);
+}
+
+#[test]
+fn test_to_workflow_rejects_unknown_dependency() {
+    let mut definition = loader()
+        .parse(YAML_WORKFLOW, DefinitionFormat::Yaml)
+        .unwrap();
+    definition.tasks[2].depends_on.push("missing".to_string());
+
+    let error = definition.to_workflow().unwrap_err();
+    assert!(error.to_string().contains("unknown task 'missing'"));
+}
+
+// ===== Validation Tests =====
+
+#[test]
+fn test_unknown_task_type_reports_line() {
+    let source = "name: eval\ntasks:\n  - name: load\n    type: echo\n  - name: infer\n    type: inferenec\n";
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(5));
+    assert!(errors[0].message.contains("unknown type 'inferenec'"));
+    assert!(errors[0].message.contains("registered: echo"));
+}
+
+#[test]
+fn test_unknown_dependency_reports_line() {
+    let source = r#"name = "eval"
+
+[[tasks]]
+name = "load"
+type = "echo"
+
+[[tasks]]
+name = "infer"
+type = "echo"
+depends_on = ["lod"]
+"#;
+
+    let errors = errors(source, DefinitionFormat::Toml);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(8));
+    assert!(errors[0].message.contains("depends on unknown task 'lod'"));
+}
+
+#[test]
+fn test_cycle_detected() {
+    let source = r#"name: eval
+tasks:
+  - name: load
+    type: echo
+  - name: infer
+    type: echo
+    depends_on: [load, score]
+  - name: score
+    type: echo
+    depends_on: [infer]
+  - name: report
+    type: echo
+    depends_on: [score]
+"#;
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(5));
+    assert_eq!(
+        errors[0].message,
+        "circular dependency between tasks infer, score"
+    );
+}
+
+#[test]
+fn test_self_dependency_detected() {
+    let source = "name: eval\ntasks:\n  - name: load\n    type: echo\n    depends_on: [load]\n";
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors[0].line, Some(3));
+    assert!(errors[0]
+        .message
+        .contains("circular dependency between tasks load"));
+}
+
+#[test]
+fn test_duplicate_task_names() {
+    let source =
+        "name: eval\ntasks:\n  - name: load\n    type: echo\n  - name: load\n    type: echo\n";
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(5));
+    assert!(errors[0].message.contains("duplicate task name 'load'"));
+}
+
+#[test]
+fn test_staged_task_cannot_depend_on_later_task() {
+    let source = r#"name: eval
+stages:
+  - name: first
+    tasks:
+      - name: infer
+        type: echo
+        depends_on: [load]
+  - name: second
+    tasks:
+      - name: load
+        type: echo
+"#;
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(5));
+    assert!(errors[0]
+        .message
+        .contains("'load', which does not run before it"));
+}
+
+#[test]
+fn test_parallel_stage_tasks_cannot_depend_on_each_other() {
+    let source = r#"name: eval
+stages:
+  - name: infer
+    parallel: true
+    tasks:
+      - name: model_a
+        type: echo
+      - name: model_b
+        type: echo
+        depends_on: [model_a]
+"#;
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors[0].line, Some(8));
+
+    // The same tasks are fine in a sequential stage
+    let sequential = source.replace("parallel: true", "parallel: false");
+    assert!(loader().check(&sequential, DefinitionFormat::Yaml).is_ok());
+}
+
+#[test]
+fn test_all_errors_reported() {
+    let source = r#"name: eval
+tasks:
+  - name: load
+    type: loader
+  - name: infer
+    type: echo
+    depends_on: [loader]
+"#;
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    let lines: Vec<Option<usize>> = errors.iter().map(|error| error.line).collect();
+    assert_eq!(lines, vec![Some(3), Some(5)]);
+}
+
+#[test]
+fn test_tasks_or_stages_required() {
+    let errors = errors("name: eval\n", DefinitionFormat::Yaml);
+    assert_eq!(errors[0].line, None);
+    assert!(errors[0].message.contains("no `tasks` or `stages`"));
+}
+
+// ===== Syntax Error Tests =====
+
+#[test]
+fn test_yaml_syntax_error_reports_line() {
+    let source = "name: eval\ntasks:\n  - name: load\n    type: echo\n    depends_on: [infer\n";
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(6));
+    assert!(errors[0]
+        .message
+        .contains("did not find expected ',' or ']'"));
+}
+
+#[test]
+fn test_yaml_unknown_field_reports_line() {
+    let source = "name: eval\ntasks:\n  - name: load\n    type: echo\n    retries: 3\n";
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors[0].line, Some(5));
+    assert!(errors[0].message.contains("unknown field `retries`"));
+    assert!(!errors[0].message.contains("line 5"));
+}
+
+#[test]
+fn test_toml_syntax_error_reports_line() {
+    let source = "name = \"eval\"\n\n[[tasks]]\nname = \"load\"\ntype = echo\n";
+
+    let errors = errors(source, DefinitionFormat::Toml);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(5));
+}
+
+#[test]
+fn test_toml_missing_field_reports_line() {
+    let source = "name = \"eval\"\n\n[[tasks]]\nname = \"load\"\n";
+
+    let errors = errors(source, DefinitionFormat::Toml);
+    assert!(errors[0].line.is_some());
+    assert!(errors[0].message.contains("missing field `type`"));
+}
+
+#[test]
+fn test_parse_error_message_lists_lines() {
+    let source = "name: eval\ntasks:\n  - name: load\n    type: loader\n";
+
+    let error = loader().parse(source, DefinitionFormat::Yaml).unwrap_err();
+    let message = error.to_string();
+    assert!(message.contains("Invalid pipeline definition"));
+    assert!(message.contains("line 3: task 'load' has unknown type 'loader'"));
+}
+
+// ===== Output Reference Tests =====
+
+/// Loader that also knows the built-in task types and their outputs
+fn builtin_loader() -> PipelineLoader {
+    let mut registry = TaskRegistry::with_builtin_tasks();
+    registry.register("echo", |config| {
+        Ok(Arc::new(EchoTask {
+            config: config.clone(),
+        }) as Arc<dyn Task>)
+    });
+    PipelineLoader::new(registry)
+}
+
+#[test]
+fn test_references_to_upstream_tasks_accepted() {
+    let source = r#"name: eval
+tasks:
+  - name: load
+    type: data_loading
+  - name: infer
+    type: echo
+    depends_on: [load]
+  - name: score
+    type: echo
+    depends_on: [infer]
+    config:
+      samples: ${steps.load.output.total_samples}
+      title: "${steps.load.output.source} (${steps.load.output.total_samples} samples)"
+      first: ${steps.load.output.batches.0.size}
+      anything: ${steps.infer.output.whatever}
+"#;
+
+    assert!(builtin_loader()
+        .check(source, DefinitionFormat::Yaml)
+        .is_ok());
+}
+
+#[test]
+fn test_reference_to_unknown_task() {
+    let source = r#"name: eval
+tasks:
+  - name: infer
+    type: echo
+    config:
+      dataset: ${steps.lod.output.path}
+"#;
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(3));
+    assert_eq!(
+        errors[0].message,
+        "task 'infer' config field 'dataset' references unknown task 'lod'"
+    );
+}
+
+#[test]
+fn test_reference_requires_dependency() {
+    let source = r#"name: eval
+tasks:
+  - name: load
+    type: echo
+  - name: infer
+    type: echo
+    config:
+      dataset: ${steps.load.output.path}
+"#;
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors[0].line, Some(5));
+    assert!(errors[0]
+        .message
+        .contains("references 'load', which it does not depend on"));
+}
+
+#[test]
+fn test_reference_to_undeclared_output_field() {
+    let source = r#"name = "eval"
+
+[[tasks]]
+name = "load"
+type = "data_loading"
+
+[[tasks]]
+name = "infer"
+type = "echo"
+depends_on = ["load"]
+config = { dataset = "${steps.load.output.path}" }
+"#;
+
+    let errors = builtin_loader()
+        .check(source, DefinitionFormat::Toml)
+        .unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(8));
+    assert!(errors[0].message.contains(
+        "step 'load' has no output 'path' (available: batch_size, batches, batches_loaded, dataset_id, selection, source"
+    ));
+}
+
+#[test]
+fn test_reference_type_checked() {
+    let source = r#"name: eval
+tasks:
+  - name: load
+    type: data_loading
+  - name: infer
+    type: echo
+    depends_on: [load]
+    config:
+      title: "Batches: ${steps.load.output.batches}"
+      size: ${steps.load.output.total_samples.value}
+"#;
+
+    let errors = builtin_loader()
+        .check(source, DefinitionFormat::Yaml)
+        .unwrap_err();
+    let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages.len(), 2);
+    assert!(
+        messages
+            .iter()
+            .any(|m| m
+                .contains("${steps.load.output.batches} is an array and cannot be interpolated"))
+    );
+    assert!(
+        messages
+            .iter()
+            .any(|m| m
+                .contains("'total_samples' of step 'load' is a number and has no field 'value'"))
+    );
+}
+
+#[test]
+fn test_malformed_reference() {
+    let source = r#"name: eval
+tasks:
+  - name: infer
+    type: echo
+    config:
+      dataset: ${load.output.path}
+"#;
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors[0].line, Some(3));
+    assert!(errors[0]
+        .message
+        .contains("Invalid reference '${load.output.path}'"));
+}
+
+// ===== Condition and Trigger Tests =====
+
+const CONDITIONAL_WORKFLOW: &str = r#"name: eval
+parameters:
+  threshold: 0.9
+  judge_model: gpt-4
+tasks:
+  - name: evaluate
+    type: echo
+    config:
+      accuracy: 0.71
+  - name: judge
+    type: echo
+    depends_on: [evaluate]
+    config:
+      model: ${params.judge_model}
+    when:
+      compare:
+        left: ${steps.evaluate.output.config.accuracy}
+        op: lt
+        right: ${params.threshold}
+  - name: notify
+    type: echo
+    depends_on: [judge]
+    trigger: on_failure
+"#;
+
+#[test]
+fn test_parse_conditions_and_triggers() {
+    let workflow = loader()
+        .parse(CONDITIONAL_WORKFLOW, DefinitionFormat::Yaml)
+        .unwrap()
+        .to_workflow()
+        .unwrap();
+
+    assert_eq!(workflow.parameters["threshold"], 0.9);
+    assert_eq!(
+        step(&workflow, "judge").condition,
+        Some(Condition::compare(
+            serde_json::json!("${steps.evaluate.output.config.accuracy}"),
+            CompareOp::Lt,
+            serde_json::json!("${params.threshold}"),
+        ))
+    );
+    assert_eq!(step(&workflow, "judge").trigger, StepTrigger::OnSuccess);
+    assert_eq!(step(&workflow, "notify").trigger, StepTrigger::OnFailure);
+}
+
+#[tokio::test]
+async fn test_conditional_workflow_runs() {
+    let workflow = loader()
+        .parse(CONDITIONAL_WORKFLOW, DefinitionFormat::Yaml)
+        .unwrap()
+        .to_workflow()
+        .unwrap();
+    let engine = DefaultWorkflowEngine::with_registry(registry());
+
+    let state = engine.execute(&workflow).await.unwrap();
+
+    let judge = step(&state.workflow, "judge");
+    assert_eq!(judge.status, WorkflowStatus::Completed);
+    assert_eq!(state.step_outputs[&judge.id]["config"]["model"], "gpt-4");
+    assert_eq!(
+        step(&state.workflow, "notify").status,
+        WorkflowStatus::Skipped
+    );
+}
+
+#[test]
+fn test_condition_references_checked() {
+    let source = r#"name: eval
+tasks:
+  - name: evaluate
+    type: echo
+  - name: judge
+    type: echo
+    when:
+      all:
+        - truthy: ${steps.evaluate.output.config.enabled}
+        - truthy: ${params.judge}
+"#;
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages.len(), 2);
+    assert!(messages.iter().any(|m| m.contains(
+        "task 'judge' condition field 'all[0].truthy' references 'evaluate', which it does not depend on"
+    )));
+    assert!(messages
+        .iter()
+        .any(|m| m.contains("${params.judge} is not a declared parameter")));
+}
+
+#[test]
+fn test_undeclared_parameter_in_config() {
+    let source = r#"name = "eval"
+
+[parameters]
+model = "gpt-4"
+
+[[tasks]]
+name = "infer"
+type = "echo"
+config = { model = "${params.modle}", title = "Run ${params.model}" }
+"#;
+
+    let errors = errors(source, DefinitionFormat::Toml);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(7));
+    assert_eq!(
+        errors[0].message,
+        "task 'infer' config field 'model': ${params.modle} is not a declared parameter"
+    );
+}
+
+#[test]
+fn test_task_cannot_depend_on_cleanup_task() {
+    let source = r#"name: eval
+tasks:
+  - name: cleanup
+    type: echo
+    trigger: always
+  - name: report
+    type: echo
+    depends_on: [cleanup]
+"#;
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(6));
+    assert!(errors[0]
+        .message
+        .contains("task 'report' depends on cleanup task 'cleanup', which runs after it"));
+}
+
+#[test]
+fn test_pipeline_rejects_conditions() {
+    let definition = loader()
+        .parse(CONDITIONAL_WORKFLOW, DefinitionFormat::Yaml)
+        .unwrap();
+
+    let error = definition.to_pipeline().unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Task 'judge' has a condition, trigger or fan-out"));
+}
+
+// ===== Fan-Out Tests =====
+
+#[test]
+fn test_parse_fan_out() {
+    let source = r#"name: sharded
+tasks:
+  - name: load
+    type: data_loading
+  - name: infer
+    type: echo
+    depends_on: [load]
+    fan_out:
+      over: ${steps.load.output.batches}
+      item_key: batch
+      max_concurrency: 4
+      allowed_failures: 1
+  - name: gather
+    type: reduce
+    depends_on: [infer]
+    config:
+      field: output.results
+"#;
+
+    let workflow = builtin_loader()
+        .parse(source, DefinitionFormat::Yaml)
+        .unwrap()
+        .to_workflow()
+        .unwrap();
+
+    let fan_out = step(&workflow, "infer").fan_out.as_ref().unwrap();
+    assert_eq!(fan_out.over, "${steps.load.output.batches}");
+    assert_eq!(fan_out.item_key, "batch");
+    assert_eq!(fan_out.max_concurrency, Some(4));
+    assert_eq!(fan_out.allowed_failures, 1);
+    assert!(step(&workflow, "load").fan_out.is_none());
+}
+
+#[test]
+fn test_fan_out_over_must_be_list() {
+    let source = r#"name: sharded
+tasks:
+  - name: load
+    type: data_loading
+  - name: infer
+    type: echo
+    depends_on: [load]
+    fan_out:
+      over: ${steps.load.output.total_samples}
+  - name: score
+    type: echo
+    fan_out:
+      over: shards
+"#;
+
+    let errors = builtin_loader()
+        .check(source, DefinitionFormat::Yaml)
+        .unwrap_err();
+    let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages.len(), 2);
+    assert!(messages.iter().any(|m| m.contains(
+        "task 'infer' fan_out field 'over': ${steps.load.output.total_samples} is a number, not a list to fan out over"
+    )));
+    assert!(messages
+        .iter()
+        .any(|m| m.contains("task 'score' fan_out 'over' must be a list or a reference to one")));
+}
+
+#[test]
+fn test_fan_out_needs_concurrency() {
+    let source = r#"name: sharded
+tasks:
+  - name: infer
+    type: echo
+    fan_out:
+      over: [a, b]
+      max_concurrency: 0
+"#;
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(3));
+    assert!(errors[0]
+        .message
+        .contains("task 'infer' fan_out max_concurrency must be at least 1"));
+}
+
+#[test]
+fn test_fan_out_references_checked() {
+    let source = r#"name: sharded
+tasks:
+  - name: infer
+    type: echo
+    fan_out:
+      over: ${steps.load.output.batches}
+"#;
+
+    let errors = errors(source, DefinitionFormat::Yaml);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message,
+        "task 'infer' fan_out field 'over' references unknown task 'load'"
+    );
+}
+
+#[test]
+fn test_references_to_fan_out_output_checked() {
+    let source = r#"name: sharded
+tasks:
+  - name: infer
+    type: inference
+    fan_out:
+      over: [gpt-4, claude-3]
+      item_key: model
+  - name: report
+    type: echo
+    depends_on: [infer]
+    config:
+      failed: ${steps.infer.output.failed}
+      results: ${steps.infer.output.results}
+"#;
+
+    let errors = builtin_loader()
+        .check(source, DefinitionFormat::Yaml)
+        .unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].message.contains(
+        "config field 'results': step 'infer' has no output 'results' (available: errors, failed, items, outputs, succeeded)"
+    ));
+}
+
+#[test]
+fn test_parse_cache_opt_out() {
+    let source = r#"name: cached
+tasks:
+  - name: load
+    type: echo
+  - name: sample
+    type: echo
+    depends_on: [load]
+    cache: false
+"#;
+
+    let workflow = loader()
+        .parse(source, DefinitionFormat::Yaml)
+        .unwrap()
+        .to_workflow()
+        .unwrap();
+    assert!(step(&workflow, "load").cache);
+    assert!(!step(&workflow, "sample").cache);
+}
+
+#[test]
+fn test_parse_backoff() {
+    let source = r#"name: retried
+tasks:
+  - name: infer
+    type: echo
+    max_retries: 4
+    backoff:
+      strategy: linear
+      initial_delay_ms: 200
+      increment_ms: 100
+      jitter: equal
+"#;
+
+    let workflow = loader()
+        .parse(source, DefinitionFormat::Yaml)
+        .unwrap()
+        .to_workflow()
+        .unwrap();
+    let infer = step(&workflow, "infer");
+    assert_eq!(infer.max_retries, 4);
+    assert_eq!(
+        infer.backoff,
+        Backoff::linear(
+            std::time::Duration::from_millis(200),
+            std::time::Duration::from_millis(100)
+        )
+        .with_jitter(JitterStrategy::Equal)
+    );
+}
+
+#[test]
+fn test_invalid_backoff_reported() {
+    let source = r#"name: retried
+tasks:
+  - name: infer
+    type: echo
+    backoff:
+      strategy: exponential
+      multiplier: 0.5
+"#;
+
+    let errors = loader().check(source, DefinitionFormat::Yaml).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(3));
+    assert!(errors[0]
+        .message
+        .contains("task 'infer' backoff multiplier must be at least 1, got 0.5"));
+}
+
+// ===== File Loading Tests =====
+
+#[tokio::test]
+async fn test_load_yaml_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("eval.yaml");
+    std::fs::write(&path, YAML_WORKFLOW).unwrap();
+
+    let workflow = loader().load_workflow(&path).await.unwrap();
+    assert_eq!(workflow.steps.len(), 3);
+}
+
+#[tokio::test]
+async fn test_load_toml_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("eval.toml");
+    std::fs::write(&path, TOML_PIPELINE).unwrap();
+
+    let pipeline = loader().load_pipeline(&path).await.unwrap();
+    assert_eq!(pipeline.stages.len(), 2);
+}
+
+#[tokio::test]
+async fn test_load_error_names_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("broken.yml");
+    std::fs::write(
+        &path,
+        "name: eval\ntasks:\n  - name: load\n    type: loader\n",
+    )
+    .unwrap();
+
+    let message = loader().load(&path).await.unwrap_err().to_string();
+    assert!(message.contains("broken.yml"));
+    assert!(message.contains("line 3"));
+}
+
+#[tokio::test]
+async fn test_load_rejects_unknown_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("eval.json");
+    std::fs::write(&path, "{}").unwrap();
+
+    let error = loader().load(&path).await.unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Unsupported pipeline definition file"));
+}
+
+#[tokio::test]
+async fn test_loaded_workflow_runs() {
+    let definition = loader()
+        .parse(YAML_WORKFLOW, DefinitionFormat::Yaml)
+        .unwrap();
+    let workflow = definition.to_workflow().unwrap();
+    let engine = DefaultWorkflowEngine::with_registry(registry());
+
+    let state = engine.execute(&workflow).await.unwrap();
+
+    let score = step(&state.workflow, "score");
+    let inputs = &state.step_outputs[&score.id]["inputs"];
+    assert!(inputs.get("run_inference").is_some());
+    assert!(inputs.get("load_dataset").is_some());
+}
@@ -1,6 +1,227 @@
+use async_trait::async_trait;
+use llm_research_core::{CoreError, Result};
 use llm_research_workflow::*;
 use uuid::Uuid;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Echoes its task type and config, like the engine's former mock dispatch
+struct EchoTask {
+    task_type: String,
+    config: serde_json::Value,
+}
+
+#[async_trait]
+impl Task for EchoTask {
+    async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
+        Ok(TaskResult::success(serde_json::json!({
+            "task_type": self.task_type,
+            "status": "completed",
+            "config": self.config,
+            "inputs": context.inputs,
+        })))
+    }
+
+    fn name(&self) -> &str {
+        &self.task_type
+    }
+}
+
+struct FailingTask;
+
+#[async_trait]
+impl Task for FailingTask {
+    async fn execute(&self, _context: TaskContext) -> Result<TaskResult> {
+        Ok(TaskResult::failure("model endpoint unavailable".to_string()))
+    }
+
+    fn name(&self) -> &str {
+        "failing"
+    }
+}
+
+/// Fails with a retryable or permanent error until it has run `failures` times
+struct FlakyTask {
+    failures: usize,
+    permanent: bool,
+    attempts: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Task for FlakyTask {
+    async fn execute(&self, _context: TaskContext) -> Result<TaskResult> {
+        if self.attempts.fetch_add(1, Ordering::SeqCst) >= self.failures {
+            return Ok(TaskResult::success(serde_json::json!({"ok": true})));
+        }
+        if self.permanent {
+            Err(CoreError::Validation("prompt template has no {input}".to_string()))
+        } else {
+            Err(CoreError::Internal("connection reset by provider".to_string()))
+        }
+    }
+
+    fn name(&self) -> &str {
+        "flaky"
+    }
+}
+
+/// Blocks until the test releases it, so the workflow can be acted on mid-step
+struct GateTask {
+    gate: Arc<Notify>,
+}
+
+#[async_trait]
+impl Task for GateTask {
+    async fn execute(&self, _context: TaskContext) -> Result<TaskResult> {
+        self.gate.notified().await;
+        Ok(TaskResult::success(serde_json::json!({"released": true})))
+    }
+
+    fn name(&self) -> &str {
+        "gate"
+    }
+}
+
+/// Processes `batches` batches with a pause point before each one
+struct BatchTask {
+    batches: usize,
+    processed: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Task for BatchTask {
+    async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
+        for _ in 0..self.batches {
+            context.control.checkpoint().await?;
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            self.processed.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(TaskResult::success(serde_json::json!({"batches": self.batches})))
+    }
+
+    fn name(&self) -> &str {
+        "batches"
+    }
+}
+
+/// Tracks how many probes run at once
+#[derive(Default)]
+struct Concurrency {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+/// Holds a concurrency slot for a short while
+struct ProbeTask {
+    concurrency: Arc<Concurrency>,
+}
+
+#[async_trait]
+impl Task for ProbeTask {
+    async fn execute(&self, _context: TaskContext) -> Result<TaskResult> {
+        let now = self.concurrency.current.fetch_add(1, Ordering::SeqCst) + 1;
+        self.concurrency.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        self.concurrency.current.fetch_sub(1, Ordering::SeqCst);
+        Ok(TaskResult::success(serde_json::json!({"probe": true})))
+    }
+
+    fn name(&self) -> &str {
+        "probe"
+    }
+}
+
+/// Engine with a "probe" task reporting peak concurrency, plus the gate
+fn probe_engine(limits: ResourceLimits) -> (DefaultWorkflowEngine, Arc<Concurrency>, Arc<Notify>) {
+    let concurrency = Arc::new(Concurrency::default());
+    let gate = Arc::new(Notify::new());
+    let mut registry = registry();
+
+    let probes = concurrency.clone();
+    registry.register("probe", move |_| {
+        Ok(Arc::new(ProbeTask { concurrency: probes.clone() }) as Arc<dyn Task>)
+    });
+    let task_gate = gate.clone();
+    registry.register("gate", move |_| {
+        Ok(Arc::new(GateTask { gate: task_gate.clone() }) as Arc<dyn Task>)
+    });
+
+    let engine = DefaultWorkflowEngine::with_registry(registry).with_resource_limits(limits);
+    (engine, concurrency, gate)
+}
+
+fn probe_steps(count: usize) -> Vec<WorkflowStep> {
+    (0..count)
+        .map(|i| WorkflowStep::new(format!("probe {}", i), "probe".to_string(), serde_json::json!({})))
+        .collect()
+}
+
+/// Engine whose registry maps the placeholder task types used below to `EchoTask`
+fn engine() -> DefaultWorkflowEngine {
+    DefaultWorkflowEngine::with_registry(registry())
+}
+
+/// `engine()` plus a "gate" task released through the returned `Notify` and a
+/// "batches" task counting processed batches in the returned counter
+fn controllable_engine() -> (Arc<DefaultWorkflowEngine>, Arc<Notify>, Arc<AtomicUsize>) {
+    let gate = Arc::new(Notify::new());
+    let processed = Arc::new(AtomicUsize::new(0));
+    let mut registry = registry();
+
+    let task_gate = gate.clone();
+    registry.register("gate", move |_| {
+        Ok(Arc::new(GateTask { gate: task_gate.clone() }) as Arc<dyn Task>)
+    });
+    let counter = processed.clone();
+    registry.register("batches", move |config| {
+        Ok(Arc::new(BatchTask {
+            batches: config["batches"].as_u64().unwrap_or(1) as usize,
+            processed: counter.clone(),
+        }) as Arc<dyn Task>)
+    });
+
+    let engine = Arc::new(DefaultWorkflowEngine::with_registry(registry));
+    (engine, gate, processed)
+}
+
+/// Wait for the step named `name` to start running
+async fn step_started(events: &mut tokio::sync::broadcast::Receiver<WorkflowEvent>, name: &str) {
+    loop {
+        let event = events.recv().await.unwrap();
+        if event.step_id.is_some() && event.name == name && event.status == WorkflowStatus::Running {
+            return;
+        }
+    }
+}
+
+/// Wait for the workflow itself to reach `status`
+async fn workflow_status(
+    events: &mut tokio::sync::broadcast::Receiver<WorkflowEvent>,
+    status: WorkflowStatus,
+) {
+    loop {
+        let event = events.recv().await.unwrap();
+        if event.step_id.is_none() && event.status == status {
+            return;
+        }
+    }
+}
+
+fn registry() -> TaskRegistry {
+    let mut registry = TaskRegistry::with_builtin_tasks();
+    for task_type in ["task", "test_task", "task0", "task1", "task2", "task3", "task4"] {
+        registry.register(task_type, move |config| {
+            Ok(Arc::new(EchoTask {
+                task_type: task_type.to_string(),
+                config: config.clone(),
+            }) as Arc<dyn Task>)
+        });
+    }
+    registry.register("failing", |_| Ok(Arc::new(FailingTask) as Arc<dyn Task>));
+    registry
+}
 
 // ===== Workflow Struct Tests =====
 
@@ -134,6 +355,7 @@ fn test_workflow_status_all_variants() {
         WorkflowStatus::Completed,
         WorkflowStatus::Failed,
         WorkflowStatus::Cancelled,
+        WorkflowStatus::Skipped,
     ];
 
     for status in statuses {
@@ -159,12 +381,32 @@ fn test_default_workflow_engine_default_trait() {
     assert!(true);
 }
 
+#[test]
+fn test_default_engine_registers_builtin_tasks() {
+    let engine = DefaultWorkflowEngine::new();
+
+    assert_eq!(
+        engine.registry().task_types(),
+        vec![
+            "aggregation",
+            "data_loading",
+            "evaluation",
+            "inference",
+            "llm_judge",
+            "prompt_rendering",
+            "reduce",
+            "reporting"
+        ]
+    );
+    assert!(!engine.registry().contains("task"));
+}
+
 // ===== Workflow Execution Tests =====
 
 #[tokio::test]
 async fn test_execute_empty_workflow() {
     let workflow = Workflow::new("Empty Workflow".to_string(), vec![]);
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let result = engine.execute(&workflow).await;
     assert!(result.is_ok());
@@ -185,7 +427,7 @@ async fn test_execute_single_step_workflow() {
     );
 
     let workflow = Workflow::new("Single Step Workflow".to_string(), vec![step]);
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let result = engine.execute(&workflow).await;
     assert!(result.is_ok());
@@ -213,7 +455,7 @@ async fn test_execute_workflow_with_sequential_steps() {
     let step2_id = step2.id;
 
     let workflow = Workflow::new("Sequential Workflow".to_string(), vec![step1, step2]);
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let result = engine.execute(&workflow).await;
     assert!(result.is_ok());
@@ -244,7 +486,7 @@ async fn test_execute_workflow_with_dependencies() {
     .with_dependencies(vec![step1_id]);
 
     let workflow = Workflow::new("Dependent Workflow".to_string(), vec![step1, step2]);
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let result = engine.execute(&workflow).await;
     assert!(result.is_ok());
@@ -281,7 +523,7 @@ async fn test_execute_workflow_with_multiple_dependencies() {
         "Multiple Dependencies".to_string(),
         vec![step1, step2, step3],
     );
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let result = engine.execute(&workflow).await;
     assert!(result.is_ok());
@@ -318,7 +560,7 @@ async fn test_deadlock_detection_circular_dependency() {
         "Circular Dependency".to_string(),
         vec![step1_circular, step2],
     );
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let result = engine.execute(&workflow).await;
     assert!(result.is_err());
@@ -340,7 +582,7 @@ async fn test_deadlock_detection_missing_dependency() {
     .with_dependencies(vec![non_existent_dep]);
 
     let workflow = Workflow::new("Missing Dependency".to_string(), vec![step]);
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let result = engine.execute(&workflow).await;
     assert!(result.is_err());
@@ -362,10 +604,9 @@ async fn test_step_with_zero_retries() {
     .with_max_retries(0);
 
     let workflow = Workflow::new("No Retry Workflow".to_string(), vec![step]);
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let result = engine.execute(&workflow).await;
-    // Should still succeed (mock tasks always succeed)
     assert!(result.is_ok());
 }
 
@@ -379,12 +620,131 @@ async fn test_step_with_high_retry_count() {
     .with_max_retries(100);
 
     let workflow = Workflow::new("High Retry Workflow".to_string(), vec![step]);
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let result = engine.execute(&workflow).await;
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_failing_task_fails_workflow() {
+    let step = WorkflowStep::new(
+        "Flaky Step".to_string(),
+        "failing".to_string(),
+        serde_json::json!({}),
+    )
+    .with_max_retries(0);
+
+    let workflow = Workflow::new("Failing Workflow".to_string(), vec![step]);
+    let engine = engine();
+
+    let error = engine.execute(&workflow).await.unwrap_err();
+    assert!(error.to_string().contains("Flaky Step"));
+    assert!(error.to_string().contains("model endpoint unavailable"));
+}
+
+#[tokio::test]
+async fn test_unknown_task_type_fails_without_retrying() {
+    let step = WorkflowStep::new(
+        "Mystery Step".to_string(),
+        "does_not_exist".to_string(),
+        serde_json::json!({}),
+    )
+    .with_max_retries(5);
+
+    let workflow = Workflow::new("Unknown Task Workflow".to_string(), vec![step]);
+    let engine = engine();
+
+    let start = std::time::Instant::now();
+    let error = engine.execute(&workflow).await.unwrap_err();
+    assert!(error.to_string().contains("Unknown task type 'does_not_exist'"));
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+}
+
+// ===== Retry Policy Tests =====
+
+/// An engine whose "flaky" task fails `failures` times, counting attempts
+fn flaky_engine(failures: usize, permanent: bool) -> (DefaultWorkflowEngine, Arc<AtomicUsize>) {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let mut registry = registry();
+    let counter = attempts.clone();
+    registry.register("flaky", move |_| {
+        Ok(Arc::new(FlakyTask { failures, permanent, attempts: counter.clone() }) as Arc<dyn Task>)
+    });
+    (DefaultWorkflowEngine::with_registry(registry), attempts)
+}
+
+fn flaky_step(max_retries: usize, backoff: Backoff) -> WorkflowStep {
+    WorkflowStep::new("flaky".to_string(), "flaky".to_string(), serde_json::json!({}))
+        .with_max_retries(max_retries)
+        .with_backoff(backoff)
+}
+
+#[tokio::test]
+async fn test_retryable_error_retried_after_backoff() {
+    let (engine, attempts) = flaky_engine(2, false);
+    let backoff = Backoff::constant(std::time::Duration::from_millis(50))
+        .with_jitter(JitterStrategy::None);
+    let workflow = Workflow::new("Flaky".to_string(), vec![flaky_step(3, backoff)]);
+
+    let start = std::time::Instant::now();
+    let state = engine.execute(&workflow).await.unwrap();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(state.workflow.steps[0].retry_count, 2);
+    assert_eq!(state.workflow.steps[0].status, WorkflowStatus::Completed);
+    assert!(start.elapsed() >= std::time::Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn test_permanent_error_not_retried() {
+    let (engine, attempts) = flaky_engine(usize::MAX, true);
+    let workflow = Workflow::new("Invalid".to_string(), vec![flaky_step(5, Backoff::default())]);
+
+    let start = std::time::Instant::now();
+    let error = engine.execute(&workflow).await.unwrap_err();
+
+    assert!(error.to_string().contains("prompt template has no {input}"));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    assert!(start.elapsed() < std::time::Duration::from_millis(500));
+
+    let state = engine.state(workflow.id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.steps[0].retry_count, 0);
+    assert_eq!(state.workflow.steps[0].status, WorkflowStatus::Failed);
+}
+
+#[tokio::test]
+async fn test_retries_stop_at_max_retries() {
+    let (engine, attempts) = flaky_engine(usize::MAX, false);
+    let backoff = Backoff::linear(
+        std::time::Duration::from_millis(10),
+        std::time::Duration::from_millis(10),
+    );
+    let workflow = Workflow::new("Down".to_string(), vec![flaky_step(2, backoff)]);
+
+    let error = engine.execute(&workflow).await.unwrap_err();
+
+    assert!(error.to_string().contains("connection reset by provider"));
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_step_backoff_serialization() {
+    let step = flaky_step(2, Backoff::linear(
+        std::time::Duration::from_millis(100),
+        std::time::Duration::from_millis(50),
+    ));
+
+    let json = serde_json::to_value(&step).unwrap();
+    assert_eq!(json["backoff"]["strategy"], "linear");
+    assert_eq!(json["backoff"]["increment_ms"], 50);
+
+    let mut legacy = json.clone();
+    legacy.as_object_mut().unwrap().remove("backoff");
+    let parsed: WorkflowStep = serde_json::from_value(legacy).unwrap();
+    assert_eq!(parsed.backoff, Backoff::default());
+}
+
 // ===== Workflow State Management =====
 
 #[tokio::test]
@@ -396,7 +756,7 @@ async fn test_workflow_state_includes_timestamps() {
     );
 
     let workflow = Workflow::new("Timestamped Workflow".to_string(), vec![step]);
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let result = engine.execute(&workflow).await;
     assert!(result.is_ok());
@@ -428,7 +788,7 @@ async fn test_workflow_state_step_outputs() {
     let step2_id = step2.id;
 
     let workflow = Workflow::new("Output Test".to_string(), vec![step1, step2]);
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let result = engine.execute(&workflow).await;
     assert!(result.is_ok());
@@ -446,32 +806,444 @@ async fn test_workflow_state_step_outputs() {
     assert_eq!(output2.get("status").unwrap(), "completed");
 }
 
+#[tokio::test]
+async fn test_step_receives_upstream_outputs() {
+    let step1 = WorkflowStep::new(
+        "Load".to_string(),
+        "task1".to_string(),
+        serde_json::json!({"source": "s3://bucket/data.jsonl"}),
+    );
+    let step1_id = step1.id;
+
+    let step2 = WorkflowStep::new(
+        "Evaluate".to_string(),
+        "task2".to_string(),
+        serde_json::json!({}),
+    )
+    .with_dependencies(vec![step1_id]);
+    let step2_id = step2.id;
+
+    let workflow = Workflow::new("Inputs Workflow".to_string(), vec![step1, step2]);
+    let engine = engine();
+
+    let state = engine.execute(&workflow).await.unwrap();
+
+    let output = state.step_outputs.get(&step2_id).unwrap();
+    assert_eq!(
+        output["inputs"]["Load"]["config"]["source"],
+        "s3://bucket/data.jsonl"
+    );
+}
+
+#[tokio::test]
+async fn test_builtin_tasks_run_end_to_end() {
+    let evaluate = WorkflowStep::new(
+        "Evaluate".to_string(),
+        "evaluation".to_string(),
+        serde_json::json!({"metrics": ["accuracy"], "batch_size": 25}),
+    );
+    let evaluate_id = evaluate.id;
+
+    let aggregate = WorkflowStep::new(
+        "Aggregate".to_string(),
+        "aggregation".to_string(),
+        serde_json::json!({"metrics": ["accuracy"]}),
+    )
+    .with_dependencies(vec![evaluate_id]);
+    let aggregate_id = aggregate.id;
+
+    let workflow = Workflow::new("Builtin Workflow".to_string(), vec![evaluate, aggregate]);
+    let engine = DefaultWorkflowEngine::new();
+
+    let state = engine.execute(&workflow).await.unwrap();
+
+    assert_eq!(state.step_outputs[&evaluate_id]["batches_processed"], 4);
+    let aggregated = &state.step_outputs[&aggregate_id];
+    assert_eq!(aggregated["sources"], 1);
+    assert_eq!(aggregated["metrics"]["accuracy"]["count"], 100);
+}
+
+#[tokio::test]
+async fn test_invalid_step_config_fails() {
+    let step = WorkflowStep::new(
+        "Load".to_string(),
+        "data_loading".to_string(),
+        serde_json::json!({"batch_size": "lots"}),
+    );
+
+    let workflow = Workflow::new("Invalid Config".to_string(), vec![step]);
+    let engine = DefaultWorkflowEngine::new();
+
+    let error = engine.execute(&workflow).await.unwrap_err();
+    assert!(error.to_string().contains("Invalid config for task type 'data_loading'"));
+}
+
+// ===== Step Output Reference Tests =====
+
+#[tokio::test]
+async fn test_step_config_references_resolved() {
+    let load = WorkflowStep::new(
+        "load".to_string(),
+        "task".to_string(),
+        serde_json::json!({"path": "data/cnn.jsonl", "samples": 200}),
+    );
+    let infer = WorkflowStep::new(
+        "infer".to_string(),
+        "task".to_string(),
+        serde_json::json!({
+            "dataset": "${steps.load.output.config.path}",
+            "samples": "${steps.load.output.config.samples}",
+            "title": "${steps.load.output.config.samples} samples",
+        }),
+    )
+    .with_dependencies(vec![load.id]);
+    let infer_id = infer.id;
+    let workflow = Workflow::new("References".to_string(), vec![load, infer]);
+
+    let state = engine().execute(&workflow).await.unwrap();
+
+    let config = &state.step_outputs[&infer_id]["config"];
+    assert_eq!(config["dataset"], "data/cnn.jsonl");
+    assert_eq!(config["samples"], 200);
+    assert_eq!(config["title"], "200 samples");
+    // The workflow keeps the templated config, so a resumed run resolves it again
+    assert_eq!(state.workflow.steps[1].config["dataset"], "${steps.load.output.config.path}");
+}
+
+#[tokio::test]
+async fn test_missing_reference_key_fails_step() {
+    let load = WorkflowStep::new("load".to_string(), "task".to_string(), serde_json::json!({}));
+    let infer = WorkflowStep::new(
+        "infer".to_string(),
+        "task".to_string(),
+        serde_json::json!({"dataset": "${steps.load.output.config.path}"}),
+    )
+    .with_dependencies(vec![load.id]);
+    let workflow = Workflow::new("Missing Key".to_string(), vec![load, infer]);
+    let workflow_id = workflow.id;
+    let engine = engine();
+
+    let message = engine.execute(&workflow).await.unwrap_err().to_string();
+    assert!(message.contains("Step 'infer': Config field 'dataset'"));
+    assert!(message.contains("'config' has no key 'path'"));
+
+    // Not retried: the output will not change
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Failed);
+    assert_eq!(state.workflow.steps[1].retry_count, 0);
+}
+
+// ===== Conditional Step Tests =====
+
+fn echo_step(name: &str, config: serde_json::Value) -> WorkflowStep {
+    WorkflowStep::new(name.to_string(), "task".to_string(), config)
+}
+
+#[tokio::test]
+async fn test_step_skipped_when_condition_false() {
+    let evaluate = echo_step("evaluate", serde_json::json!({"accuracy": 0.92}));
+    let judge = echo_step("judge", serde_json::json!({}))
+        .with_dependencies(vec![evaluate.id])
+        .with_condition(Condition::compare(
+            serde_json::json!("${steps.evaluate.output.config.accuracy}"),
+            CompareOp::Lt,
+            serde_json::json!("${params.threshold}"),
+        ));
+    let workflow = Workflow::new("Judge If Needed".to_string(), vec![evaluate, judge])
+        .with_parameters(serde_json::json!({"threshold": 0.9}));
+
+    let state = engine().execute(&workflow).await.unwrap();
+
+    assert_eq!(state.workflow.status, WorkflowStatus::Completed);
+    assert_eq!(state.workflow.steps[0].status, WorkflowStatus::Completed);
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Skipped);
+    assert!(!state.step_outputs.contains_key(&state.workflow.steps[1].id));
+    assert_eq!(state.progress(), 1.0);
+}
+
+#[tokio::test]
+async fn test_step_runs_when_condition_true() {
+    let evaluate = echo_step("evaluate", serde_json::json!({"accuracy": 0.71}));
+    let judge = echo_step("judge", serde_json::json!({"model": "${params.judge_model}"}))
+        .with_dependencies(vec![evaluate.id])
+        .with_condition(Condition::compare(
+            serde_json::json!("${steps.evaluate.output.config.accuracy}"),
+            CompareOp::Lt,
+            serde_json::json!("${params.threshold}"),
+        ));
+    let judge_id = judge.id;
+    let workflow = Workflow::new("Judge If Needed".to_string(), vec![evaluate, judge])
+        .with_parameters(serde_json::json!({"threshold": 0.9, "judge_model": "gpt-4"}));
+
+    let state = engine().execute(&workflow).await.unwrap();
+
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Completed);
+    assert_eq!(state.step_outputs[&judge_id]["config"]["model"], "gpt-4");
+}
+
+#[tokio::test]
+async fn test_dependents_of_skipped_step_run() {
+    let optional = echo_step("optional", serde_json::json!({}))
+        .with_condition(Condition::Truthy(serde_json::json!("${params.enabled}")));
+    let report = echo_step("report", serde_json::json!({}))
+        .with_dependencies(vec![optional.id]);
+    let workflow = Workflow::new("Optional Step".to_string(), vec![optional, report])
+        .with_parameters(serde_json::json!({"enabled": false}));
+
+    let state = engine().execute(&workflow).await.unwrap();
+
+    assert_eq!(state.workflow.steps[0].status, WorkflowStatus::Skipped);
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Completed);
+    // The skipped step contributes no input
+    let inputs = &state.step_outputs[&state.workflow.steps[1].id]["inputs"];
+    assert_eq!(inputs, &serde_json::json!({}));
+}
+
+#[tokio::test]
+async fn test_step_using_skipped_output_is_skipped() {
+    let optional = echo_step("optional", serde_json::json!({"path": "data.jsonl"}))
+        .with_condition(Condition::Truthy(serde_json::json!(false)));
+    let consumer = echo_step("consumer", serde_json::json!({"path": "${steps.optional.output.config.path}"}))
+        .with_dependencies(vec![optional.id]);
+    let after = echo_step("after", serde_json::json!({}))
+        .with_dependencies(vec![consumer.id]);
+    let workflow = Workflow::new("Skip Chain".to_string(), vec![optional, consumer, after]);
+
+    let state = engine().execute(&workflow).await.unwrap();
+
+    assert_eq!(state.workflow.status, WorkflowStatus::Completed);
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Skipped);
+    assert_eq!(state.workflow.steps[2].status, WorkflowStatus::Completed);
+}
+
+#[tokio::test]
+async fn test_condition_error_fails_step() {
+    let evaluate = echo_step("evaluate", serde_json::json!({}));
+    let judge = echo_step("judge", serde_json::json!({}))
+        .with_dependencies(vec![evaluate.id])
+        .with_condition(Condition::compare(
+            serde_json::json!("${params.threshold}"),
+            CompareOp::Gt,
+            serde_json::json!(0.5),
+        ));
+    let workflow = Workflow::new("Bad Condition".to_string(), vec![evaluate, judge]);
+    let workflow_id = workflow.id;
+    let engine = engine();
+
+    let message = engine.execute(&workflow).await.unwrap_err().to_string();
+    assert!(message.contains("Step 'judge' condition"));
+    assert!(message.contains("workflow parameters has no key 'threshold'"));
+
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Failed);
+}
+
+#[tokio::test]
+async fn test_on_failure_step_runs_after_failure() {
+    let broken = WorkflowStep::new("broken".to_string(), "failing".to_string(), serde_json::json!({}))
+        .with_max_retries(0);
+    let downstream = echo_step("downstream", serde_json::json!({}))
+        .with_dependencies(vec![broken.id]);
+    let notify = echo_step("notify", serde_json::json!({}))
+        .with_dependencies(vec![downstream.id])
+        .with_trigger(StepTrigger::OnFailure);
+    let workflow = Workflow::new("Notify On Failure".to_string(), vec![broken, downstream, notify]);
+    let workflow_id = workflow.id;
+    let engine = engine();
+
+    let error = engine.execute(&workflow).await.unwrap_err();
+    assert!(error.to_string().contains("model endpoint unavailable"));
+
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Failed);
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Pending);
+    assert_eq!(state.workflow.steps[2].status, WorkflowStatus::Completed);
+}
+
+#[tokio::test]
+async fn test_on_failure_step_skipped_on_success() {
+    let work = echo_step("work", serde_json::json!({}));
+    let notify = echo_step("notify", serde_json::json!({}))
+        .with_dependencies(vec![work.id])
+        .with_trigger(StepTrigger::OnFailure);
+    let workflow = Workflow::new("Notify On Failure".to_string(), vec![work, notify]);
+
+    let state = engine().execute(&workflow).await.unwrap();
+
+    assert_eq!(state.workflow.status, WorkflowStatus::Completed);
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Skipped);
+}
+
+#[tokio::test]
+async fn test_always_step_runs_after_regular_steps() {
+    let cleanup = echo_step("cleanup", serde_json::json!({})).with_trigger(StepTrigger::Always);
+    let first = echo_step("first", serde_json::json!({}));
+    let second = echo_step("second", serde_json::json!({})).with_dependencies(vec![first.id]);
+    let workflow = Workflow::new("Always Clean Up".to_string(), vec![cleanup, first, second]);
+    let engine = engine();
+    let mut events = engine.subscribe();
+
+    let state = engine.execute(&workflow).await.unwrap();
+    assert!(state.workflow.steps.iter().all(|s| s.status == WorkflowStatus::Completed));
+
+    // Listed first, but started only once the others completed
+    let mut completed = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if event.step_id.is_some() && event.status == WorkflowStatus::Completed {
+            completed.push(event.name);
+        }
+    }
+    assert_eq!(completed, vec!["first", "second", "cleanup"]);
+}
+
+#[tokio::test]
+async fn test_always_step_runs_after_failure() {
+    let broken = WorkflowStep::new("broken".to_string(), "failing".to_string(), serde_json::json!({}))
+        .with_max_retries(0);
+    let release = echo_step("release_gpus", serde_json::json!({})).with_trigger(StepTrigger::Always);
+    let notify = echo_step("notify", serde_json::json!({}))
+        .with_dependencies(vec![release.id])
+        .with_trigger(StepTrigger::OnFailure);
+    let workflow = Workflow::new("Clean Up".to_string(), vec![broken, release, notify]);
+    let workflow_id = workflow.id;
+    let engine = engine();
+
+    assert!(engine.execute(&workflow).await.is_err());
+
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Failed);
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Completed);
+    assert_eq!(state.workflow.steps[2].status, WorkflowStatus::Completed);
+}
+
+#[test]
+fn test_conditional_step_serialization() {
+    let step = echo_step("judge", serde_json::json!({}))
+        .with_condition(Condition::Not(Box::new(Condition::Truthy(serde_json::json!(
+            "${params.skip_judge}"
+        )))))
+        .with_trigger(StepTrigger::Always);
+
+    let serialized = serde_json::to_value(&step).unwrap();
+    assert_eq!(serialized["trigger"], "always");
+    assert_eq!(serialized["condition"], serde_json::json!({"not": {"truthy": "${params.skip_judge}"}}));
+
+    let deserialized: WorkflowStep = serde_json::from_value(serialized).unwrap();
+    assert_eq!(deserialized.condition, step.condition);
+    assert_eq!(deserialized.trigger, StepTrigger::Always);
+}
+
 // ===== Pause/Resume/Cancel Operations =====
 
 #[tokio::test]
 async fn test_pause_workflow() {
+    let (engine, gate, _) = controllable_engine();
+    let first = WorkflowStep::new("first".to_string(), "gate".to_string(), serde_json::json!({}));
+    let second = WorkflowStep::new("second".to_string(), "task".to_string(), serde_json::json!({}))
+        .with_dependencies(vec![first.id]);
+    let workflow = Workflow::new("Pausable Workflow".to_string(), vec![first, second]);
+    let workflow_id = workflow.id;
+
+    let mut events = engine.subscribe();
+    let runner = engine.clone();
+    let handle = tokio::spawn(async move { runner.execute(&workflow).await });
+    step_started(&mut events, "first").await;
+
+    // Pause while the first step runs; the engine stops before the second
+    engine.pause(workflow_id).await.unwrap();
+    gate.notify_one();
+
+    let state = handle.await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Paused);
+    assert_eq!(state.workflow.steps[0].status, WorkflowStatus::Completed);
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Pending);
+    assert_eq!(state.step_outputs.len(), 1);
+
+    // Resuming runs only the remaining step
+    engine.resume(workflow_id).await.unwrap();
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Completed);
+    assert_eq!(state.step_outputs.len(), 2);
+}
+
+#[tokio::test]
+async fn test_pause_and_resume_recorded_by_running_workflow() {
+    let (engine, gate, _) = controllable_engine();
+    let first = WorkflowStep::new("first".to_string(), "gate".to_string(), serde_json::json!({}));
+    let second = WorkflowStep::new("second".to_string(), "task".to_string(), serde_json::json!({}))
+        .with_dependencies(vec![first.id]);
+    let workflow = Workflow::new("Pausable Workflow".to_string(), vec![first, second]);
+    let workflow_id = workflow.id;
+
+    let mut events = engine.subscribe();
+    let runner = engine.clone();
+    let handle = tokio::spawn(async move { runner.execute(&workflow).await });
+    step_started(&mut events, "first").await;
+
+    // The run records the pause while its step is still in flight
+    engine.pause(workflow_id).await.unwrap();
+    workflow_status(&mut events, WorkflowStatus::Paused).await;
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Paused);
+    assert_eq!(state.workflow.steps[0].status, WorkflowStatus::Running);
+
+    engine.resume(workflow_id).await.unwrap();
+    workflow_status(&mut events, WorkflowStatus::Running).await;
+    gate.notify_one();
+
+    // The run never stopped, so it finishes both steps
+    let state = handle.await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Completed);
+    assert_eq!(state.step_outputs.len(), 2);
+    assert_eq!(
+        engine.state(workflow_id).await.unwrap().unwrap().workflow.status,
+        WorkflowStatus::Completed
+    );
+}
+
+#[tokio::test]
+async fn test_pause_keeps_steps_finished_while_pausing() {
+    let (engine, gate, _) = controllable_engine();
+    let first = WorkflowStep::new("first".to_string(), "gate".to_string(), serde_json::json!({}));
+    let second = WorkflowStep::new("second".to_string(), "task".to_string(), serde_json::json!({}))
+        .with_dependencies(vec![first.id]);
+    let workflow = Workflow::new("Pausable Workflow".to_string(), vec![first, second]);
+    let workflow_id = workflow.id;
+
+    let mut events = engine.subscribe();
+    let runner = engine.clone();
+    let handle = tokio::spawn(async move { runner.execute(&workflow).await });
+    step_started(&mut events, "first").await;
+
+    // The step finishes as the pause arrives
+    gate.notify_one();
+    engine.pause(workflow_id).await.unwrap();
+    handle.await.unwrap().unwrap();
+
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Paused);
+    assert_eq!(state.workflow.steps[0].status, WorkflowStatus::Completed);
+    assert_eq!(state.step_outputs.len(), 1);
+}
+
+#[tokio::test]
+async fn test_pause_completed_workflow_fails() {
     let step = WorkflowStep::new(
         "Step".to_string(),
         "task".to_string(),
         serde_json::json!({}),
     );
+    let workflow = Workflow::new("Finished Workflow".to_string(), vec![step]);
+    let engine = engine();
+    engine.execute(&workflow).await.unwrap();
 
-    let workflow = Workflow::new("Pausable Workflow".to_string(), vec![step]);
-    let workflow_id = workflow.id;
-    let engine = DefaultWorkflowEngine::new();
-
-    // Execute workflow first
-    let result = engine.execute(&workflow).await;
-    assert!(result.is_ok());
-
-    // Pause the workflow
-    let pause_result = engine.pause(workflow_id).await;
-    assert!(pause_result.is_ok());
+    let error = engine.pause(workflow.id).await.unwrap_err();
+    assert!(error.to_string().contains("cannot be paused"));
 }
 
 #[tokio::test]
 async fn test_pause_nonexistent_workflow() {
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
     let fake_id = Uuid::new_v4();
 
     let result = engine.pause(fake_id).await;
@@ -483,28 +1255,96 @@ async fn test_pause_nonexistent_workflow() {
 
 #[tokio::test]
 async fn test_cancel_workflow() {
+    let (engine, _gate, _) = controllable_engine();
+    let first = WorkflowStep::new("first".to_string(), "gate".to_string(), serde_json::json!({}));
+    let second = WorkflowStep::new("second".to_string(), "task".to_string(), serde_json::json!({}))
+        .with_dependencies(vec![first.id]);
+    let workflow = Workflow::new("Cancellable Workflow".to_string(), vec![first, second]);
+    let workflow_id = workflow.id;
+
+    let mut events = engine.subscribe();
+    let runner = engine.clone();
+    let handle = tokio::spawn(async move { runner.execute(&workflow).await });
+    step_started(&mut events, "first").await;
+
+    // The gate is never released: cancellation must interrupt the step
+    engine.cancel(workflow_id).await.unwrap();
+
+    let error = handle.await.unwrap().unwrap_err();
+    assert!(error.to_string().contains("cancelled"));
+
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Cancelled);
+    assert_eq!(state.workflow.steps[0].status, WorkflowStatus::Cancelled);
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Pending);
+    assert!(state.workflow.completed_at.is_some());
+}
+
+#[tokio::test]
+async fn test_cancel_completed_workflow_fails() {
     let step = WorkflowStep::new(
         "Step".to_string(),
         "task".to_string(),
         serde_json::json!({}),
     );
+    let workflow = Workflow::new("Finished Workflow".to_string(), vec![step]);
+    let engine = engine();
+    engine.execute(&workflow).await.unwrap();
+
+    let error = engine.cancel(workflow.id).await.unwrap_err();
+    assert!(error.to_string().contains("cannot be cancelled"));
+}
 
-    let workflow = Workflow::new("Cancellable Workflow".to_string(), vec![step]);
+#[tokio::test]
+async fn test_cancel_paused_workflow() {
+    let (engine, gate, _) = controllable_engine();
+    let first = WorkflowStep::new("first".to_string(), "gate".to_string(), serde_json::json!({}));
+    let second = WorkflowStep::new("second".to_string(), "task".to_string(), serde_json::json!({}))
+        .with_dependencies(vec![first.id]);
+    let workflow = Workflow::new("Paused Workflow".to_string(), vec![first, second]);
     let workflow_id = workflow.id;
-    let engine = DefaultWorkflowEngine::new();
 
-    // Execute workflow first
-    let result = engine.execute(&workflow).await;
-    assert!(result.is_ok());
+    let mut events = engine.subscribe();
+    let runner = engine.clone();
+    let handle = tokio::spawn(async move { runner.execute(&workflow).await });
+    step_started(&mut events, "first").await;
+    engine.pause(workflow_id).await.unwrap();
+    gate.notify_one();
+    handle.await.unwrap().unwrap();
+
+    engine.cancel(workflow_id).await.unwrap();
 
-    // Cancel the workflow
-    let cancel_result = engine.cancel(workflow_id).await;
-    assert!(cancel_result.is_ok());
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Cancelled);
+    assert!(engine.resume(workflow_id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_cancel_interrupts_retry_backoff() {
+    let (engine, _, _) = controllable_engine();
+    let step = WorkflowStep::new("flaky".to_string(), "failing".to_string(), serde_json::json!({}))
+        .with_max_retries(3);
+    let workflow = Workflow::new("Retrying Workflow".to_string(), vec![step]);
+    let workflow_id = workflow.id;
+
+    let mut events = engine.subscribe();
+    let runner = engine.clone();
+    let handle = tokio::spawn(async move { runner.execute(&workflow).await });
+    step_started(&mut events, "flaky").await;
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let start = std::time::Instant::now();
+    engine.cancel(workflow_id).await.unwrap();
+    assert!(handle.await.unwrap().is_err());
+    assert!(start.elapsed() < std::time::Duration::from_millis(500));
+
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Cancelled);
 }
 
 #[tokio::test]
 async fn test_cancel_nonexistent_workflow() {
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
     let fake_id = Uuid::new_v4();
 
     let result = engine.cancel(fake_id).await;
@@ -516,27 +1356,38 @@ async fn test_cancel_nonexistent_workflow() {
 
 #[tokio::test]
 async fn test_resume_paused_workflow() {
+    let (engine, _, processed) = controllable_engine();
     let step = WorkflowStep::new(
-        "Step".to_string(),
-        "task".to_string(),
-        serde_json::json!({}),
+        "batched".to_string(),
+        "batches".to_string(),
+        serde_json::json!({"batches": 40}),
     );
-
     let workflow = Workflow::new("Resumable Workflow".to_string(), vec![step]);
     let workflow_id = workflow.id;
-    let engine = DefaultWorkflowEngine::new();
 
-    // Execute workflow
-    let result = engine.execute(&workflow).await;
-    assert!(result.is_ok());
+    let mut events = engine.subscribe();
+    let runner = engine.clone();
+    let handle = tokio::spawn(async move { runner.execute(&workflow).await });
+    step_started(&mut events, "batched").await;
+    tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+
+    // The task stops at its next batch boundary and waits there
+    engine.pause(workflow_id).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+    let paused_at = processed.load(Ordering::SeqCst);
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert_eq!(processed.load(Ordering::SeqCst), paused_at);
+    assert!(paused_at < 40);
+    assert_eq!(
+        engine.state(workflow_id).await.unwrap().unwrap().workflow.status,
+        WorkflowStatus::Paused
+    );
 
-    // Pause workflow
-    let pause_result = engine.pause(workflow_id).await;
-    assert!(pause_result.is_ok());
+    engine.resume(workflow_id).await.unwrap();
 
-    // Resume workflow
-    let resume_result = engine.resume(workflow_id).await;
-    assert!(resume_result.is_ok());
+    let state = handle.await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Completed);
+    assert_eq!(processed.load(Ordering::SeqCst), 40);
 }
 
 #[tokio::test]
@@ -549,7 +1400,7 @@ async fn test_resume_non_paused_workflow() {
 
     let workflow = Workflow::new("Running Workflow".to_string(), vec![step]);
     let workflow_id = workflow.id;
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     // Execute workflow (it will be completed, not paused)
     let result = engine.execute(&workflow).await;
@@ -565,7 +1416,7 @@ async fn test_resume_non_paused_workflow() {
 
 #[tokio::test]
 async fn test_resume_nonexistent_workflow() {
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
     let fake_id = Uuid::new_v4();
 
     let result = engine.resume(fake_id).await;
@@ -575,6 +1426,63 @@ async fn test_resume_nonexistent_workflow() {
     assert!(error.to_string().contains("not found"));
 }
 
+// ===== Progress Event Tests =====
+
+#[tokio::test]
+async fn test_workflow_emits_status_events() {
+    let first = WorkflowStep::new("first".to_string(), "task".to_string(), serde_json::json!({}));
+    let second = WorkflowStep::new("second".to_string(), "task".to_string(), serde_json::json!({}))
+        .with_dependencies(vec![first.id]);
+    let workflow = Workflow::new("Observed Workflow".to_string(), vec![first, second]);
+    let engine = engine();
+    let mut events = engine.subscribe();
+
+    engine.execute(&workflow).await.unwrap();
+
+    let mut transitions = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        assert_eq!(event.workflow_id, workflow.id);
+        transitions.push((event.name, event.status, event.progress));
+    }
+
+    assert_eq!(
+        transitions,
+        vec![
+            ("Observed Workflow".to_string(), WorkflowStatus::Running, 0.0),
+            ("first".to_string(), WorkflowStatus::Running, 0.0),
+            ("first".to_string(), WorkflowStatus::Completed, 0.5),
+            ("second".to_string(), WorkflowStatus::Running, 0.5),
+            ("second".to_string(), WorkflowStatus::Completed, 1.0),
+            ("Observed Workflow".to_string(), WorkflowStatus::Completed, 1.0),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_failed_step_emits_failure_event() {
+    let step = WorkflowStep::new("broken".to_string(), "failing".to_string(), serde_json::json!({}))
+        .with_max_retries(0);
+    let workflow = Workflow::new("Failing Workflow".to_string(), vec![step]);
+    let engine = engine();
+    let mut events = engine.subscribe();
+
+    assert!(engine.execute(&workflow).await.is_err());
+
+    let mut last_step_event = None;
+    let mut last_event = None;
+    while let Ok(event) = events.try_recv() {
+        if event.step_id.is_some() {
+            last_step_event = Some(event.clone());
+        }
+        last_event = Some(event);
+    }
+
+    let step_event = last_step_event.unwrap();
+    assert_eq!(step_event.status, WorkflowStatus::Failed);
+    assert!(step_event.message.unwrap().contains("model endpoint unavailable"));
+    assert_eq!(last_event.unwrap().status, WorkflowStatus::Failed);
+}
+
 // ===== Concurrent Step Execution Tests =====
 
 #[tokio::test]
@@ -591,7 +1499,7 @@ async fn test_parallel_independent_steps() {
         .collect();
 
     let workflow = Workflow::new("Parallel Workflow".to_string(), steps);
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let start = std::time::Instant::now();
     let result = engine.execute(&workflow).await;
@@ -652,7 +1560,7 @@ async fn test_complex_dependency_graph() {
         "Complex DAG".to_string(),
         vec![step1, step2, step3, step4],
     );
-    let engine = DefaultWorkflowEngine::new();
+    let engine = engine();
 
     let result = engine.execute(&workflow).await;
     assert!(result.is_ok());
@@ -662,6 +1570,193 @@ async fn test_complex_dependency_graph() {
     assert_eq!(state.step_outputs.len(), 4);
 }
 
+#[tokio::test]
+async fn test_ready_steps_run_concurrently() {
+    let (engine, concurrency, _) = probe_engine(ResourceLimits::new(4));
+    let workflow = Workflow::new("Fan Out".to_string(), probe_steps(4));
+
+    let state = engine.execute(&workflow).await.unwrap();
+
+    assert_eq!(state.workflow.status, WorkflowStatus::Completed);
+    assert_eq!(concurrency.peak.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_respected() {
+    let (engine, concurrency, _) = probe_engine(ResourceLimits::new(2));
+    let workflow = Workflow::new("Limited".to_string(), probe_steps(6));
+
+    let state = engine.execute(&workflow).await.unwrap();
+
+    assert_eq!(state.step_outputs.len(), 6);
+    assert_eq!(concurrency.peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_max_concurrency_builder() {
+    let engine = engine().with_max_concurrency(8);
+    assert_eq!(engine.resource_limits().max_concurrency, 8);
+    assert_eq!(engine.resource_limits().gpus, 0);
+}
+
+#[tokio::test]
+async fn test_dependents_wait_for_parallel_steps() {
+    let (engine, concurrency, _) = probe_engine(ResourceLimits::new(4));
+    let mut steps = probe_steps(3);
+    let join = WorkflowStep::new("join".to_string(), "task".to_string(), serde_json::json!({}))
+        .with_dependencies(steps.iter().map(|s| s.id).collect());
+    steps.push(join);
+    let workflow = Workflow::new("Fan In".to_string(), steps);
+
+    let state = engine.execute(&workflow).await.unwrap();
+
+    let join_output = &state.step_outputs[&state.workflow.steps[3].id];
+    assert_eq!(join_output["inputs"].as_object().unwrap().len(), 3);
+    assert_eq!(concurrency.peak.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_resource_hints_limit_gpu_steps() {
+    let (engine, concurrency, _) = probe_engine(ResourceLimits::new(4).with_gpus(1));
+    let steps = probe_steps(3)
+        .into_iter()
+        .map(|step| step.with_resources(ResourceHints::new().with_gpus(1)))
+        .collect();
+    let workflow = Workflow::new("GPU Bound".to_string(), steps);
+
+    let state = engine.execute(&workflow).await.unwrap();
+
+    assert_eq!(state.workflow.status, WorkflowStatus::Completed);
+    assert_eq!(concurrency.peak.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_resource_hints_limit_memory() {
+    let (engine, concurrency, _) = probe_engine(ResourceLimits::new(4).with_memory_mb(1000));
+    let steps = probe_steps(4)
+        .into_iter()
+        .map(|step| step.with_resources(ResourceHints::new().with_memory_mb(400)))
+        .collect();
+    let workflow = Workflow::new("Memory Bound".to_string(), steps);
+
+    engine.execute(&workflow).await.unwrap();
+
+    assert_eq!(concurrency.peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_oversized_step_runs_alone() {
+    let (engine, concurrency, _) = probe_engine(ResourceLimits::new(2));
+    let mut steps = probe_steps(3);
+    steps[0].resources = ResourceHints::new().with_slots(8);
+    let workflow = Workflow::new("Oversized".to_string(), steps);
+
+    let state = engine.execute(&workflow).await.unwrap();
+
+    assert_eq!(state.workflow.status, WorkflowStatus::Completed);
+    assert_eq!(concurrency.peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_step_timeout_fails_step() {
+    let (engine, _, _gate) = probe_engine(ResourceLimits::new(4));
+    let step = WorkflowStep::new("stuck".to_string(), "gate".to_string(), serde_json::json!({}))
+        .with_max_retries(0)
+        .with_timeout_secs(1);
+    let workflow = Workflow::new("Timeout".to_string(), vec![step]);
+    let workflow_id = workflow.id;
+
+    let error = engine.execute(&workflow).await.unwrap_err();
+    assert!(error.to_string().contains("timed out after 1s"));
+
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.steps[0].status, WorkflowStatus::Failed);
+}
+
+#[tokio::test]
+async fn test_step_within_timeout_completes() {
+    let (engine, _, _) = probe_engine(ResourceLimits::new(4));
+    let step = WorkflowStep::new("quick".to_string(), "probe".to_string(), serde_json::json!({}))
+        .with_timeout_secs(5);
+    let workflow = Workflow::new("Quick".to_string(), vec![step]);
+
+    let state = engine.execute(&workflow).await.unwrap();
+    assert_eq!(state.workflow.steps[0].status, WorkflowStatus::Completed);
+}
+
+// ===== Failure Policy Tests =====
+
+#[tokio::test]
+async fn test_fail_fast_cancels_running_steps() {
+    let (engine, _, _gate) = probe_engine(ResourceLimits::new(4));
+    let blocked = WorkflowStep::new("blocked".to_string(), "gate".to_string(), serde_json::json!({}));
+    let broken = WorkflowStep::new("broken".to_string(), "failing".to_string(), serde_json::json!({}))
+        .with_max_retries(0);
+    let after = WorkflowStep::new("after".to_string(), "task".to_string(), serde_json::json!({}))
+        .with_dependencies(vec![blocked.id]);
+    let workflow = Workflow::new("Fail Fast".to_string(), vec![blocked, broken, after]);
+    let workflow_id = workflow.id;
+
+    let error = engine.execute(&workflow).await.unwrap_err();
+    assert!(error.to_string().contains("model endpoint unavailable"));
+
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Failed);
+    assert_eq!(state.workflow.steps[0].status, WorkflowStatus::Cancelled);
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Failed);
+    assert_eq!(state.workflow.steps[2].status, WorkflowStatus::Pending);
+}
+
+#[tokio::test]
+async fn test_continue_on_error_runs_independent_steps() {
+    let (engine, _, _) = probe_engine(ResourceLimits::new(1));
+    let broken = WorkflowStep::new("broken".to_string(), "failing".to_string(), serde_json::json!({}))
+        .with_max_retries(0);
+    let downstream = WorkflowStep::new("downstream".to_string(), "task".to_string(), serde_json::json!({}))
+        .with_dependencies(vec![broken.id]);
+    let independent = WorkflowStep::new("independent".to_string(), "probe".to_string(), serde_json::json!({}));
+    let workflow = Workflow::new("Keep Going".to_string(), vec![broken, downstream, independent])
+        .with_failure_policy(FailurePolicy::ContinueOnError);
+    let workflow_id = workflow.id;
+
+    assert!(engine.execute(&workflow).await.is_err());
+
+    let state = engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.status, WorkflowStatus::Failed);
+    assert_eq!(state.workflow.steps[0].status, WorkflowStatus::Failed);
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Pending);
+    assert_eq!(state.workflow.steps[2].status, WorkflowStatus::Completed);
+    assert!(state.step_outputs.contains_key(&state.workflow.steps[2].id));
+}
+
+#[tokio::test]
+async fn test_continue_on_error_reports_every_failure() {
+    let engine = engine();
+    let steps = (0..2)
+        .map(|i| {
+            WorkflowStep::new(format!("broken {}", i), "failing".to_string(), serde_json::json!({}))
+                .with_max_retries(0)
+        })
+        .collect();
+    let workflow = Workflow::new("Many Failures".to_string(), steps)
+        .with_failure_policy(FailurePolicy::ContinueOnError);
+
+    let error = engine.execute(&workflow).await.unwrap_err();
+    assert!(error.to_string().contains("2 steps failed"));
+}
+
+#[test]
+fn test_failure_policy_defaults_to_fail_fast() {
+    let workflow = Workflow::new("Default".to_string(), vec![]);
+    assert_eq!(workflow.failure_policy, FailurePolicy::FailFast);
+
+    // Workflows serialized before the field existed still load
+    let mut value = serde_json::to_value(&workflow).unwrap();
+    value.as_object_mut().unwrap().remove("failure_policy");
+    let loaded: Workflow = serde_json::from_value(value).unwrap();
+    assert_eq!(loaded.failure_policy, FailurePolicy::FailFast);
+}
+
 // ===== Workflow Serialization Tests =====
 
 #[test]
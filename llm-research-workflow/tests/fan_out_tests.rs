@@ -0,0 +1,389 @@
+use async_trait::async_trait;
+use llm_research_core::{CoreError, Result};
+use llm_research_workflow::*;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Echoes its config, failing for items marked `fail` and, the first time
+/// only, for items marked `flaky`; items marked `invalid` are rejected
+struct ShardTask {
+    config: serde_json::Value,
+    runs: Arc<Mutex<HashMap<String, usize>>>,
+    running: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Task for ShardTask {
+    async fn execute(&self, _context: TaskContext) -> Result<TaskResult> {
+        let item = &self.config["item"];
+        let runs = {
+            let mut runs = self.runs.lock().unwrap();
+            let count = runs.entry(item.to_string()).or_default();
+            *count += 1;
+            *count
+        };
+
+        let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+        self.running.fetch_sub(1, Ordering::SeqCst);
+
+        if item["invalid"] == true {
+            return Err(CoreError::Validation(format!(
+                "shard {} is malformed",
+                item["id"]
+            )));
+        }
+        if item["fail"] == true || (item["flaky"] == true && runs == 1) {
+            return Ok(TaskResult::failure(format!("shard {} failed", item["id"])));
+        }
+        Ok(TaskResult::success(json!({"config": self.config})))
+    }
+
+    fn name(&self) -> &str {
+        "shard"
+    }
+}
+
+struct Shards {
+    engine: DefaultWorkflowEngine,
+    runs: Arc<Mutex<HashMap<String, usize>>>,
+    peak: Arc<AtomicUsize>,
+}
+
+/// Engine with the built-in tasks, a "shard" task and an "echo" task
+fn shards() -> Shards {
+    let runs = Arc::new(Mutex::new(HashMap::new()));
+    let running = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+
+    let mut registry = TaskRegistry::with_builtin_tasks();
+    let (task_runs, task_peak) = (runs.clone(), peak.clone());
+    registry.register("shard", move |config| {
+        Ok(Arc::new(ShardTask {
+            config: config.clone(),
+            runs: task_runs.clone(),
+            running: running.clone(),
+            peak: task_peak.clone(),
+        }) as Arc<dyn Task>)
+    });
+    registry.register("echo", |config| {
+        Ok(Arc::new(ShardTask {
+            config: config.clone(),
+            runs: Default::default(),
+            running: Default::default(),
+            peak: Default::default(),
+        }) as Arc<dyn Task>)
+    });
+
+    Shards {
+        engine: DefaultWorkflowEngine::with_registry(registry),
+        runs,
+        peak,
+    }
+}
+
+/// A step listing `items` as its output, and a "shard" step fanned out over them
+fn fan_out_workflow(items: serde_json::Value, fan_out: FanOut) -> Workflow {
+    let split = WorkflowStep::new(
+        "split".to_string(),
+        "echo".to_string(),
+        json!({"shards": items}),
+    );
+    let process = WorkflowStep::new(
+        "process".to_string(),
+        "shard".to_string(),
+        json!({"model": "gpt-4"}),
+    )
+    .with_dependencies(vec![split.id])
+    .with_max_retries(0)
+    .with_fan_out(fan_out);
+    Workflow::new("Fan Out".to_string(), vec![split, process])
+}
+
+fn over_shards() -> FanOut {
+    FanOut::new(json!("${steps.split.output.config.shards}"))
+}
+
+fn shard_items(count: usize) -> serde_json::Value {
+    (0..count).map(|id| json!({"id": id})).collect()
+}
+
+// ===== Fan-Out Execution Tests =====
+
+#[tokio::test]
+async fn test_fan_out_runs_one_child_per_item() {
+    let shards = shards();
+    let workflow = fan_out_workflow(shard_items(3), over_shards());
+    let process_id = workflow.steps[1].id;
+
+    let state = shards.engine.execute(&workflow).await.unwrap();
+
+    let output = &state.step_outputs[&process_id];
+    assert_eq!(output["items"], 3);
+    assert_eq!(output["succeeded"], 3);
+    assert_eq!(output["failed"], 0);
+    // In item order, each child with the step config plus its item
+    for (i, child) in output["outputs"].as_array().unwrap().iter().enumerate() {
+        assert_eq!(child["config"]["model"], "gpt-4");
+        assert_eq!(child["config"]["item"]["id"], i);
+    }
+}
+
+#[tokio::test]
+async fn test_fan_out_item_key() {
+    let shards = shards();
+    let workflow = fan_out_workflow(json!(["a", "b"]), over_shards().with_item_key("prompt"));
+    let process_id = workflow.steps[1].id;
+
+    let state = shards.engine.execute(&workflow).await.unwrap();
+
+    let outputs = &state.step_outputs[&process_id]["outputs"];
+    assert_eq!(outputs[0]["config"]["prompt"], "a");
+    assert_eq!(outputs[1]["config"]["prompt"], "b");
+}
+
+#[tokio::test]
+async fn test_fan_out_over_literal_and_parameter_lists() {
+    let shards = shards();
+    let models = WorkflowStep::new("models".to_string(), "echo".to_string(), json!({}))
+        .with_fan_out(FanOut::new(json!("${params.models}")).with_item_key("model"));
+    let seeds = WorkflowStep::new("seeds".to_string(), "echo".to_string(), json!({}))
+        .with_fan_out(FanOut::new(json!([1, 2, 3])).with_item_key("seed"));
+    let workflow = Workflow::new("Sweep".to_string(), vec![models, seeds])
+        .with_parameters(json!({"models": ["gpt-4", "claude-3"]}));
+
+    let state = shards.engine.execute(&workflow).await.unwrap();
+
+    let models = &state.step_outputs[&state.workflow.steps[0].id];
+    assert_eq!(models["items"], 2);
+    assert_eq!(models["outputs"][1]["config"]["model"], "claude-3");
+    assert_eq!(state.step_outputs[&state.workflow.steps[1].id]["items"], 3);
+}
+
+#[tokio::test]
+async fn test_fan_out_bounded_concurrency() {
+    let shards = shards();
+    let workflow = fan_out_workflow(shard_items(6), over_shards().with_max_concurrency(2));
+
+    shards.engine.execute(&workflow).await.unwrap();
+
+    assert_eq!(shards.peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_fan_out_zero_concurrency_fails() {
+    let shards = shards();
+    let workflow = fan_out_workflow(shard_items(2), over_shards().with_max_concurrency(0));
+
+    // Rejected rather than left waiting for a child slot forever
+    let message = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        shards.engine.execute(&workflow),
+    )
+    .await
+    .unwrap()
+    .unwrap_err()
+    .to_string();
+    assert!(message.contains("max_concurrency must be at least 1"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_fan_out_empty_list() {
+    let shards = shards();
+    let workflow = fan_out_workflow(json!([]), over_shards());
+    let process_id = workflow.steps[1].id;
+
+    let state = shards.engine.execute(&workflow).await.unwrap();
+
+    assert_eq!(state.step_outputs[&process_id]["items"], 0);
+    assert_eq!(state.step_outputs[&process_id]["outputs"], json!([]));
+}
+
+#[tokio::test]
+async fn test_fan_out_over_non_list_fails() {
+    let shards = shards();
+    let workflow = fan_out_workflow(json!({"id": 0}), over_shards());
+    let workflow_id = workflow.id;
+
+    let message = shards
+        .engine
+        .execute(&workflow)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("Step 'process' fan-out: 'over' is an object, not a list"));
+
+    let state = shards.engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Failed);
+}
+
+#[tokio::test]
+async fn test_fan_out_skipped_with_its_list() {
+    let shards = shards();
+    let mut workflow = fan_out_workflow(shard_items(2), over_shards());
+    workflow.steps[0].condition = Some(Condition::Truthy(json!(false)));
+
+    let state = shards.engine.execute(&workflow).await.unwrap();
+
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Skipped);
+}
+
+// ===== Partial Failure Tests =====
+
+#[tokio::test]
+async fn test_fan_out_allowed_failures() {
+    let shards = shards();
+    let items = json!([{"id": 0}, {"id": 1, "fail": true}, {"id": 2}]);
+    let workflow = fan_out_workflow(items, over_shards().with_allowed_failures(1));
+    let process_id = workflow.steps[1].id;
+
+    let state = shards.engine.execute(&workflow).await.unwrap();
+
+    let output = &state.step_outputs[&process_id];
+    assert_eq!(output["succeeded"], 2);
+    assert_eq!(output["failed"], 1);
+    assert!(output["outputs"][1].is_null());
+    assert_eq!(output["errors"][0]["index"], 1);
+    assert_eq!(output["errors"][0]["error"], "shard 1 failed");
+}
+
+#[tokio::test]
+async fn test_fan_out_too_many_failures_fails_step() {
+    let shards = shards();
+    let items = json!([{"id": 0, "fail": true}, {"id": 1, "fail": true}, {"id": 2}]);
+    let workflow = fan_out_workflow(items, over_shards().with_allowed_failures(1));
+    let workflow_id = workflow.id;
+
+    let message = shards
+        .engine
+        .execute(&workflow)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("2 of 3 items failed (allowed: 1), first: shard 0 failed"));
+
+    let state = shards.engine.state(workflow_id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.steps[1].status, WorkflowStatus::Failed);
+}
+
+#[tokio::test]
+async fn test_fan_out_retry_reruns_only_failed_items() {
+    let shards = shards();
+    let items = json!([{"id": 0}, {"id": 1, "flaky": true}, {"id": 2}]);
+    let mut workflow = fan_out_workflow(items, over_shards());
+    workflow.steps[1].max_retries = 1;
+    let process_id = workflow.steps[1].id;
+
+    let state = shards.engine.execute(&workflow).await.unwrap();
+
+    assert_eq!(state.step_outputs[&process_id]["succeeded"], 3);
+    assert_eq!(state.workflow.steps[1].retry_count, 1);
+    let runs = shards.runs.lock().unwrap();
+    assert_eq!(runs[&json!({"id": 0}).to_string()], 1);
+    assert_eq!(runs[&json!({"id": 1, "flaky": true}).to_string()], 2);
+    assert_eq!(runs[&json!({"id": 2}).to_string()], 1);
+}
+
+#[tokio::test]
+async fn test_fan_out_invalid_items_not_retried() {
+    let shards = shards();
+    let items = json!([{"id": 0, "invalid": true}, {"id": 1}]);
+    let mut workflow = fan_out_workflow(items, over_shards());
+    workflow.steps[1].max_retries = 3;
+
+    let message = shards
+        .engine
+        .execute(&workflow)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("shard 0 is malformed"));
+
+    let state = shards.engine.state(workflow.id).await.unwrap().unwrap();
+    assert_eq!(state.workflow.steps[1].retry_count, 0);
+    let runs = shards.runs.lock().unwrap();
+    assert_eq!(runs[&json!({"id": 0, "invalid": true}).to_string()], 1);
+}
+
+// ===== Reduce Tests =====
+
+#[tokio::test]
+async fn test_reduce_gathers_fan_out_outputs() {
+    let shards = shards();
+    let items = json!([{"id": 0}, {"id": 1, "fail": true}, {"id": 2}]);
+    let mut workflow = fan_out_workflow(items, over_shards().with_allowed_failures(1));
+    let reduce = WorkflowStep::new(
+        "gather".to_string(),
+        "reduce".to_string(),
+        json!({"field": "config.item.id"}),
+    )
+    .with_dependencies(vec![workflow.steps[1].id]);
+    let reduce_id = reduce.id;
+    workflow.steps.push(reduce);
+
+    let state = shards.engine.execute(&workflow).await.unwrap();
+
+    let output = &state.step_outputs[&reduce_id];
+    assert_eq!(output["values"], json!([0, 2]));
+    assert_eq!(output["count"], 2);
+    assert_eq!(output["failed"], 1);
+}
+
+#[tokio::test]
+async fn test_fan_out_evaluation_then_aggregate() {
+    let evaluate = WorkflowStep::new(
+        "evaluate".to_string(),
+        "evaluation".to_string(),
+        json!({"metrics": ["accuracy"]}),
+    )
+    .with_fan_out(FanOut::new(json!(["shard-0", "shard-1"])).with_item_key("shard"));
+    let reduce = WorkflowStep::new("reduce".to_string(), "reduce".to_string(), json!({}))
+        .with_dependencies(vec![evaluate.id]);
+    let aggregate = WorkflowStep::new(
+        "aggregate".to_string(),
+        "aggregation".to_string(),
+        json!({"metrics": ["accuracy"]}),
+    )
+    .with_dependencies(vec![reduce.id]);
+    let aggregate_id = aggregate.id;
+    let workflow = Workflow::new(
+        "Sharded Evaluation".to_string(),
+        vec![evaluate, reduce, aggregate],
+    );
+
+    let state = DefaultWorkflowEngine::new()
+        .execute(&workflow)
+        .await
+        .unwrap();
+
+    // Each shard evaluated 100 samples
+    let metrics = &state.step_outputs[&aggregate_id]["metrics"];
+    assert_eq!(metrics["accuracy"]["count"], 200);
+}
+
+// ===== Serialization Tests =====
+
+#[test]
+fn test_fan_out_defaults() {
+    let fan_out: FanOut = serde_json::from_value(json!({"over": "${params.models}"})).unwrap();
+
+    assert_eq!(fan_out, FanOut::new(json!("${params.models}")));
+    assert_eq!(fan_out.item_key, "item");
+    assert_eq!(fan_out.max_concurrency, None);
+    assert_eq!(fan_out.allowed_failures, 0);
+}
+
+#[test]
+fn test_fan_out_step_serialization() {
+    let step = WorkflowStep::new("process".to_string(), "shard".to_string(), json!({}))
+        .with_fan_out(over_shards().with_max_concurrency(4));
+
+    let serialized = serde_json::to_string(&step).unwrap();
+    let deserialized: WorkflowStep = serde_json::from_str(&serialized).unwrap();
+
+    assert_eq!(deserialized.fan_out, step.fan_out);
+}
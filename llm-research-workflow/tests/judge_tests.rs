@@ -0,0 +1,411 @@
+use async_trait::async_trait;
+use llm_research_core::{CoreError, Evaluation, Result};
+use llm_research_workflow::*;
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+type Reply = Box<dyn Fn(&InferenceRequest) -> String + Send + Sync>;
+
+/// Answers each request with `reply`, and keeps the requests
+struct ScriptedJudge {
+    reply: Reply,
+    requests: Mutex<Vec<InferenceRequest>>,
+}
+
+impl ScriptedJudge {
+    fn new(reply: impl Fn(&InferenceRequest) -> String + Send + Sync + 'static) -> Arc<Self> {
+        Arc::new(Self {
+            reply: Box::new(reply),
+            requests: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn requests(&self) -> Vec<InferenceRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl InferenceBackend for ScriptedJudge {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    async fn complete(&self, request: &InferenceRequest) -> Result<Completion> {
+        self.requests.lock().unwrap().push(request.clone());
+        Ok(Completion {
+            text: (self.reply)(request),
+            model: request.model.clone(),
+            finish_reason: Some("stop".to_string()),
+            usage: TokenUsage::default(),
+            logprobs: None,
+            timing: None,
+        })
+    }
+}
+
+/// The text of the last user message of `request`
+fn last_user(request: &InferenceRequest) -> &str {
+    request
+        .messages
+        .iter()
+        .rev()
+        .find(|m| m.role == Role::User)
+        .map_or("", |m| m.content.as_str())
+}
+
+/// The response shown under `label` in a judge prompt
+fn shown<'a>(prompt: &'a str, label: &str) -> &'a str {
+    let start = prompt.find(&format!("[{}]\n", label)).unwrap() + label.len() + 3;
+    prompt[start..].lines().next().unwrap()
+}
+
+fn result(index: usize, prompt: &str, response: &str) -> serde_json::Value {
+    json!({
+        "index": index,
+        "prompt": prompt,
+        "response": response,
+        "tokens_used": 20,
+        "latency_ms": 150,
+        "provider": "simulated",
+        "model": "candidate",
+    })
+}
+
+fn config(extra: serde_json::Value) -> LlmJudgeConfig {
+    let mut config = json!({
+        "judge": {"model": "judge-model", "rate_limit_per_minute": 60_000},
+        "results": [
+            result(0, "What is 2 + 2?", "4"),
+            result(1, "What is 3 + 3?", "I am not sure"),
+        ],
+    });
+    config
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    serde_json::from_value(config).unwrap()
+}
+
+async fn judge(
+    config: LlmJudgeConfig,
+    backend: Arc<ScriptedJudge>,
+) -> std::result::Result<serde_json::Value, CoreError> {
+    LlmJudgeTask::new(config)
+        .with_backend(backend)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .map(|result| result.output)
+}
+
+fn evaluations(output: &serde_json::Value) -> Vec<Evaluation> {
+    serde_json::from_value(output["evaluations"].clone()).unwrap()
+}
+
+// ===== Verdict Parsing Tests =====
+
+#[test]
+fn test_parse_verdict() {
+    let verdict: ScoreVerdict =
+        parse_verdict("Sure.\n```json\n{\"score\": 7, \"rationale\": \"Close\"}\n```").unwrap();
+    assert_eq!(verdict.score, 7.0);
+    assert_eq!(verdict.rationale, "Close");
+
+    let verdict: PreferenceVerdict =
+        parse_verdict("{\"winner\": \"A\", \"rationale\": \"Shorter\"}").unwrap();
+    assert_eq!(verdict.winner, Preference::A);
+    let verdict: PreferenceVerdict =
+        parse_verdict("{\"winner\": \"tie\", \"rationale\": \"Same\"}").unwrap();
+    assert_eq!(verdict.winner, Preference::Tie);
+
+    assert!(parse_verdict::<ScoreVerdict>("Seven out of ten").is_err());
+    assert!(parse_verdict::<ScoreVerdict>("{\"score\": 7}").is_err());
+    assert!(
+        parse_verdict::<PreferenceVerdict>("{\"winner\": \"C\", \"rationale\": \"\"}").is_err()
+    );
+}
+
+// ===== Pointwise Tests =====
+
+#[tokio::test]
+async fn test_pointwise_scores_with_rationale() {
+    let backend = ScriptedJudge::new(|request| {
+        let response = shown(last_user(request), "Response");
+        if response == "4" {
+            json!({"score": 9, "rationale": "Correct", "criteria": {"accuracy": 10, "clarity": 8}})
+        } else {
+            json!({"score": 2, "rationale": "Evasive", "criteria": {"accuracy": 1, "clarity": 3}})
+        }
+        .to_string()
+    });
+    let sample_ids = [Uuid::new_v4(), Uuid::new_v4()];
+    let config = config(json!({
+        "rubric": {
+            "instructions": "Grade the arithmetic.",
+            "criteria": [
+                {"name": "accuracy", "description": "Is the answer right?"},
+                {"name": "clarity", "description": "Is it stated plainly?"},
+            ],
+        },
+        "samples": [
+            {"sample_id": sample_ids[0], "expected_output": "4"},
+            {"sample_id": sample_ids[1], "expected_output": "6"},
+        ],
+    }));
+    let output = judge(config, backend.clone()).await.unwrap();
+
+    assert_eq!(output["mode"], "pointwise");
+    assert_eq!(output["judge_model"], "judge-model");
+    assert_eq!(output["judgments"], 2);
+    assert_eq!(output["parse_retries"], 0);
+    assert_eq!(output["summary"]["mean_score"], 5.5);
+    assert_eq!(output["summary"]["min_score"], 2.0);
+    assert_eq!(output["summary"]["criteria"]["accuracy"], 5.5);
+
+    let evaluations = evaluations(&output);
+    assert_eq!(evaluations[0].sample_id, sample_ids[0]);
+    assert_eq!(evaluations[0].input, "What is 2 + 2?");
+    assert_eq!(evaluations[0].output, "4");
+    assert_eq!(evaluations[0].expected_output.as_deref(), Some("4"));
+    assert_eq!(evaluations[0].latency_ms, 150);
+    assert_eq!(evaluations[0].metrics["judge_score"], 9.0);
+    assert_eq!(evaluations[0].metrics["judge_normalized_score"], 8.0 / 9.0);
+    assert_eq!(evaluations[0].metrics["judge_rationale"], "Correct");
+    assert_eq!(evaluations[1].metrics["judge_rationale"], "Evasive");
+    assert_eq!(evaluations[1].metrics["judge_criteria"]["clarity"], 3.0);
+
+    let request = &backend.requests()[0];
+    assert_eq!(request.model, "judge-model");
+    assert_eq!(request.messages[0].role, Role::System);
+    let rubric = &request.messages[0].content;
+    assert!(rubric.contains("Grade the arithmetic."), "{}", rubric);
+    assert!(
+        rubric.contains("- accuracy: Is the answer right?"),
+        "{}",
+        rubric
+    );
+    assert!(rubric.contains("from 1 to 10"), "{}", rubric);
+    assert!(last_user(request).contains("[Reference answer]\n"));
+}
+
+#[tokio::test]
+async fn test_pointwise_retries_malformed_verdicts() {
+    // The first reply to each response is prose, the next out of range
+    let backend = ScriptedJudge::new(|request| match request.messages.len() {
+        2 => "I'd say this is pretty good.".to_string(),
+        4 => json!({"score": 12, "rationale": "Great"}).to_string(),
+        _ => json!({"score": 8, "rationale": "Good"}).to_string(),
+    });
+    let output = judge(config(json!({})), backend.clone()).await.unwrap();
+
+    assert_eq!(output["parse_retries"], 4);
+    assert_eq!(evaluations(&output)[0].metrics["judge_attempts"], 3);
+    assert_eq!(evaluations(&output)[0].metrics["judge_score"], 8.0);
+
+    // Each retry shows the judge its reply and what was wrong with it
+    let retry = backend
+        .requests()
+        .into_iter()
+        .find(|request| request.messages.len() == 6)
+        .unwrap();
+    assert_eq!(retry.messages[2].role, Role::Assistant);
+    assert!(retry.messages[3].content.contains("no JSON object"));
+    assert!(retry.messages[5].content.contains("outside 1 to 10"));
+}
+
+#[tokio::test]
+async fn test_pointwise_gives_up_after_retries() {
+    let backend = ScriptedJudge::new(|_| "No opinion".to_string());
+    let err = judge(config(json!({"parse_retries": 1})), backend.clone())
+        .await
+        .unwrap_err();
+
+    assert!(err.is_retryable());
+    assert!(err.to_string().contains("no valid verdict"), "{}", err);
+    assert!(err.to_string().contains("in 2 attempts"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_missing_criterion_score_is_malformed() {
+    let backend = ScriptedJudge::new(|_| json!({"score": 5, "rationale": "Fine"}).to_string());
+    let config = config(json!({
+        "rubric": {"criteria": [{"name": "tone", "description": "Polite?"}]},
+        "parse_retries": 0,
+    }));
+    let err = judge(config, backend).await.unwrap_err();
+    assert!(err.to_string().contains("no score for 'tone'"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_judge_bounds_requests_without_in_flight_limit() {
+    /// Gives every response the same score, slowly, tracking how many
+    /// requests are under way at once
+    #[derive(Default)]
+    struct SlowJudge {
+        current: AtomicUsize,
+        max: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl InferenceBackend for SlowJudge {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        async fn complete(&self, request: &InferenceRequest) -> Result<Completion> {
+            let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            self.current.fetch_sub(1, Ordering::SeqCst);
+            Ok(Completion {
+                text: json!({"score": 7, "rationale": "Fine"}).to_string(),
+                model: request.model.clone(),
+                finish_reason: Some("stop".to_string()),
+                usage: TokenUsage::default(),
+                logprobs: None,
+                timing: None,
+            })
+        }
+    }
+
+    let results: Vec<_> = (0..150)
+        .map(|i| result(i, &format!("Prompt {}", i), "Answer"))
+        .collect();
+    let config = config(json!({
+        "judge": {
+            "model": "judge-model",
+            "rate_limit_per_minute": 60_000,
+            "max_in_flight": null,
+            "rate_limit_key": "judge-tests|unbounded",
+        },
+        "results": results,
+    }));
+    let backend = Arc::new(SlowJudge::default());
+    let output = LlmJudgeTask::new(config)
+        .with_backend(backend.clone())
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap()
+        .output;
+
+    assert_eq!(output["judgments"], 150);
+    assert_eq!(backend.max.load(Ordering::SeqCst), 64);
+}
+
+// ===== Pairwise Tests =====
+
+fn pairwise(extra: serde_json::Value) -> LlmJudgeConfig {
+    let mut fields = json!({
+        "mode": "pairwise",
+        "baseline": [
+            result(0, "What is 2 + 2?", "Five"),
+            result(1, "What is 3 + 3?", "6"),
+        ],
+    });
+    fields
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    config(fields)
+}
+
+#[tokio::test]
+async fn test_pairwise_swaps_positions() {
+    // Prefers whichever response is a numeral, wherever it is shown
+    let backend = ScriptedJudge::new(|request| {
+        let prompt = last_user(request);
+        let numeral = |label| shown(prompt, label).parse::<u32>().is_ok();
+        let winner = match (numeral("Response A"), numeral("Response B")) {
+            (true, false) => "A",
+            (false, true) => "B",
+            _ => "tie",
+        };
+        json!({"winner": winner, "rationale": format!("{} is a number", winner)}).to_string()
+    });
+    let output = judge(pairwise(json!({})), backend.clone()).await.unwrap();
+
+    assert_eq!(backend.requests().len(), 4);
+    assert_eq!(output["mode"], "pairwise");
+    assert_eq!(output["parse_retries"], 0);
+    assert_eq!(output["summary"]["wins"], 1);
+    assert_eq!(output["summary"]["losses"], 1);
+    assert_eq!(output["summary"]["inconsistent"], 0);
+    assert_eq!(output["summary"]["win_rate"], 0.5);
+
+    let evaluations = evaluations(&output);
+    assert_eq!(evaluations[0].metrics["judge_preference"], "a");
+    assert_eq!(evaluations[0].metrics["judge_score"], 1.0);
+    assert_eq!(evaluations[1].metrics["judge_preference"], "b");
+    let verdicts = evaluations[0].metrics["judge_verdicts"].as_array().unwrap();
+    assert_eq!(verdicts[0]["baseline_first"], false);
+    assert_eq!(verdicts[0]["rationale"], "A is a number");
+    assert_eq!(verdicts[1]["baseline_first"], true);
+    assert_eq!(verdicts[1]["winner"], "a");
+    assert_eq!(verdicts[1]["rationale"], "B is a number");
+}
+
+#[tokio::test]
+async fn test_pairwise_cancels_position_bias() {
+    // Always prefers the first response shown
+    let backend = ScriptedJudge::new(|_| {
+        json!({"winner": "A", "rationale": "The first is better"}).to_string()
+    });
+    let output = judge(pairwise(json!({})), backend.clone()).await.unwrap();
+
+    assert_eq!(output["summary"]["ties"], 2);
+    assert_eq!(output["summary"]["inconsistent"], 2);
+    assert_eq!(output["summary"]["win_rate"], 0.5);
+    let evaluations = evaluations(&output);
+    assert_eq!(evaluations[0].metrics["judge_preference"], "tie");
+    assert_eq!(evaluations[0].metrics["judge_consistent"], false);
+
+    // Without swapping, the bias decides every comparison
+    let backend = ScriptedJudge::new(|_| {
+        json!({"winner": "A", "rationale": "The first is better"}).to_string()
+    });
+    let output = judge(pairwise(json!({"swap_positions": false})), backend.clone())
+        .await
+        .unwrap();
+    assert_eq!(backend.requests().len(), 2);
+    assert_eq!(output["summary"]["wins"], 2);
+}
+
+#[tokio::test]
+async fn test_pairwise_needs_matching_baseline() {
+    let backend = ScriptedJudge::new(|_| String::new());
+
+    let config = pairwise(json!({"baseline": [result(0, "What is 2 + 2?", "4")]}));
+    let err = judge(config, backend.clone()).await.unwrap_err();
+    assert!(matches!(err, CoreError::Validation(_)));
+
+    let config = pairwise(json!({"baseline": [
+        result(0, "What is 2 + 2?", "4"),
+        result(1, "What is 4 + 4?", "8"),
+    ]}));
+    let err = judge(config, backend.clone()).await.unwrap_err();
+    assert!(err.to_string().contains("Response 1"), "{}", err);
+    assert!(backend.requests().is_empty());
+}
+
+// ===== Registry Tests =====
+
+#[test]
+fn test_registry_builds_llm_judge() {
+    let registry = TaskRegistry::with_builtin_tasks();
+    let task = registry
+        .create(
+            "llm_judge",
+            &json!({
+                "judge": {"provider": "simulated", "model": "judge-model"},
+                "results": [result(0, "Hi", "Hello")],
+            }),
+        )
+        .unwrap();
+    assert_eq!(task.name(), "llm_judge");
+
+    let schema = registry.output_schema("llm_judge").unwrap();
+    assert_eq!(schema.field("evaluations"), Some(ValueType::Array));
+}
@@ -1,6 +1,105 @@
+use async_trait::async_trait;
+use llm_research_core::Result;
 use llm_research_workflow::pipeline::*;
+use llm_research_workflow::{Backoff, Task, TaskContext, TaskRegistry, TaskResult, WorkflowStep};
 use uuid::Uuid;
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+struct EchoTask {
+    config: serde_json::Value,
+}
+
+#[async_trait]
+impl Task for EchoTask {
+    async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
+        Ok(TaskResult::success(serde_json::json!({
+            "status": "completed",
+            "config": self.config,
+            "inputs": context.inputs,
+        })))
+    }
+
+    fn name(&self) -> &str {
+        "echo"
+    }
+}
+
+/// Fails with a retryable error until it has run `failures` times
+struct FlakyTask {
+    failures: usize,
+    runs: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Task for FlakyTask {
+    async fn execute(&self, _context: TaskContext) -> Result<TaskResult> {
+        if self.runs.fetch_add(1, Ordering::SeqCst) < self.failures {
+            return Ok(TaskResult::failure("Flaky failure".to_string()));
+        }
+        Ok(TaskResult::success(serde_json::json!({"status": "completed"})))
+    }
+
+    fn name(&self) -> &str {
+        "flaky"
+    }
+}
+
+/// Never finishes
+struct HangingTask;
+
+#[async_trait]
+impl Task for HangingTask {
+    async fn execute(&self, _context: TaskContext) -> Result<TaskResult> {
+        std::future::pending().await
+    }
+
+    fn name(&self) -> &str {
+        "hanging"
+    }
+}
+
+/// Executor that runs the placeholder task type "type" as `EchoTask`
+fn executor() -> ExperimentPipeline {
+    let mut registry = TaskRegistry::with_builtin_tasks();
+    registry.register("type", |config| {
+        Ok(Arc::new(EchoTask {
+            config: config.clone(),
+        }) as Arc<dyn Task>)
+    });
+    ExperimentPipeline::with_registry(registry)
+}
+
+/// Executor whose "flaky" task fails once, counting its runs in the returned
+/// counter, and whose "hanging" task never finishes
+fn unreliable_executor() -> (ExperimentPipeline, Arc<AtomicUsize>) {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut registry = TaskRegistry::new();
+    let counter = runs.clone();
+    registry.register("flaky", move |_| {
+        Ok(Arc::new(FlakyTask {
+            failures: 1,
+            runs: counter.clone(),
+        }) as Arc<dyn Task>)
+    });
+    registry.register("hanging", |_| Ok(Arc::new(HangingTask) as Arc<dyn Task>));
+    (ExperimentPipeline::with_registry(registry), runs)
+}
+
+fn single_task_pipeline(task: PipelineTask) -> Pipeline {
+    Pipeline {
+        id: Uuid::new_v4(),
+        name: "Single Task".to_string(),
+        stages: vec![PipelineStage {
+            id: Uuid::new_v4(),
+            name: "Only Stage".to_string(),
+            parallel: false,
+            tasks: vec![task],
+        }],
+    }
+}
 
 // ===== Pipeline Construction Tests =====
 
@@ -147,6 +246,22 @@ fn test_dag_multiple_stages() {
     assert!(dag.is_ok());
 }
 
+#[test]
+fn test_dag_from_workflow_steps() {
+    let load = WorkflowStep::new("load".to_string(), "data_loading".to_string(), serde_json::json!({}));
+    let infer = WorkflowStep::new("infer".to_string(), "inference".to_string(), serde_json::json!({}))
+        .with_dependencies(vec![load.id]);
+    let steps = [load, infer];
+
+    let dag = TaskDAG::from_tasks(steps.iter().map(PipelineTask::from)).unwrap();
+
+    let ready = dag.get_ready_tasks(&HashSet::new());
+    assert_eq!(ready, vec![steps[0].id]);
+
+    let completed: HashSet<Uuid> = [steps[0].id].into_iter().collect();
+    assert_eq!(dag.get_ready_tasks(&completed), vec![steps[1].id]);
+}
+
 // ===== Topological Sort Tests =====
 
 #[test]
@@ -494,7 +609,7 @@ async fn test_pipeline_executor_parallel_stage() {
         stages: vec![stage],
     };
 
-    let executor = ExperimentPipeline::new();
+    let executor = executor();
     let result = executor.run(&pipeline).await;
     assert!(result.is_ok());
 
@@ -520,7 +635,7 @@ async fn test_pipeline_executor_sequential_stage() {
         stages: vec![stage],
     };
 
-    let executor = ExperimentPipeline::new();
+    let executor = executor();
     let result = executor.run(&pipeline).await;
     assert!(result.is_ok());
 
@@ -528,6 +643,134 @@ async fn test_pipeline_executor_sequential_stage() {
     assert_eq!(outputs.len(), 2);
 }
 
+#[tokio::test]
+async fn test_pipeline_executor_runs_default_pipeline_tasks() {
+    let pipeline = ExperimentPipeline::default_pipeline();
+    let executor = ExperimentPipeline::new();
+
+    let outputs = executor.run(&pipeline).await.unwrap();
+    assert_eq!(outputs.len(), 5);
+
+    let task_id = |name: &str| {
+        pipeline
+            .stages
+            .iter()
+            .flat_map(|stage| &stage.tasks)
+            .find(|task| task.name == name)
+            .unwrap()
+            .id
+    };
+    assert_eq!(outputs[&task_id("load_dataset")]["total_samples"], 1000);
+    assert_eq!(outputs[&task_id("aggregate_results")]["sources"], 1);
+    assert_eq!(outputs[&task_id("generate_report")]["report_generated"], true);
+}
+
+#[tokio::test]
+async fn test_pipeline_executor_passes_dependency_outputs() {
+    let task1 = PipelineTask::new("First".to_string(), "type".to_string(), serde_json::json!({"n": 1}));
+    let task2 = PipelineTask::new("Second".to_string(), "type".to_string(), serde_json::json!({}))
+        .with_dependencies(vec![task1.id]);
+    let task2_id = task2.id;
+
+    let pipeline = Pipeline {
+        id: Uuid::new_v4(),
+        name: "Inputs".to_string(),
+        stages: vec![PipelineStage {
+            id: Uuid::new_v4(),
+            name: "Stage".to_string(),
+            parallel: false,
+            tasks: vec![task1, task2],
+        }],
+    };
+
+    let outputs = executor().run(&pipeline).await.unwrap();
+    assert_eq!(outputs[&task2_id]["inputs"]["First"]["config"]["n"], 1);
+}
+
+#[tokio::test]
+async fn test_pipeline_executor_resolves_output_references() {
+    let load = PipelineTask::new(
+        "load".to_string(),
+        "type".to_string(),
+        serde_json::json!({"path": "data/cnn.jsonl"}),
+    );
+    let infer = PipelineTask::new(
+        "infer".to_string(),
+        "type".to_string(),
+        serde_json::json!({"dataset": "${steps.load.output.config.path}"}),
+    )
+    .with_dependencies(vec![load.id]);
+    let infer_id = infer.id;
+
+    let pipeline = Pipeline {
+        id: Uuid::new_v4(),
+        name: "References".to_string(),
+        stages: vec![PipelineStage {
+            id: Uuid::new_v4(),
+            name: "Stage".to_string(),
+            parallel: false,
+            tasks: vec![load, infer],
+        }],
+    };
+
+    let outputs = executor().run(&pipeline).await.unwrap();
+    assert_eq!(outputs[&infer_id]["config"]["dataset"], "data/cnn.jsonl");
+}
+
+#[tokio::test]
+async fn test_pipeline_executor_unknown_task_type() {
+    let task = PipelineTask::new("Task".to_string(), "missing".to_string(), serde_json::json!({}));
+
+    let pipeline = Pipeline {
+        id: Uuid::new_v4(),
+        name: "Unknown".to_string(),
+        stages: vec![PipelineStage {
+            id: Uuid::new_v4(),
+            name: "Stage".to_string(),
+            parallel: false,
+            tasks: vec![task],
+        }],
+    };
+
+    let error = executor().run(&pipeline).await.unwrap_err();
+    assert!(error.to_string().contains("Unknown task type 'missing'"));
+}
+
+#[tokio::test]
+async fn test_pipeline_task_retried() {
+    let (executor, runs) = unreliable_executor();
+    let task = PipelineTask::new("flaky".to_string(), "flaky".to_string(), serde_json::json!({}))
+        .with_max_retries(1)
+        .with_backoff(Backoff::constant(Duration::from_millis(10)));
+    let task_id = task.id;
+
+    let outputs = executor.run(&single_task_pipeline(task)).await.unwrap();
+    assert_eq!(outputs[&task_id]["status"], "completed");
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_pipeline_task_not_retried_by_default() {
+    let (executor, runs) = unreliable_executor();
+    let task = PipelineTask::new("flaky".to_string(), "flaky".to_string(), serde_json::json!({}));
+
+    let error = executor.run(&single_task_pipeline(task)).await.unwrap_err();
+    assert!(error.to_string().contains("Flaky failure"));
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_pipeline_task_timeout() {
+    let (executor, _) = unreliable_executor();
+    let task = PipelineTask::new("hang".to_string(), "hanging".to_string(), serde_json::json!({}))
+        .with_timeout_secs(1);
+
+    let start = std::time::Instant::now();
+    let error = executor.run(&single_task_pipeline(task)).await.unwrap_err();
+    assert!(error.to_string().contains("Task 'hang' timed out after 1s"));
+    assert!(start.elapsed() < Duration::from_secs(3));
+}
+
 // ===== Edge Cases =====
 
 #[test]
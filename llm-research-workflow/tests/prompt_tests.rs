@@ -0,0 +1,345 @@
+use llm_research_core::{CoreError, DatasetSample, PromptTemplate};
+use llm_research_workflow::*;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn template(text: &str) -> PromptTemplate {
+    PromptTemplate::new("qa".to_string(), None, text.to_string())
+}
+
+async fn store_with(templates: &[&PromptTemplate]) -> Arc<InMemoryPromptTemplateStore> {
+    let store = Arc::new(InMemoryPromptTemplateStore::new());
+    for template in templates {
+        store.save(template).await.unwrap();
+    }
+    store
+}
+
+/// A JSONL dataset of questions about `n` numbers, the first half in the
+/// train split and the rest in test
+fn dataset(dir: &Path, n: usize) -> String {
+    let rows: Vec<String> = (0..n)
+        .map(|i| {
+            json!({
+                "question": format!("What is {} + {}?", i, i),
+                "answer": (i * 2).to_string(),
+                "meta": {"topic": "sums"},
+                "split": if i < n / 2 { "train" } else { "test" },
+            })
+            .to_string()
+        })
+        .collect();
+    let path = dir.join("qa.jsonl");
+    std::fs::write(&path, rows.join("\n")).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+fn loading(source: &str, split: &str) -> serde_json::Value {
+    json!({
+        "source": source,
+        "split": split,
+        "fields": {"expected_output": "answer"},
+    })
+}
+
+/// Batches of the samples in `split` of `source`, as a data loading step
+/// returns them
+async fn batches(source: &str, split: &str) -> serde_json::Value {
+    let config = serde_json::from_value(loading(source, split)).unwrap();
+    DataLoadingTask::new(config)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap()
+        .output["batches"]
+        .clone()
+}
+
+fn rendering(template: &PromptTemplate, batches: serde_json::Value) -> serde_json::Value {
+    json!({
+        "template_id": template.id,
+        "batches": batches,
+    })
+}
+
+async fn render(
+    store: Arc<InMemoryPromptTemplateStore>,
+    config: serde_json::Value,
+) -> Result<serde_json::Value, CoreError> {
+    let config: PromptRenderingConfig = serde_json::from_value(config).unwrap();
+    PromptRenderingTask::new(config)
+        .with_store(store)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .map(|result| result.output)
+}
+
+fn prompts(output: &serde_json::Value) -> Vec<&str> {
+    output["prompts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|prompt| prompt.as_str().unwrap())
+        .collect()
+}
+
+// ===== Template Store Tests =====
+
+#[tokio::test]
+async fn test_in_memory_store_versions() {
+    let first = template("Q: {{question}}");
+    let second = PromptTemplate {
+        version: 2,
+        template: "Question: {{question}}".to_string(),
+        ..first.clone()
+    };
+    let store = store_with(&[&second, &first]).await;
+
+    let latest = store.get(first.id, None).await.unwrap().unwrap();
+    assert_eq!(latest.version, 2);
+    let pinned = store.get(first.id, Some(1)).await.unwrap().unwrap();
+    assert_eq!(pinned.template, "Q: {{question}}");
+    assert!(store.get(first.id, Some(3)).await.unwrap().is_none());
+    assert!(store.get(Uuid::new_v4(), None).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_file_store_versions() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FilePromptTemplateStore::new(dir.path().join("prompts"))
+        .await
+        .unwrap();
+    let first = template("Q: {{question}}");
+    assert!(store.get(first.id, None).await.unwrap().is_none());
+
+    store.save(&first).await.unwrap();
+    for version in [2, 10] {
+        let later = PromptTemplate {
+            version,
+            ..first.clone()
+        };
+        store.save(&later).await.unwrap();
+    }
+
+    let reopened = FilePromptTemplateStore::new(store.dir()).await.unwrap();
+    assert_eq!(
+        reopened.get(first.id, None).await.unwrap().unwrap().version,
+        10
+    );
+    assert_eq!(
+        reopened
+            .get(first.id, Some(2))
+            .await
+            .unwrap()
+            .unwrap()
+            .version,
+        2
+    );
+    assert!(reopened.get(first.id, Some(3)).await.unwrap().is_none());
+}
+
+// ===== Rendering Tests =====
+
+#[tokio::test]
+async fn test_renders_samples_from_data_loading() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dataset(dir.path(), 6);
+    let template = template("Answer briefly.\n{{question}} ({{topic}})");
+    let store = store_with(&[&template]).await;
+
+    let mut config = rendering(&template, batches(&source, "test").await);
+    config["variables"] = json!({"topic": "meta.topic"});
+    let output = render(store, config).await.unwrap();
+
+    assert_eq!(output["template_id"], json!(template.id));
+    assert_eq!(output["template_version"], 1);
+    assert_eq!(output["samples_rendered"], 3);
+    assert_eq!(
+        prompts(&output),
+        vec![
+            "Answer briefly.\nWhat is 3 + 3? (sums)",
+            "Answer briefly.\nWhat is 4 + 4? (sums)",
+            "Answer briefly.\nWhat is 5 + 5? (sums)",
+        ]
+    );
+
+    let log = output["render_log"].as_array().unwrap();
+    assert_eq!(log.len(), 3);
+    assert_eq!(log[0]["index"], 3);
+    assert_eq!(
+        log[0]["sample_id"],
+        json!(sample_id(default_dataset_id(&source), 3))
+    );
+    assert_eq!(
+        log[0]["variables"],
+        json!({"question": "What is 3 + 3?", "topic": "sums"})
+    );
+    assert_eq!(log[0]["expected_output"], "6");
+    assert_eq!(output["few_shot_examples"], json!([]));
+}
+
+#[tokio::test]
+async fn test_renders_whole_input() {
+    let template = template("Say: {{input}}");
+    let store = store_with(&[&template]).await;
+    let sample = DatasetSample::new(Uuid::new_v4(), 0, json!("hello"), None, json!({}));
+    let batches = json!([{"samples": [sample]}]);
+    let output = render(store, rendering(&template, batches)).await.unwrap();
+
+    assert_eq!(prompts(&output), vec!["Say: hello"]);
+}
+
+#[tokio::test]
+async fn test_missing_variable_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dataset(dir.path(), 4);
+    let template = template("{{question}} in {{language}}");
+    let store = store_with(&[&template]).await;
+
+    let err = render(store, rendering(&template, batches(&source, "test").await))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, CoreError::Validation(_)));
+    assert!(!err.is_retryable());
+    let message = err.to_string();
+    assert!(message.contains("sample 2"), "{}", message);
+    assert!(
+        message.contains("Missing variable: language"),
+        "{}",
+        message
+    );
+    assert!(message.contains("has meta, question, split"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_missing_mapped_field_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dataset(dir.path(), 4);
+    let template = template("{{question}} ({{topic}})");
+    let store = store_with(&[&template]).await;
+
+    let mut config = rendering(&template, batches(&source, "test").await);
+    config["variables"] = json!({"topic": "meta.subject"});
+    let err = render(store, config).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("no input field 'meta.subject' for template variable 'topic'"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_missing_template_version() {
+    let template = template("{{question}}");
+    let store = store_with(&[&template]).await;
+
+    let mut config = rendering(&template, json!([]));
+    config["template_version"] = json!(2);
+    let err = render(Arc::clone(&store), config).await.unwrap_err();
+    assert!(matches!(err, CoreError::NotFound(_)));
+    assert!(err.to_string().contains("version 2"), "{}", err);
+
+    let unknown = json!({"template_id": Uuid::new_v4(), "batches": []});
+    let err = render(store, unknown).await.unwrap_err();
+    assert!(matches!(err, CoreError::NotFound(_)));
+}
+
+// ===== Few-Shot Tests =====
+
+#[tokio::test]
+async fn test_few_shot_examples_from_another_split() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dataset(dir.path(), 6);
+    let template = template("{{examples}}\n\nQ: {{question}}\nA:");
+    let store = store_with(&[&template]).await;
+
+    let mut examples = loading(&source, "train");
+    examples["limit"] = json!(2);
+    let mut config = rendering(&template, batches(&source, "test").await);
+    config["few_shot"] = json!({
+        "examples": examples,
+        "template": "Q: {{question}}\nA: {{expected_output}}",
+    });
+    let output = render(store, config).await.unwrap();
+
+    assert_eq!(
+        prompts(&output)[0],
+        "Q: What is 0 + 0?\nA: 0\n\nQ: What is 1 + 1?\nA: 2\n\nQ: What is 3 + 3?\nA:"
+    );
+    let dataset_id = default_dataset_id(&source);
+    assert_eq!(
+        output["few_shot_examples"],
+        json!([sample_id(dataset_id, 0), sample_id(dataset_id, 1)])
+    );
+    // The examples are logged once rather than with every sample
+    assert!(output["render_log"][0]["variables"]
+        .get("examples")
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_few_shot_examples_must_not_overlap() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dataset(dir.path(), 6);
+    let template = template("{{examples}}\n\nQ: {{question}}");
+    let store = store_with(&[&template]).await;
+
+    let mut config = rendering(&template, batches(&source, "test").await);
+    config["few_shot"] = json!({"examples": loading(&source, "test")});
+    let err = render(store, config).await.unwrap_err();
+    assert!(err.to_string().contains("another split"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_few_shot_needs_template_variable() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dataset(dir.path(), 6);
+    let template = template("Q: {{question}}");
+    let store = store_with(&[&template]).await;
+
+    let mut config = rendering(&template, batches(&source, "test").await);
+    config["few_shot"] = json!({"examples": loading(&source, "train")});
+    let err = render(store, config).await.unwrap_err();
+    assert!(err.to_string().contains("{{examples}}"), "{}", err);
+}
+
+// ===== Registry Tests =====
+
+#[tokio::test]
+async fn test_registry_renders_from_template_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dataset(dir.path(), 4);
+    let template = template("Q: {{question}}");
+    let templates = dir.path().join("prompts");
+    FilePromptTemplateStore::new(&templates)
+        .await
+        .unwrap()
+        .save(&template)
+        .await
+        .unwrap();
+
+    let mut config = rendering(&template, batches(&source, "test").await);
+    config["template_dir"] = json!(templates);
+    let task = TaskRegistry::with_builtin_tasks()
+        .create("prompt_rendering", &config)
+        .unwrap();
+    let output = task
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap()
+        .output;
+    assert_eq!(
+        prompts(&output),
+        vec!["Q: What is 2 + 2?", "Q: What is 3 + 3?"]
+    );
+
+    let err = TaskRegistry::with_builtin_tasks()
+        .create("prompt_rendering", &rendering(&template, json!([])))
+        .unwrap()
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("template_dir"), "{}", err);
+}
@@ -0,0 +1,423 @@
+use async_trait::async_trait;
+use llm_research_core::{CoreError, Result};
+use llm_research_workflow::*;
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn limiter(limits: RateLimits) -> Arc<RateLimiter> {
+    Arc::new(RateLimiter::new(limits))
+}
+
+fn requests_per_minute(n: usize) -> RateLimits {
+    RateLimits {
+        requests_per_minute: Some(n),
+        ..Default::default()
+    }
+}
+
+fn tokens_per_minute(n: usize) -> RateLimits {
+    RateLimits {
+        tokens_per_minute: Some(n),
+        ..Default::default()
+    }
+}
+
+// ===== Budget Tests =====
+
+#[tokio::test(start_paused = true)]
+async fn test_requests_per_minute_allows_short_burst_then_paces() {
+    // Five seconds' worth of 120 a minute go at once, not the whole minute's
+    let limiter = limiter(requests_per_minute(120));
+    let start = Instant::now();
+
+    for _ in 0..10 {
+        limiter.acquire(0).await.complete(0);
+    }
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    // Then one request every half second
+    limiter.acquire(0).await.complete(0);
+    assert_eq!(start.elapsed(), Duration::from_millis(500));
+    limiter.acquire(0).await.complete(0);
+    assert_eq!(start.elapsed(), Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_slow_budget_still_admits_one_request() {
+    let limiter = limiter(requests_per_minute(2));
+    let start = Instant::now();
+
+    limiter.acquire(0).await.complete(0);
+    assert_eq!(start.elapsed(), Duration::ZERO);
+    limiter.acquire(0).await.complete(0);
+    assert_eq!(start.elapsed().as_secs(), 30);
+    limiter.acquire(0).await.complete(0);
+    assert_eq!(start.elapsed().as_secs(), 60);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tokens_per_minute_waits_for_budget() {
+    // Holds 1000 tokens, refilled at 200 a second
+    let limiter = limiter(tokens_per_minute(12_000));
+    let start = Instant::now();
+
+    let first = limiter.acquire(800).await;
+    assert_eq!(start.elapsed(), Duration::ZERO);
+    first.complete(800);
+
+    // 200 left; 600 more refill in 3 seconds
+    limiter.acquire(800).await.complete(800);
+    assert_eq!(start.elapsed().as_secs(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tokens_per_minute_settles_actual_usage() {
+    let limiter = limiter(tokens_per_minute(12_000));
+    let start = Instant::now();
+
+    // The estimate was high, so the unused tokens are returned
+    limiter.acquire(800).await.complete(100);
+    limiter.acquire(800).await.complete(100);
+    assert_eq!(start.elapsed(), Duration::ZERO);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_oversized_request_waits_for_full_bucket() {
+    // Holds 50 tokens, refilled at 10 a second
+    let limiter = limiter(tokens_per_minute(600));
+    let start = Instant::now();
+
+    limiter.acquire(30).await.complete(30);
+    // Larger than the bucket: admitted once the bucket is full again
+    limiter.acquire(1200).await.complete(1200);
+    assert_eq!(start.elapsed().as_secs(), 3);
+    // and the debt is paid off before anything else is admitted
+    limiter.acquire(1).await.complete(1);
+    assert!(start.elapsed().as_secs() >= 118);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_max_in_flight() {
+    let limiter = limiter(RateLimits {
+        max_in_flight: Some(1),
+        ..Default::default()
+    });
+    let held = limiter.acquire(0).await;
+
+    let waiting = tokio::spawn({
+        let limiter = Arc::clone(&limiter);
+        async move { limiter.acquire(0).await.complete(0) }
+    });
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    assert!(!waiting.is_finished());
+
+    held.complete(0);
+    waiting.await.unwrap();
+}
+
+// ===== Rate Limit Response Tests =====
+
+#[tokio::test(start_paused = true)]
+async fn test_retry_after_pauses_every_request() {
+    let limiter = limiter(RateLimits::default());
+    let start = Instant::now();
+
+    limiter
+        .acquire(0)
+        .await
+        .rate_limited(Some(Duration::from_secs(5)));
+    assert!(limiter.paused_until().is_some());
+
+    let (a, b) = tokio::join!(limiter.acquire(0), limiter.acquire(0));
+    assert_eq!(start.elapsed().as_secs(), 5);
+    a.complete(0);
+    b.complete(0);
+    assert!(limiter.paused_until().is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limit_pause_doubles_without_retry_after() {
+    let limiter = limiter(RateLimits::default());
+    let mut pauses = Vec::new();
+    for _ in 0..3 {
+        let start = Instant::now();
+        limiter.acquire(0).await.rate_limited(None);
+        limiter.acquire(0).await.rate_limited(None);
+        pauses.push(start.elapsed().as_secs());
+    }
+    assert_eq!(pauses, vec![1, 6, 24]);
+
+    // A success resets the pause
+    limiter.acquire(0).await.complete(0);
+    let start = Instant::now();
+    limiter.acquire(0).await.rate_limited(None);
+    limiter.acquire(0).await.complete(0);
+    assert_eq!(start.elapsed().as_secs(), 1);
+}
+
+#[test]
+fn test_shared_limiters() {
+    let limits = requests_per_minute(100);
+    let a = RateLimiter::shared("rate-limit-tests|shared", limits);
+    let b = RateLimiter::shared("rate-limit-tests|shared", limits);
+    let other = RateLimiter::shared("rate-limit-tests|other", limits);
+
+    assert!(Arc::ptr_eq(&a, &b));
+    assert!(!Arc::ptr_eq(&a, &other));
+    assert_eq!(a.limits(), limits);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shared_limiter_takes_last_declared_limits() {
+    let key = "rate-limit-tests|last-declared";
+    let inference = RateLimiter::shared(key, requests_per_minute(100));
+    let judge_limits = RateLimits {
+        requests_per_minute: Some(2),
+        max_in_flight: Some(4),
+        ..Default::default()
+    };
+    let judge = RateLimiter::shared(key, judge_limits);
+    assert!(Arc::ptr_eq(&inference, &judge));
+    assert_eq!(inference.limits(), judge_limits);
+
+    // Both tasks draw on one budget of 2 requests a minute
+    let start = Instant::now();
+    inference.acquire(0).await.complete(0);
+    judge.acquire(0).await.complete(0);
+    assert_eq!(start.elapsed().as_secs(), 30);
+
+    // A looser declaration raises the shared budget again
+    let loose = RateLimiter::shared(key, requests_per_minute(1000));
+    assert!(Arc::ptr_eq(&inference, &loose));
+    assert_eq!(inference.limits(), requests_per_minute(1000));
+}
+
+#[test]
+fn test_configured_limits_override_declarations_until_reset() {
+    let key = "rate-limit-tests|configured";
+    let declared = RateLimiter::shared(key, requests_per_minute(60));
+    let configured = RateLimiter::configure(key, requests_per_minute(600));
+    assert!(Arc::ptr_eq(&declared, &configured));
+    assert_eq!(declared.limits(), requests_per_minute(600));
+
+    // Tasks declaring the defaults don't lower a configured key
+    let task = RateLimiter::shared(key, requests_per_minute(60));
+    assert!(Arc::ptr_eq(&task, &configured));
+    assert_eq!(task.limits(), requests_per_minute(600));
+
+    RateLimiter::reset(key);
+    let fresh = RateLimiter::shared(key, requests_per_minute(60));
+    assert!(!Arc::ptr_eq(&fresh, &configured));
+    assert_eq!(fresh.limits(), requests_per_minute(60));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_lowered_in_flight_limit_waits_for_held_slots() {
+    let limiter = limiter(RateLimits {
+        max_in_flight: Some(3),
+        ..Default::default()
+    });
+    let held: Vec<RatePermit> = vec![
+        limiter.acquire(0).await,
+        limiter.acquire(0).await,
+        limiter.acquire(0).await,
+    ];
+    limiter.set_limits(RateLimits {
+        max_in_flight: Some(1),
+        ..Default::default()
+    });
+
+    let waiting = tokio::spawn({
+        let limiter = Arc::clone(&limiter);
+        async move { limiter.acquire(0).await.complete(0) }
+    });
+    let mut held = held.into_iter();
+    held.next().unwrap().complete(0);
+    held.next().unwrap().complete(0);
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    assert!(!waiting.is_finished());
+
+    held.next().unwrap().complete(0);
+    waiting.await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_raised_in_flight_limit_admits_more() {
+    let limiter = limiter(RateLimits {
+        max_in_flight: Some(1),
+        ..Default::default()
+    });
+    let held = limiter.acquire(0).await;
+    limiter.set_limits(RateLimits {
+        max_in_flight: Some(2),
+        ..Default::default()
+    });
+
+    // The second slot is free while the first is still held
+    limiter.acquire(0).await.complete(0);
+    held.complete(0);
+}
+
+// ===== InferenceTask Tests =====
+
+/// Counts how many requests are outstanding at once
+#[derive(Default)]
+struct ConcurrencyProbe {
+    current: AtomicUsize,
+    max: AtomicUsize,
+}
+
+#[async_trait]
+impl InferenceBackend for ConcurrencyProbe {
+    fn name(&self) -> &str {
+        "probe"
+    }
+
+    async fn complete(&self, request: &InferenceRequest) -> Result<Completion> {
+        let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max.fetch_max(current, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        self.current.fetch_sub(1, Ordering::SeqCst);
+        SimulatedBackend.complete(request).await
+    }
+}
+
+#[tokio::test]
+async fn test_inference_task_max_in_flight() {
+    let probe = Arc::new(ConcurrencyProbe::default());
+    let config = InferenceConfig {
+        prompts: (0..8).map(|i| format!("Prompt {}", i)).collect(),
+        rate_limit_per_minute: 60_000,
+        max_in_flight: Some(2),
+        rate_limit_key: Some("rate-limit-tests|max-in-flight".to_string()),
+        ..Default::default()
+    };
+    let result = InferenceTask::new(config)
+        .with_backend(probe.clone())
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap();
+
+    assert_eq!(result.output["predictions_generated"], 8);
+    assert_eq!(probe.max.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_inference_task_bounds_requests_without_in_flight_limit() {
+    let probe = Arc::new(ConcurrencyProbe::default());
+    let config = InferenceConfig {
+        prompts: (0..150).map(|i| format!("Prompt {}", i)).collect(),
+        rate_limit_per_minute: 60_000,
+        max_in_flight: None,
+        rate_limit_key: Some("rate-limit-tests|unbounded".to_string()),
+        ..Default::default()
+    };
+    let result = InferenceTask::new(config)
+        .with_backend(probe.clone())
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap();
+
+    assert_eq!(probe.max.load(Ordering::SeqCst), 64);
+    let results = result.output["results"].as_array().unwrap();
+    assert_eq!(results.len(), 150);
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(result["index"], i);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_inference_task_paces_requests() {
+    let config = InferenceConfig {
+        provider: InferenceProvider::Simulated,
+        prompts: (0..3).map(|i| format!("Prompt {}", i)).collect(),
+        rate_limit_per_minute: 1,
+        ..Default::default()
+    };
+    let start = Instant::now();
+    let result = InferenceTask::new(config)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap();
+
+    assert_eq!(result.output["predictions_generated"], 3);
+    assert!(start.elapsed() >= Duration::from_secs(120));
+    // Latency covers the request, not the wait for the budget
+    for result in result.output["results"].as_array().unwrap() {
+        assert!(result["latency_ms"].as_u64().unwrap() < 1000);
+    }
+}
+
+#[tokio::test]
+async fn test_inference_task_honors_retry_after() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("retry-after", "1")
+                .set_body_json(json!({"error": {"message": "Rate limit reached"}})),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "model": "llama-3-8b-instruct",
+            "choices": [{"message": {"role": "assistant", "content": "Paris"}, "finish_reason": "stop"}],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 3},
+        })))
+        .mount(&server)
+        .await;
+
+    let config: InferenceConfig = serde_json::from_value(json!({
+        "provider": "local",
+        "base_url": format!("{}/v1", server.uri()),
+        "prompts": ["Capital of France?"],
+        "rate_limit_per_minute": 60000,
+        "backoff": {"strategy": "constant", "delay_ms": 10, "jitter": "none"},
+    }))
+    .unwrap();
+    let start = std::time::Instant::now();
+    let result = InferenceTask::new(config)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap();
+
+    assert_eq!(result.output["results"][0]["response"], "Paris");
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_rate_limited_error_carries_retry_after() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("retry-after-ms", "1500")
+                .set_body_json(json!({"error": {"message": "Rate limit reached"}})),
+        )
+        .mount(&server)
+        .await;
+
+    let error = OpenAiBackend::new(format!("{}/v1", server.uri()))
+        .complete(&InferenceRequest::new(
+            "llama-3-8b-instruct",
+            vec![ChatMessage::user("Hi")],
+        ))
+        .await
+        .unwrap_err();
+
+    match error {
+        CoreError::RateLimited { retry_after, .. } => {
+            assert_eq!(retry_after, Some(Duration::from_millis(1500)))
+        }
+        other => panic!("expected a rate limit error, got {:?}", other),
+    }
+}
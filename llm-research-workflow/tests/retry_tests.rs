@@ -0,0 +1,144 @@
+use llm_research_core::retry::RetryPolicy;
+use llm_research_workflow::*;
+use std::time::Duration;
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+// ===== Delay Tests =====
+
+#[test]
+fn test_default_backoff_doubles_from_one_second() {
+    let backoff = Backoff::default().with_jitter(JitterStrategy::None);
+
+    assert_eq!(backoff.delay(0, 3), Some(ms(1000)));
+    assert_eq!(backoff.delay(1, 3), Some(ms(2000)));
+    assert_eq!(backoff.delay(2, 3), Some(ms(4000)));
+    assert_eq!(backoff.delay(3, 3), None);
+}
+
+#[test]
+fn test_exponential_backoff_capped() {
+    let backoff = Backoff::exponential(ms(500))
+        .with_max_delay(ms(3000))
+        .with_jitter(JitterStrategy::None);
+
+    assert_eq!(backoff.delay(2, 10), Some(ms(2000)));
+    assert_eq!(backoff.delay(5, 10), Some(ms(3000)));
+}
+
+#[test]
+fn test_linear_and_constant_backoff() {
+    let linear = Backoff::linear(ms(100), ms(50)).with_jitter(JitterStrategy::None);
+    assert_eq!(linear.delay(0, 5), Some(ms(100)));
+    assert_eq!(linear.delay(2, 5), Some(ms(200)));
+
+    let constant = Backoff::constant(ms(250)).with_jitter(JitterStrategy::None);
+    assert_eq!(constant.delay(0, 2), Some(ms(250)));
+    assert_eq!(constant.delay(1, 2), Some(ms(250)));
+    assert_eq!(constant.delay(2, 2), None);
+}
+
+#[test]
+fn test_no_retries_means_no_delay() {
+    assert_eq!(Backoff::default().delay(0, 0), None);
+}
+
+#[test]
+fn test_jitter_bounds() {
+    let full = Backoff::constant(ms(400));
+    let equal = Backoff::constant(ms(400)).with_jitter(JitterStrategy::Equal);
+
+    for _ in 0..50 {
+        assert!(full.delay(0, 1).unwrap() <= ms(400));
+        let delay = equal.delay(0, 1).unwrap();
+        assert!(delay >= ms(200) && delay <= ms(400), "{:?}", delay);
+    }
+}
+
+#[test]
+fn test_policy_reports_max_attempts() {
+    let policy = Backoff::linear(ms(10), ms(10)).policy(4);
+    assert_eq!(policy.max_attempts(), 4);
+    assert!(policy.next_delay(3).is_some());
+    assert!(policy.next_delay(4).is_none());
+}
+
+// ===== Parsing and Validation Tests =====
+
+#[test]
+fn test_parse_backoff_with_defaults() {
+    let backoff: Backoff = serde_yaml::from_str("strategy: exponential\nmultiplier: 3\n").unwrap();
+    assert_eq!(
+        backoff,
+        Backoff::Exponential {
+            initial_delay_ms: 1000,
+            multiplier: 3.0,
+            max_delay_ms: 60_000,
+            jitter: JitterStrategy::Full,
+        }
+    );
+
+    let backoff: Backoff =
+        serde_json::from_value(serde_json::json!({"strategy": "linear", "jitter": "none"}))
+            .unwrap();
+    assert_eq!(
+        backoff,
+        Backoff::linear(ms(1000), ms(1000)).with_jitter(JitterStrategy::None)
+    );
+}
+
+#[test]
+fn test_parse_backoff_rejects_unknown_fields() {
+    let error = serde_json::from_value::<Backoff>(serde_json::json!({
+        "strategy": "constant",
+        "initial_delay_ms": 100,
+    }))
+    .unwrap_err();
+    assert!(error.to_string().contains("initial_delay_ms"));
+
+    let error = serde_json::from_value::<Backoff>(serde_json::json!({"strategy": "fibonacci"}))
+        .unwrap_err();
+    assert!(error.to_string().contains("fibonacci"));
+}
+
+#[test]
+fn test_validate_backoff() {
+    assert!(Backoff::default().validate().is_ok());
+
+    let shrinking = Backoff::Exponential {
+        initial_delay_ms: 1000,
+        multiplier: 0.5,
+        max_delay_ms: 60_000,
+        jitter: JitterStrategy::None,
+    };
+    assert!(shrinking
+        .validate()
+        .unwrap_err()
+        .contains("multiplier must be at least 1"));
+
+    let inverted = Backoff::linear(ms(5000), ms(100)).with_max_delay(ms(1000));
+    assert!(inverted
+        .validate()
+        .unwrap_err()
+        .contains("max_delay_ms (1000) is less than initial_delay_ms (5000)"));
+}
+
+// ===== Task Result Classification Tests =====
+
+#[test]
+fn test_task_result_from_error() {
+    let transient =
+        TaskResult::from_error(&llm_research_core::CoreError::Internal("503".to_string()));
+    assert!(!transient.success);
+    assert!(transient.retryable);
+
+    let invalid =
+        TaskResult::from_error(&llm_research_core::CoreError::Validation("bad".to_string()));
+    assert!(!invalid.retryable);
+    assert_eq!(invalid.error.as_deref(), Some("Validation error: bad"));
+
+    assert!(TaskResult::failure("timeout".to_string()).retryable);
+    assert!(!TaskResult::permanent_failure("unknown model".to_string()).retryable);
+}
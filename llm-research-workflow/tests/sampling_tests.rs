@@ -0,0 +1,454 @@
+use llm_research_core::{
+    CoreError, DataSplit, DatasetSample, ExperimentId, ExperimentRun, ReproducibilitySettings,
+    SampleConfig, SampleSize, SampleStrategy, UserId,
+};
+use llm_research_workflow::*;
+use serde_json::json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Samples 0..n whose `label` metadata cycles through `labels`; every fifth
+/// is in the test split
+fn samples(n: usize, labels: &[&str]) -> Vec<DatasetSample> {
+    let dataset_id = Uuid::new_v4();
+    let fields = FieldMapping {
+        input: Some("text".to_string()),
+        ..Default::default()
+    };
+    (0..n)
+        .map(|i| {
+            let row = json!({
+                "text": format!("Sample {}", i),
+                "label": labels[i % labels.len()],
+                "split": if i % 5 == 0 { "test" } else { "train" },
+            });
+            fields.sample(dataset_id, i as i64, row).unwrap()
+        })
+        .collect()
+}
+
+fn sampler(strategy: SampleStrategy, size: SampleSize, seed: u64) -> Sampler {
+    Sampler::new(
+        SampleConfig {
+            strategy,
+            size,
+            seed: Some(seed),
+            stratify_by: Some("label".to_string()),
+        },
+        &ReproducibilitySettings::default(),
+    )
+}
+
+fn indices(samples: &[DatasetSample]) -> Vec<i64> {
+    samples.iter().map(|sample| sample.index).collect()
+}
+
+// ===== Strategy Tests =====
+
+#[test]
+fn test_sequential_sample() {
+    let selected = sampler(SampleStrategy::Sequential, SampleSize::Count(3), 0)
+        .select(samples(10, &["a"]))
+        .unwrap();
+    assert_eq!(indices(&selected), vec![0, 1, 2]);
+
+    let all = sampler(SampleStrategy::Sequential, SampleSize::All, 0)
+        .select(samples(10, &["a"]))
+        .unwrap();
+    assert_eq!(all.len(), 10);
+}
+
+#[test]
+fn test_random_sample_is_seeded() {
+    let draw = |seed| {
+        indices(
+            &sampler(SampleStrategy::Random, SampleSize::Count(10), seed)
+                .select(samples(100, &["a"]))
+                .unwrap(),
+        )
+    };
+
+    let first = draw(7);
+    assert_eq!(first.len(), 10);
+    assert_eq!(first, draw(7));
+    assert_ne!(first, draw(8));
+    // Kept in dataset order
+    assert!(first.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_ne!(first, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_sample_size() {
+    let sampler = |size| sampler(SampleStrategy::Random, size, 0);
+    assert_eq!(sampler(SampleSize::All).size_of(40).unwrap(), 40);
+    assert_eq!(sampler(SampleSize::Count(50)).size_of(40).unwrap(), 40);
+    assert_eq!(sampler(SampleSize::Percentage(25)).size_of(40).unwrap(), 10);
+    // Rounded to the nearest sample
+    assert_eq!(sampler(SampleSize::Percentage(10)).size_of(45).unwrap(), 5);
+    assert_eq!(sampler(SampleSize::Percentage(10)).size_of(44).unwrap(), 4);
+
+    let error = sampler(SampleSize::Percentage(120))
+        .size_of(40)
+        .unwrap_err();
+    assert!(matches!(error, CoreError::Validation(_)));
+}
+
+#[test]
+fn test_stratified_sample_keeps_proportions() {
+    // 60 a, 30 b, 10 c
+    let labels: Vec<&str> = (0..10)
+        .map(|i| match i {
+            0..=5 => "a",
+            6..=8 => "b",
+            _ => "c",
+        })
+        .collect();
+    let selected = sampler(SampleStrategy::Stratified, SampleSize::Percentage(20), 3)
+        .select(samples(100, &labels))
+        .unwrap();
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for sample in &selected {
+        *counts
+            .entry(sample.metadata["label"].as_str().unwrap().to_string())
+            .or_default() += 1;
+    }
+    assert_eq!(selected.len(), 20);
+    assert_eq!(counts["a"], 12);
+    assert_eq!(counts["b"], 6);
+    assert_eq!(counts["c"], 2);
+
+    let again = sampler(SampleStrategy::Stratified, SampleSize::Percentage(20), 3)
+        .select(samples(100, &labels))
+        .unwrap();
+    assert_eq!(indices(&again), indices(&selected));
+}
+
+#[test]
+fn test_stratified_sample_rounds_by_largest_remainder() {
+    // 5 a, 3 b, 2 c; 5 of 10 would be 2.5, 1.5 and 1
+    let labels = ["a", "a", "a", "a", "a", "b", "b", "b", "c", "c"];
+    let selected = sampler(SampleStrategy::Stratified, SampleSize::Count(5), 0)
+        .select(samples(10, &labels))
+        .unwrap();
+
+    let count = |label: &str| {
+        selected
+            .iter()
+            .filter(|sample| sample.metadata["label"] == label)
+            .count()
+    };
+    assert_eq!(selected.len(), 5);
+    assert_eq!(count("a") + count("b"), 4);
+    assert_eq!(count("c"), 1);
+}
+
+#[test]
+fn test_stratified_sample_errors() {
+    let without_field = Sampler::new(
+        SampleConfig {
+            strategy: SampleStrategy::Stratified,
+            size: SampleSize::All,
+            seed: None,
+            stratify_by: None,
+        },
+        &ReproducibilitySettings::default(),
+    );
+    let error = without_field.select(samples(4, &["a"])).unwrap_err();
+    assert!(error.to_string().contains("needs `stratify_by`"));
+
+    let unknown_field = Sampler::new(
+        SampleConfig {
+            strategy: SampleStrategy::Stratified,
+            size: SampleSize::All,
+            seed: None,
+            stratify_by: Some("topic".to_string()),
+        },
+        &ReproducibilitySettings::default(),
+    );
+    let error = unknown_field.select(samples(4, &["a"])).unwrap_err();
+    assert!(error.to_string().contains("Sample 0 has no field 'topic'"));
+
+    let custom = sampler(
+        SampleStrategy::Custom("curriculum".to_string()),
+        SampleSize::All,
+        0,
+    );
+    assert!(matches!(
+        custom.select(samples(4, &["a"])).unwrap_err(),
+        CoreError::Validation(_)
+    ));
+}
+
+#[test]
+fn test_split() {
+    let selected = sampler(SampleStrategy::Sequential, SampleSize::All, 0)
+        .with_split(DataSplit::Test, DEFAULT_SPLIT_FIELD)
+        .select(samples(20, &["a"]))
+        .unwrap();
+    assert_eq!(indices(&selected), vec![0, 5, 10, 15]);
+
+    let holdout = sampler(SampleStrategy::Sequential, SampleSize::All, 0)
+        .with_split(
+            DataSplit::Custom("holdout".to_string()),
+            DEFAULT_SPLIT_FIELD,
+        )
+        .select(samples(20, &["a"]))
+        .unwrap();
+    assert!(holdout.is_empty());
+}
+
+// ===== Seed Tests =====
+
+#[test]
+fn test_seed_precedence() {
+    let config = |seed| SampleConfig {
+        strategy: SampleStrategy::Random,
+        size: SampleSize::Count(1),
+        seed,
+        stratify_by: None,
+    };
+    let experiment = ReproducibilitySettings {
+        random_seed: Some(42),
+        ..Default::default()
+    };
+
+    assert_eq!(Sampler::new(config(Some(7)), &experiment).seed(), 7);
+    assert_eq!(Sampler::new(config(None), &experiment).seed(), 42);
+
+    // Without either, a fresh seed is drawn and recorded
+    let sampler = Sampler::new(config(None), &ReproducibilitySettings::default());
+    let selection = sampler.selection(Vec::new());
+    assert_eq!(selection.seed, sampler.seed());
+}
+
+#[test]
+fn test_selection_recorded_on_run() {
+    let sampler = sampler(SampleStrategy::Random, SampleSize::Count(3), 11)
+        .with_split(DataSplit::Train, DEFAULT_SPLIT_FIELD);
+    let selected = sampler.select(samples(20, &["a"])).unwrap();
+    let selection = sampler.selection(selected.iter().map(|sample| sample.id).collect());
+
+    let mut run = ExperimentRun::new(ExperimentId::new(), 1, "Run".to_string(), UserId::new());
+    assert_eq!(SampleSelection::from_run(&run).unwrap(), None);
+    selection.record(&mut run).unwrap();
+
+    let recorded = SampleSelection::from_run(&run).unwrap().unwrap();
+    assert_eq!(recorded, selection);
+    assert_eq!(recorded.seed, 11);
+    assert_eq!(recorded.split, Some(DataSplit::Train));
+    assert_eq!(recorded.sample_ids.len(), 3);
+    assert!(run.metadata.contains_key(SAMPLE_SELECTION_KEY));
+}
+
+// ===== DataLoadingTask Tests =====
+
+fn dataset(dir: &tempfile::TempDir, n: usize) -> String {
+    let path = dir.path().join("qa.jsonl");
+    let rows: String = (0..n)
+        .map(|i| {
+            format!(
+                "{}\n",
+                json!({
+                    "question": format!("q{}", i),
+                    "answer": format!("a{}", i),
+                    "topic": if i % 2 == 0 { "math" } else { "history" },
+                    "split": if i < n / 2 { "train" } else { "test" },
+                })
+            )
+        })
+        .collect();
+    std::fs::write(&path, rows).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+async fn load(config: serde_json::Value) -> serde_json::Value {
+    let config: DataLoadingConfig = serde_json::from_value(config).unwrap();
+    DataLoadingTask::new(config)
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap()
+        .output
+}
+
+fn loaded_ids(output: &serde_json::Value) -> Vec<serde_json::Value> {
+    output["batches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|batch| batch["samples"].as_array().unwrap().clone())
+        .map(|sample| sample["id"].clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_data_loading_samples_split() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = json!({
+        "source": dataset(&dir, 40),
+        "batch_size": 4,
+        "stream": false,
+        "limit": null,
+        "fields": {"input": "question", "expected_output": "answer"},
+        "split": "test",
+        "sample": {
+            "strategy": "stratified",
+            "size": {"count": 6},
+            "seed": null,
+            "stratify_by": "topic",
+        },
+        "random_seed": 42,
+    });
+
+    let output = load(config.clone()).await;
+    assert_eq!(output["total_samples"], 6);
+    let selection: SampleSelection = serde_json::from_value(output["selection"].clone()).unwrap();
+    assert_eq!(selection.seed, 42);
+    assert_eq!(selection.split, Some(DataSplit::Test));
+    assert_eq!(json!(selection.sample_ids), json!(loaded_ids(&output)));
+
+    for sample in output["batches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|batch| batch["samples"].as_array().unwrap())
+    {
+        assert_eq!(sample["metadata"]["split"], "test");
+        assert!(sample["index"].as_i64().unwrap() >= 20);
+    }
+
+    // The same seed loads the same samples
+    let again = load(config.clone()).await;
+    assert_eq!(again["selection"], output["selection"]);
+
+    let mut reseeded = config;
+    reseeded["random_seed"] = json!(43);
+    assert_ne!(
+        load(reseeded).await["selection"]["sample_ids"],
+        output["selection"]["sample_ids"]
+    );
+}
+
+#[tokio::test]
+async fn test_data_loading_sequential_sample_streams() {
+    let dir = tempfile::tempdir().unwrap();
+    let task = DataLoadingTask::new(DataLoadingConfig {
+        source: dataset(&dir, 100),
+        batch_size: 2,
+        stream: true,
+        split: Some(DataSplit::Test),
+        sample: Some(SampleConfig {
+            strategy: SampleStrategy::Sequential,
+            size: SampleSize::Count(3),
+            seed: None,
+            stratify_by: None,
+        }),
+        ..Default::default()
+    });
+
+    // Only as many samples as selected are read
+    let mut stream = task.open().await.unwrap();
+    let first = stream.next_batch(10).await.unwrap();
+    assert_eq!(indices(&first), vec![50, 51, 52]);
+
+    let output = task
+        .execute(TaskContext::new(Uuid::new_v4(), json!({})))
+        .await
+        .unwrap()
+        .output;
+    assert_eq!(output["total_samples"], 3);
+    assert_eq!(
+        output["selection"]["sample_ids"],
+        json!(first.iter().map(|sample| sample.id).collect::<Vec<_>>())
+    );
+}
+
+#[tokio::test]
+async fn test_data_loading_without_sampling_records_no_selection() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = load(json!({
+        "source": dataset(&dir, 4),
+        "batch_size": 10,
+        "stream": false,
+        "limit": null,
+    }))
+    .await;
+    assert_eq!(output["total_samples"], 4);
+    assert!(output["selection"].is_null());
+}
+
+#[tokio::test]
+async fn test_data_loading_takes_experiment_seed() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = json!({
+        "source": dataset(&dir, 20),
+        "sample": {"strategy": "random", "size": {"count": 5}, "seed": null, "stratify_by": null},
+    });
+    let experiment = ReproducibilitySettings {
+        random_seed: Some(42),
+        ..Default::default()
+    };
+    let run = |config: serde_json::Value| {
+        let context =
+            TaskContext::new(Uuid::new_v4(), json!({})).with_reproducibility(experiment.clone());
+        async move {
+            DataLoadingTask::new(serde_json::from_value(config).unwrap())
+                .execute(context)
+                .await
+                .unwrap()
+                .output
+        }
+    };
+
+    let output = run(config.clone()).await;
+    assert_eq!(output["selection"]["seed"], 42);
+    assert_eq!(
+        output["selection"],
+        load(json!({
+            "source": config["source"],
+            "sample": config["sample"],
+            "random_seed": 42,
+        }))
+        .await["selection"]
+    );
+
+    // The task's own seed takes the experiment's place
+    let mut reseeded = config;
+    reseeded["random_seed"] = json!(7);
+    assert_eq!(run(reseeded).await["selection"]["seed"], 7);
+}
+
+#[tokio::test]
+async fn test_workflow_seeds_data_loading() {
+    let dir = tempfile::tempdir().unwrap();
+    let step = WorkflowStep::new(
+        "load".to_string(),
+        "data_loading".to_string(),
+        json!({
+            "source": dataset(&dir, 20),
+            "sample": {"strategy": "random", "size": {"count": 5}, "seed": null, "stratify_by": null},
+        }),
+    );
+    let engine = DefaultWorkflowEngine::with_registry(TaskRegistry::with_builtin_tasks());
+    let seed_of = |workflow: Workflow| {
+        let engine = &engine;
+        async move {
+            let state = engine.execute(&workflow).await.unwrap();
+            let output = &state.step_outputs[&state.workflow.steps[0].id];
+            SampleSelection::from_output(output).unwrap().unwrap().seed
+        }
+    };
+
+    let workflow = Workflow::new("load".to_string(), vec![step.clone()])
+        .with_parameters(json!({"random_seed": 11}));
+    assert_eq!(seed_of(workflow).await, 11);
+
+    // The experiment's own seed comes before the parameter
+    let workflow = Workflow::new("load".to_string(), vec![step])
+        .with_parameters(json!({"random_seed": 11}))
+        .with_reproducibility(ReproducibilitySettings {
+            random_seed: Some(12),
+            ..Default::default()
+        });
+    assert_eq!(seed_of(workflow).await, 12);
+}
@@ -0,0 +1,394 @@
+use async_trait::async_trait;
+use llm_research_core::Result;
+use llm_research_workflow::*;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Records the name of every step it runs and echoes its inputs
+struct RecordingTask {
+    step: String,
+    runs: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl Task for RecordingTask {
+    async fn execute(&self, context: TaskContext) -> Result<TaskResult> {
+        self.runs.lock().unwrap().push(self.step.clone());
+        Ok(TaskResult::success(serde_json::json!({
+            "step": self.step,
+            "inputs": context.inputs,
+        })))
+    }
+
+    fn name(&self) -> &str {
+        "record"
+    }
+}
+
+fn recording_engine(
+    store: Arc<dyn WorkflowStateStore>,
+) -> (DefaultWorkflowEngine, Arc<Mutex<Vec<String>>>) {
+    let runs = Arc::new(Mutex::new(Vec::new()));
+    let recorded = runs.clone();
+    let mut registry = TaskRegistry::new();
+    registry.register("record", move |config| {
+        Ok(Arc::new(RecordingTask {
+            step: config["step"].as_str().unwrap_or_default().to_string(),
+            runs: recorded.clone(),
+        }) as Arc<dyn Task>)
+    });
+
+    let engine = DefaultWorkflowEngine::with_registry(registry).with_state_store(store);
+    (engine, runs)
+}
+
+/// Three chained steps: load -> infer -> report
+fn chain_workflow() -> Workflow {
+    let load = WorkflowStep::new(
+        "load".to_string(),
+        "record".to_string(),
+        serde_json::json!({"step": "load"}),
+    );
+    let infer = WorkflowStep::new(
+        "infer".to_string(),
+        "record".to_string(),
+        serde_json::json!({"step": "infer"}),
+    )
+    .with_dependencies(vec![load.id]);
+    let report = WorkflowStep::new(
+        "report".to_string(),
+        "record".to_string(),
+        serde_json::json!({"step": "report"}),
+    )
+    .with_dependencies(vec![infer.id]);
+
+    Workflow::new("chain".to_string(), vec![load, infer, report])
+}
+
+/// State of `chain_workflow` as checkpointed by a process that died while
+/// running the second step
+fn interrupted_state() -> WorkflowState {
+    let mut state = WorkflowState::new(chain_workflow());
+    state.workflow.status = WorkflowStatus::Running;
+    state.workflow.started_at = Some(chrono::Utc::now());
+
+    let load_id = state.workflow.steps[0].id;
+    state.workflow.steps[0].status = WorkflowStatus::Completed;
+    state
+        .step_outputs
+        .insert(load_id, serde_json::json!({"step": "load", "rows": 128}));
+    state.workflow.steps[1].status = WorkflowStatus::Running;
+    state
+}
+
+fn with_status(status: WorkflowStatus) -> WorkflowState {
+    let mut state = WorkflowState::new(chain_workflow());
+    state.workflow.status = status;
+    state
+}
+
+// ===== File Store Tests =====
+
+#[tokio::test]
+async fn test_file_store_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileWorkflowStateStore::new(dir.path().join("checkpoints"))
+        .await
+        .unwrap();
+    let state = interrupted_state();
+    let workflow_id = state.workflow.id;
+
+    store.save(&state).await.unwrap();
+    let loaded = store.load(workflow_id).await.unwrap().unwrap();
+
+    assert_eq!(loaded.workflow.id, workflow_id);
+    assert_eq!(loaded.workflow.status, WorkflowStatus::Running);
+    assert_eq!(loaded.workflow.steps[0].status, WorkflowStatus::Completed);
+    assert_eq!(loaded.workflow.steps[1].status, WorkflowStatus::Running);
+    assert_eq!(loaded.step_outputs, state.step_outputs);
+
+    // Only the checkpoint itself is left behind, no temporary file
+    let files: Vec<_> = std::fs::read_dir(store.dir()).unwrap().collect();
+    assert_eq!(files.len(), 1);
+}
+
+#[tokio::test]
+async fn test_file_store_load_missing() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileWorkflowStateStore::new(dir.path()).await.unwrap();
+
+    assert!(store.load(Uuid::new_v4()).await.unwrap().is_none());
+    assert!(store.delete(Uuid::new_v4()).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_file_store_list_incomplete() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileWorkflowStateStore::new(dir.path()).await.unwrap();
+
+    for status in [
+        WorkflowStatus::Pending,
+        WorkflowStatus::Running,
+        WorkflowStatus::Paused,
+        WorkflowStatus::Completed,
+        WorkflowStatus::Failed,
+        WorkflowStatus::Cancelled,
+    ] {
+        store.save(&with_status(status)).await.unwrap();
+    }
+    std::fs::write(dir.path().join("notes.txt"), "not a checkpoint").unwrap();
+
+    let incomplete = store.list_incomplete().await.unwrap();
+    assert_eq!(incomplete.len(), 3);
+    assert!(incomplete.iter().all(|s| matches!(
+        s.workflow.status,
+        WorkflowStatus::Pending | WorkflowStatus::Running | WorkflowStatus::Paused
+    )));
+}
+
+#[tokio::test]
+async fn test_file_store_delete() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileWorkflowStateStore::new(dir.path()).await.unwrap();
+    let state = with_status(WorkflowStatus::Running);
+
+    store.save(&state).await.unwrap();
+    store.delete(state.workflow.id).await.unwrap();
+
+    assert!(store.load(state.workflow.id).await.unwrap().is_none());
+    assert!(store.list_incomplete().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_file_store_rejects_corrupt_checkpoint() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileWorkflowStateStore::new(dir.path()).await.unwrap();
+    let workflow_id = Uuid::new_v4();
+    std::fs::write(dir.path().join(format!("{}.json", workflow_id)), "{").unwrap();
+
+    assert!(store.load(workflow_id).await.is_err());
+}
+
+// ===== In-Memory Store Tests =====
+
+#[tokio::test]
+async fn test_in_memory_store() {
+    let store = InMemoryWorkflowStateStore::new();
+    let running = with_status(WorkflowStatus::Running);
+    let completed = with_status(WorkflowStatus::Completed);
+
+    store.save(&running).await.unwrap();
+    store.save(&completed).await.unwrap();
+
+    let incomplete = store.list_incomplete().await.unwrap();
+    assert_eq!(incomplete.len(), 1);
+    assert_eq!(incomplete[0].workflow.id, running.workflow.id);
+
+    store.delete(running.workflow.id).await.unwrap();
+    assert!(store.load(running.workflow.id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_stores_list_incomplete_in_started_order() {
+    let now = chrono::Utc::now();
+    let mut states = Vec::new();
+    for minutes_ago in [5, 20, 10] {
+        let mut state = with_status(WorkflowStatus::Running);
+        state.workflow.started_at = Some(now - chrono::Duration::minutes(minutes_ago));
+        states.push(state);
+    }
+    states.push(with_status(WorkflowStatus::Pending));
+    let expected: Vec<Uuid> = [3, 1, 2, 0].iter().map(|&i| states[i].workflow.id).collect();
+
+    let dir = tempfile::tempdir().unwrap();
+    let stores: [Arc<dyn WorkflowStateStore>; 2] = [
+        Arc::new(InMemoryWorkflowStateStore::new()),
+        Arc::new(FileWorkflowStateStore::new(dir.path()).await.unwrap()),
+    ];
+    for store in stores {
+        for state in &states {
+            store.save(state).await.unwrap();
+        }
+        let listed: Vec<Uuid> = store
+            .list_incomplete()
+            .await
+            .unwrap()
+            .iter()
+            .map(|state| state.workflow.id)
+            .collect();
+        assert_eq!(listed, expected);
+    }
+}
+
+// ===== Engine Checkpointing Tests =====
+
+#[tokio::test]
+async fn test_engine_checkpoints_completed_workflow() {
+    let store = Arc::new(InMemoryWorkflowStateStore::new());
+    let (engine, runs) = recording_engine(store.clone());
+    let workflow = chain_workflow();
+
+    engine.execute(&workflow).await.unwrap();
+
+    let saved = store.load(workflow.id).await.unwrap().unwrap();
+    assert_eq!(saved.workflow.status, WorkflowStatus::Completed);
+    assert_eq!(saved.step_outputs.len(), 3);
+    assert!(saved
+        .workflow
+        .steps
+        .iter()
+        .all(|s| s.status == WorkflowStatus::Completed));
+    assert_eq!(*runs.lock().unwrap(), vec!["load", "infer", "report"]);
+}
+
+#[tokio::test]
+async fn test_engine_checkpoints_failed_step() {
+    let store = Arc::new(InMemoryWorkflowStateStore::new());
+    let (engine, _runs) = recording_engine(store.clone());
+    let step = WorkflowStep::new(
+        "missing".to_string(),
+        "unknown".to_string(),
+        serde_json::json!({}),
+    );
+    let workflow = Workflow::new("broken".to_string(), vec![step]);
+
+    assert!(engine.execute(&workflow).await.is_err());
+
+    let saved = store.load(workflow.id).await.unwrap().unwrap();
+    assert_eq!(saved.workflow.status, WorkflowStatus::Failed);
+    assert_eq!(saved.workflow.steps[0].status, WorkflowStatus::Failed);
+    assert!(saved.workflow.error.is_some());
+}
+
+#[tokio::test]
+async fn test_engine_state_falls_back_to_store() {
+    let store = Arc::new(InMemoryWorkflowStateStore::new());
+    let state = interrupted_state();
+    store.save(&state).await.unwrap();
+
+    let (engine, _runs) = recording_engine(store);
+    let loaded = engine.state(state.workflow.id).await.unwrap().unwrap();
+    assert_eq!(loaded.workflow.status, WorkflowStatus::Running);
+}
+
+// ===== Recovery Tests =====
+
+#[tokio::test]
+async fn test_recover_skips_completed_steps() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(FileWorkflowStateStore::new(dir.path()).await.unwrap());
+    let state = interrupted_state();
+    let workflow_id = state.workflow.id;
+    store.save(&state).await.unwrap();
+
+    // A fresh engine, as after a restart
+    let (engine, runs) = recording_engine(store.clone());
+    let recovered = engine.recover().await.unwrap();
+
+    assert_eq!(recovered.len(), 1);
+    assert_eq!(recovered[0].workflow.status, WorkflowStatus::Completed);
+    assert_eq!(*runs.lock().unwrap(), vec!["infer", "report"]);
+
+    // The interrupted step sees the output checkpointed before the restart
+    let infer_id = recovered[0].workflow.steps[1].id;
+    assert_eq!(
+        recovered[0].step_outputs[&infer_id]["inputs"]["load"]["rows"],
+        128
+    );
+
+    let saved = store.load(workflow_id).await.unwrap().unwrap();
+    assert_eq!(saved.workflow.status, WorkflowStatus::Completed);
+    assert!(store.list_incomplete().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_recover_runs_pending_workflow() {
+    let store = Arc::new(InMemoryWorkflowStateStore::new());
+    store
+        .save(&with_status(WorkflowStatus::Pending))
+        .await
+        .unwrap();
+
+    let (engine, runs) = recording_engine(store);
+    let recovered = engine.recover().await.unwrap();
+
+    assert_eq!(recovered.len(), 1);
+    assert_eq!(runs.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_recover_ignores_finished_workflows() {
+    let store = Arc::new(InMemoryWorkflowStateStore::new());
+    store
+        .save(&with_status(WorkflowStatus::Completed))
+        .await
+        .unwrap();
+    store
+        .save(&with_status(WorkflowStatus::Failed))
+        .await
+        .unwrap();
+    store
+        .save(&with_status(WorkflowStatus::Cancelled))
+        .await
+        .unwrap();
+
+    let (engine, runs) = recording_engine(store);
+
+    assert!(engine.recover().await.unwrap().is_empty());
+    assert!(runs.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_recover_keeps_paused_workflow_paused() {
+    let store = Arc::new(InMemoryWorkflowStateStore::new());
+    let mut state = interrupted_state();
+    state.workflow.status = WorkflowStatus::Paused;
+    let workflow_id = state.workflow.id;
+    store.save(&state).await.unwrap();
+
+    let (engine, runs) = recording_engine(store.clone());
+    assert!(engine.recover().await.unwrap().is_empty());
+    assert!(runs.lock().unwrap().is_empty());
+
+    engine.resume(workflow_id).await.unwrap();
+
+    assert_eq!(*runs.lock().unwrap(), vec!["infer", "report"]);
+    let saved = store.load(workflow_id).await.unwrap().unwrap();
+    assert_eq!(saved.workflow.status, WorkflowStatus::Completed);
+}
+
+#[tokio::test]
+async fn test_recover_reports_failed_workflow() {
+    let store = Arc::new(InMemoryWorkflowStateStore::new());
+    let mut state = interrupted_state();
+    state.workflow.steps[2].task_type = "unknown".to_string();
+    store.save(&state).await.unwrap();
+
+    let (engine, runs) = recording_engine(store);
+    let recovered = engine.recover().await.unwrap();
+
+    assert_eq!(recovered.len(), 1);
+    assert_eq!(recovered[0].workflow.status, WorkflowStatus::Failed);
+    assert_eq!(*runs.lock().unwrap(), vec!["infer"]);
+}
+
+#[tokio::test]
+async fn test_recover_requires_state_store() {
+    let engine = DefaultWorkflowEngine::new();
+    assert!(engine.recover().await.is_err());
+}
+
+#[tokio::test]
+async fn test_resume_from_store_after_restart() {
+    let store = Arc::new(InMemoryWorkflowStateStore::new());
+    let mut state = interrupted_state();
+    state.workflow.status = WorkflowStatus::Paused;
+    let workflow_id = state.workflow.id;
+    store.save(&state).await.unwrap();
+
+    // resume finds the workflow in the store without an explicit recover
+    let (engine, runs) = recording_engine(store);
+    engine.resume(workflow_id).await.unwrap();
+
+    assert_eq!(*runs.lock().unwrap(), vec!["infer", "report"]);
+}
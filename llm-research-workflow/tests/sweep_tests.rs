@@ -0,0 +1,825 @@
+use async_trait::async_trait;
+use llm_research_core::{
+    CoreError, ExperimentId, ExperimentParameters, ParameterValue, Result, RunMetrics, RunStatus,
+    ScalarMetricPoint, ScalarMetricSeries, SearchSpace, SearchStrategy, UserId,
+};
+use llm_research_workflow::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+fn space(name: &str, values: Vec<ParameterValue>, distribution: Option<&str>) -> SearchSpace {
+    SearchSpace {
+        parameter_name: name.to_string(),
+        values,
+        distribution: distribution.map(str::to_string),
+    }
+}
+
+fn parameters(
+    strategy: SearchStrategy,
+    spaces: Vec<SearchSpace>,
+    max_trials: Option<u32>,
+) -> ExperimentParameters {
+    ExperimentParameters {
+        fixed: HashMap::from([("model".to_string(), ParameterValue::String("m".into()))]),
+        search_spaces: spaces,
+        search_strategy: Some(strategy),
+        max_trials,
+        concurrent_trials: Some(1),
+    }
+}
+
+fn sweep(parameters: ExperimentParameters, objective: Objective) -> Sweep {
+    Sweep::new(ExperimentId::new(), UserId::new(), parameters, objective).with_seed(7)
+}
+
+fn float(value: &ParameterValue) -> f64 {
+    match value {
+        ParameterValue::Float(f) => *f,
+        ParameterValue::Integer(i) => *i as f64,
+        other => panic!("not a number: {:?}", other),
+    }
+}
+
+fn series(name: &str, points: impl IntoIterator<Item = (u64, f64)>) -> ScalarMetricSeries {
+    ScalarMetricSeries {
+        name: name.to_string(),
+        points: points
+            .into_iter()
+            .map(|(step, value)| ScalarMetricPoint {
+                step,
+                value,
+                timestamp: chrono::Utc::now(),
+            })
+            .collect(),
+        unit: None,
+        tags: HashMap::new(),
+    }
+}
+
+/// Scores trials by `score = -(x - 3)^2 - (y - 1)^2`, over `x` alone if
+/// there is no `y`, and records every trial it runs. Trials with `x` equal to
+/// `fail` fail.
+#[derive(Default)]
+struct QuadraticRunner {
+    trials: Mutex<Vec<Trial>>,
+    running: AtomicUsize,
+    peak: AtomicUsize,
+    fail: Option<f64>,
+}
+
+#[async_trait]
+impl TrialRunner for QuadraticRunner {
+    async fn run_trial(&self, trial: &Trial) -> Result<RunMetrics> {
+        self.trials.lock().unwrap().push(trial.clone());
+        let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(tokio::time::Duration::from_millis(5)).await;
+        self.running.fetch_sub(1, Ordering::SeqCst);
+
+        let x = float(&trial.parameters["x"]);
+        if Some(x) == self.fail {
+            return Err(CoreError::Internal("out of memory".to_string()));
+        }
+        let y = trial.parameters.get("y").map(float).unwrap_or(1.0);
+        let mut metrics = RunMetrics::default();
+        metrics.custom_metrics.insert(
+            "score".to_string(),
+            json!(-(x - 3.0).powi(2) - (y - 1.0).powi(2)),
+        );
+        Ok(metrics)
+    }
+}
+
+// ===== Search Space Tests =====
+
+#[test]
+fn test_domain_from_space() {
+    let choice = Domain::from_space(&space(
+        "lr",
+        vec![ParameterValue::Float(0.1), ParameterValue::Float(0.01)],
+        None,
+    ))
+    .unwrap();
+    assert!(choice.is_categorical());
+
+    let uniform = Domain::from_space(&space(
+        "temperature",
+        vec![ParameterValue::Float(0.0), ParameterValue::Float(2.0)],
+        Some("uniform"),
+    ))
+    .unwrap();
+    assert_eq!(
+        uniform,
+        Domain::Uniform {
+            low: 0.0,
+            high: 2.0
+        }
+    );
+    assert_eq!(uniform.value_at(0.25), ParameterValue::Float(0.5));
+    assert_eq!(uniform.position(&ParameterValue::Float(1.5)), Some(0.75));
+
+    let log = Domain::from_space(&space(
+        "lr",
+        vec![ParameterValue::Float(1e-4), ParameterValue::Float(1e-2)],
+        Some("log-uniform"),
+    ))
+    .unwrap();
+    assert!((float(&log.value_at(0.5)) - 1e-3).abs() < 1e-9);
+
+    let ints = Domain::from_space(&space(
+        "layers",
+        vec![ParameterValue::Integer(1), ParameterValue::Integer(3)],
+        Some("int-uniform"),
+    ))
+    .unwrap();
+    assert_eq!(
+        ints.values(),
+        Some((1..=3).map(ParameterValue::Integer).collect())
+    );
+}
+
+#[test]
+fn test_invalid_search_spaces() {
+    let error = |s: SearchSpace| Domain::from_space(&s).unwrap_err().to_string();
+
+    assert!(error(space("a", vec![], None)).contains("Search space 'a': no values"));
+    assert!(error(space(
+        "b",
+        vec![ParameterValue::Float(1.0)],
+        Some("uniform")
+    ))
+    .contains("takes 2 values, got 1"));
+    assert!(error(space(
+        "c",
+        vec![ParameterValue::Float(2.0), ParameterValue::Float(1.0)],
+        Some("uniform")
+    ))
+    .contains("lower bound 2 is not below 1"));
+    assert!(error(space(
+        "d",
+        vec![ParameterValue::Float(0.0), ParameterValue::Float(1.0)],
+        Some("log-uniform")
+    ))
+    .contains("must be positive"));
+    assert!(error(space(
+        "e",
+        vec![ParameterValue::Float(0.0), ParameterValue::Float(1.0)],
+        Some("beta")
+    ))
+    .contains("unknown distribution 'beta'"));
+
+    let duplicate = ParameterSpace::new(&[
+        space("x", vec![ParameterValue::Integer(1)], None),
+        space("x", vec![ParameterValue::Integer(2)], None),
+    ])
+    .unwrap_err();
+    assert!(duplicate
+        .to_string()
+        .contains("Parameter 'x' has more than one search space"));
+}
+
+#[test]
+fn test_grid_is_cartesian_product() {
+    let space = ParameterSpace::new(&[
+        space("x", (1..=2).map(ParameterValue::Integer).collect(), None),
+        space(
+            "y",
+            ["a", "b", "c"]
+                .map(|v| ParameterValue::String(v.to_string()))
+                .to_vec(),
+            None,
+        ),
+    ])
+    .unwrap();
+    let grid = space.grid().unwrap();
+    assert_eq!(grid.len(), 6);
+    assert_eq!(grid[0]["x"], ParameterValue::Integer(1));
+    assert_eq!(grid[0]["y"], ParameterValue::String("a".into()));
+    assert_eq!(grid[5]["x"], ParameterValue::Integer(2));
+    assert_eq!(grid[5]["y"], ParameterValue::String("c".into()));
+}
+
+#[test]
+fn test_grid_rejects_continuous_parameters() {
+    let space = ParameterSpace::new(&[space(
+        "temperature",
+        vec![ParameterValue::Float(0.0), ParameterValue::Float(1.0)],
+        Some("uniform"),
+    )])
+    .unwrap();
+    let error = space.grid().unwrap_err().to_string();
+    assert!(error.contains("'temperature' has a continuous distribution"));
+}
+
+#[test]
+fn test_seeded_sampling_is_reproducible() {
+    let space = ParameterSpace::new(&[
+        space(
+            "x",
+            vec![ParameterValue::Float(-1.0), ParameterValue::Float(1.0)],
+            Some("uniform"),
+        ),
+        space(
+            "n",
+            vec![ParameterValue::Integer(1), ParameterValue::Integer(4)],
+            Some("randint"),
+        ),
+    ])
+    .unwrap();
+    let draw = |seed| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..20).map(|_| space.sample(&mut rng)).collect::<Vec<_>>()
+    };
+
+    assert_eq!(draw(1), draw(1));
+    assert_ne!(draw(1), draw(2));
+    for trial in draw(1) {
+        assert!((-1.0..=1.0).contains(&float(&trial["x"])));
+        assert!((1.0..=4.0).contains(&float(&trial["n"])));
+    }
+}
+
+// ===== Hyperband Schedule Tests =====
+
+#[test]
+fn test_hyperband_brackets() {
+    let config = HyperbandConfig {
+        min_budget: 1,
+        max_budget: 27,
+        eta: 3,
+    };
+    let brackets = config.brackets();
+    assert_eq!(brackets.len(), 4);
+
+    let first: Vec<(usize, u64)> = brackets[0].iter().map(|r| (r.trials, r.budget)).collect();
+    assert_eq!(first, vec![(27, 1), (9, 3), (3, 9), (1, 27)]);
+    let last: Vec<(usize, u64)> = brackets[3].iter().map(|r| (r.trials, r.budget)).collect();
+    assert_eq!(last, vec![(4, 27)]);
+    for bracket in &brackets {
+        assert_eq!(bracket.last().unwrap().budget, 27);
+    }
+
+    let invalid = HyperbandConfig { eta: 1, ..config };
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn test_hyperband_brackets_with_huge_eta() {
+    let config = HyperbandConfig {
+        min_budget: 1,
+        max_budget: u64::MAX,
+        eta: 1 << 32,
+    };
+    assert!(config.validate().is_ok());
+
+    let brackets = config.brackets();
+    assert_eq!(brackets.len(), 2);
+    for bracket in &brackets {
+        assert_eq!(bracket.last().unwrap().budget, u64::MAX);
+    }
+}
+
+// ===== Sweep Tests =====
+
+#[tokio::test]
+async fn test_grid_sweep_finds_best() {
+    let runner = Arc::new(QuadraticRunner::default());
+    let values = (0..6).map(ParameterValue::Integer).collect();
+    let report = sweep(
+        parameters(SearchStrategy::Grid, vec![space("x", values, None)], None),
+        Objective::maximize("score"),
+    )
+    .run(runner.clone())
+    .await
+    .unwrap();
+
+    assert_eq!(report.trials.len(), 6);
+    let best = report.best.unwrap();
+    assert_eq!(best.parameters["x"], ParameterValue::Integer(3));
+    assert_eq!(best.parameters["model"], ParameterValue::String("m".into()));
+    assert_eq!(best.value, 0.0);
+
+    assert_eq!(report.sweep_run.status, RunStatus::Completed);
+    assert_eq!(report.sweep_run.metrics.custom_metrics["score"], json!(0.0));
+    assert_eq!(
+        report.sweep_run.metadata["best_run_id"],
+        serde_json::to_value(best.run_id).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_grid_sweep_capped_by_max_trials() {
+    let runner = Arc::new(QuadraticRunner::default());
+    let values = (0..6).map(ParameterValue::Integer).collect();
+    let report = sweep(
+        parameters(
+            SearchStrategy::Grid,
+            vec![space("x", values, None)],
+            Some(2),
+        ),
+        Objective::maximize("score"),
+    )
+    .run(runner.clone())
+    .await
+    .unwrap();
+    assert_eq!(report.trials.len(), 2);
+    assert_eq!(runner.trials.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_trial_runs_are_children_of_sweep_run() {
+    let runner = Arc::new(QuadraticRunner::default());
+    let values = (1..=2).map(ParameterValue::Integer).collect();
+    let report = sweep(
+        parameters(SearchStrategy::Grid, vec![space("x", values, None)], None),
+        Objective::maximize("score"),
+    )
+    .with_first_run_number(10)
+    .run(runner.clone())
+    .await
+    .unwrap();
+
+    assert_eq!(report.sweep_run.run_number, 10);
+    let numbers: Vec<u32> = report.trials.iter().map(|t| t.run.run_number).collect();
+    assert_eq!(numbers, vec![11, 12]);
+    for trial in &report.trials {
+        assert_eq!(trial.run.parent_run_id, Some(report.sweep_run.id));
+        assert_eq!(trial.run.status, RunStatus::Completed);
+        assert!(trial.run.started_at.is_some() && trial.run.ended_at.is_some());
+    }
+
+    let ran = runner.trials.lock().unwrap();
+    assert_eq!(ran[0].run_id, report.trials[0].run.id);
+    assert_eq!(ran[0].number, 11);
+}
+
+#[tokio::test]
+async fn test_failed_trial_recorded_and_excluded() {
+    let runner = Arc::new(QuadraticRunner {
+        fail: Some(3.0),
+        ..Default::default()
+    });
+    let values = (2..5).map(ParameterValue::Integer).collect();
+    let report = sweep(
+        parameters(SearchStrategy::Grid, vec![space("x", values, None)], None),
+        Objective::maximize("score"),
+    )
+    .run(runner)
+    .await
+    .unwrap();
+
+    let failed = &report.trials[1];
+    assert_eq!(failed.run.status, RunStatus::Failed);
+    let error = failed.run.error.as_ref().unwrap();
+    assert!(error.message.contains("out of memory"));
+    assert!(error.is_retryable);
+
+    let best = report.best.unwrap();
+    assert_ne!(best.parameters["x"], ParameterValue::Integer(3));
+    assert_eq!(best.value, -1.0);
+}
+
+#[tokio::test]
+async fn test_sweep_without_results_fails_sweep_run() {
+    let runner = Arc::new(QuadraticRunner::default());
+    let values = (0..2).map(ParameterValue::Integer).collect();
+    let report = sweep(
+        parameters(SearchStrategy::Grid, vec![space("x", values, None)], None),
+        Objective::maximize("accuracy"),
+    )
+    .run(runner)
+    .await
+    .unwrap();
+
+    assert!(report.best.is_none());
+    assert!(report
+        .trials
+        .iter()
+        .all(|t| t.run.status == RunStatus::Failed));
+    assert_eq!(report.sweep_run.status, RunStatus::Failed);
+}
+
+#[tokio::test]
+async fn test_minimize_objective() {
+    let runner = Arc::new(QuadraticRunner::default());
+    let values = (0..6).map(ParameterValue::Integer).collect();
+    let report = sweep(
+        parameters(SearchStrategy::Grid, vec![space("x", values, None)], None),
+        Objective::minimize("score"),
+    )
+    .run(runner)
+    .await
+    .unwrap();
+    assert_eq!(
+        report.best.unwrap().parameters["x"],
+        ParameterValue::Integer(0)
+    );
+}
+
+#[tokio::test]
+async fn test_random_sweep_is_seeded() {
+    let spaces = || {
+        vec![space(
+            "x",
+            vec![ParameterValue::Float(0.0), ParameterValue::Float(6.0)],
+            Some("uniform"),
+        )]
+    };
+    let run = |seed| async move {
+        let runner = Arc::new(QuadraticRunner::default());
+        sweep(
+            parameters(SearchStrategy::Random, spaces(), Some(5)),
+            Objective::maximize("score"),
+        )
+        .with_seed(seed)
+        .run(runner)
+        .await
+        .unwrap()
+        .trials
+        .into_iter()
+        .map(|t| t.run.parameters["x"].clone())
+        .collect::<Vec<_>>()
+    };
+
+    let first = run(42).await;
+    assert_eq!(first.len(), 5);
+    assert_eq!(first, run(42).await);
+    assert_ne!(first, run(43).await);
+}
+
+#[tokio::test]
+async fn test_random_sweep_needs_max_trials() {
+    let runner = Arc::new(QuadraticRunner::default());
+    let result = sweep(
+        parameters(SearchStrategy::Random, vec![], None),
+        Objective::maximize("score"),
+    )
+    .run(runner)
+    .await;
+    assert!(matches!(result, Err(CoreError::Validation(m)) if m.contains("needs max_trials")));
+}
+
+#[tokio::test]
+async fn test_custom_strategy_unsupported() {
+    let runner = Arc::new(QuadraticRunner::default());
+    let result = sweep(
+        parameters(SearchStrategy::Custom("cma-es".to_string()), vec![], None),
+        Objective::maximize("score"),
+    )
+    .run(runner)
+    .await;
+    assert!(matches!(result, Err(CoreError::Validation(m)) if m.contains("'cma-es'")));
+}
+
+#[tokio::test]
+async fn test_concurrent_trials_bound() {
+    let runner = Arc::new(QuadraticRunner::default());
+    let values = (0..8).map(ParameterValue::Integer).collect();
+    let mut params = parameters(SearchStrategy::Grid, vec![space("x", values, None)], None);
+    params.concurrent_trials = Some(3);
+    let report = sweep(params, Objective::maximize("score"))
+        .run(runner.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(report.trials.len(), 8);
+    assert_eq!(runner.peak.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_bayesian_sweep_converges() {
+    let runner = Arc::new(QuadraticRunner::default());
+    let spaces = vec![
+        space(
+            "x",
+            vec![ParameterValue::Float(-10.0), ParameterValue::Float(10.0)],
+            Some("uniform"),
+        ),
+        space(
+            "y",
+            vec![ParameterValue::Float(-10.0), ParameterValue::Float(10.0)],
+            Some("uniform"),
+        ),
+    ];
+    let report = sweep(
+        parameters(SearchStrategy::Bayesian, spaces, Some(60)),
+        Objective::maximize("score"),
+    )
+    .run(runner)
+    .await
+    .unwrap();
+
+    assert_eq!(report.trials.len(), 60);
+    let best = report.best.unwrap();
+    assert!(best.value > -1.0, "best score {}", best.value);
+
+    // Trials after the random startup concentrate near the optimum
+    let mean_late = report.trials[40..]
+        .iter()
+        .map(|t| t.value.unwrap())
+        .sum::<f64>()
+        / 20.0;
+    let mean_startup = report.trials[..10]
+        .iter()
+        .map(|t| t.value.unwrap())
+        .sum::<f64>()
+        / 10.0;
+    assert!(
+        mean_late > mean_startup,
+        "{} <= {}",
+        mean_late,
+        mean_startup
+    );
+}
+
+#[tokio::test]
+async fn test_bayesian_categorical_parameters() {
+    let runner = Arc::new(QuadraticRunner::default());
+    let values = (0..7).map(ParameterValue::Integer).collect();
+    let report = sweep(
+        parameters(
+            SearchStrategy::Bayesian,
+            vec![space("x", values, None)],
+            Some(30),
+        ),
+        Objective::maximize("score"),
+    )
+    .with_tpe(TpeConfig {
+        startup_trials: 5,
+        ..Default::default()
+    })
+    .run(runner)
+    .await
+    .unwrap();
+
+    assert_eq!(
+        report.best.unwrap().parameters["x"],
+        ParameterValue::Integer(3)
+    );
+    let late_hits = report.trials[20..]
+        .iter()
+        .filter(|t| t.run.parameters["x"] == ParameterValue::Integer(3))
+        .count();
+    assert!(
+        late_hits >= 5,
+        "{} of the last 10 trials at the optimum",
+        late_hits
+    );
+}
+
+/// Reports `score` after each step up to the budget: learning curves that
+/// rise towards `-(x - 3)^2`, so intermediate values rank trials correctly
+#[derive(Default)]
+struct CurveRunner {
+    evaluations: Mutex<Vec<(u32, u64)>>,
+}
+
+#[async_trait]
+impl TrialRunner for CurveRunner {
+    async fn run_trial(&self, trial: &Trial) -> Result<RunMetrics> {
+        let budget = trial.budget.unwrap();
+        self.evaluations
+            .lock()
+            .unwrap()
+            .push((trial.number, budget));
+        let x = float(&trial.parameters["x"]);
+        let mut metrics = RunMetrics::default();
+        metrics.scalars.push(series(
+            "score",
+            (1..=budget).map(|step| (step, -(x - 3.0).powi(2) - 10.0 / step as f64)),
+        ));
+        Ok(metrics)
+    }
+}
+
+#[tokio::test]
+async fn test_hyperband_sweep_early_stops() {
+    let runner = Arc::new(CurveRunner::default());
+    let spaces = vec![space(
+        "x",
+        vec![ParameterValue::Float(0.0), ParameterValue::Float(6.0)],
+        Some("uniform"),
+    )];
+    let report = sweep(
+        parameters(SearchStrategy::Hyperband, spaces, None),
+        Objective::maximize("score"),
+    )
+    .with_hyperband(HyperbandConfig {
+        min_budget: 1,
+        max_budget: 9,
+        eta: 3,
+    })
+    .run(runner.clone())
+    .await
+    .unwrap();
+
+    // Brackets of 9, 5 and 3 trials
+    assert_eq!(report.trials.len(), 17);
+    let finished: Vec<&TrialRecord> = report.trials.iter().filter(|t| !t.early_stopped).collect();
+    assert_eq!(finished.len(), 1 + 1 + 3);
+    for trial in &finished {
+        assert_eq!(trial.budget, Some(9));
+    }
+    for trial in report.trials.iter().filter(|t| t.early_stopped) {
+        assert!(trial.budget.unwrap() < 9);
+        assert_eq!(trial.run.status, RunStatus::Completed);
+    }
+
+    // Promoted trials ran again with the larger budget
+    let evaluations = runner.evaluations.lock().unwrap();
+    assert_eq!(evaluations.len(), 9 + 3 + 1 + 5 + 1 + 3);
+
+    // The best is the full-budget trial closest to x = 3
+    let best = report.best.unwrap();
+    let closest = finished
+        .iter()
+        .min_by(|a, b| {
+            let distance = |t: &TrialRecord| (float(&t.run.parameters["x"]) - 3.0).abs();
+            distance(a).total_cmp(&distance(b))
+        })
+        .unwrap();
+    assert_eq!(best.run_id, closest.run.id);
+}
+
+#[tokio::test]
+async fn test_hyperband_capped_by_max_trials() {
+    let runner = Arc::new(CurveRunner::default());
+    let spaces = vec![space(
+        "x",
+        (0..6).map(ParameterValue::Integer).collect(),
+        None,
+    )];
+    let report = sweep(
+        parameters(SearchStrategy::Hyperband, spaces, Some(11)),
+        Objective::maximize("score"),
+    )
+    .with_hyperband(HyperbandConfig {
+        min_budget: 1,
+        max_budget: 9,
+        eta: 3,
+    })
+    .run(runner)
+    .await
+    .unwrap();
+    assert_eq!(report.trials.len(), 11);
+}
+
+#[test]
+fn test_objective_value_at_budget() {
+    let mut metrics = RunMetrics::default();
+    metrics
+        .scalars
+        .push(series("loss", [(1, 0.9), (2, 0.5), (4, 0.2)]));
+    let objective = Objective::minimize("loss");
+
+    assert_eq!(objective.value(&metrics, None), Some(0.2));
+    assert_eq!(objective.value(&metrics, Some(3)), Some(0.5));
+    assert_eq!(objective.value(&metrics, Some(0)), None);
+    assert_eq!(Objective::minimize("accuracy").value(&metrics, None), None);
+}
+
+// ===== Workflow Trial Runner Tests =====
+
+#[tokio::test]
+async fn test_workflow_trial_runner() {
+    let mut registry = TaskRegistry::new();
+    registry.register("score", |config| {
+        let x = config["x"].as_f64().unwrap_or(f64::NAN);
+        let budget = config["budget"].as_u64().unwrap_or(0);
+        Ok(Arc::new(ScoreTask { x, budget }) as Arc<dyn Task>)
+    });
+    let engine = Arc::new(DefaultWorkflowEngine::with_registry(registry));
+    let workflow = Workflow::new(
+        "train".to_string(),
+        vec![WorkflowStep::new(
+            "evaluate".to_string(),
+            "score".to_string(),
+            json!({"x": "${params.x}", "budget": "${params.budget}"}),
+        )],
+    )
+    .with_parameters(json!({"budget": 1}));
+    let runner = Arc::new(
+        WorkflowTrialRunner::new(engine, workflow)
+            .with_metric("score", "${steps.evaluate.output.score}")
+            .with_metric("curve", "${steps.evaluate.output.curve}"),
+    );
+
+    let values = (0..6).map(ParameterValue::Integer).collect();
+    let mut params = parameters(SearchStrategy::Grid, vec![space("x", values, None)], None);
+    params.concurrent_trials = Some(3);
+    let report = sweep(params, Objective::maximize("score"))
+        .run(runner.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        report.best.unwrap().parameters["x"],
+        ParameterValue::Integer(3)
+    );
+
+    let trial = Trial {
+        number: 1,
+        run_id: llm_research_core::RunId::new(),
+        parameters: HashMap::from([("x".to_string(), ParameterValue::Integer(2))]),
+        budget: Some(3),
+    };
+    let metrics = runner.run_trial(&trial).await.unwrap();
+    let curve = metrics.scalars.iter().find(|s| s.name == "curve").unwrap();
+    let steps: Vec<u64> = curve.points.iter().map(|p| p.step).collect();
+    assert_eq!(steps, vec![1, 2, 3]);
+    let score = metrics.scalars.iter().find(|s| s.name == "score").unwrap();
+    assert_eq!(score.points[0].step, 3);
+    assert_eq!(score.points[0].value, -1.0);
+}
+
+#[tokio::test]
+async fn test_workflow_trial_runner_records_sample_selection() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("qa.jsonl");
+    let rows: String = (0..20)
+        .map(|i| {
+            format!(
+                "{}\n",
+                json!({"question": format!("q{}", i), "answer": "a"})
+            )
+        })
+        .collect();
+    std::fs::write(&path, rows).unwrap();
+
+    let mut registry = TaskRegistry::with_builtin_tasks();
+    registry.register("score", |config| {
+        let x = config["x"].as_f64().unwrap_or(f64::NAN);
+        Ok(Arc::new(ScoreTask { x, budget: 0 }) as Arc<dyn Task>)
+    });
+    let engine = Arc::new(DefaultWorkflowEngine::with_registry(registry));
+    let workflow = Workflow::new(
+        "train".to_string(),
+        vec![
+            WorkflowStep::new(
+                "load".to_string(),
+                "data_loading".to_string(),
+                json!({
+                    "source": path.to_str().unwrap(),
+                    "fields": {"input": "question", "expected_output": "answer"},
+                    "sample": {"strategy": "random", "size": {"count": 4}, "seed": null, "stratify_by": null},
+                }),
+            ),
+            WorkflowStep::new(
+                "evaluate".to_string(),
+                "score".to_string(),
+                json!({"x": "${params.x}"}),
+            ),
+        ],
+    )
+    .with_parameters(json!({"random_seed": 5}));
+    let runner = Arc::new(
+        WorkflowTrialRunner::new(engine, workflow)
+            .with_metric("score", "${steps.evaluate.output.score}"),
+    );
+
+    let values = (2..=3).map(ParameterValue::Integer).collect();
+    let report = sweep(
+        parameters(SearchStrategy::Grid, vec![space("x", values, None)], None),
+        Objective::maximize("score"),
+    )
+    .run(runner)
+    .await
+    .unwrap();
+
+    let selections: Vec<SampleSelection> = report
+        .trials
+        .iter()
+        .map(|trial| SampleSelection::from_run(&trial.run).unwrap().unwrap())
+        .collect();
+    assert_eq!(selections.len(), 2);
+    for selection in &selections {
+        assert_eq!(selection.seed, 5);
+        assert_eq!(selection.sample_ids.len(), 4);
+    }
+    // Seeded alike, every trial runs on the same samples
+    assert_eq!(selections[0], selections[1]);
+}
+
+struct ScoreTask {
+    x: f64,
+    budget: u64,
+}
+
+#[async_trait]
+impl Task for ScoreTask {
+    async fn execute(&self, _context: TaskContext) -> Result<TaskResult> {
+        let score = -(self.x - 3.0).powi(2);
+        let curve: Vec<f64> = (1..=self.budget)
+            .map(|step| score - 1.0 / step as f64)
+            .collect();
+        Ok(TaskResult::success(json!({"score": score, "curve": curve})))
+    }
+
+    fn name(&self) -> &str {
+        "score"
+    }
+}
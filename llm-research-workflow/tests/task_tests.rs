@@ -1,6 +1,9 @@
 use llm_research_workflow::*;
+use llm_research_metrics::{SequentialConfig, StreamingAggregator};
+use llm_research_core::CoreError;
 use uuid::Uuid;
 use std::sync::Arc;
+use std::time::Duration;
 
 // ===== TaskContext Tests =====
 
@@ -18,6 +21,9 @@ fn test_task_context_creation() {
     let context = TaskContext {
         experiment_id,
         config: config.clone(),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     assert_eq!(context.experiment_id, experiment_id);
@@ -30,6 +36,9 @@ fn test_task_context_with_empty_config() {
     let context = TaskContext {
         experiment_id,
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     assert_eq!(context.experiment_id, experiment_id);
@@ -44,6 +53,9 @@ fn test_task_context_serialization() {
     let context = TaskContext {
         experiment_id,
         config: config.clone(),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let serialized = serde_json::to_string(&context).unwrap();
@@ -59,6 +71,9 @@ fn test_task_context_clone() {
     let context = TaskContext {
         experiment_id,
         config: serde_json::json!({"key": "value"}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let cloned = context.clone();
@@ -134,6 +149,7 @@ async fn test_evaluation_task_creation() {
     let config = EvaluationConfig {
         metrics: vec!["accuracy".to_string(), "bleu".to_string()],
         batch_size: 50,
+        early_stopping: None,
     };
 
     let task = EvaluationTask::new(config.clone());
@@ -158,6 +174,9 @@ async fn test_evaluation_task_execute_success() {
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = task.execute(context).await;
@@ -180,12 +199,16 @@ async fn test_evaluation_task_with_custom_metrics() {
     let config = EvaluationConfig {
         metrics: vec!["accuracy".to_string()],
         batch_size: 20,
+        early_stopping: None,
     };
     let task = EvaluationTask::new(config);
 
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = task.execute(context).await;
@@ -203,6 +226,9 @@ async fn test_evaluation_task_output_contains_metrics() {
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = task.execute(context).await.unwrap();
@@ -222,6 +248,216 @@ async fn test_evaluation_task_output_contains_metrics() {
     }
 }
 
+#[tokio::test]
+async fn test_evaluation_task_early_stopping() {
+    let config = EvaluationConfig {
+        metrics: vec!["accuracy".to_string()],
+        batch_size: 10,
+        early_stopping: Some(EarlyStoppingConfig {
+            metric: "accuracy".to_string(),
+            baseline: 0.9,
+            test: SequentialConfig {
+                min_samples: 20,
+                ..SequentialConfig::default()
+            },
+        }),
+    };
+    let task = EvaluationTask::new(config);
+
+    let context = TaskContext {
+        experiment_id: Uuid::new_v4(),
+        config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
+    };
+
+    let output = task.execute(context).await.unwrap().output;
+    let early_stopping = &output["early_stopping"];
+
+    // Mock predictions are only 10% accurate, far below the 0.9 baseline
+    assert_eq!(early_stopping["stopped"], true);
+    assert_eq!(early_stopping["reason"], "inferior");
+    assert!(early_stopping["samples_evaluated"].as_u64().unwrap() < 100);
+    assert_eq!(
+        output["total_samples"],
+        early_stopping["samples_evaluated"]
+    );
+}
+
+#[tokio::test]
+async fn test_evaluation_task_early_stopping_not_triggered() {
+    let config = EvaluationConfig {
+        metrics: vec!["accuracy".to_string()],
+        batch_size: 25,
+        early_stopping: Some(EarlyStoppingConfig {
+            metric: "accuracy".to_string(),
+            baseline: 0.1,
+            test: SequentialConfig::default(),
+        }),
+    };
+    let task = EvaluationTask::new(config);
+
+    let context = TaskContext {
+        experiment_id: Uuid::new_v4(),
+        config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
+    };
+
+    let output = task.execute(context).await.unwrap().output;
+    let early_stopping = &output["early_stopping"];
+
+    assert_eq!(early_stopping["stopped"], false);
+    assert_eq!(early_stopping["reason"], "continue");
+    assert_eq!(early_stopping["samples_evaluated"], 100);
+    assert_eq!(output["batches_processed"], 4);
+}
+
+#[tokio::test]
+async fn test_evaluation_task_early_stopping_unknown_metric() {
+    let config = EvaluationConfig {
+        metrics: vec!["accuracy".to_string()],
+        batch_size: 10,
+        early_stopping: Some(EarlyStoppingConfig {
+            metric: "bleu".to_string(),
+            baseline: 0.5,
+            test: SequentialConfig::default(),
+        }),
+    };
+    let task = EvaluationTask::new(config);
+
+    let context = TaskContext {
+        experiment_id: Uuid::new_v4(),
+        config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
+    };
+
+    assert!(task.execute(context).await.is_err());
+}
+
+#[test]
+fn test_evaluation_config_deserializes_without_early_stopping() {
+    let config: EvaluationConfig = serde_json::from_value(serde_json::json!({
+        "metrics": ["accuracy"],
+        "batch_size": 10,
+    }))
+    .unwrap();
+
+    assert!(config.early_stopping.is_none());
+}
+
+#[tokio::test]
+async fn test_evaluation_task_reports_mergeable_aggregates() {
+    let task = EvaluationTask::new(EvaluationConfig {
+        metrics: vec!["accuracy".to_string()],
+        batch_size: 30,
+        early_stopping: None,
+    });
+
+    let context = TaskContext {
+        experiment_id: Uuid::new_v4(),
+        config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
+    };
+
+    let output = task.execute(context).await.unwrap().output;
+
+    let aggregate: StreamingAggregator =
+        serde_json::from_value(output["aggregates"]["accuracy"].clone()).unwrap();
+    assert_eq!(aggregate.count(), 100);
+    assert!((aggregate.stats().mean() - 0.1).abs() < 1e-9);
+    assert!(output["metrics"]["accuracy"].get("p95").is_some());
+}
+
+// ===== ReduceTask Tests =====
+
+fn reduce_context(inputs: serde_json::Value) -> TaskContext {
+    let inputs = serde_json::from_value(inputs).unwrap();
+    TaskContext::new(Uuid::new_v4(), serde_json::json!({})).with_inputs(inputs)
+}
+
+#[tokio::test]
+async fn test_reduce_task_gathers_fan_out_children() {
+    let task = ReduceTask::new(ReduceConfig {
+        field: Some("scores".to_string()),
+        flatten: true,
+    });
+    let context = reduce_context(serde_json::json!({
+        "evaluate": {
+            "items": 3,
+            "failed": 1,
+            "outputs": [{"scores": [0.9, 0.8]}, null, {"scores": [0.7]}],
+        },
+        "baseline": {"scores": [0.5]},
+    }));
+
+    let output = task.execute(context).await.unwrap().output;
+
+    // Steps in name order, children in item order
+    assert_eq!(output["values"], serde_json::json!([0.5, 0.9, 0.8, 0.7]));
+    assert_eq!(output["count"], 4);
+    assert_eq!(output["failed"], 1);
+}
+
+#[tokio::test]
+async fn test_reduce_task_collects_whole_outputs() {
+    let task = ReduceTask::new(ReduceConfig::default());
+    let context = reduce_context(serde_json::json!({
+        "infer": {"items": 2, "failed": 0, "outputs": [{"id": 0}, {"id": 1}]},
+    }));
+
+    let output = task.execute(context).await.unwrap().output;
+
+    assert_eq!(output["values"], serde_json::json!([{"id": 0}, {"id": 1}]));
+}
+
+#[tokio::test]
+async fn test_reduce_task_missing_field() {
+    let task = ReduceTask::new(ReduceConfig {
+        field: Some("metrics.accuracy".to_string()),
+        flatten: false,
+    });
+    let context = reduce_context(serde_json::json!({"evaluate": {"metrics": {}}}));
+
+    let error = task.execute(context).await.unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Output of step 'evaluate' has no field 'metrics.accuracy'"));
+}
+
+#[tokio::test]
+async fn test_reduce_task_merges_aggregates() {
+    let evaluate = EvaluationTask::new(EvaluationConfig {
+        metrics: vec!["accuracy".to_string()],
+        batch_size: 50,
+        early_stopping: None,
+    });
+    let mut shards = Vec::new();
+    for _ in 0..2 {
+        let context = TaskContext::new(Uuid::new_v4(), serde_json::json!({}));
+        shards.push(evaluate.execute(context).await.unwrap().output);
+    }
+    let context = reduce_context(serde_json::json!({
+        "evaluate": {"items": 2, "failed": 0, "outputs": shards},
+    }));
+
+    let output = ReduceTask::new(ReduceConfig::default())
+        .execute(context)
+        .await
+        .unwrap()
+        .output;
+
+    let accuracy: StreamingAggregator =
+        serde_json::from_value(output["aggregates"]["accuracy"].clone()).unwrap();
+    assert_eq!(accuracy.count(), 200);
+}
+
 // ===== DataLoadingTask Tests =====
 
 #[tokio::test]
@@ -231,6 +467,7 @@ async fn test_data_loading_task_creation() {
         batch_size: 100,
         stream: false,
         limit: Some(1000),
+        ..Default::default()
     };
 
     let task = DataLoadingTask::new(config);
@@ -240,10 +477,11 @@ async fn test_data_loading_task_creation() {
 #[tokio::test]
 async fn test_data_loading_task_execute_batched() {
     let config = DataLoadingConfig {
-        source: "test_source".to_string(),
+        source: "synthetic://test_source".to_string(),
         batch_size: 50,
         stream: false,
         limit: Some(100),
+        ..Default::default()
     };
 
     let task = DataLoadingTask::new(config);
@@ -251,6 +489,9 @@ async fn test_data_loading_task_execute_batched() {
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = task.execute(context).await;
@@ -261,7 +502,7 @@ async fn test_data_loading_task_execute_batched() {
     assert!(task_result.error.is_none());
 
     let output = task_result.output;
-    assert_eq!(output.get("source").unwrap(), "test_source");
+    assert_eq!(output.get("source").unwrap(), "synthetic://test_source");
     assert_eq!(output.get("total_samples").unwrap(), 100);
     assert_eq!(output.get("batch_size").unwrap(), 50);
     assert_eq!(output.get("streaming").unwrap(), false);
@@ -270,10 +511,11 @@ async fn test_data_loading_task_execute_batched() {
 #[tokio::test]
 async fn test_data_loading_task_execute_streaming() {
     let config = DataLoadingConfig {
-        source: "test_stream".to_string(),
+        source: "synthetic://test_stream".to_string(),
         batch_size: 25,
         stream: true,
         limit: Some(50),
+        ..Default::default()
     };
 
     let task = DataLoadingTask::new(config);
@@ -281,6 +523,9 @@ async fn test_data_loading_task_execute_streaming() {
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = task.execute(context).await;
@@ -297,10 +542,11 @@ async fn test_data_loading_task_execute_streaming() {
 #[tokio::test]
 async fn test_data_loading_task_with_large_limit() {
     let config = DataLoadingConfig {
-        source: "large_dataset".to_string(),
+        source: "synthetic://large_dataset".to_string(),
         batch_size: 100,
         stream: false,
         limit: Some(500),
+        ..Default::default()
     };
 
     let task = DataLoadingTask::new(config);
@@ -308,6 +554,9 @@ async fn test_data_loading_task_with_large_limit() {
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = task.execute(context).await;
@@ -325,10 +574,11 @@ async fn test_data_loading_task_with_large_limit() {
 #[tokio::test]
 async fn test_data_loading_task_no_limit() {
     let config = DataLoadingConfig {
-        source: "unlimited_source".to_string(),
+        source: "synthetic://unlimited_source".to_string(),
         batch_size: 100,
         stream: false,
         limit: None, // Should default to 1000
+        ..Default::default()
     };
 
     let task = DataLoadingTask::new(config);
@@ -336,6 +586,9 @@ async fn test_data_loading_task_no_limit() {
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = task.execute(context).await;
@@ -348,6 +601,13 @@ async fn test_data_loading_task_no_limit() {
 
 // ===== InferenceTask Tests =====
 
+fn simulated_inference() -> InferenceConfig {
+    InferenceConfig {
+        provider: InferenceProvider::Simulated,
+        ..Default::default()
+    }
+}
+
 #[tokio::test]
 async fn test_inference_task_creation() {
     let config = InferenceConfig::default();
@@ -358,24 +618,58 @@ async fn test_inference_task_creation() {
 #[tokio::test]
 async fn test_inference_task_default_config() {
     let config = InferenceConfig::default();
+    assert_eq!(config.provider, InferenceProvider::OpenAI);
     assert_eq!(config.model, "gpt-4");
     assert_eq!(config.max_tokens, 1000);
     assert_eq!(config.temperature, 0.7);
     assert_eq!(config.rate_limit_per_minute, 60);
     assert_eq!(config.max_retries, 3);
+    assert_eq!(config.backoff, Backoff::default());
     assert_eq!(config.timeout_seconds, 30);
 }
 
 #[tokio::test]
-async fn test_inference_task_execute_openai() {
+async fn test_inference_config_backoff() {
+    let config: InferenceConfig = serde_json::from_value(serde_json::json!({
+        "max_retries": 2,
+        "backoff": {"strategy": "constant", "delay_ms": 250, "jitter": "none"},
+    }))
+    .unwrap();
+
+    assert_eq!(
+        config.backoff,
+        Backoff::constant(Duration::from_millis(250)).with_jitter(JitterStrategy::None)
+    );
+}
+
+#[tokio::test]
+async fn test_inference_config_simulated_only_when_asked() {
+    // Leaving out the provider must not quietly fabricate responses
+    let config: InferenceConfig = serde_json::from_value(serde_json::json!({
+        "model": "gpt-4o",
+    }))
+    .unwrap();
+    assert_eq!(config.provider, InferenceProvider::OpenAI);
+
+    let config: InferenceConfig = serde_json::from_value(serde_json::json!({
+        "provider": "simulated",
+    }))
+    .unwrap();
+    assert_eq!(config.provider, InferenceProvider::Simulated);
+}
+
+#[tokio::test]
+async fn test_inference_task_execute_simulated() {
     let config = InferenceConfig {
-        provider: InferenceProvider::OpenAI,
+        provider: InferenceProvider::Simulated,
         model: "gpt-4".to_string(),
         max_tokens: 500,
         temperature: 0.7,
         rate_limit_per_minute: 60,
         max_retries: 3,
+        backoff: Backoff::default(),
         timeout_seconds: 30,
+        ..Default::default()
     };
 
     let task = InferenceTask::new(config);
@@ -383,6 +677,9 @@ async fn test_inference_task_execute_openai() {
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = task.execute(context).await;
@@ -392,7 +689,7 @@ async fn test_inference_task_execute_openai() {
     assert!(task_result.success);
 
     let output = task_result.output;
-    assert!(output.get("provider").is_some());
+    assert_eq!(output.get("provider").unwrap(), "simulated");
     assert_eq!(output.get("model").unwrap(), "gpt-4");
     assert!(output.get("predictions_generated").is_some());
     assert!(output.get("total_tokens").is_some());
@@ -400,73 +697,78 @@ async fn test_inference_task_execute_openai() {
 }
 
 #[tokio::test]
-async fn test_inference_task_execute_anthropic() {
-    let config = InferenceConfig {
-        provider: InferenceProvider::Anthropic,
-        model: "claude-3-opus".to_string(),
-        max_tokens: 1000,
-        temperature: 0.5,
-        rate_limit_per_minute: 30,
-        max_retries: 5,
-        timeout_seconds: 60,
-    };
+async fn test_inference_task_requires_prompts_for_real_providers() {
+    let task = InferenceTask::new(InferenceConfig {
+        provider: InferenceProvider::Local,
+        base_url: Some("http://127.0.0.1:9/v1".to_string()),
+        ..Default::default()
+    });
+    let context = TaskContext::new(Uuid::new_v4(), serde_json::json!({}));
 
-    let task = InferenceTask::new(config);
+    let error = task.execute(context).await.unwrap_err();
+    assert!(matches!(error, CoreError::Validation(_)));
+    assert!(error.to_string().contains("no prompts"));
+}
 
-    let context = TaskContext {
-        experiment_id: Uuid::new_v4(),
-        config: serde_json::json!({}),
+#[tokio::test]
+async fn test_inference_provider_backends() {
+    let backend = |provider: InferenceProvider| {
+        InferenceConfig {
+            provider,
+            base_url: Some("http://localhost:8080/v1".to_string()),
+            ..Default::default()
+        }
+        .backend()
     };
 
-    let result = task.execute(context).await;
-    assert!(result.is_ok());
+    assert_eq!(backend(InferenceProvider::Simulated).unwrap().name(), "simulated");
+    assert_eq!(backend(InferenceProvider::OpenAI).unwrap().name(), "openai");
+    assert_eq!(backend(InferenceProvider::HuggingFace).unwrap().name(), "huggingface");
+    assert_eq!(backend(InferenceProvider::Local).unwrap().name(), "local");
+    assert_eq!(backend(InferenceProvider::Anthropic).unwrap().name(), "anthropic");
+    assert_eq!(backend(InferenceProvider::Cohere).unwrap().name(), "cohere");
 
-    let task_result = result.unwrap();
-    assert!(task_result.success);
+    // The public API needs a key
+    let missing_key = InferenceConfig {
+        provider: InferenceProvider::Anthropic,
+        api_key_env: Some("RESEARCH_LAB_TEST_UNSET_KEY".to_string()),
+        ..Default::default()
+    }
+    .backend();
+    let error = missing_key.err().unwrap();
+    assert!(matches!(error, CoreError::Unauthorized(_)));
+    assert!(error.to_string().contains("RESEARCH_LAB_TEST_UNSET_KEY"));
 }
 
 #[tokio::test]
-async fn test_inference_task_provider_variants() {
-    let providers = vec![
-        InferenceProvider::OpenAI,
-        InferenceProvider::Anthropic,
-        InferenceProvider::Cohere,
-        InferenceProvider::HuggingFace,
-        InferenceProvider::Local,
-    ];
-
-    for provider in providers {
-        let provider_clone = provider.clone();
-        let config = InferenceConfig {
-            provider,
-            model: "test-model".to_string(),
-            max_tokens: 500,
-            temperature: 0.7,
-            rate_limit_per_minute: 60,
-            max_retries: 3,
-            timeout_seconds: 30,
-        };
-
-        let task = InferenceTask::new(config);
-
-        let context = TaskContext {
-            experiment_id: Uuid::new_v4(),
-            config: serde_json::json!({}),
-        };
-
-        let result = task.execute(context).await;
-        assert!(result.is_ok(), "Provider {:?} should succeed", provider_clone);
-    }
+async fn test_inference_model_parameters() {
+    let config = InferenceConfig::default();
+    let parameters = config.model_parameters();
+    assert_eq!(parameters.temperature, Some(0.7));
+    assert_eq!(parameters.max_tokens, Some(1000));
+
+    let config: InferenceConfig = serde_json::from_value(serde_json::json!({
+        "parameters": {"temperature": 0.0, "top_p": 0.9, "seed": 7},
+    }))
+    .unwrap();
+    let parameters = config.model_parameters();
+    assert_eq!(parameters.temperature, Some(0.0));
+    assert_eq!(parameters.top_p, Some(0.9));
+    assert_eq!(parameters.seed, Some(7));
+    assert_eq!(parameters.max_tokens, None);
 }
 
 #[tokio::test]
 async fn test_inference_task_output_structure() {
-    let config = InferenceConfig::default();
+    let config = simulated_inference();
     let task = InferenceTask::new(config);
 
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = task.execute(context).await.unwrap();
@@ -494,6 +796,7 @@ async fn test_inference_provider_serialization() {
         (InferenceProvider::Cohere, "\"cohere\""),
         (InferenceProvider::HuggingFace, "\"huggingface\""),
         (InferenceProvider::Local, "\"local\""),
+        (InferenceProvider::Simulated, "\"simulated\""),
     ];
 
     for (provider, expected) in providers {
@@ -505,6 +808,95 @@ async fn test_inference_provider_serialization() {
     }
 }
 
+#[tokio::test]
+async fn test_inference_task_cancelled_mid_flight() {
+    let task = InferenceTask::new(simulated_inference());
+    let context = TaskContext::new(Uuid::new_v4(), serde_json::json!({}));
+    let control = context.control.clone();
+
+    let handle = tokio::spawn(async move { task.execute(context).await });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let start = std::time::Instant::now();
+    control.cancel();
+    let result = handle.await.unwrap();
+
+    // Requests in flight are abandoned rather than awaited to completion
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("cancelled"));
+    assert!(start.elapsed() < std::time::Duration::from_millis(500));
+}
+
+#[tokio::test]
+async fn test_inference_task_waits_while_paused() {
+    let task = InferenceTask::new(InferenceConfig {
+        rate_limit_per_minute: 60_000,
+        ..simulated_inference()
+    });
+    let context = TaskContext::new(Uuid::new_v4(), serde_json::json!({}));
+    let control = context.control.clone();
+    control.pause();
+
+    let handle = tokio::spawn(async move { task.execute(context).await });
+    tokio::time::sleep(std::time::Duration::from_millis(400)).await;
+    assert!(!handle.is_finished());
+
+    control.resume();
+    let result = handle.await.unwrap().unwrap();
+    assert!(result.success);
+    assert_eq!(result.output["predictions_generated"], 10);
+}
+
+// ===== Pause and Cancellation Tests =====
+
+#[tokio::test]
+async fn test_evaluation_task_pauses_between_batches() {
+    let task = EvaluationTask::new(EvaluationConfig {
+        batch_size: 10,
+        ..Default::default()
+    });
+    let context = TaskContext::new(Uuid::new_v4(), serde_json::json!({}));
+    let control = context.control.clone();
+    control.pause();
+
+    let handle = tokio::spawn(async move { task.execute(context).await });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(!handle.is_finished());
+
+    control.resume();
+    let result = handle.await.unwrap().unwrap();
+    assert_eq!(result.output["batches_processed"], 10);
+}
+
+#[tokio::test]
+async fn test_evaluation_task_cancelled_while_paused() {
+    let task = EvaluationTask::new(EvaluationConfig::default());
+    let context = TaskContext::new(Uuid::new_v4(), serde_json::json!({}));
+    let control = context.control.clone();
+    control.pause();
+
+    let handle = tokio::spawn(async move { task.execute(context).await });
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    control.cancel();
+
+    assert!(handle.await.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn test_data_loading_task_cancelled() {
+    let task = DataLoadingTask::new(DataLoadingConfig {
+        source: "synthetic://test".to_string(),
+        batch_size: 10,
+        stream: false,
+        limit: Some(100),
+        ..Default::default()
+    });
+    let context = TaskContext::new(Uuid::new_v4(), serde_json::json!({}));
+    context.control.cancel();
+
+    assert!(task.execute(context).await.is_err());
+}
+
 // ===== TaskExecutor Tests =====
 
 #[tokio::test]
@@ -522,6 +914,9 @@ async fn test_task_executor_execute_one() {
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = executor.execute_one(task, context).await;
@@ -538,17 +933,21 @@ async fn test_task_executor_execute_batch() {
     let tasks: Vec<Arc<dyn Task>> = vec![
         Arc::new(EvaluationTask::new(EvaluationConfig::default())),
         Arc::new(DataLoadingTask::new(DataLoadingConfig {
-            source: "test".to_string(),
+            source: "synthetic://test".to_string(),
             batch_size: 10,
             stream: false,
             limit: Some(10),
+            ..Default::default()
         })),
-        Arc::new(InferenceTask::new(InferenceConfig::default())),
+        Arc::new(InferenceTask::new(simulated_inference())),
     ];
 
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = executor.execute_batch(tasks, context).await;
@@ -562,6 +961,36 @@ async fn test_task_executor_execute_batch() {
     }
 }
 
+#[tokio::test]
+async fn test_task_executor_merge_aggregates_across_workers() {
+    let executor = TaskExecutor::new(2);
+
+    let tasks: Vec<Arc<dyn Task>> = (0..3)
+        .map(|_| Arc::new(EvaluationTask::new(EvaluationConfig::default())) as Arc<dyn Task>)
+        .collect();
+
+    let context = TaskContext {
+        experiment_id: Uuid::new_v4(),
+        config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
+    };
+
+    let mut results = executor.execute_batch(tasks, context).await.unwrap();
+    results.push(TaskResult::failure("worker lost".to_string()));
+
+    let accuracy = TaskExecutor::merge_aggregates(&results, "accuracy")
+        .unwrap()
+        .unwrap();
+    assert_eq!(accuracy.count(), 300);
+    assert!((accuracy.stats().mean() - 0.1).abs() < 1e-9);
+
+    assert!(TaskExecutor::merge_aggregates(&results, "unknown")
+        .unwrap()
+        .is_none());
+}
+
 #[tokio::test]
 async fn test_task_executor_with_progress_tracking() {
     let executor = TaskExecutor::new(4);
@@ -573,6 +1002,9 @@ async fn test_task_executor_with_progress_tracking() {
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = executor.execute_one(task, context).await;
@@ -586,10 +1018,11 @@ async fn test_task_executor_concurrency_limit() {
     let tasks: Vec<Arc<dyn Task>> = (0..3)
         .map(|_| {
             Arc::new(DataLoadingTask::new(DataLoadingConfig {
-                source: "test".to_string(),
+                source: "synthetic://test".to_string(),
                 batch_size: 10,
                 stream: false,
                 limit: Some(10),
+                ..Default::default()
             })) as Arc<dyn Task>
         })
         .collect();
@@ -597,6 +1030,9 @@ async fn test_task_executor_concurrency_limit() {
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let start = std::time::Instant::now();
@@ -621,6 +1057,9 @@ async fn test_task_executor_empty_batch() {
     let context = TaskContext {
         experiment_id: Uuid::new_v4(),
         config: serde_json::json!({}),
+        inputs: Default::default(),
+        reproducibility: Default::default(),
+        control: Default::default(),
     };
 
     let result = executor.execute_batch(tasks, context).await;
@@ -630,6 +1069,58 @@ async fn test_task_executor_empty_batch() {
     assert_eq!(results.len(), 0);
 }
 
+#[tokio::test]
+async fn test_task_executor_cancel_task() {
+    let executor = Arc::new(TaskExecutor::new(4));
+    let task: Arc<dyn Task> = Arc::new(InferenceTask::new(simulated_inference()));
+    let task_id = Uuid::new_v4();
+    let context = TaskContext::new(Uuid::new_v4(), serde_json::json!({}));
+
+    let runner = executor.clone();
+    let handle =
+        tokio::spawn(async move { runner.execute_one_with_id(task_id, task, context).await });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    executor.cancel_task(task_id).await;
+
+    let result = handle.await.unwrap().unwrap();
+    assert!(!result.success);
+    assert_eq!(result.error.as_deref(), Some("Task cancelled"));
+}
+
+#[tokio::test]
+async fn test_task_executor_cancel_batch_through_context() {
+    let executor = TaskExecutor::new(2);
+    let tasks: Vec<Arc<dyn Task>> = (0..3)
+        .map(|_| Arc::new(InferenceTask::new(simulated_inference())) as Arc<dyn Task>)
+        .collect();
+    let context = TaskContext::new(Uuid::new_v4(), serde_json::json!({}));
+    let control = context.control.clone();
+
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        control.cancel();
+    });
+
+    let results = executor.execute_batch(tasks, context).await.unwrap();
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|r| !r.success));
+}
+
+#[tokio::test]
+async fn test_task_executor_reports_cancellation() {
+    let executor = TaskExecutor::new(1);
+    let mut rx = executor.enable_progress_tracking().await;
+    let task: Arc<dyn Task> = Arc::new(EvaluationTask::new(EvaluationConfig::default()));
+    let context = TaskContext::new(Uuid::new_v4(), serde_json::json!({}));
+    context.control.cancel();
+
+    let result = executor.execute_one(task, context).await.unwrap();
+    assert!(!result.success);
+
+    assert_eq!(rx.recv().await.unwrap().status, "starting");
+    assert_eq!(rx.recv().await.unwrap().status, "cancelled");
+}
+
 // ===== TaskProgress Tests =====
 
 #[test]
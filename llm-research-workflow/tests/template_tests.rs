@@ -0,0 +1,198 @@
+use llm_research_workflow::*;
+use serde_json::json;
+use std::collections::HashMap;
+
+fn outputs() -> HashMap<String, serde_json::Value> {
+    let mut outputs = HashMap::new();
+    outputs.insert(
+        "load_dataset".to_string(),
+        json!({
+            "path": "data/cnn.jsonl",
+            "total_samples": 200,
+            "splits": {"test": {"size": 50}},
+            "batches": [{"id": 0}, {"id": 1}],
+        }),
+    );
+    outputs
+}
+
+// ===== Reference Parsing Tests =====
+
+#[test]
+fn test_parse_reference() {
+    let reference = OutputRef::parse("steps.load_dataset.output.splits.test").unwrap();
+    assert_eq!(reference.step, "load_dataset");
+    assert_eq!(reference.path, vec!["splits", "test"]);
+    assert_eq!(
+        reference.to_string(),
+        "${steps.load_dataset.output.splits.test}"
+    );
+}
+
+#[test]
+fn test_parse_whole_output_reference() {
+    let reference = OutputRef::parse("steps.load_dataset.output").unwrap();
+    assert!(reference.path.is_empty());
+}
+
+#[test]
+fn test_parse_invalid_references() {
+    for expression in [
+        "load_dataset.output.path",
+        "steps.load_dataset.path",
+        "steps..output",
+        "steps.load_dataset.output..path",
+    ] {
+        let error = OutputRef::parse(expression).unwrap_err();
+        assert!(
+            error.to_string().contains("Invalid reference"),
+            "{}",
+            expression
+        );
+    }
+}
+
+#[test]
+fn test_find_references() {
+    let config = json!({
+        "dataset": "${steps.load_dataset.output.path}",
+        "prompts": ["Summarize ${steps.load_dataset.output.total_samples} articles"],
+        "literal": "costs $${price}",
+        "batch_size": 8,
+    });
+
+    let mut uses = find_references(&config).unwrap();
+    uses.sort_by(|a, b| a.field.cmp(&b.field));
+
+    assert_eq!(uses.len(), 2);
+    assert_eq!(uses[0].field, "dataset");
+    assert!(!uses[0].embedded);
+    assert_eq!(uses[1].field, "prompts[0]");
+    assert!(uses[1].embedded);
+    assert_eq!(uses[1].reference.path(), ["total_samples"]);
+}
+
+#[test]
+fn test_find_references_unterminated() {
+    let error = find_references(&json!({"path": "${steps.load.output.path"})).unwrap_err();
+    assert!(error.to_string().contains("Unterminated reference"));
+}
+
+// ===== Resolution Tests =====
+
+#[test]
+fn test_resolve_whole_field_keeps_type() {
+    let config = json!({
+        "samples": "${steps.load_dataset.output.total_samples}",
+        "test_split": "${steps.load_dataset.output.splits.test}",
+        "first_batch": "${steps.load_dataset.output.batches.0.id}",
+    });
+
+    let resolved = resolve_config(&config, &outputs(), &json!({})).unwrap();
+    assert_eq!(resolved["samples"], json!(200));
+    assert_eq!(resolved["test_split"], json!({"size": 50}));
+    assert_eq!(resolved["first_batch"], json!(0));
+}
+
+#[test]
+fn test_resolve_interpolates_strings() {
+    let config = json!({
+        "title": "${steps.load_dataset.output.total_samples} samples from ${steps.load_dataset.output.path}",
+        "literal": "costs $${price}",
+    });
+
+    let resolved = resolve_config(&config, &outputs(), &json!({})).unwrap();
+    assert_eq!(resolved["title"], "200 samples from data/cnn.jsonl");
+    assert_eq!(resolved["literal"], "costs ${price}");
+}
+
+#[test]
+fn test_resolve_leaves_plain_config_untouched() {
+    let config = json!({"model": "gpt-4", "temperature": 0.2, "stop": ["\n"]});
+    assert_eq!(
+        resolve_config(&config, &HashMap::new(), &json!({})).unwrap(),
+        config
+    );
+}
+
+#[test]
+fn test_resolve_missing_key() {
+    let config = json!({"dataset": {"path": "${steps.load_dataset.output.paht}"}});
+
+    let message = resolve_config(&config, &outputs(), &json!({}))
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("Config field 'dataset.path'"));
+    assert!(message.contains("output of step 'load_dataset' has no key 'paht'"));
+    assert!(message.contains("available: batches, path, splits, total_samples"));
+}
+
+#[test]
+fn test_resolve_missing_nested_key() {
+    let config = json!({"size": "${steps.load_dataset.output.splits.train.size}"});
+
+    let message = resolve_config(&config, &outputs(), &json!({}))
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("'splits' has no key 'train' (available: test)"));
+}
+
+#[test]
+fn test_resolve_index_out_of_range() {
+    let config = json!({"batch": "${steps.load_dataset.output.batches.5}"});
+
+    let message = resolve_config(&config, &outputs(), &json!({}))
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("has no index '5' (2 items)"));
+}
+
+#[test]
+fn test_resolve_step_without_output() {
+    let config = json!({"predictions": "${steps.run_inference.output.results}"});
+
+    let message = resolve_config(&config, &outputs(), &json!({}))
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("step 'run_inference' has no output"));
+}
+
+#[test]
+fn test_resolve_rejects_interpolating_objects() {
+    let config = json!({"title": "Split: ${steps.load_dataset.output.splits}"});
+
+    let message = resolve_config(&config, &outputs(), &json!({}))
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("is an object and cannot be interpolated"));
+}
+
+// ===== Output Schema Tests =====
+
+#[test]
+fn test_output_schema_check() {
+    let schema = OutputSchema::new()
+        .with_field("path", ValueType::String)
+        .with_field("batches", ValueType::Array);
+    let check = |expression: &str| schema.check(&OutputRef::parse(expression).unwrap());
+
+    assert_eq!(check("steps.load.output"), Ok(ValueType::Object));
+    assert_eq!(check("steps.load.output.path"), Ok(ValueType::String));
+    assert_eq!(check("steps.load.output.batches.0.id"), Ok(ValueType::Any));
+
+    let missing = check("steps.load.output.paht").unwrap_err();
+    assert!(missing.contains("no output 'paht' (available: batches, path)"));
+
+    let scalar = check("steps.load.output.path.suffix").unwrap_err();
+    assert!(scalar.contains("is a string and has no field 'suffix'"));
+}
+
+#[test]
+fn test_builtin_tasks_declare_output_schemas() {
+    let registry = TaskRegistry::with_builtin_tasks();
+    for task_type in registry.task_types() {
+        assert!(registry.output_schema(task_type).is_some(), "{}", task_type);
+    }
+    let schema = registry.output_schema("data_loading").unwrap();
+    assert_eq!(schema.field("total_samples"), Some(ValueType::Number));
+}